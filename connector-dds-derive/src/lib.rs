@@ -0,0 +1,524 @@
+//! `#[derive(DdsType)]` generates the XML type definition of a Rust struct or
+//! enum, so the `<types>` section of a Connector configuration and the Rust
+//! type are declared in one place.
+//!
+//! Container attributes:
+//! - `#[dds(name = "ShapeType")]`: DDS type name, the Rust name by default.
+//!   A qualified name such as `Geo::Point` is defined in `<module>`s.
+//! - `#[dds(topic = "Square")]`: the `TopicType::TOPIC_NAME` of a struct, the
+//!   DDS type name by default.
+//! - `#[dds(extensibility = "appendable")]`: `final`, `appendable`,
+//!   `extensible` or `mutable`.
+//!
+//! Field attributes:
+//! - `#[dds(key)]`: marks a key member.
+//! - `#[dds(base)]`: the field is the base type of the struct (`baseType`). It
+//!   must come with `#[serde(flatten)]` so its members are not nested.
+//! - `#[dds(string_max_length = 128)]`, `#[dds(sequence_max_length = 8)]`: a
+//!   bound, either a number or the name of a `<const>`.
+//! - `#[dds(name = "...")]`: member name; `#[serde(rename = "...")]`, or the
+//!   `#[serde(rename_all = "...")]` of the struct, is used when absent.
+//!   Fields with `#[serde(skip)]` are left out.
+//!
+//! Enums must only have unit variants; explicit discriminants become the
+//! enumerator values. Since the Connector represents an enum by the value of
+//! its enumerator, the derive also implements `Serialize` and `Deserialize`
+//! for enums that way: do not derive them as well.
+//!
+//! `dds_config!("examples/ShapeExample.xml")` embeds a configuration file,
+//! relative to the crate's manifest directory, and generates its types and a
+//! module per `<domain_participant>` whose accessors return its readers and
+//! writers bound to their topic type (see
+//! `rticonnector_codegen::generate_config`). A misspelled entity or a reader
+//! used with the wrong type fails to compile, and so does a configuration
+//! that refers to an undeclared topic or type.
+
+use std::path::Path;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Attribute, Data, DataEnum, DataStruct, DeriveInput, Error, Expr, ExprLit,
+    ExprUnary, Fields, Lit, LitStr, Meta, Result, Token, UnOp,
+};
+
+#[proc_macro_derive(DdsType, attributes(dds))]
+pub fn derive_dds_type(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "DdsType cannot be derived for generic types",
+        ));
+    }
+    let container = ContainerAttributes::parse(input)?;
+    match &input.data {
+        Data::Struct(data) => expand_struct(input, &container, data),
+        Data::Enum(data) => expand_enum(input, &container, data),
+        Data::Union(_) => Err(Error::new(
+            input.ident.span(),
+            "DdsType cannot be derived for unions",
+        )),
+    }
+}
+
+#[proc_macro]
+pub fn dds_config(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as LitStr);
+    expand_config(&path)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_config(path: &LitStr) -> Result<TokenStream2> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| Error::new(path.span(), "CARGO_MANIFEST_DIR is not set"))?;
+    let file = Path::new(&manifest_dir).join(path.value());
+    let error = |message: &dyn std::fmt::Display| {
+        Error::new(path.span(), format!("{}: {}", file.display(), message))
+    };
+    let config = rticonnector_config::Config::from_file(&file).map_err(|e| error(&e))?;
+    // The generated code embeds the file with `include_str!`, which also
+    // recompiles the crate when it changes.
+    let source = rticonnector_codegen::generate_config(&config, &file.to_string_lossy())
+        .map_err(|e| error(&e))?;
+    source.parse().map_err(|e| error(&e))
+}
+
+struct ContainerAttributes {
+    name: String,
+    /// Only for structs, which are the ones that implement `TopicType`.
+    topic: Option<LitStr>,
+    extensibility: Option<String>,
+}
+
+impl ContainerAttributes {
+    fn parse(input: &DeriveInput) -> Result<Self> {
+        let mut attributes = Self {
+            name: input.ident.to_string(),
+            topic: None,
+            extensibility: None,
+        };
+        for attr in dds_attributes(&input.attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    attributes.name = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("topic") {
+                    attributes.topic = Some(meta.value()?.parse::<LitStr>()?);
+                } else if meta.path.is_ident("extensibility") {
+                    let value = meta.value()?.parse::<LitStr>()?;
+                    match value.value().as_str() {
+                        "final" | "appendable" | "extensible" | "mutable" => {
+                            attributes.extensibility = Some(value.value())
+                        }
+                        _ => {
+                            return Err(Error::new(
+                                value.span(),
+                                "expected `final`, `appendable`, `extensible` or `mutable`",
+                            ))
+                        }
+                    }
+                } else {
+                    return Err(meta.error("unknown dds container attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(attributes)
+    }
+}
+
+#[derive(Default)]
+struct FieldAttributes {
+    name: Option<String>,
+    key: bool,
+    base: bool,
+    string_max_length: Option<String>,
+    sequence_max_length: Option<String>,
+}
+
+impl FieldAttributes {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut attributes = Self::default();
+        for attr in dds_attributes(attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("key") {
+                    attributes.key = true;
+                } else if meta.path.is_ident("base") {
+                    attributes.base = true;
+                } else if meta.path.is_ident("name") {
+                    attributes.name = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("string_max_length") {
+                    attributes.string_max_length = Some(bound(&meta.value()?.parse()?)?);
+                } else if meta.path.is_ident("sequence_max_length") {
+                    attributes.sequence_max_length = Some(bound(&meta.value()?.parse()?)?);
+                } else {
+                    return Err(meta.error("unknown dds field attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(attributes)
+    }
+}
+
+/// The serde attributes that change how a field maps to a DDS member, and
+/// the `rename_all` of the struct.
+#[derive(Default)]
+struct SerdeAttributes {
+    rename: Option<String>,
+    rename_all: Option<RenameRule>,
+    skip: bool,
+    flatten: bool,
+}
+
+impl SerdeAttributes {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut attributes = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+            let nested =
+                attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+            for meta in nested {
+                match &meta {
+                    Meta::Path(path) if path.is_ident("skip") => attributes.skip = true,
+                    Meta::Path(path) if path.is_ident("flatten") => attributes.flatten = true,
+                    Meta::NameValue(name_value) if name_value.path.is_ident("rename") => {
+                        if let Expr::Lit(ExprLit {
+                            lit: Lit::Str(value),
+                            ..
+                        }) = &name_value.value
+                        {
+                            attributes.rename = Some(value.value());
+                        }
+                    }
+                    Meta::NameValue(name_value) if name_value.path.is_ident("rename_all") => {
+                        let Expr::Lit(ExprLit {
+                            lit: Lit::Str(value),
+                            ..
+                        }) = &name_value.value
+                        else {
+                            return Err(Error::new(name_value.value.span(), "expected a string"));
+                        };
+                        attributes.rename_all = Some(RenameRule::parse(value)?);
+                    }
+                    Meta::List(list) if list.path.is_ident("rename_all") => {
+                        return Err(Error::new(
+                            list.span(),
+                            "DdsType members have a single name: use `rename_all = \"...\"`",
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+/// A `#[serde(rename_all = "...")]` rule, applied to the field names the way
+/// serde applies it.
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(value: &LitStr) -> Result<Self> {
+        Ok(match value.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => return Err(Error::new(value.span(), "unknown rename rule")),
+        })
+    }
+
+    /// Renames `field`, a snake_case Rust field name.
+    fn apply(self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_string(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => field
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                        .unwrap_or_default()
+                })
+                .collect(),
+            Self::Camel => {
+                let pascal = Self::Pascal.apply(field);
+                let mut chars = pascal.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            }
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+fn dds_attributes(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|a| a.path().is_ident("dds"))
+}
+
+/// A length bound: an integer literal or the name of a `<const>`.
+fn bound(lit: &Lit) -> Result<String> {
+    match lit {
+        Lit::Int(value) => Ok(value.base10_digits().to_string()),
+        Lit::Str(value) => Ok(value.value()),
+        _ => Err(Error::new(
+            lit.span(),
+            "expected an integer or the name of a const",
+        )),
+    }
+}
+
+fn expand_struct(
+    input: &DeriveInput,
+    container: &ContainerAttributes,
+    data: &DataStruct,
+) -> Result<TokenStream2> {
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new(
+            input.ident.span(),
+            "DdsType can only be derived for structs with named fields",
+        ));
+    };
+
+    let rename_all = SerdeAttributes::parse(&input.attrs)?.rename_all;
+    let mut dependencies = Vec::new();
+    let mut members = Vec::new();
+    let mut base_type = None;
+    for field in &fields.named {
+        let attributes = FieldAttributes::parse(&field.attrs)?;
+        let serde = SerdeAttributes::parse(&field.attrs)?;
+        if serde.skip {
+            continue;
+        }
+        let ty = &field.ty;
+        dependencies.push(quote! {
+            <#ty as ::connector_dds::topic::DdsType>::collect_definitions(types);
+        });
+
+        if attributes.base {
+            if base_type.is_some() {
+                return Err(Error::new(field.span(), "a struct can only have one base type"));
+            }
+            if !serde.flatten {
+                return Err(Error::new(
+                    field.span(),
+                    "the base type field needs `#[serde(flatten)]` so its members are not nested",
+                ));
+            }
+            base_type = Some(quote! {
+                ::std::option::Option::Some(
+                    <#ty as ::connector_dds::topic::DdsType>::member_type()
+                        .type_name()
+                        .to_string(),
+                )
+            });
+            continue;
+        }
+
+        let name = attributes.name.or(serde.rename).unwrap_or_else(|| {
+            let name = field.ident.as_ref().unwrap().unraw().to_string();
+            match rename_all {
+                Some(rule) => rule.apply(&name),
+                None => name,
+            }
+        });
+        let key = attributes.key;
+        let string_max_length = attributes.string_max_length.map(|length| {
+            quote! { member_type.string_max_length = ::std::option::Option::Some(#length.to_string()); }
+        });
+        let sequence_max_length = attributes.sequence_max_length.map(|length| {
+            quote! { member_type.sequence_max_length = ::std::option::Option::Some(#length.to_string()); }
+        });
+        members.push(quote! {
+            ::connector_dds::topic::MemberDefinition {
+                name: #name.to_string(),
+                member_type: {
+                    #[allow(unused_mut)]
+                    let mut member_type = <#ty as ::connector_dds::topic::DdsType>::member_type();
+                    #string_max_length
+                    #sequence_max_length
+                    member_type
+                },
+                key: #key,
+            }
+        });
+    }
+
+    let ident = &input.ident;
+    let name = &container.name;
+    let base_type = base_type.unwrap_or_else(|| quote! { ::std::option::Option::None });
+    let extensibility = option_string(&container.extensibility);
+    let topic_name = container
+        .topic
+        .as_ref()
+        .map(|topic| quote! { const TOPIC_NAME: &'static str = #topic; });
+    Ok(quote! {
+        impl ::connector_dds::topic::DdsType for #ident {
+            fn member_type() -> ::connector_dds::topic::MemberType {
+                ::connector_dds::topic::MemberType::non_basic(#name)
+            }
+
+            fn collect_definitions(types: &mut ::connector_dds::topic::TypeDefinitions) {
+                if !types.begin(#name) {
+                    return;
+                }
+                #(#dependencies)*
+                let definition = ::connector_dds::topic::StructDefinition {
+                    name: #name.to_string(),
+                    base_type: #base_type,
+                    extensibility: #extensibility,
+                    members: ::std::vec![#(#members),*],
+                };
+                types.push(#name, definition.to_xml());
+            }
+        }
+
+        impl ::connector_dds::topic::TopicType for #ident {
+            const TYPE_NAME: &'static str = #name;
+            #topic_name
+        }
+    })
+}
+
+fn expand_enum(
+    input: &DeriveInput,
+    container: &ContainerAttributes,
+    data: &DataEnum,
+) -> Result<TokenStream2> {
+    if let Some(topic) = &container.topic {
+        return Err(Error::new(
+            topic.span(),
+            "`topic` only applies to structs, the types topics carry",
+        ));
+    }
+    let mut enumerators = Vec::new();
+    let mut to_values = Vec::new();
+    let mut from_values = Vec::new();
+    let mut next_value = 0i64;
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new(
+                variant.span(),
+                "DdsType enums can only have unit variants",
+            ));
+        }
+        if let Some((_, discriminant)) = &variant.discriminant {
+            next_value = discriminant_value(discriminant)?;
+        }
+        let value = i32::try_from(next_value)
+            .map_err(|_| Error::new(variant.span(), "enumerator value does not fit in a long"))?;
+        let name = FieldAttributes::parse(&variant.attrs)?
+            .name
+            .or(SerdeAttributes::parse(&variant.attrs)?.rename)
+            .unwrap_or_else(|| variant.ident.unraw().to_string());
+        enumerators.push(quote! { (#name.to_string(), #value) });
+        let variant = &variant.ident;
+        to_values.push(quote! { Self::#variant => #value });
+        from_values.push(quote! { #value => ::std::result::Result::Ok(Self::#variant) });
+        next_value += 1;
+    }
+
+    let ident = &input.ident;
+    let name = &container.name;
+    let extensibility = option_string(&container.extensibility);
+    Ok(quote! {
+        impl ::connector_dds::topic::DdsType for #ident {
+            fn member_type() -> ::connector_dds::topic::MemberType {
+                ::connector_dds::topic::MemberType::non_basic(#name)
+            }
+
+            fn collect_definitions(types: &mut ::connector_dds::topic::TypeDefinitions) {
+                if !types.begin(#name) {
+                    return;
+                }
+                let definition = ::connector_dds::topic::EnumDefinition {
+                    name: #name.to_string(),
+                    extensibility: #extensibility,
+                    enumerators: ::std::vec![#(#enumerators),*],
+                };
+                types.push(#name, definition.to_xml());
+            }
+        }
+
+        impl ::serde::Serialize for #ident {
+            fn serialize<S: ::serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> ::std::result::Result<S::Ok, S::Error> {
+                let value: i32 = match self {
+                    #(#to_values),*
+                };
+                serializer.serialize_i32(value)
+            }
+        }
+
+        impl<'de> ::serde::Deserialize<'de> for #ident {
+            fn deserialize<D: ::serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> ::std::result::Result<Self, D::Error> {
+                match <i32 as ::serde::Deserialize>::deserialize(deserializer)? {
+                    #(#from_values,)*
+                    value => ::std::result::Result::Err(<D::Error as ::serde::de::Error>::custom(
+                        ::std::format!("{} is not a valid {}", value, #name),
+                    )),
+                }
+            }
+        }
+    })
+}
+
+fn discriminant_value(expr: &Expr) -> Result<i64> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(value),
+            ..
+        }) => value.base10_parse(),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => discriminant_value(expr).map(|value| -value),
+        _ => Err(Error::new(
+            expr.span(),
+            "enumerator values must be integer literals",
+        )),
+    }
+}
+
+fn option_string(value: &Option<String>) -> TokenStream2 {
+    match value {
+        Some(value) => quote! { ::std::option::Option::Some(#value.to_string()) },
+        None => quote! { ::std::option::Option::None },
+    }
+}
@@ -0,0 +1,33 @@
+//! Creates the participants of `ShapeExample.xml` through the modules
+//! generated by `dds_config!`: the reader and writer names and their topic
+//! types are checked when this example compiles.
+
+use std::time::Duration;
+
+use connector_dds::dds_config;
+
+dds_config!("../rticonnector/examples/ShapeExample.xml");
+
+use my_participant_library::my_participant::Participant;
+use types::ShapeType;
+
+fn main() {
+    let participant = Participant::new().unwrap();
+    let writer = participant.my_square_writer().unwrap();
+    let reader = participant.my_square_reader().unwrap();
+
+    writer
+        .write(&ShapeType {
+            color: "BLUE".to_string(),
+            x: 10,
+            y: 20,
+            shapesize: 30,
+        })
+        .unwrap();
+
+    reader.wait(Duration::from_secs(1)).unwrap();
+    reader.take().unwrap();
+    for shape in reader.samples().unwrap() {
+        println!("{:?}", shape);
+    }
+}
@@ -0,0 +1,42 @@
+//! Prints the `<types>` section for the shapes demo types, derived from the
+//! Rust definitions below.
+
+use connector_dds::topic::types_xml;
+use connector_dds::DdsType;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, DdsType, Default)]
+#[dds(extensibility = "extensible")]
+struct ShapeType {
+    #[dds(key, string_max_length = 128)]
+    color: String,
+    x: i32,
+    y: i32,
+    shapesize: i32,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(DdsType, Default)]
+#[dds(extensibility = "extensible")]
+enum ShapeFillKind {
+    #[default]
+    SOLID_FILL = 0,
+    TRANSPARENT_FILL = 1,
+    HORIZONTAL_HATCH_FILL = 2,
+    VERTICAL_HATCH_FILL = 3,
+}
+
+#[derive(Serialize, Deserialize, DdsType, Default)]
+#[dds(extensibility = "extensible")]
+struct ShapeTypeExtended {
+    #[dds(base)]
+    #[serde(flatten)]
+    base: ShapeType,
+    #[serde(rename = "fillKind")]
+    fill_kind: ShapeFillKind,
+    angle: f32,
+}
+
+fn main() {
+    print!("{}", types_xml::<ShapeTypeExtended>());
+}
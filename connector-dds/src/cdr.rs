@@ -0,0 +1,1171 @@
+//! XCDR serialization of [`DynamicData`], as specified by DDS-XTypes 1.3.
+//!
+//! Samples are encoded after the 4-byte encapsulation header of RTPS, which
+//! tells the version and endianness: plain `CDR` or `PL_CDR` for version 1,
+//! `CDR2`, `D_CDR2` or `PL_CDR2` for version 2, depending on the
+//! extensibility of the top-level type. The payload is padded to a multiple
+//! of 4 bytes, the padding being recorded in the options of the header.
+//!
+//! The rules that differ between versions:
+//!
+//! - XCDR1 aligns 8-byte values to 8, XCDR2 to 4.
+//! - A `char16` takes 4 bytes in XCDR1 and 2 in XCDR2, and a `wstring` is
+//!   NUL-terminated UTF-32 in XCDR1 and UTF-16 with a length in bytes in
+//!   XCDR2.
+//! - XCDR1 serializes an appendable struct as a final one. XCDR2 precedes it
+//!   with a DHEADER, the length of its members, as it does for the sequences
+//!   and arrays whose elements are not primitive.
+//! - A mutable struct is a parameter list in XCDR1, each member behind a
+//!   2 + 2 byte header (or an extended one) and the list closed by a
+//!   sentinel; in XCDR2 it is a DHEADER followed by members behind an
+//!   EMHEADER. Key members are flagged as must-understand.
+//! - An optional member of a final or appendable struct is behind a
+//!   parameter header in XCDR1, empty when the member is unset, and behind a
+//!   presence flag in XCDR2.
+//!
+//! Member IDs are the ones of [`Types::member_ids`]: the `id` attribute of a
+//! member, or the ID following the one of the previous member.
+//!
+//! The [`key_hash`] of a sample is computed from its key members alone,
+//! serialized in big-endian XCDR2 without the headers of the structs holding
+//! them; a nested struct without key members contributes all of them. It is
+//! the serialized key padded with zeros when the key of the type never takes
+//! more than 16 bytes, and the MD5 digest of the serialized key otherwise.
+//!
+//! A [`recording_encoder`] stores the samples of a recording as XCDR, to be
+//! decoded with [`from_bytes`] and the types in the recording's header.
+
+use std::sync::Arc;
+
+use rticonnector::config::types::{Extensibility, Member, MemberType, Struct, TypeKind, Types};
+use rticonnector::recorder::Encoder;
+use thiserror::Error;
+
+use crate::util::{
+    join, layer, struct_definition, DynamicData, DynamicDataError, DynamicValue, Layer,
+};
+
+/// Error returned when encoding or decoding XCDR.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CdrError {
+    #[error(transparent)]
+    DynamicData(#[from] DynamicDataError),
+    #[error("unknown encapsulation {0:#06x}")]
+    Encapsulation(u16),
+    #[error("the buffer ends within a value, at offset {0}")]
+    UnexpectedEnd(usize),
+    #[error("`{member}`: {message}")]
+    InvalidValue { member: String, message: String },
+    #[error("`{type_name}` has no member with ID {id}, which must be understood")]
+    UnknownMemberId { type_name: String, id: u32 },
+    #[error("`{0}` has no key members")]
+    NoKey(String),
+}
+
+/// The version of the extended CDR representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XcdrVersion {
+    Xcdr1,
+    Xcdr2,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    Big,
+    #[default]
+    Little,
+}
+
+const PID_EXTENDED: u16 = 0x3f01;
+const PID_LIST_END: u16 = 0x3f02;
+const PID_MUST_UNDERSTAND: u16 = 0x4000;
+/// The IDs and lengths that do not fit a short parameter header.
+const PID_MAX_ID: u32 = 0x3f00;
+const EMHEADER_MUST_UNDERSTAND: u32 = 0x8000_0000;
+const KEY_HASH_LENGTH: usize = 16;
+
+/// Encodes `data` with its encapsulation header.
+pub fn to_bytes(
+    data: &DynamicData,
+    version: XcdrVersion,
+    endianness: Endianness,
+) -> Result<Vec<u8>, CdrError> {
+    let types = data.types();
+    let s = struct_definition(types, data.type_name())?;
+    let mut writer = Writer::new(version, endianness);
+    writer.structure(types, s, data, "")?;
+    let mut payload = writer.bytes;
+    let padding = (4 - payload.len() % 4) % 4;
+    payload.resize(payload.len() + padding, 0);
+
+    let kind = match (version, s.extensibility) {
+        (XcdrVersion::Xcdr1, Extensibility::Mutable) => 0x0002,
+        (XcdrVersion::Xcdr1, _) => 0x0000,
+        (XcdrVersion::Xcdr2, Extensibility::Final) => 0x0010,
+        (XcdrVersion::Xcdr2, Extensibility::Appendable) => 0x0014,
+        (XcdrVersion::Xcdr2, Extensibility::Mutable) => 0x0012,
+    };
+    let identifier: u16 = kind | u16::from(endianness == Endianness::Little);
+    let mut bytes = Vec::with_capacity(4 + payload.len());
+    bytes.extend_from_slice(&identifier.to_be_bytes());
+    bytes.extend_from_slice(&[0, padding as u8]);
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+/// An [`Encoder`] for a [`Recorder`](rticonnector::recorder::Recorder) that
+/// stores the JSON samples of a recording with [`to_bytes`].
+pub fn recording_encoder(version: XcdrVersion, endianness: Endianness) -> Encoder {
+    Box::new(move |topic, json| {
+        let types = Arc::new(topic.types.clone());
+        let data = DynamicData::from_json(types, &topic.type_name, json)
+            .map_err(|e| e.to_string())?;
+        to_bytes(&data, version, endianness).map_err(|e| e.to_string())
+    })
+}
+
+/// Decodes a sample of the struct `type_name` of `types`, with its
+/// encapsulation header, in either version and endianness.
+pub fn from_bytes(
+    types: Arc<Types>,
+    type_name: &str,
+    bytes: &[u8],
+) -> Result<DynamicData, CdrError> {
+    let Some(header) = bytes.get(..4) else {
+        return Err(CdrError::UnexpectedEnd(bytes.len()));
+    };
+    let identifier = u16::from_be_bytes([header[0], header[1]]);
+    let version = match identifier & !1 {
+        0x0000 | 0x0002 => XcdrVersion::Xcdr1,
+        0x0010 | 0x0012 | 0x0014 => XcdrVersion::Xcdr2,
+        _ => return Err(CdrError::Encapsulation(identifier)),
+    };
+    let endianness = match identifier & 1 {
+        0 => Endianness::Big,
+        _ => Endianness::Little,
+    };
+    let mut reader = Reader {
+        bytes,
+        position: 4,
+        origin: 4,
+        end: bytes.len(),
+        version,
+        endianness,
+    };
+    let s = struct_definition(&types, type_name)?;
+    reader.structure(&types, s, "")
+}
+
+impl DynamicData {
+    /// Encodes the sample with [`cdr::to_bytes`](to_bytes).
+    pub fn to_cdr_buffer(
+        &self,
+        version: XcdrVersion,
+        endianness: Endianness,
+    ) -> Result<Vec<u8>, CdrError> {
+        to_bytes(self, version, endianness)
+    }
+
+    /// Computes the key hash of the sample with [`cdr::key_hash`](key_hash).
+    pub fn key_hash(&self) -> Result<[u8; 16], CdrError> {
+        key_hash(self)
+    }
+
+    /// Decodes a sample with [`cdr::from_bytes`](from_bytes).
+    pub fn from_cdr_buffer(
+        types: Arc<Types>,
+        type_name: &str,
+        bytes: &[u8],
+    ) -> Result<Self, CdrError> {
+        from_bytes(types, type_name, bytes)
+    }
+}
+
+/// Computes the 16-byte DDS key hash of `data`, whose type must have key
+/// members.
+pub fn key_hash(data: &DynamicData) -> Result<[u8; KEY_HASH_LENGTH], CdrError> {
+    let types = data.types();
+    let s = struct_definition(types, data.type_name())?;
+    if !types.all_members(s).iter().any(|m| m.key) {
+        return Err(CdrError::NoKey(data.type_name().to_string()));
+    }
+    let mut writer = Writer::new(XcdrVersion::Xcdr2, Endianness::Big);
+    writer.key_holder(types, s, data, "")?;
+    let mut hash = [0; KEY_HASH_LENGTH];
+    // The bound check of DynamicData guarantees the size, but a key holder
+    // longer than its maximum must not be truncated either.
+    match key_holder_size(types, s, 0)? {
+        Some(_) if writer.bytes.len() <= KEY_HASH_LENGTH => {
+            hash[..writer.bytes.len()].copy_from_slice(&writer.bytes)
+        }
+        _ => hash = md5::compute(&writer.bytes).0,
+    }
+    Ok(hash)
+}
+
+/// The members of the key holder of `s`: its key members, or all of them for
+/// a nested struct without any.
+fn key_members<'s>(types: &'s Types, s: &'s Struct) -> Vec<(usize, &'s Member)> {
+    let members = types.all_members(s);
+    let keyed = members.iter().any(|m| m.key);
+    members
+        .into_iter()
+        .enumerate()
+        .filter(|(_, m)| m.key || !keyed)
+        .collect()
+}
+
+/// The offset after the largest key holder of `s` written at `offset`;
+/// `None` when it can exceed the length of a key hash.
+fn key_holder_size(types: &Types, s: &Struct, offset: usize) -> Result<Option<usize>, CdrError> {
+    let mut offset = Some(offset);
+    for (_, member) in key_members(types, s) {
+        let Some(start) = offset else { break };
+        let start = start + usize::from(member.optional);
+        offset = match layer(types, &member.member_type)? {
+            Layer::Struct(s) => key_holder_size(types, s, start)?,
+            _ => max_size(types, &member.member_type, start)?,
+        };
+    }
+    Ok(offset)
+}
+
+/// The offset after the largest value of `member_type` written at `offset`
+/// in XCDR2; `None` when it can exceed the length of a key hash.
+fn max_size(
+    types: &Types,
+    member_type: &MemberType,
+    offset: usize,
+) -> Result<Option<usize>, CdrError> {
+    let aligned = |offset: usize, size: usize| {
+        let alignment = size.clamp(1, 4);
+        let end = offset.next_multiple_of(alignment) + size;
+        (end <= KEY_HASH_LENGTH).then_some(end)
+    };
+    let (elements, element) = match layer(types, member_type)? {
+        Layer::Basic(TypeKind::String, bound) => {
+            return Ok(bound.and_then(|bound| aligned(offset, 4 + bound as usize + 1)));
+        }
+        Layer::Basic(TypeKind::WString, bound) => {
+            return Ok(bound.and_then(|bound| aligned(offset, 4 + 2 * bound as usize)));
+        }
+        Layer::Basic(kind, _) => {
+            let size = basic_size(&kind, XcdrVersion::Xcdr2).unwrap_or_default();
+            return Ok(aligned(offset, size));
+        }
+        Layer::Enum(_) => return Ok(aligned(offset, 4)),
+        Layer::Struct(s) => return struct_max_size(types, s, offset),
+        Layer::Array(length, mut element) => {
+            let mut count = length as usize;
+            while let Layer::Array(length, inner) = layer(types, &element)? {
+                count *= length as usize;
+                element = inner;
+            }
+            let start = match is_primitive(types, &element)? {
+                true => Some(offset),
+                false => aligned(offset, 4),
+            };
+            (start.map(|o| (o, count)), element)
+        }
+        Layer::Sequence(None, _) => return Ok(None),
+        Layer::Sequence(Some(length), element) => {
+            let mut start = Some(offset);
+            if !is_primitive(types, &element)? {
+                start = start.and_then(|o| aligned(o, 4));
+            }
+            (
+                start
+                    .and_then(|o| aligned(o, 4))
+                    .map(|o| (o, length as usize)),
+                element,
+            )
+        }
+    };
+    let Some((mut offset, count)) = elements else {
+        return Ok(None);
+    };
+    for _ in 0..count {
+        match max_size(types, &element, offset)? {
+            Some(end) => offset = end,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(offset))
+}
+
+/// The [`max_size`] of a struct within a collection, which keeps its headers
+/// in a key holder.
+fn struct_max_size(types: &Types, s: &Struct, offset: usize) -> Result<Option<usize>, CdrError> {
+    let header = |offset: Option<usize>| {
+        offset
+            .map(|o| o.next_multiple_of(4) + 4)
+            .filter(|end| *end <= KEY_HASH_LENGTH)
+    };
+    let mut offset = match s.extensibility {
+        Extensibility::Final => Some(offset),
+        Extensibility::Appendable | Extensibility::Mutable => header(Some(offset)),
+    };
+    for member in types.all_members(s) {
+        offset = match s.extensibility {
+            Extensibility::Mutable => {
+                let mut start = header(offset);
+                if fixed_size(types, &member.member_type)?.is_none() {
+                    start = header(start);
+                }
+                start
+            }
+            _ => offset.map(|o| o + usize::from(member.optional)),
+        };
+        let Some(start) = offset else { break };
+        offset = max_size(types, &member.member_type, start)?;
+    }
+    Ok(offset)
+}
+
+/// Whether a member of `member_type` is serialized without any header in
+/// collections: the basic types but strings, and enums.
+fn is_primitive(types: &Types, member_type: &MemberType) -> Result<bool, CdrError> {
+    Ok(match layer(types, member_type)? {
+        Layer::Basic(kind, _) => !matches!(kind, TypeKind::String | TypeKind::WString),
+        Layer::Enum(_) => true,
+        _ => false,
+    })
+}
+
+/// The size of a value of the basic `kind`; `None` for strings.
+fn basic_size(kind: &TypeKind, version: XcdrVersion) -> Option<usize> {
+    Some(match kind {
+        TypeKind::Boolean
+        | TypeKind::Char8
+        | TypeKind::Octet
+        | TypeKind::Int8
+        | TypeKind::UInt8 => 1,
+        TypeKind::Char16 if version == XcdrVersion::Xcdr1 => 4,
+        TypeKind::Char16 | TypeKind::Short | TypeKind::UnsignedShort => 2,
+        TypeKind::Long | TypeKind::UnsignedLong | TypeKind::Float => 4,
+        TypeKind::LongLong | TypeKind::UnsignedLongLong | TypeKind::Double => 8,
+        TypeKind::LongDouble => 16,
+        TypeKind::String | TypeKind::WString | TypeKind::NonBasic(_) => return None,
+    })
+}
+
+/// The size of a member that an EMHEADER can tell without a NEXTINT.
+fn fixed_size(types: &Types, member_type: &MemberType) -> Result<Option<usize>, CdrError> {
+    Ok(match layer(types, member_type)? {
+        Layer::Enum(_) => Some(4),
+        Layer::Basic(kind, _) => basic_size(&kind, XcdrVersion::Xcdr2).filter(|size| *size <= 8),
+        _ => None,
+    })
+}
+
+fn invalid(path: &str, message: impl Into<String>) -> CdrError {
+    CdrError::InvalidValue {
+        member: path.to_string(),
+        message: message.into(),
+    }
+}
+
+struct Writer {
+    bytes: Vec<u8>,
+    /// Where alignment is measured from.
+    origin: usize,
+    version: XcdrVersion,
+    endianness: Endianness,
+}
+
+impl Writer {
+    fn new(version: XcdrVersion, endianness: Endianness) -> Self {
+        Self {
+            bytes: Vec::new(),
+            origin: 0,
+            version,
+            endianness,
+        }
+    }
+
+    /// A writer for a member whose length must be known before it is
+    /// appended; alignment restarts from the member.
+    fn member(&self) -> Self {
+        Self::new(self.version, self.endianness)
+    }
+
+    fn align(&mut self, size: usize) {
+        let max = match self.version {
+            XcdrVersion::Xcdr1 => 8,
+            XcdrVersion::Xcdr2 => 4,
+        };
+        let alignment = size.clamp(1, max);
+        let offset = self.bytes.len() - self.origin;
+        let padding = (alignment - offset % alignment) % alignment;
+        self.bytes.resize(self.bytes.len() + padding, 0);
+    }
+
+    fn number<const N: usize>(&mut self, big: [u8; N], little: [u8; N]) {
+        self.align(N);
+        match self.endianness {
+            Endianness::Big => self.bytes.extend_from_slice(&big),
+            Endianness::Little => self.bytes.extend_from_slice(&little),
+        }
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.number(value.to_be_bytes(), value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.number(value.to_be_bytes(), value.to_le_bytes());
+    }
+
+    /// Reserves a DHEADER, to [`close`](Self::close) once what it measures is
+    /// written.
+    fn open(&mut self) -> usize {
+        self.u32(0);
+        self.bytes.len()
+    }
+
+    fn close(&mut self, start: usize) {
+        let length = (self.bytes.len() - start) as u32;
+        let bytes = match self.endianness {
+            Endianness::Big => length.to_be_bytes(),
+            Endianness::Little => length.to_le_bytes(),
+        };
+        self.bytes[start - 4..start].copy_from_slice(&bytes);
+    }
+
+    fn structure(
+        &mut self,
+        types: &Types,
+        s: &Struct,
+        data: &DynamicData,
+        path: &str,
+    ) -> Result<(), CdrError> {
+        let members = types.all_members(s);
+        let ids = types.member_ids(s);
+        match (s.extensibility, self.version) {
+            (Extensibility::Final, _) | (Extensibility::Appendable, XcdrVersion::Xcdr1) => {
+                self.members(types, &members, &ids, data, path)
+            }
+            (Extensibility::Appendable, XcdrVersion::Xcdr2) => {
+                let start = self.open();
+                self.members(types, &members, &ids, data, path)?;
+                self.close(start);
+                Ok(())
+            }
+            (Extensibility::Mutable, XcdrVersion::Xcdr1) => {
+                for (index, (member, id)) in members.iter().zip(ids).enumerate() {
+                    if let Some(value) = data.get_by_index(index)? {
+                        let path = join(path, &member.name);
+                        self.parameter(types, id, member, Some(value), &path)?;
+                    }
+                }
+                self.align(4);
+                self.u16(PID_LIST_END);
+                self.u16(0);
+                Ok(())
+            }
+            (Extensibility::Mutable, XcdrVersion::Xcdr2) => {
+                let start = self.open();
+                for (index, (member, id)) in members.iter().zip(ids).enumerate() {
+                    if let Some(value) = data.get_by_index(index)? {
+                        let path = join(path, &member.name);
+                        self.emheader_member(types, id, member, value, &path)?;
+                    }
+                }
+                self.close(start);
+                Ok(())
+            }
+        }
+    }
+
+    /// The key members of a struct, by [`key_members`], without any header.
+    fn key_holder(
+        &mut self,
+        types: &Types,
+        s: &Struct,
+        data: &DynamicData,
+        path: &str,
+    ) -> Result<(), CdrError> {
+        for (id, member) in key_members(types, s) {
+            let value = data.get_by_index(id)?;
+            let path = join(path, &member.name);
+            if member.optional {
+                self.bytes.push(u8::from(value.is_some()));
+            }
+            match (layer(types, &member.member_type)?, value) {
+                (_, None) if member.optional => {}
+                (_, None) => return Err(invalid(&path, "a member is unset")),
+                (Layer::Struct(s), Some(DynamicValue::Struct(data))) => {
+                    self.key_holder(types, s, data, &path)?
+                }
+                (_, Some(value)) => self.value(types, &member.member_type, value, &path)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// The members of a final or appendable struct, in order.
+    fn members(
+        &mut self,
+        types: &Types,
+        members: &[&Member],
+        ids: &[u32],
+        data: &DynamicData,
+        path: &str,
+    ) -> Result<(), CdrError> {
+        for (index, (member, &id)) in members.iter().zip(ids).enumerate() {
+            let value = data.get_by_index(index)?;
+            let path = join(path, &member.name);
+            match (member.optional, self.version) {
+                (false, _) => {
+                    let value = value.ok_or_else(|| invalid(&path, "a member is unset"))?;
+                    self.value(types, &member.member_type, value, &path)?;
+                }
+                (true, XcdrVersion::Xcdr1) => {
+                    self.parameter(types, id, member, value, &path)?;
+                }
+                (true, XcdrVersion::Xcdr2) => {
+                    self.bytes.push(u8::from(value.is_some()));
+                    if let Some(value) = value {
+                        self.value(types, &member.member_type, value, &path)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// A member behind an XCDR1 parameter header; empty when `value` is
+    /// `None`.
+    fn parameter(
+        &mut self,
+        types: &Types,
+        id: u32,
+        member: &Member,
+        value: Option<&DynamicValue>,
+        path: &str,
+    ) -> Result<(), CdrError> {
+        let mut content = self.member();
+        if let Some(value) = value {
+            content.value(types, &member.member_type, value, path)?;
+            content.align(4);
+        }
+        let flags = if member.key { PID_MUST_UNDERSTAND } else { 0 };
+        self.align(4);
+        let length = content.bytes.len();
+        if id < PID_MAX_ID && length <= usize::from(u16::MAX) {
+            self.u16(flags | id as u16);
+            self.u16(length as u16);
+        } else {
+            self.u16(PID_EXTENDED | PID_MUST_UNDERSTAND);
+            self.u16(8);
+            self.u32(id);
+            self.u32(length as u32);
+        }
+        self.bytes.extend_from_slice(&content.bytes);
+        Ok(())
+    }
+
+    /// A member of an XCDR2 mutable struct, behind its EMHEADER.
+    fn emheader_member(
+        &mut self,
+        types: &Types,
+        id: u32,
+        member: &Member,
+        value: &DynamicValue,
+        path: &str,
+    ) -> Result<(), CdrError> {
+        let mut content = self.member();
+        content.value(types, &member.member_type, value, path)?;
+        let flags = if member.key {
+            EMHEADER_MUST_UNDERSTAND
+        } else {
+            0
+        };
+        self.align(4);
+        match fixed_size(types, &member.member_type)? {
+            Some(size) => {
+                let length_code = size.trailing_zeros();
+                self.u32(flags | length_code << 28 | id);
+            }
+            None => {
+                self.u32(flags | 4 << 28 | id);
+                self.u32(content.bytes.len() as u32);
+            }
+        }
+        self.bytes.extend_from_slice(&content.bytes);
+        Ok(())
+    }
+
+    fn value(
+        &mut self,
+        types: &Types,
+        member_type: &MemberType,
+        value: &DynamicValue,
+        path: &str,
+    ) -> Result<(), CdrError> {
+        match (layer(types, member_type)?, value) {
+            (Layer::Array(_, mut element), DynamicValue::Array(_)) => {
+                element.array_dimensions.clear();
+                if self.version == XcdrVersion::Xcdr2 && !is_primitive(types, &element)? {
+                    let start = self.open();
+                    self.array(types, member_type, value, path)?;
+                    self.close(start);
+                    Ok(())
+                } else {
+                    self.array(types, member_type, value, path)
+                }
+            }
+            (Layer::Sequence(_, element), DynamicValue::Sequence(items)) => {
+                let start =
+                    match self.version == XcdrVersion::Xcdr2 && !is_primitive(types, &element)? {
+                        true => Some(self.open()),
+                        false => None,
+                    };
+                self.u32(items.len() as u32);
+                for (index, item) in items.iter().enumerate() {
+                    self.value(types, &element, item, &format!("{}[{}]", path, index))?;
+                }
+                if let Some(start) = start {
+                    self.close(start);
+                }
+                Ok(())
+            }
+            (Layer::Struct(s), DynamicValue::Struct(data)) => self.structure(types, s, data, path),
+            (Layer::Enum(_), DynamicValue::Enum(value)) => {
+                self.number(value.to_be_bytes(), value.to_le_bytes());
+                Ok(())
+            }
+            (Layer::Basic(kind, _), value) => self.basic(&kind, value, path),
+            (layer, value) => Err(DynamicDataError::Mismatch {
+                member: path.to_string(),
+                expected: layer.describe(),
+                found: value.kind_name(),
+            }
+            .into()),
+        }
+    }
+
+    /// The elements of every dimension of an array, without headers.
+    fn array(
+        &mut self,
+        types: &Types,
+        member_type: &MemberType,
+        value: &DynamicValue,
+        path: &str,
+    ) -> Result<(), CdrError> {
+        match (layer(types, member_type)?, value) {
+            (Layer::Array(_, element), DynamicValue::Array(items)) => {
+                for (index, item) in items.iter().enumerate() {
+                    let path = format!("{}[{}]", path, index);
+                    if element.array_dimensions.is_empty() {
+                        self.value(types, &element, item, &path)?;
+                    } else {
+                        self.array(types, &element, item, &path)?;
+                    }
+                }
+                Ok(())
+            }
+            _ => self.value(types, member_type, value, path),
+        }
+    }
+
+    fn basic(&mut self, kind: &TypeKind, value: &DynamicValue, path: &str) -> Result<(), CdrError> {
+        match (kind, value) {
+            (TypeKind::Boolean, DynamicValue::Boolean(b)) => self.bytes.push(u8::from(*b)),
+            (TypeKind::Char8, DynamicValue::Char(c)) => {
+                let byte = u8::try_from(u32::from(*c))
+                    .map_err(|_| invalid(path, format!("{:?} is not a char8", c)))?;
+                self.bytes.push(byte);
+            }
+            (TypeKind::Char16, DynamicValue::Char(c)) => match self.version {
+                XcdrVersion::Xcdr1 => self.u32(u32::from(*c)),
+                XcdrVersion::Xcdr2 => {
+                    let unit = u16::try_from(u32::from(*c))
+                        .map_err(|_| invalid(path, format!("{:?} is not a char16", c)))?;
+                    self.u16(unit);
+                }
+            },
+            (TypeKind::Octet | TypeKind::UInt8, DynamicValue::Octet(n)) => self.bytes.push(*n),
+            (TypeKind::Int8, DynamicValue::Int8(n)) => self.bytes.push(*n as u8),
+            (TypeKind::Short, DynamicValue::Short(n)) => {
+                self.number(n.to_be_bytes(), n.to_le_bytes())
+            }
+            (TypeKind::UnsignedShort, DynamicValue::UnsignedShort(n)) => self.u16(*n),
+            (TypeKind::Long, DynamicValue::Long(n)) => {
+                self.number(n.to_be_bytes(), n.to_le_bytes())
+            }
+            (TypeKind::UnsignedLong, DynamicValue::UnsignedLong(n)) => self.u32(*n),
+            (TypeKind::LongLong, DynamicValue::LongLong(n)) => {
+                self.number(n.to_be_bytes(), n.to_le_bytes())
+            }
+            (TypeKind::UnsignedLongLong, DynamicValue::UnsignedLongLong(n)) => {
+                self.number(n.to_be_bytes(), n.to_le_bytes())
+            }
+            (TypeKind::Float, DynamicValue::Float(n)) => {
+                self.number(n.to_be_bytes(), n.to_le_bytes())
+            }
+            (TypeKind::Double, DynamicValue::Double(n)) => {
+                self.number(n.to_be_bytes(), n.to_le_bytes())
+            }
+            (TypeKind::LongDouble, DynamicValue::Double(n)) => {
+                let quad = f64_to_binary128(*n);
+                self.number(quad.to_be_bytes(), quad.to_le_bytes());
+            }
+            (TypeKind::String, DynamicValue::String(s)) => {
+                self.u32(s.len() as u32 + 1);
+                self.bytes.extend_from_slice(s.as_bytes());
+                self.bytes.push(0);
+            }
+            (TypeKind::WString, DynamicValue::String(s)) => match self.version {
+                XcdrVersion::Xcdr1 => {
+                    self.u32(s.chars().count() as u32 + 1);
+                    for c in s.chars().chain(['\0']) {
+                        self.u32(c.into());
+                    }
+                }
+                XcdrVersion::Xcdr2 => {
+                    let units: Vec<u16> = s.encode_utf16().collect();
+                    self.u32(units.len() as u32 * 2);
+                    for unit in units {
+                        self.u16(unit);
+                    }
+                }
+            },
+            (kind, value) => {
+                return Err(DynamicDataError::Mismatch {
+                    member: path.to_string(),
+                    expected: kind.xml_name().to_string(),
+                    found: value.kind_name(),
+                }
+                .into())
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The header of a member of a mutable struct.
+struct MemberHeader {
+    id: u32,
+    must_understand: bool,
+    /// The length of the member, after the header.
+    length: usize,
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+    position: usize,
+    /// Where alignment is measured from.
+    origin: usize,
+    /// Where the value being read must end.
+    end: usize,
+    version: XcdrVersion,
+    endianness: Endianness,
+}
+
+impl Reader<'_> {
+    fn align(&mut self, size: usize) {
+        let max = match self.version {
+            XcdrVersion::Xcdr1 => 8,
+            XcdrVersion::Xcdr2 => 4,
+        };
+        let alignment = size.clamp(1, max);
+        let offset = self.position - self.origin;
+        self.position += (alignment - offset % alignment) % alignment;
+    }
+
+    fn take(&mut self, length: usize) -> Result<&[u8], CdrError> {
+        let start = self.position;
+        match start.checked_add(length) {
+            Some(end) if end <= self.end => {
+                self.position = end;
+                Ok(&self.bytes[start..end])
+            }
+            _ => Err(CdrError::UnexpectedEnd(self.end)),
+        }
+    }
+
+    fn number<const N: usize>(&mut self) -> Result<[u8; N], CdrError> {
+        self.align(N);
+        let mut bytes: [u8; N] = self.take(N)?.try_into().expect("N bytes were taken");
+        // Numbers are decoded from little-endian bytes.
+        if self.endianness == Endianness::Big {
+            bytes.reverse();
+        }
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, CdrError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, CdrError> {
+        Ok(u16::from_le_bytes(self.number()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, CdrError> {
+        Ok(u32::from_le_bytes(self.number()?))
+    }
+
+    /// Reads a DHEADER, returning where what it measures ends.
+    fn dheader(&mut self) -> Result<usize, CdrError> {
+        let length = self.u32()? as usize;
+        match self.position.checked_add(length) {
+            Some(end) if end <= self.end => Ok(end),
+            _ => Err(CdrError::UnexpectedEnd(self.end)),
+        }
+    }
+
+    /// Reads a member of `length` bytes from here, with alignment restarting
+    /// from it, then moves past it.
+    fn member<T>(
+        &mut self,
+        length: usize,
+        read: impl FnOnce(&mut Self) -> Result<T, CdrError>,
+    ) -> Result<T, CdrError> {
+        let end = match self.position.checked_add(length) {
+            Some(end) if end <= self.end => end,
+            _ => return Err(CdrError::UnexpectedEnd(self.end)),
+        };
+        let (origin, outer_end) = (self.origin, self.end);
+        self.origin = self.position;
+        self.end = end;
+        let result = read(self);
+        self.origin = origin;
+        self.end = outer_end;
+        self.position = end;
+        result
+    }
+
+    fn structure(
+        &mut self,
+        types: &Arc<Types>,
+        s: &Struct,
+        path: &str,
+    ) -> Result<DynamicData, CdrError> {
+        let mut data = DynamicData::new(types.clone(), &s.name)?;
+        let members = types.all_members(s);
+        let ids = types.member_ids(s);
+        match (s.extensibility, self.version) {
+            (Extensibility::Final, _) | (Extensibility::Appendable, XcdrVersion::Xcdr1) => {
+                self.members(types, &members, &mut data, path, false)?;
+            }
+            (Extensibility::Appendable, XcdrVersion::Xcdr2) => {
+                let end = self.dheader()?;
+                let outer_end = self.end;
+                self.end = end;
+                // The members a newer version of the type appended are
+                // skipped; the ones an older version lacks keep their default.
+                let result = self.members(types, &members, &mut data, path, true);
+                self.end = outer_end;
+                self.position = end;
+                result?;
+            }
+            (Extensibility::Mutable, XcdrVersion::Xcdr1) => loop {
+                self.align(4);
+                let pid = self.u16()?;
+                let mut length = usize::from(self.u16()?);
+                let must_understand = pid & PID_MUST_UNDERSTAND != 0;
+                let mut id = u32::from(pid & 0x3fff);
+                if id == u32::from(PID_LIST_END) {
+                    break;
+                }
+                if id == u32::from(PID_EXTENDED) {
+                    id = self.u32()?;
+                    length = self.u32()? as usize;
+                }
+                let header = MemberHeader {
+                    id,
+                    must_understand,
+                    length,
+                };
+                self.mutable_member(types, &members, &ids, &mut data, header, path)?;
+            },
+            (Extensibility::Mutable, XcdrVersion::Xcdr2) => {
+                let end = self.dheader()?;
+                while self.position < end {
+                    self.align(4);
+                    let header = self.u32()?;
+                    let must_understand = header & EMHEADER_MUST_UNDERSTAND != 0;
+                    let id = header & 0x0fff_ffff;
+                    let length = match header >> 28 & 0x7 {
+                        code @ 0..=3 => 1 << code,
+                        4 => self.u32()? as usize,
+                        // The NEXTINT is also the start of the member.
+                        code => {
+                            let next = self.u32()? as usize;
+                            self.position -= 4;
+                            let size = match code {
+                                5 => 1,
+                                6 => 4,
+                                _ => 8,
+                            };
+                            next.checked_mul(size)
+                                .and_then(|n| n.checked_add(4))
+                                .ok_or(CdrError::UnexpectedEnd(self.end))?
+                        }
+                    };
+                    let header = MemberHeader {
+                        id,
+                        must_understand,
+                        length,
+                    };
+                    self.mutable_member(types, &members, &ids, &mut data, header, path)?;
+                }
+                self.position = end;
+            }
+        }
+        Ok(data)
+    }
+
+    /// The members of a final or appendable struct. Only the DHEADER of an
+    /// XCDR2 appendable struct tells where its members stop, so the ones
+    /// past `self.end` are missing there and truncated anywhere else.
+    fn members(
+        &mut self,
+        types: &Arc<Types>,
+        members: &[&Member],
+        data: &mut DynamicData,
+        path: &str,
+        delimited: bool,
+    ) -> Result<(), CdrError> {
+        for (index, member) in members.iter().enumerate() {
+            if delimited && self.position >= self.end {
+                break;
+            }
+            let path = join(path, &member.name);
+            let value = match (member.optional, self.version) {
+                (false, _) => Some(self.value(types, &member.member_type, &path)?),
+                (true, XcdrVersion::Xcdr1) => {
+                    self.align(4);
+                    let pid = self.u16()?;
+                    let mut length = usize::from(self.u16()?);
+                    if pid & 0x3fff == PID_EXTENDED {
+                        self.u32()?;
+                        length = self.u32()? as usize;
+                    }
+                    match length {
+                        0 => None,
+                        _ => Some(
+                            self.member(length, |r| r.value(types, &member.member_type, &path))?,
+                        ),
+                    }
+                }
+                (true, XcdrVersion::Xcdr2) => match self.u8()? {
+                    0 => None,
+                    _ => Some(self.value(types, &member.member_type, &path)?),
+                },
+            };
+            if let Some(value) = value {
+                data.set_by_index(index, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// A member of a mutable struct; an unknown one is skipped unless it
+    /// must be understood.
+    fn mutable_member(
+        &mut self,
+        types: &Arc<Types>,
+        members: &[&Member],
+        ids: &[u32],
+        data: &mut DynamicData,
+        header: MemberHeader,
+        path: &str,
+    ) -> Result<(), CdrError> {
+        let Some(index) = ids.iter().position(|id| *id == header.id) else {
+            if header.must_understand {
+                return Err(CdrError::UnknownMemberId {
+                    type_name: data.type_name().to_string(),
+                    id: header.id,
+                });
+            }
+            return self.member(header.length, |_| Ok(()));
+        };
+        let member = members[index];
+        let path = join(path, &member.name);
+        let value = self.member(header.length, |r| {
+            r.value(types, &member.member_type, &path)
+        })?;
+        data.set_by_index(index, value)?;
+        Ok(())
+    }
+
+    fn value(
+        &mut self,
+        types: &Arc<Types>,
+        member_type: &MemberType,
+        path: &str,
+    ) -> Result<DynamicValue, CdrError> {
+        match layer(types, member_type)? {
+            Layer::Array(_, mut element) => {
+                element.array_dimensions.clear();
+                if self.version == XcdrVersion::Xcdr2 && !is_primitive(types, &element)? {
+                    self.dheader()?;
+                }
+                self.array(types, member_type, path)
+            }
+            Layer::Sequence(_, element) => {
+                if self.version == XcdrVersion::Xcdr2 && !is_primitive(types, &element)? {
+                    self.dheader()?;
+                }
+                let length = self.u32()? as usize;
+                // Each element takes at least a byte, which bounds a corrupt
+                // length before anything is allocated.
+                if length > self.end - self.position {
+                    return Err(CdrError::UnexpectedEnd(self.end));
+                }
+                let items = (0..length)
+                    .map(|index| self.value(types, &element, &format!("{}[{}]", path, index)))
+                    .collect::<Result<_, _>>()?;
+                Ok(DynamicValue::Sequence(items))
+            }
+            Layer::Struct(s) => Ok(DynamicValue::Struct(self.structure(types, s, path)?)),
+            Layer::Enum(_) => Ok(DynamicValue::Enum(i32::from_le_bytes(self.number()?))),
+            Layer::Basic(kind, _) => self.basic(&kind, path),
+        }
+    }
+
+    fn array(
+        &mut self,
+        types: &Arc<Types>,
+        member_type: &MemberType,
+        path: &str,
+    ) -> Result<DynamicValue, CdrError> {
+        match layer(types, member_type)? {
+            Layer::Array(length, element) => {
+                let items = (0..length)
+                    .map(|index| {
+                        let path = format!("{}[{}]", path, index);
+                        if element.array_dimensions.is_empty() {
+                            self.value(types, &element, &path)
+                        } else {
+                            self.array(types, &element, &path)
+                        }
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(DynamicValue::Array(items))
+            }
+            _ => self.value(types, member_type, path),
+        }
+    }
+
+    fn basic(&mut self, kind: &TypeKind, path: &str) -> Result<DynamicValue, CdrError> {
+        Ok(match kind {
+            TypeKind::Boolean => match self.u8()? {
+                0 => DynamicValue::Boolean(false),
+                1 => DynamicValue::Boolean(true),
+                other => return Err(invalid(path, format!("{} is not a boolean", other))),
+            },
+            TypeKind::Char8 => DynamicValue::Char(char::from(self.u8()?)),
+            TypeKind::Char16 => {
+                let code = match self.version {
+                    XcdrVersion::Xcdr1 => self.u32()?,
+                    XcdrVersion::Xcdr2 => u32::from(self.u16()?),
+                };
+                let c = char::from_u32(code)
+                    .ok_or_else(|| invalid(path, format!("{:#x} is not a character", code)))?;
+                DynamicValue::Char(c)
+            }
+            TypeKind::Octet | TypeKind::UInt8 => DynamicValue::Octet(self.u8()?),
+            TypeKind::Int8 => DynamicValue::Int8(self.u8()? as i8),
+            TypeKind::Short => DynamicValue::Short(i16::from_le_bytes(self.number()?)),
+            TypeKind::UnsignedShort => DynamicValue::UnsignedShort(self.u16()?),
+            TypeKind::Long => DynamicValue::Long(i32::from_le_bytes(self.number()?)),
+            TypeKind::UnsignedLong => DynamicValue::UnsignedLong(self.u32()?),
+            TypeKind::LongLong => DynamicValue::LongLong(i64::from_le_bytes(self.number()?)),
+            TypeKind::UnsignedLongLong => {
+                DynamicValue::UnsignedLongLong(u64::from_le_bytes(self.number()?))
+            }
+            TypeKind::Float => DynamicValue::Float(f32::from_le_bytes(self.number()?)),
+            TypeKind::Double => DynamicValue::Double(f64::from_le_bytes(self.number()?)),
+            TypeKind::LongDouble => {
+                DynamicValue::Double(binary128_to_f64(u128::from_le_bytes(self.number()?)))
+            }
+            TypeKind::String => {
+                let length = self.u32()? as usize;
+                let bytes = self.take(length)?;
+                let Some((0, text)) = bytes.split_last() else {
+                    return Err(invalid(path, "the string is not NUL-terminated"));
+                };
+                let text = std::str::from_utf8(text)
+                    .map_err(|e| invalid(path, format!("the string is not UTF-8: {}", e)))?;
+                DynamicValue::String(text.to_string())
+            }
+            TypeKind::WString => match self.version {
+                XcdrVersion::Xcdr1 => {
+                    let length = self.u32()? as usize;
+                    if length > (self.end - self.position) / 4 {
+                        return Err(CdrError::UnexpectedEnd(self.end));
+                    }
+                    let mut text = String::new();
+                    for _ in 0..length {
+                        let code = self.u32()?;
+                        let c = char::from_u32(code).ok_or_else(|| {
+                            invalid(path, format!("{:#x} is not a character", code))
+                        })?;
+                        if c != '\0' {
+                            text.push(c);
+                        }
+                    }
+                    DynamicValue::String(text)
+                }
+                XcdrVersion::Xcdr2 => {
+                    let length = self.u32()? as usize;
+                    let units = (0..length / 2)
+                        .map(|_| self.u16())
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let text = String::from_utf16(&units)
+                        .map_err(|e| invalid(path, format!("the string is not UTF-16: {}", e)))?;
+                    DynamicValue::String(text)
+                }
+            },
+            TypeKind::NonBasic(name) => {
+                return Err(DynamicDataError::UnknownType(name.clone()).into())
+            }
+        })
+    }
+}
+
+/// The IEEE 754 binary128 value of `value`, for `longDouble`s.
+fn f64_to_binary128(value: f64) -> u128 {
+    let bits = value.to_bits();
+    let sign = u128::from(bits >> 63) << 127;
+    let exponent = (bits >> 52 & 0x7ff) as i32;
+    let mantissa = bits & ((1 << 52) - 1);
+    match exponent {
+        0 if mantissa == 0 => sign,
+        0 => {
+            // A subnormal double is a normal quad.
+            let top = 63 - mantissa.leading_zeros() as i32;
+            let exponent = (top - 1074 + 16383) as u128;
+            let mantissa = (u128::from(mantissa) << (112 - top)) & ((1 << 112) - 1);
+            sign | exponent << 112 | mantissa
+        }
+        0x7ff => sign | 0x7fff << 112 | u128::from(mantissa) << 60,
+        _ => {
+            let exponent = (exponent - 1023 + 16383) as u128;
+            sign | exponent << 112 | u128::from(mantissa) << 60
+        }
+    }
+}
+
+/// The nearest `f64` below a binary128 `longDouble`, whose extra precision
+/// is truncated.
+fn binary128_to_f64(quad: u128) -> f64 {
+    let sign = if quad >> 127 == 1 { -1.0 } else { 1.0 };
+    let exponent = (quad >> 112 & 0x7fff) as i32;
+    let mantissa = quad & ((1 << 112) - 1);
+    let value = match exponent {
+        0 => 0.0,
+        0x7fff if mantissa == 0 => f64::INFINITY,
+        0x7fff => f64::NAN,
+        _ => {
+            let exponent = exponent - 16383;
+            let fraction = 1.0 + (mantissa >> 60) as f64 / (1u64 << 52) as f64;
+            fraction * 2f64.powi(exponent)
+        }
+    };
+    sign * value
+}
@@ -0,0 +1,214 @@
+//! The entry point of the DDS-style API.
+//!
+//! A [`DomainParticipant`] owns the [`Connector`] created for one
+//! `<domain_participant>` of a configuration, along with the model of that
+//! configuration, and hands out its [`Publisher`]s and [`Subscriber`]s.
+//!
+//! The model is parsed in Rust, which does not support every definition the
+//! C library does (`<union>`, `<bitmask>`, `<valuetype>`, `<include>`, ...).
+//! When it cannot be built the participant is created all the same: readers
+//! and writers are then looked up by the Connector alone, and their types are
+//! not checked.
+//!
+//! ```no_run
+//! use connector_dds::DomainParticipant;
+//!
+//! let participant = DomainParticipant::new(
+//!     "MyParticipantLibrary::MyPubParticipant",
+//!     "ShapeExample.xml",
+//! )
+//! .unwrap();
+//! let publisher = participant.publisher("MyPublisher").unwrap();
+//! for writer in publisher.writer_names() {
+//!     println!("{}", writer);
+//! }
+//! let square = participant.topic("Square").unwrap();
+//! println!("{}: {}", square.name, square.type_name);
+//! ```
+
+use std::path::Path;
+
+use rticonnector::builder::BuildError;
+use rticonnector::config::participants::Participant;
+use rticonnector::config::{Config, ConfigError};
+use rticonnector::{Connector, ConnectorBuilder};
+
+use crate::error::Error;
+use crate::publisher::Publisher;
+use crate::schema;
+use crate::subscriber::Subscriber;
+use crate::topic::{Topic, TopicType};
+
+/// A participant created from a `<domain_participant>` configuration.
+pub struct DomainParticipant {
+    name: String,
+    /// `None` when the configuration cannot be parsed in Rust.
+    config: Option<Config>,
+    connector: Connector,
+}
+
+impl DomainParticipant {
+    /// Creates the participant `config_name` (`"Library::Participant"`) of
+    /// the configuration file `config_file`.
+    pub fn new(config_name: &str, config_file: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::from_builder(Connector::builder(config_name).config_file(config_file))
+    }
+
+    /// Creates the participant of `builder`, with its configuration sources
+    /// and overrides.
+    ///
+    /// A configuration holding definitions the Rust model does not support
+    /// is left to the C library, unless there are overrides to apply to it;
+    /// any other error reading it is returned.
+    pub fn from_builder(builder: ConnectorBuilder) -> Result<Self, Error> {
+        let config = match builder.resolve() {
+            Ok(config) => Some(config),
+            Err(BuildError::Config(ConfigError::Unsupported { .. })) => None,
+            Err(error) => return Err(error.into()),
+        };
+        let name = builder.config_name().to_string();
+        if let Some(config) = &config {
+            if config.participant(&name).is_none() {
+                return Err(Error::UnknownParticipant(name));
+            }
+        }
+        let connector = builder.build()?;
+        Ok(Self {
+            name,
+            config,
+            connector,
+        })
+    }
+
+    /// The `"Library::Participant"` name of the participant.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn connector(&self) -> &Connector {
+        &self.connector
+    }
+
+    /// The configuration the participant was created from, overrides
+    /// included; `None` when it cannot be parsed in Rust.
+    pub fn config(&self) -> Option<&Config> {
+        self.config.as_ref()
+    }
+
+    /// The domain ID the participant joined, when the configuration sets it.
+    pub fn domain_id(&self) -> Option<u32> {
+        self.config.as_ref()?.domain_id(self.model()?)
+    }
+
+    /// Looks up a publisher by the name of its `<publisher>`. Without a
+    /// model of the configuration, any name is accepted.
+    pub fn publisher(&self, name: &str) -> Result<Publisher<'_>, Error> {
+        if self
+            .model()
+            .is_some_and(|model| !model.publishers.iter().any(|p| p.name == name))
+        {
+            return Err(Error::UnknownPublisher {
+                participant: self.name.clone(),
+                publisher: name.to_string(),
+            });
+        }
+        Ok(Publisher::new(self, name))
+    }
+
+    /// Looks up a subscriber by the name of its `<subscriber>`. Without a
+    /// model of the configuration, any name is accepted.
+    pub fn subscriber(&self, name: &str) -> Result<Subscriber<'_>, Error> {
+        if self
+            .model()
+            .is_some_and(|model| !model.subscribers.iter().any(|s| s.name == name))
+        {
+            return Err(Error::UnknownSubscriber {
+                participant: self.name.clone(),
+                subscriber: name.to_string(),
+            });
+        }
+        Ok(Subscriber::new(self, name))
+    }
+
+    /// The publishers of the participant; none without a model of the
+    /// configuration.
+    pub fn publishers(&self) -> impl Iterator<Item = Publisher<'_>> {
+        self.model()
+            .into_iter()
+            .flat_map(|model| &model.publishers)
+            .map(move |p| Publisher::new(self, &p.name))
+    }
+
+    /// The subscribers of the participant; none without a model of the
+    /// configuration.
+    pub fn subscribers(&self) -> impl Iterator<Item = Subscriber<'_>> {
+        self.model()
+            .into_iter()
+            .flat_map(|model| &model.subscribers)
+            .map(move |s| Subscriber::new(self, &s.name))
+    }
+
+    /// Looks up a topic the participant can use: one of its own or of its
+    /// domain, its own first.
+    pub fn topic(&self, name: &str) -> Option<Topic> {
+        let type_name = self.config.as_ref()?.topic_type(self.model()?, name)?;
+        Some(Topic {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+        })
+    }
+
+    /// The topics the participant can use, its own first, then the ones of
+    /// its domain that it does not redefine.
+    pub fn topics(&self) -> Vec<Topic> {
+        let (Some(config), Some(participant)) = (&self.config, self.model()) else {
+            return Vec::new();
+        };
+        let domain = participant
+            .domain_ref
+            .as_deref()
+            .and_then(|d| config.domain(d));
+        let mut topics: Vec<Topic> = Vec::new();
+        let names = participant
+            .topics
+            .iter()
+            .chain(domain.into_iter().flat_map(|d| &d.topics))
+            .map(|t| &t.name);
+        for name in names {
+            if topics.iter().any(|t| t.name == *name) {
+                continue;
+            }
+            if let Some(topic) = self.topic(name) {
+                topics.push(topic);
+            }
+        }
+        topics
+    }
+
+    /// Checks that `T` matches the type registered for `topic_ref`, the topic
+    /// of the reader or writer `entity`. A topic the configuration does not
+    /// resolve, or a configuration without a model, is left to the C library
+    /// to report.
+    pub(crate) fn check_type<T: TopicType>(
+        &self,
+        entity: &str,
+        topic_ref: &str,
+    ) -> Result<(), Error> {
+        let (Some(config), Some(participant)) = (&self.config, self.model()) else {
+            return Ok(());
+        };
+        let Some(type_name) = config.topic_type(participant, topic_ref) else {
+            return Ok(());
+        };
+        schema::check::<T>(&config.types, type_name).map_err(|source| Error::Schema {
+            entity: entity.to_string(),
+            source,
+        })
+    }
+
+    /// The model of the participant, checked to exist when it was created;
+    /// `None` when the configuration cannot be parsed in Rust.
+    pub(crate) fn model(&self) -> Option<&Participant> {
+        self.config.as_ref()?.participant(&self.name)
+    }
+}
@@ -0,0 +1,69 @@
+//! The error type of the DDS-style API.
+
+use rticonnector::builder::BuildError;
+use rticonnector::{de, ser};
+use thiserror::Error;
+
+use crate::schema::SchemaError;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Build(#[from] BuildError),
+    #[error("no participant `{0}` in the configuration")]
+    UnknownParticipant(String),
+    #[error("participant `{participant}` has no publisher `{publisher}`")]
+    UnknownPublisher {
+        participant: String,
+        publisher: String,
+    },
+    #[error("participant `{participant}` has no subscriber `{subscriber}`")]
+    UnknownSubscriber {
+        participant: String,
+        subscriber: String,
+    },
+    #[error("subscriber `{subscriber}` has no data reader `{reader}`")]
+    UnknownReader { subscriber: String, reader: String },
+    #[error("publisher `{publisher}` has no data writer `{writer}`")]
+    UnknownWriter { publisher: String, writer: String },
+    #[error("`{entity}`: {source}")]
+    Schema {
+        entity: String,
+        #[source]
+        source: SchemaError,
+    },
+    #[error("`{writer}`: the sample does not belong to the instance of the handle")]
+    InstanceMismatch { writer: String },
+    #[error("`{entity}`: {message}")]
+    Entity { entity: String, message: String },
+    #[error("`{0}`: timed out")]
+    Timeout(String),
+    #[error("`{reader}`: {source}")]
+    Deserialize {
+        reader: String,
+        #[source]
+        source: de::Error,
+    },
+    #[error("`{writer}`: {source}")]
+    Serialize {
+        writer: String,
+        #[source]
+        source: ser::Error,
+    },
+}
+
+impl Error {
+    /// An error raised by the C library on the reader or writer `entity`.
+    pub(crate) fn entity(entity: &str, message: impl std::fmt::Display) -> Self {
+        let last_error = rticonnector::Connector::get_last_error();
+        let message = if last_error.is_empty() {
+            message.to_string()
+        } else {
+            format!("{} ({})", message, last_error.trim())
+        };
+        Error::Entity {
+            entity: entity.to_string(),
+            message,
+        }
+    }
+}
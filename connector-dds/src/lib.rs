@@ -1,6 +1,13 @@
+pub mod cdr;
+pub mod domain;
+pub mod error;
+pub mod publisher;
+pub mod schema;
+pub mod subscriber;
+pub mod topic;
+pub mod util;
 
-mod domain;
-mod publisher;
-mod subscriber;
-mod topic;
-mod util;
\ No newline at end of file
+pub use connector_dds_derive::{dds_config, DdsType};
+pub use domain::DomainParticipant;
+pub use error::Error;
+pub use util::InstanceHandle;
\ No newline at end of file
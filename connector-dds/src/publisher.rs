@@ -1,104 +1,370 @@
-use std::{ffi::{c_char, c_int, c_void, CStr}, marker::PhantomData, ptr, time::Duration};
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    ffi::{c_char, c_int, c_void, CStr},
+    marker::PhantomData,
+    ptr,
+    time::Duration,
+};
 
+use rticonnector::config::participants;
 use rticonnector::Connector;
-use rticonnector_sys::{RTI_Connector_get_matched_publications, RTI_Connector_wait_for_acknowledgments, RTI_Connector_wait_for_matched_subscription};
+use rticonnector_sys::{
+    RTI_Connector_free_string, RTI_Connector_get_matched_subscriptions,
+    RTI_Connector_wait_for_acknowledgments, RTI_Connector_wait_for_matched_subscription,
+};
+use serde::Serialize;
+use serde_json::Value;
 
+use crate::domain::DomainParticipant;
+use crate::error::Error;
+use crate::subscriber::SampleIdentity;
 use crate::topic::TopicType;
+use crate::util::{self, InstanceHandle};
 
+/// A `<publisher>` of a [`DomainParticipant`].
+pub struct Publisher<'a> {
+    participant: &'a DomainParticipant,
+    name: String,
+}
+
+impl<'a> Publisher<'a> {
+    pub(crate) fn new(participant: &'a DomainParticipant, name: &str) -> Self {
+        Self {
+            participant,
+            name: name.to_string(),
+        }
+    }
 
-pub struct Publisher;
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn participant(&self) -> &'a DomainParticipant {
+        self.participant
+    }
+
+    /// The `"Publisher::Writer"` names of the publisher's data writers; none
+    /// without a model of the configuration.
+    pub fn writer_names(&self) -> Vec<String> {
+        self.model()
+            .into_iter()
+            .flat_map(|model| &model.writers)
+            .map(|writer| format!("{}::{}", self.name, writer.name))
+            .collect()
+    }
 
-impl Publisher {
-    
+    /// Looks up the data writer `name`, the name of its `<data_writer>`, and
+    /// binds it to `T`, once checked against the type of its topic with
+    /// [`schema::check`](crate::schema::check).
+    pub fn datawriter<T: TopicType>(&self, name: &str) -> Result<DataWriter<'a, T>, Error> {
+        let name = match self.model() {
+            Some(model) => {
+                let Some(writer) = model.writers.iter().find(|w| w.name == name) else {
+                    return Err(Error::UnknownWriter {
+                        publisher: self.name.clone(),
+                        writer: name.to_string(),
+                    });
+                };
+                let name = format!("{}::{}", self.name, name);
+                self.participant.check_type::<T>(&name, &writer.topic_ref)?;
+                name
+            }
+            // Left to the Connector to look up.
+            None => format!("{}::{}", self.name, name),
+        };
+        DataWriter::new(self.participant.connector(), &name)
+    }
+
+    /// The model of the publisher, checked to exist when it was looked up;
+    /// `None` without a model of the configuration.
+    pub(crate) fn model(&self) -> Option<&'a participants::Publisher> {
+        self.participant
+            .model()?
+            .publishers
+            .iter()
+            .find(|p| p.name == self.name)
+    }
 }
 
+/// The optional parameters of [`DataWriter::write_with`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriteParams {
+    /// Nanoseconds since the Unix epoch; the current time when unset.
+    pub source_timestamp: Option<i64>,
+    /// The identity of the sample; assigned by the writer when unset.
+    pub identity: Option<SampleIdentity>,
+    /// The identity of the sample this one relates to, such as the request
+    /// a reply answers.
+    pub related_sample_identity: Option<SampleIdentity>,
+}
+
+impl WriteParams {
+    pub fn with_source_timestamp(mut self, source_timestamp: i64) -> Self {
+        self.source_timestamp = Some(source_timestamp);
+        self
+    }
+
+    pub fn with_identity(mut self, identity: SampleIdentity) -> Self {
+        self.identity = Some(identity);
+        self
+    }
 
-pub struct DataWriter<'a, T> where T : TopicType<'a> {
+    pub fn with_related_sample_identity(mut self, identity: SampleIdentity) -> Self {
+        self.related_sample_identity = Some(identity);
+        self
+    }
+
+    /// The `params_json` of `RTI_Connector_write`, with the `action` of the
+    /// write, such as `"dispose"`, when it is not a plain write.
+    pub fn to_json(self, action: Option<&str>) -> String {
+        let mut params = serde_json::Map::new();
+        if let Some(action) = action {
+            params.insert("action".to_string(), action.into());
+        }
+        if let Some(source_timestamp) = self.source_timestamp {
+            params.insert("source_timestamp".to_string(), source_timestamp.into());
+        }
+        if let Some(identity) = self.identity {
+            params.insert("identity".to_string(), identity.to_json());
+        }
+        if let Some(identity) = self.related_sample_identity {
+            params.insert("related_sample_identity".to_string(), identity.to_json());
+        }
+        serde_json::Value::Object(params).to_string()
+    }
+}
+
+/// A data writer that writes values of type `T`.
+///
+/// The instance a sample belongs to is identified by the key members of the
+/// DDS type, which the C library reads from the sample; [`dispose`] and
+/// [`unregister`] only need those to be set. The writer keeps track of the
+/// instances it registered or wrote, as [`InstanceHandle`]s.
+///
+/// [`dispose`]: DataWriter::dispose
+/// [`unregister`]: DataWriter::unregister
+pub struct DataWriter<'a, T>
+where
+    T: TopicType,
+{
     pub(crate) connector: &'a Connector,
     pub(crate) data_writer: *mut c_void,
+    name: String,
+    key_members: Vec<String>,
+    /// The instances registered and not unregistered since.
+    instances: RefCell<HashSet<InstanceHandle>>,
     _marker: PhantomData<T>,
 }
 
-impl <'a, T> DataWriter<'a, T> where T : TopicType<'a> {
-
-    pub(crate) fn  new(connector: &'a Connector, data_writer: *mut c_void) -> DataWriter<'a, T>  {
-        
-
-        Self {
+impl<'a, T> DataWriter<'a, T>
+where
+    T: TopicType,
+{
+    pub(crate) fn new(connector: &'a Connector, name: &str) -> Result<DataWriter<'a, T>, Error> {
+        let data_writer = connector
+            .get_dynamic_datawriter(name)
+            .map_err(|e| Error::entity(name, e))?
+            .as_ptr();
+        let key_members = util::key_members::<T>().map_err(|source| Error::Schema {
+            entity: name.to_string(),
+            source,
+        })?;
+        Ok(Self {
             connector,
             data_writer,
+            name: name.to_string(),
+            key_members,
+            instances: RefCell::new(HashSet::new()),
             _marker: PhantomData,
+        })
+    }
+
+    /// The `"Publisher::Writer"` name of the writer.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Writes `value`, stamped with the current time, registering its
+    /// instance.
+    pub fn write(&self, value: &T) -> Result<(), Error> {
+        self.write_with(value, WriteParams::default())
+    }
+
+    /// Writes `value` with the source timestamp and identities of `params`,
+    /// registering its instance.
+    pub fn write_with(&self, value: &T, params: WriteParams) -> Result<(), Error> {
+        let handle = self.instance_handle(value)?;
+        self.write_action(value, None, params)?;
+        self.instances.borrow_mut().insert(handle);
+        Ok(())
+    }
+
+    /// Writes `value`, which must belong to the instance of `handle`.
+    pub fn write_with_handle(&self, value: &T, handle: &InstanceHandle) -> Result<(), Error> {
+        if self.instance_handle(value)? != *handle {
+            return Err(Error::InstanceMismatch {
+                writer: self.name.clone(),
+            });
         }
+        self.write(value)
+    }
+
+    /// Disposes of the instance of `value`.
+    pub fn dispose(&self, value: &T) -> Result<(), Error> {
+        self.write_action(value, Some("dispose"), WriteParams::default())
+    }
+
+    /// Disposes of the instance of `handle`.
+    pub fn dispose_instance(&self, handle: &InstanceHandle) -> Result<(), Error> {
+        self.write_action(&handle.key(), Some("dispose"), WriteParams::default())
+    }
+
+    /// Tells the readers this writer no longer updates the instance of
+    /// `value`.
+    pub fn unregister(&self, value: &T) -> Result<(), Error> {
+        let handle = self.instance_handle(value)?;
+        self.write_action(value, Some("unregister"), WriteParams::default())?;
+        self.instances.borrow_mut().remove(&handle);
+        Ok(())
+    }
+
+    /// Tells the readers this writer no longer updates the instance of
+    /// `handle`.
+    pub fn unregister_instance(&self, handle: &InstanceHandle) -> Result<(), Error> {
+        self.write_action(&handle.key(), Some("unregister"), WriteParams::default())?;
+        self.instances.borrow_mut().remove(handle);
+        Ok(())
     }
 
-    /// Safe wrapper for `RTI_Connector_wait_for_acknowledgments`
-    pub fn wait_for_acknowledgments(&self, timeout: Duration) -> Result<i32, &'static str> {
-        // Call the unsafe FFI function
+    /// Registers the instance of `value`, whose key members are the only
+    /// ones read.
+    ///
+    /// Nothing is sent to the middleware: the instance is announced by its
+    /// first write. The [`InstanceHandle`] returned is a local construct
+    /// built from the key members, not the DDS instance handle, and is only
+    /// known to this writer.
+    pub fn register_instance(&self, value: &T) -> Result<InstanceHandle, Error> {
+        let handle = self.instance_handle(value)?;
+        self.instances.borrow_mut().insert(handle.clone());
+        Ok(handle)
+    }
+
+    /// The handle of the instance of `value`, if the writer registered it.
+    pub fn lookup_instance(&self, value: &T) -> Result<Option<InstanceHandle>, Error> {
+        let handle = self.instance_handle(value)?;
+        Ok(self.instances.borrow().contains(&handle).then_some(handle))
+    }
+
+    fn instance_handle(&self, value: &T) -> Result<InstanceHandle, Error> {
+        InstanceHandle::from_value(&self.key_members, value)
+            .map_err(|e| Error::entity(&self.name, e))
+    }
+
+    fn write_action<S>(
+        &self,
+        value: &S,
+        action: Option<&str>,
+        params: WriteParams,
+    ) -> Result<(), Error>
+    where
+        S: Serialize + ?Sized,
+    {
+        let name = &self.name;
+        self.connector
+            .clear(name)
+            .map_err(|e| Error::entity(name, e))?;
+        self.connector
+            .serialize_instance(name, value)
+            .map_err(|source| Error::Serialize {
+                writer: name.clone(),
+                source,
+            })?;
+        self.connector
+            .write(name, &params.to_json(action))
+            .map_err(|e| Error::entity(name, e))
+    }
+
+    /// Waits for the number of acknowledgments of the samples written to
+    /// change, and returns the new count.
+    pub fn wait_for_acknowledgments(&self, timeout: Duration) -> Result<i32, Error> {
         let new_count = unsafe {
-            RTI_Connector_wait_for_acknowledgments(self.data_writer, timeout.as_millis() as i32)
+            RTI_Connector_wait_for_acknowledgments(self.data_writer, timeout_millis(timeout))
         };
-
-        // Check if the function call was successful
         if new_count < 0 {
-            return Err("Error waiting for acknowledgments.");
+            return Err(Error::entity(
+                &self.name,
+                "waiting for acknowledgments failed or timed out",
+            ));
         }
-
         Ok(new_count)
     }
-    /// Safe wrapper for `RTI_Connector_wait_for_matched_subscription`
-    pub fn wait_for_matched_subscription(&self, ms_timeout: i32) -> Result<i32, &'static str> {
-        // Ensure the writer pointer is not null
-        if self.data_writer.is_null() {
-            return Err("Null pointer: RTI data writer instance does not exist.");
-        }
 
-        // Variable to hold the new count of matched subscriptions
+    /// Waits for the number of matched subscriptions to change, and returns
+    /// by how much it did.
+    pub fn wait_for_matched_subscription(&self, timeout: Duration) -> Result<i32, Error> {
         let mut current_count_change: c_int = 0;
-
-        // Call the unsafe FFI function
         let result = unsafe {
             RTI_Connector_wait_for_matched_subscription(
                 self.data_writer,
-                ms_timeout,
+                timeout_millis(timeout),
                 &mut current_count_change,
             )
         };
-
-        // Check if the function call was successful
         if result != 0 {
-            return Err("Error waiting for matched subscriptions or timeout occurred.");
+            return Err(Error::entity(
+                &self.name,
+                "waiting for matched subscriptions failed or timed out",
+            ));
         }
-
         Ok(current_count_change)
     }
-    /// Safe Rust wrapper for `RTI_Connector_get_matched_publications`
-    pub fn get_matched_publications(&self) -> Result<T, String> {
-        let mut json_ptr: *mut c_char = ptr::null_mut();
 
-        // Call the unsafe FFI function
+    /// The subscriptions the writer is matched with.
+    pub fn matched_subscriptions(&self) -> Result<Vec<MatchedSubscription>, Error> {
+        let mut json_ptr: *mut c_char = ptr::null_mut();
         let result =
-            unsafe { RTI_Connector_get_matched_publications(self.data_writer, &mut json_ptr) };
-
-        // Check if the function call was successful
-        if result != 0 {
-            return Err(format!("Data writer error code: {}", result));
-        }
-
-        // Ensure the pointer is not null
-        if json_ptr.is_null() {
-            return Err("ConnectorError::NullPointer".to_string());
+            unsafe { RTI_Connector_get_matched_subscriptions(self.data_writer, &mut json_ptr) };
+        if result != 0 || json_ptr.is_null() {
+            return Err(Error::entity(
+                &self.name,
+                "cannot get the matched subscriptions",
+            ));
         }
+        let json = unsafe {
+            let json = CStr::from_ptr(json_ptr).to_string_lossy().into_owned();
+            RTI_Connector_free_string(json_ptr);
+            json
+        };
+        MatchedSubscription::from_json(&json).map_err(|e| Error::entity(&self.name, e))
+    }
+}
 
-        // Convert the C string to a Rust String
-        unsafe {
-            let c_str = CStr::from_ptr(json_ptr);
-            let json_str  = c_str.to_str().expect("Unable to convert c string int &str");
+/// The `timeout` of the C library's waits, in milliseconds, saturated to
+/// what a `c_int` holds.
+fn timeout_millis(timeout: Duration) -> c_int {
+    timeout.as_millis().try_into().unwrap_or(c_int::MAX)
+}
 
-            // Free the memory if needed (depending on how the memory is managed in the FFI)
-            // For example: libc::free(json_ptr) or a corresponding RTI free function.
+/// A subscription matched with a [`DataWriter`], as the C library describes
+/// it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedSubscription {
+    /// The name of the data reader, such as `"MySubscriber::MySquareReader"`;
+    /// `None` for a reader without one.
+    pub name: Option<String>,
+}
 
-            let result: T = serde_json::from_str(json_str).unwrap();
-            Ok(result)
-        }
+impl MatchedSubscription {
+    /// Parses the JSON the C library returns for the matched subscriptions:
+    /// a list of `{"name": ...}` objects.
+    pub fn from_json(json: &str) -> serde_json::Result<Vec<Self>> {
+        let subscriptions: Vec<Value> = serde_json::from_str(json)?;
+        Ok(subscriptions
+            .iter()
+            .map(|subscription| Self {
+                name: subscription["name"].as_str().map(str::to_string),
+            })
+            .collect())
     }
-}
\ No newline at end of file
+}
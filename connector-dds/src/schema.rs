@@ -0,0 +1,342 @@
+//! Comparison of a [`TopicType`] with the DDS type registered for its topic.
+//!
+//! Typed readers and writers are checked when they are created, so a Rust
+//! type that drifted from the XML is reported up front rather than as
+//! dropped fields or failed writes. Members are matched by name, including
+//! the ones of base types, and compared by kind, key, optionality, array
+//! dimensions and bounds; enums are compared enumerator by enumerator.
+//!
+//! Kinds the Connector exchanges the same way through JSON are not told
+//! apart: `octet` and `uint8`, `char8` and `char16`, `double` and
+//! `longDouble`, `string` and `wstring`. A Rust bound tighter than the DDS
+//! one is accepted, since every value it allows fits.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use rticonnector::config::types::{Bound, Member, MemberType, TypeDefinition, TypeKind, Types};
+use rticonnector::config::ConfigError;
+use thiserror::Error;
+
+use crate::topic::TopicType;
+
+/// Error returned by [`check`].
+#[derive(Debug, Error)]
+pub enum SchemaError {
+    #[error("the types of `{type_name}` are invalid: {source}")]
+    InvalidRustType {
+        type_name: String,
+        #[source]
+        source: ConfigError,
+    },
+    #[error("no type `{0}` in the configuration")]
+    UnknownType(String),
+    #[error("{0}")]
+    Mismatch(Mismatch),
+}
+
+/// The incompatibilities between a Rust type and a DDS type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub rust_type: String,
+    pub dds_type: String,
+    pub differences: Vec<Difference>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` does not match the DDS type `{}` (- DDS, + Rust):",
+            self.rust_type, self.dds_type
+        )?;
+        for difference in &self.differences {
+            write!(f, "\n{}", difference)?;
+        }
+        Ok(())
+    }
+}
+
+/// A member or enumerator declared differently on each side; `None` where it
+/// is missing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// Dot-separated from the checked type, such as `pos.x`; `fill::RED` for
+    /// an enumerator of the enum of the member `fill`.
+    pub path: String,
+    pub dds: Option<String>,
+    pub rust: Option<String>,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = Vec::new();
+        if let Some(dds) = &self.dds {
+            lines.push(format!("  - {}: {}", self.path, dds));
+        }
+        if let Some(rust) = &self.rust {
+            lines.push(format!("  + {}: {}", self.path, rust));
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Checks that `T` matches the definition `type_name` of `types`, returning
+/// every incompatibility at once.
+pub fn check<T: TopicType>(types: &Types, type_name: &str) -> Result<(), SchemaError> {
+    let rust = rust_types::<T>()?;
+    if types.get(type_name).is_none() {
+        return Err(SchemaError::UnknownType(type_name.to_string()));
+    }
+    let mut comparison = Comparison {
+        dds: types,
+        rust: &rust,
+        visited: HashSet::new(),
+        differences: Vec::new(),
+    };
+    comparison.definitions("", type_name, T::TYPE_NAME);
+    if comparison.differences.is_empty() {
+        return Ok(());
+    }
+    Err(SchemaError::Mismatch(Mismatch {
+        rust_type: T::TYPE_NAME.to_string(),
+        dds_type: type_name.to_string(),
+        differences: comparison.differences,
+    }))
+}
+
+/// The types declared by `T`, parsed from [`TopicType::types_xml`].
+pub(crate) fn rust_types<T: TopicType>() -> Result<Types, SchemaError> {
+    Types::from_xml(&format!("<dds>{}</dds>", T::types_xml())).map_err(|source| {
+        SchemaError::InvalidRustType {
+            type_name: T::TYPE_NAME.to_string(),
+            source,
+        }
+    })
+}
+
+struct Comparison<'a> {
+    dds: &'a Types,
+    rust: &'a Types,
+    /// The pairs of definitions already compared, so shared and recursive
+    /// types are compared once.
+    visited: HashSet<(String, String)>,
+    differences: Vec<Difference>,
+}
+
+impl Comparison<'_> {
+    fn differ(&mut self, path: &str, dds: Option<String>, rust: Option<String>) {
+        self.differences.push(Difference {
+            path: path.to_string(),
+            dds,
+            rust,
+        });
+    }
+
+    /// Compares the definitions `dds` and `rust`; a pair that is not two
+    /// structs, two enums or two typedefs is a difference.
+    fn definitions(&mut self, path: &str, dds: &str, rust: &str) {
+        if !self.visited.insert((dds.to_string(), rust.to_string())) {
+            return;
+        }
+        match (self.dds.get(dds), self.rust.get(rust)) {
+            (Some(TypeDefinition::Struct(d)), Some(TypeDefinition::Struct(r))) => {
+                let dds_members = self.dds.all_members(d);
+                let rust_members = self.rust.all_members(r);
+                for member in &dds_members {
+                    let path = join(path, &member.name);
+                    match rust_members.iter().find(|m| m.name == member.name) {
+                        Some(rust_member) => self.members(&path, member, rust_member),
+                        None => self.differ(&path, Some(self.describe_dds(member)), None),
+                    }
+                }
+                for member in &rust_members {
+                    if !dds_members.iter().any(|m| m.name == member.name) {
+                        let path = join(path, &member.name);
+                        self.differ(&path, None, Some(self.describe_rust(member)));
+                    }
+                }
+            }
+            (Some(TypeDefinition::Enum(d)), Some(TypeDefinition::Enum(r))) => {
+                for enumerator in &d.enumerators {
+                    let path = format!("{}::{}", path, enumerator.name);
+                    let rust_value = r
+                        .enumerators
+                        .iter()
+                        .find(|e| e.name == enumerator.name)
+                        .map(|e| e.value);
+                    if rust_value != Some(enumerator.value) {
+                        let rust_value = rust_value.map(|v| format!("= {}", v));
+                        self.differ(&path, Some(format!("= {}", enumerator.value)), rust_value);
+                    }
+                }
+                for enumerator in &r.enumerators {
+                    if !d.enumerators.iter().any(|e| e.name == enumerator.name) {
+                        let path = format!("{}::{}", path, enumerator.name);
+                        self.differ(&path, None, Some(format!("= {}", enumerator.value)));
+                    }
+                }
+            }
+            (Some(TypeDefinition::Typedef(d)), Some(TypeDefinition::Typedef(r))) => {
+                let dds_member = Member::new(&d.name, d.member_type.clone());
+                let rust_member = Member::new(&r.name, r.member_type.clone());
+                self.members(path, &dds_member, &rust_member);
+            }
+            (d, r) => {
+                let path = match path {
+                    "" => dds,
+                    path => path,
+                };
+                self.differ(path, definition(d, dds), definition(r, rust));
+            }
+        }
+    }
+
+    fn members(&mut self, path: &str, dds: &Member, rust: &Member) {
+        let dds_type = self.dds.resolve_typedefs(&dds.member_type);
+        let rust_type = self.rust.resolve_typedefs(&rust.member_type);
+        let dds_shape = shape(self.dds, &dds_type, dds.key, dds.optional);
+        let rust_shape = shape(self.rust, &rust_type, rust.key, rust.optional);
+        let within = |dds_bound: &Option<Bound>, rust_bound: &Option<Bound>| match self
+            .dds_length(dds_bound)
+        {
+            None => true,
+            Some(dds_length) => self
+                .rust_length(rust_bound)
+                .is_some_and(|rust_length| rust_length <= dds_length),
+        };
+        let bounded = within(&dds_type.string_max_length, &rust_type.string_max_length)
+            && within(
+                &dds_type.sequence_max_length,
+                &rust_type.sequence_max_length,
+            );
+        if dds_shape != rust_shape || !bounded {
+            self.differ(
+                path,
+                Some(self.describe_dds(dds)),
+                Some(self.describe_rust(rust)),
+            );
+            return;
+        }
+        if let (TypeKind::NonBasic(d), TypeKind::NonBasic(r)) = (&dds_type.kind, &rust_type.kind) {
+            self.definitions(path, d, r);
+        }
+    }
+
+    fn dds_length(&self, bound: &Option<Bound>) -> Option<u32> {
+        self.dds.resolve_bound(bound.as_ref()?)
+    }
+
+    /// A bound of the Rust type may name a `<const>` of the configuration.
+    fn rust_length(&self, bound: &Option<Bound>) -> Option<u32> {
+        let bound = bound.as_ref()?;
+        self.rust
+            .resolve_bound(bound)
+            .or_else(|| self.dds.resolve_bound(bound))
+    }
+
+    fn describe_dds(&self, member: &Member) -> String {
+        let member_type = self.dds.resolve_typedefs(&member.member_type);
+        describe(self.dds, &member_type, member, |b| self.dds_length(b))
+    }
+
+    fn describe_rust(&self, member: &Member) -> String {
+        let member_type = self.rust.resolve_typedefs(&member.member_type);
+        describe(self.rust, &member_type, member, |b| self.rust_length(b))
+    }
+}
+
+/// Describes the definition `name`, such as `struct ShapeType`; `None` when
+/// it is missing.
+fn definition(definition: Option<&TypeDefinition>, name: &str) -> Option<String> {
+    let what = match definition? {
+        TypeDefinition::Struct(_) => "struct",
+        TypeDefinition::Enum(_) => "enum",
+        TypeDefinition::Typedef(_) => "typedef",
+        TypeDefinition::Const(_) => "const",
+    };
+    Some(format!("{} {}", what, name))
+}
+
+/// What must be equal on both sides: the kind, whether it is a sequence, the
+/// array dimensions, key and optional.
+fn shape(types: &Types, member_type: &MemberType, key: bool, optional: bool) -> String {
+    let dimensions: Vec<String> = member_type
+        .array_dimensions
+        .iter()
+        .map(|d| {
+            types
+                .resolve_bound(d)
+                .map_or("?".to_string(), |d| d.to_string())
+        })
+        .collect();
+    format!(
+        "{} {} {:?} {} {}",
+        kind(types, &member_type.kind),
+        member_type.is_sequence(),
+        dimensions,
+        key,
+        optional
+    )
+}
+
+/// The kind of a member, with the equivalent kinds merged and the nested
+/// types named by what they are, since their names may differ.
+fn kind(types: &Types, kind: &TypeKind) -> String {
+    match kind {
+        TypeKind::UInt8 => "octet".to_string(),
+        TypeKind::Char16 => "char8".to_string(),
+        TypeKind::LongDouble => "double".to_string(),
+        TypeKind::WString => "string".to_string(),
+        TypeKind::NonBasic(name) => match types.get(name) {
+            Some(TypeDefinition::Struct(_)) => "struct".to_string(),
+            Some(TypeDefinition::Enum(_)) => "enum".to_string(),
+            Some(TypeDefinition::Typedef(_)) => "typedef".to_string(),
+            _ => name.clone(),
+        },
+        other => other.xml_name().to_string(),
+    }
+}
+
+/// Describes a member IDL-style, such as `sequence<string<64>, 10>[2] @key`.
+fn describe(
+    types: &Types,
+    member_type: &MemberType,
+    member: &Member,
+    length: impl Fn(&Option<Bound>) -> Option<u32>,
+) -> String {
+    let mut description = match &member_type.kind {
+        TypeKind::NonBasic(name) => format!("{} {}", kind(types, &member_type.kind), name),
+        other => other.xml_name().to_string(),
+    };
+    if let Some(string_length) = length(&member_type.string_max_length) {
+        description = format!("{}<{}>", description, string_length);
+    }
+    if member_type.is_sequence() {
+        description = match length(&member_type.sequence_max_length) {
+            Some(sequence_length) => format!("sequence<{}, {}>", description, sequence_length),
+            None => format!("sequence<{}>", description),
+        };
+    }
+    for dimension in &member_type.array_dimensions {
+        match types.resolve_bound(dimension) {
+            Some(dimension) => description.push_str(&format!("[{}]", dimension)),
+            None => description.push_str("[?]"),
+        }
+    }
+    if member.key {
+        description.push_str(" @key");
+    }
+    if member.optional {
+        description.push_str(" @optional");
+    }
+    description
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
@@ -0,0 +1,467 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use rticonnector::config::participants;
+use rticonnector::{Connector, ConnectorError, DynamicDataReader};
+use serde_json::Value;
+
+use crate::domain::DomainParticipant;
+use crate::error::Error;
+use crate::topic::TopicType;
+use crate::util::{self, InstanceHandle};
+
+/// A `<subscriber>` of a [`DomainParticipant`].
+pub struct Subscriber<'a> {
+    participant: &'a DomainParticipant,
+    name: String,
+}
+
+impl<'a> Subscriber<'a> {
+    pub(crate) fn new(participant: &'a DomainParticipant, name: &str) -> Self {
+        Self {
+            participant,
+            name: name.to_string(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn participant(&self) -> &'a DomainParticipant {
+        self.participant
+    }
+
+    /// The `"Subscriber::Reader"` names of the subscriber's data readers; none
+    /// without a model of the configuration.
+    pub fn reader_names(&self) -> Vec<String> {
+        self.model()
+            .into_iter()
+            .flat_map(|model| &model.readers)
+            .map(|reader| format!("{}::{}", self.name, reader.name))
+            .collect()
+    }
+
+    /// Looks up the data reader `name`, the name of its `<data_reader>`, and
+    /// binds it to `T`, once checked against the type of its topic with
+    /// [`schema::check`](crate::schema::check).
+    pub fn datareader<T: TopicType>(&self, name: &str) -> Result<DataReader<'a, T>, Error> {
+        let name = match self.model() {
+            Some(model) => {
+                let Some(reader) = model.readers.iter().find(|r| r.name == name) else {
+                    return Err(Error::UnknownReader {
+                        subscriber: self.name.clone(),
+                        reader: name.to_string(),
+                    });
+                };
+                let name = format!("{}::{}", self.name, name);
+                self.participant.check_type::<T>(&name, &reader.topic_ref)?;
+                name
+            }
+            // Left to the Connector to look up.
+            None => format!("{}::{}", self.name, name),
+        };
+        DataReader::new(self.participant.connector(), &name)
+    }
+
+    /// The model of the subscriber, checked to exist when it was looked up;
+    /// `None` without a model of the configuration.
+    pub(crate) fn model(&self) -> Option<&'a participants::Subscriber> {
+        self.participant
+            .model()?
+            .subscribers
+            .iter()
+            .find(|s| s.name == self.name)
+    }
+}
+
+/// A data reader whose samples are read as `T`.
+///
+/// Samples are deserialized member by member with
+/// [`rticonnector::de::from_sample`], so an error names the field that could
+/// not be read. Samples without valid data, which only notify a change of
+/// instance state, are left out, except from the [`Instance`]s of
+/// [`read_instances`](DataReader::read_instances) and
+/// [`take_instances`](DataReader::take_instances).
+pub struct DataReader<'a, T> {
+    connector: &'a Connector,
+    name: String,
+    reader: DynamicDataReader<'a>,
+    key_members: Vec<String>,
+    /// The state of every instance the reader loaded a sample of, until it
+    /// is no longer alive and its samples are taken.
+    instances: RefCell<HashMap<InstanceHandle, Option<InstanceState>>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: TopicType> DataReader<'a, T> {
+    pub(crate) fn new(connector: &'a Connector, name: &str) -> Result<Self, Error> {
+        let reader = connector
+            .get_dynamic_datareader(name)
+            .map_err(|e| Error::entity(name, e))?;
+        let key_members = util::key_members::<T>().map_err(|source| Error::Schema {
+            entity: name.to_string(),
+            source,
+        })?;
+        Ok(Self {
+            connector,
+            name: name.to_string(),
+            reader,
+            key_members,
+            instances: RefCell::new(HashMap::new()),
+            _marker: PhantomData,
+        })
+    }
+
+    /// The `"Subscriber::Reader"` name of the reader.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Waits until data is available or `timeout` elapses, which is reported
+    /// as [`Error::Timeout`].
+    pub fn wait(&self, timeout: Duration) -> Result<(), Error> {
+        match self.reader.wait_for_data_on_reader(timeout) {
+            Ok(_) => Ok(()),
+            Err(ConnectorError::Timeout) => Err(Error::Timeout(self.name.clone())),
+            Err(e) => Err(Error::entity(&self.name, e)),
+        }
+    }
+
+    /// The available samples, left in the reader's cache.
+    pub fn read(&self) -> Result<Vec<T>, Error> {
+        Ok(self
+            .load(false)?
+            .into_iter()
+            .filter_map(|loaded| loaded.sample)
+            .collect())
+    }
+
+    /// The available samples, removed from the reader's cache.
+    pub fn take(&self) -> Result<Vec<T>, Error> {
+        Ok(self
+            .load(true)?
+            .into_iter()
+            .filter_map(|loaded| loaded.sample)
+            .collect())
+    }
+
+    /// The available samples with their infos, left in the reader's cache.
+    pub fn read_with_info(&self) -> Result<Vec<(T, SampleInfo)>, Error> {
+        Ok(with_info(self.load(false)?))
+    }
+
+    /// The available samples with their infos, removed from the reader's
+    /// cache.
+    pub fn take_with_info(&self) -> Result<Vec<(T, SampleInfo)>, Error> {
+        Ok(with_info(self.load(true)?))
+    }
+
+    /// The available samples grouped by instance, left in the reader's
+    /// cache.
+    pub fn read_instances(&self) -> Result<Vec<Instance<T>>, Error> {
+        Ok(by_instance(self.load(false)?))
+    }
+
+    /// The available samples grouped by instance, removed from the reader's
+    /// cache.
+    pub fn take_instances(&self) -> Result<Vec<Instance<T>>, Error> {
+        Ok(by_instance(self.load(true)?))
+    }
+
+    /// The handle of the instance of `value`, whose key members are the only
+    /// ones read, if the reader loaded a sample of it. An instance disposed
+    /// of or without writers is forgotten once its samples are taken.
+    pub fn lookup_instance(&self, value: &T) -> Result<Option<InstanceHandle>, Error> {
+        let handle = InstanceHandle::from_value(&self.key_members, value)
+            .map_err(|e| Error::entity(&self.name, e))?;
+        Ok(self
+            .instances
+            .borrow()
+            .contains_key(&handle)
+            .then_some(handle))
+    }
+
+    /// The state of the instance of `handle` as of the last sample of it the
+    /// reader loaded.
+    pub fn instance_state(&self, handle: &InstanceHandle) -> Option<InstanceState> {
+        self.instances.borrow().get(handle).copied().flatten()
+    }
+
+    /// Waits for the number of matched publications to change, and returns
+    /// by how much it did.
+    pub fn wait_for_matched_publication(&self, timeout: Duration) -> Result<i32, Error> {
+        self.reader
+            .wait_for_matched_publication(timeout.as_millis().try_into().unwrap_or(i32::MAX))
+            .map_err(|e| Error::entity(&self.name, e))
+    }
+
+    /// The publications the reader is matched with.
+    pub fn matched_publications(&self) -> Result<Vec<MatchedPublication>, Error> {
+        let json = self
+            .reader
+            .get_matched_publications()
+            .map_err(|e| Error::entity(&self.name, e))?;
+        MatchedPublication::from_json(&json).map_err(|e| Error::entity(&self.name, e))
+    }
+
+    /// Loads every available sample, with or without valid data, and
+    /// records the state of their instances.
+    fn load(&self, take: bool) -> Result<Vec<Loaded<T>>, Error> {
+        let name = &self.name;
+        let loaded = if take {
+            self.connector.take(name)
+        } else {
+            self.connector.read(name)
+        };
+        loaded.map_err(|e| Error::entity(name, e))?;
+        let count = self
+            .connector
+            .get_sample_count(name)
+            .map_err(|e| Error::entity(name, e))? as usize;
+        let mut samples = Vec::with_capacity(count);
+        for index in 1..=count {
+            let info = SampleInfo::load(self.connector, name, index)?;
+            // A sample without valid data only has its key members set.
+            let json = self
+                .connector
+                .get_json_sample(name, index)
+                .map_err(|e| Error::entity(name, e))?;
+            let json: Value = serde_json::from_str(&json).map_err(|e| Error::entity(name, e))?;
+            let handle = InstanceHandle::from_sample(&self.key_members, &json);
+            let sample = if info.valid_data {
+                let sample = self
+                    .connector
+                    .deserialize_sample(name, index)
+                    .map_err(|source| Error::Deserialize {
+                        reader: name.clone(),
+                        source,
+                    })?;
+                Some(sample)
+            } else {
+                None
+            };
+            self.instances
+                .borrow_mut()
+                .insert(handle.clone(), info.instance_state);
+            samples.push(Loaded {
+                handle,
+                info,
+                sample,
+            });
+        }
+        if take {
+            // Nothing is left in the cache: only the live instances can
+            // have more samples.
+            self.instances.borrow_mut().retain(|_, state| {
+                !matches!(
+                    state,
+                    Some(InstanceState::NotAliveDisposed | InstanceState::NotAliveNoWriters)
+                )
+            });
+        }
+        Ok(samples)
+    }
+}
+
+/// A sample loaded by a [`DataReader`]; `sample` is `None` without valid
+/// data.
+struct Loaded<T> {
+    handle: InstanceHandle,
+    info: SampleInfo,
+    sample: Option<T>,
+}
+
+fn with_info<T>(loaded: Vec<Loaded<T>>) -> Vec<(T, SampleInfo)> {
+    loaded
+        .into_iter()
+        .filter_map(|loaded| Some((loaded.sample?, loaded.info)))
+        .collect()
+}
+
+fn by_instance<T>(loaded: Vec<Loaded<T>>) -> Vec<Instance<T>> {
+    let mut instances: Vec<Instance<T>> = Vec::new();
+    for loaded in loaded {
+        let position = match instances.iter().position(|i| i.handle == loaded.handle) {
+            Some(position) => position,
+            None => {
+                instances.push(Instance {
+                    handle: loaded.handle,
+                    instance_state: None,
+                    samples: Vec::new(),
+                });
+                instances.len() - 1
+            }
+        };
+        let instance = &mut instances[position];
+        instance.instance_state = loaded.info.instance_state;
+        if let Some(sample) = loaded.sample {
+            instance.samples.push((sample, loaded.info));
+        }
+    }
+    instances
+}
+
+/// The samples of one instance loaded by a [`DataReader`], in the order they
+/// were received.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instance<T> {
+    pub handle: InstanceHandle,
+    /// The state of the instance as of its last loaded sample, including the
+    /// samples without valid data.
+    pub instance_state: Option<InstanceState>,
+    /// The samples with valid data; none when the instance was only disposed
+    /// or unregistered.
+    pub samples: Vec<(T, SampleInfo)>,
+}
+
+/// The infos of a received sample. The fields the C library does not
+/// provide are `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampleInfo {
+    pub valid_data: bool,
+    /// Nanoseconds since the Unix epoch.
+    pub source_timestamp: Option<i64>,
+    /// Nanoseconds since the Unix epoch.
+    pub reception_timestamp: Option<i64>,
+    pub sample_state: Option<SampleState>,
+    pub view_state: Option<ViewState>,
+    pub instance_state: Option<InstanceState>,
+    pub sample_identity: Option<SampleIdentity>,
+}
+
+impl SampleInfo {
+    fn load(connector: &Connector, reader: &str, index: usize) -> Result<Self, Error> {
+        let valid_data = connector
+            .get_boolean_from_infos(reader, index, "valid_data")
+            .map_err(|e| Error::entity(reader, e))?;
+        let info = |field: &str| -> Option<Value> {
+            let json = connector.get_json_from_infos(reader, index, field).ok()?;
+            serde_json::from_str(&json).ok()
+        };
+        let state = |field: &str| info(field).and_then(|v| v.as_str().map(str::to_string));
+        Ok(Self {
+            valid_data,
+            source_timestamp: info("source_timestamp").and_then(|v| v.as_i64()),
+            reception_timestamp: info("reception_timestamp").and_then(|v| v.as_i64()),
+            sample_state: state("sample_state").and_then(|s| SampleState::parse(&s)),
+            view_state: state("view_state").and_then(|s| ViewState::parse(&s)),
+            instance_state: state("instance_state").and_then(|s| InstanceState::parse(&s)),
+            sample_identity: info("sample_identity").and_then(|v| SampleIdentity::from_json(&v)),
+        })
+    }
+}
+
+/// Whether a sample was already read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SampleState {
+    Read,
+    NotRead,
+}
+
+impl SampleState {
+    /// Parses the state as the C library names it, such as `NOT_READ`.
+    pub fn parse(state: &str) -> Option<Self> {
+        match state {
+            "READ" => Some(SampleState::Read),
+            "NOT_READ" => Some(SampleState::NotRead),
+            _ => None,
+        }
+    }
+}
+
+/// Whether a sample is the first one of its instance the reader sees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ViewState {
+    New,
+    NotNew,
+}
+
+impl ViewState {
+    /// Parses the state as the C library names it, such as `NEW`.
+    pub fn parse(state: &str) -> Option<Self> {
+        match state {
+            "NEW" => Some(ViewState::New),
+            "NOT_NEW" => Some(ViewState::NotNew),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the instance of a sample is alive, disposed or has no writers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstanceState {
+    Alive,
+    NotAliveDisposed,
+    NotAliveNoWriters,
+}
+
+impl InstanceState {
+    /// Parses the state as the C library names it, such as `NOT_ALIVE_DISPOSED`.
+    pub fn parse(state: &str) -> Option<Self> {
+        match state {
+            "ALIVE" => Some(InstanceState::Alive),
+            "NOT_ALIVE_DISPOSED" => Some(InstanceState::NotAliveDisposed),
+            "NOT_ALIVE_NO_WRITERS" => Some(InstanceState::NotAliveNoWriters),
+            _ => None,
+        }
+    }
+}
+
+/// The writer and sequence number that identify a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SampleIdentity {
+    pub writer_guid: [u8; 16],
+    pub sequence_number: i64,
+}
+
+impl SampleIdentity {
+    /// Reads the identity from its JSON form,
+    /// `{"writer_guid": [16 bytes], "sequence_number": n}`.
+    pub fn from_json(value: &Value) -> Option<Self> {
+        let bytes = value["writer_guid"].as_array()?;
+        let mut writer_guid = [0; 16];
+        if bytes.len() != writer_guid.len() {
+            return None;
+        }
+        for (byte, value) in writer_guid.iter_mut().zip(bytes) {
+            *byte = u8::try_from(value.as_u64()?).ok()?;
+        }
+        Some(Self {
+            writer_guid,
+            sequence_number: value["sequence_number"].as_i64()?,
+        })
+    }
+
+    pub(crate) fn to_json(self) -> Value {
+        serde_json::json!({
+            "writer_guid": self.writer_guid,
+            "sequence_number": self.sequence_number,
+        })
+    }
+}
+
+/// A publication matched with a [`DataReader`], as the C library describes
+/// it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedPublication {
+    /// The name of the data writer, such as `"MyPublisher::MySquareWriter"`;
+    /// `None` for a writer without one.
+    pub name: Option<String>,
+}
+
+impl MatchedPublication {
+    /// Parses the JSON the C library returns for the matched publications:
+    /// a list of `{"name": ...}` objects.
+    pub fn from_json(json: &str) -> serde_json::Result<Vec<Self>> {
+        let publications: Vec<Value> = serde_json::from_str(json)?;
+        Ok(publications
+            .iter()
+            .map(|publication| Self {
+                name: publication["name"].as_str().map(str::to_string),
+            })
+            .collect())
+    }
+}
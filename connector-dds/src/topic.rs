@@ -1,21 +1,396 @@
+use std::collections::HashSet;
 
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
-use std::any::type_name;
+/// A Rust type carried by a topic, usable with typed readers and writers.
+///
+/// Implemented by `#[derive(DdsType)]` for structs, with the DDS type name as
+/// `TYPE_NAME` and, unless `#[dds(topic = "...")]` says otherwise, the same
+/// name as `TOPIC_NAME`. Samples are deserialized into owned values, so
+/// readers and writers of a `TopicType` do not borrow any buffer.
+pub trait TopicType: DdsType + Serialize + DeserializeOwned {
+    /// The name of the type in the `<types>` section, such as `ShapeType`.
+    const TYPE_NAME: &'static str;
 
-use serde::{Deserialize, Serialize};
+    /// The name of the topic the type is usually published on.
+    const TOPIC_NAME: &'static str = Self::TYPE_NAME;
 
-
-
-pub trait TopicType<'a>: Serialize + Deserialize<'a> + Sized{
-    fn name(&self) -> String {
-        type_name::<Self>().to_string().rsplit("::").last().unwrap().to_string()
+    /// The `<types>` section defining this type and every type it uses.
+    fn types_xml() -> String {
+        types_xml::<Self>()
     }
 
     fn to_json(&self) -> serde_json::Result<String> {
         serde_json::to_string(self)
     }
 
-    fn from_json(json: &'a str) -> serde_json::Result<Self> {
+    fn from_json(json: &str) -> serde_json::Result<Self> {
         serde_json::from_str::<Self>(json)
     }
-}
\ No newline at end of file
+}
+
+/// A topic of a participant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Topic {
+    pub name: String,
+    /// The qualified name of the type registered for the topic.
+    pub type_name: String,
+}
+
+/// A Rust type with a counterpart in the `<types>` section of a Connector XML
+/// configuration.
+///
+/// Implemented for the primitive types, `String`, `Vec<T>`, `[T; N]`,
+/// `Option<T>` and `Box<T>`, and by `#[derive(DdsType)]` for structs and
+/// unit-only enums.
+pub trait DdsType {
+    /// How a member of this type is declared.
+    fn member_type() -> MemberType;
+
+    /// Adds the `<struct>`/`<enum>`/`<typedef>` definitions this type needs,
+    /// dependencies first.
+    fn collect_definitions(_types: &mut TypeDefinitions) {}
+}
+
+/// Renders the `<types>` section declaring `T` and every type it uses.
+pub fn types_xml<T: DdsType>() -> String {
+    let mut types = TypeDefinitions::default();
+    T::collect_definitions(&mut types);
+    types.to_xml()
+}
+
+/// The attributes of a `<member>` that come from its type.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemberType {
+    /// The `type` attribute, e.g. `long` or `nonBasic`.
+    pub kind: String,
+    pub non_basic_type_name: Option<String>,
+    /// A number or the name of a `<const>`.
+    pub string_max_length: Option<String>,
+    /// `-1` for unbounded sequences.
+    pub sequence_max_length: Option<String>,
+    pub array_dimensions: Vec<usize>,
+    pub optional: bool,
+}
+
+impl MemberType {
+    pub fn basic(kind: &str) -> Self {
+        Self {
+            kind: kind.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn non_basic(type_name: &str) -> Self {
+        Self {
+            kind: "nonBasic".to_string(),
+            non_basic_type_name: Some(type_name.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// The name used to refer to this type, such as in `baseType`.
+    pub fn type_name(&self) -> &str {
+        self.non_basic_type_name.as_deref().unwrap_or(&self.kind)
+    }
+
+    /// Whether this is a sequence or an array, which a sequence can only
+    /// hold through a typedef.
+    fn is_collection(&self) -> bool {
+        self.sequence_max_length.is_some() || !self.array_dimensions.is_empty()
+    }
+
+    /// The name of the typedef of this type, such as `sequence_long` or
+    /// `array_2_3_string_16`.
+    fn typedef_name(&self) -> String {
+        // Typedefs are declared outside of any module.
+        let mut name = self.type_name().replace("::", "_");
+        if let Some(length) = &self.string_max_length {
+            name = format!("{}_{}", name, length);
+        }
+        match self.sequence_max_length.as_deref() {
+            Some("-1") => name = format!("sequence_{}", name),
+            Some(length) => name = format!("sequence_{}_{}", name, length),
+            None => {}
+        }
+        if !self.array_dimensions.is_empty() {
+            let dimensions: Vec<String> =
+                self.array_dimensions.iter().map(|d| d.to_string()).collect();
+            name = format!("array_{}_{}", dimensions.join("_"), name);
+        }
+        name
+    }
+
+    /// The attributes of a `<member>` or `<typedef>` that describe the type.
+    fn attributes(&self) -> String {
+        let mut xml = format!(" type=\"{}\"", self.kind);
+        if let Some(name) = &self.non_basic_type_name {
+            xml.push_str(&format!(" nonBasicTypeName=\"{}\"", name));
+        }
+        match &self.string_max_length {
+            Some(length) => xml.push_str(&format!(" stringMaxLength=\"{}\"", length)),
+            // A string without a length would get the default bound of the
+            // C library, which a `String` does not have.
+            None if self.kind == "string" => xml.push_str(" stringMaxLength=\"-1\""),
+            None => {}
+        }
+        if let Some(length) = &self.sequence_max_length {
+            xml.push_str(&format!(" sequenceMaxLength=\"{}\"", length));
+        }
+        if !self.array_dimensions.is_empty() {
+            let dimensions: Vec<String> =
+                self.array_dimensions.iter().map(|d| d.to_string()).collect();
+            xml.push_str(&format!(" arrayDimensions=\"{}\"", dimensions.join(",")));
+        }
+        if self.optional {
+            xml.push_str(" optional=\"true\"");
+        }
+        xml
+    }
+}
+
+/// A `<member>` of a `<struct>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberDefinition {
+    pub name: String,
+    pub member_type: MemberType,
+    pub key: bool,
+}
+
+impl MemberDefinition {
+    pub fn to_xml(&self) -> String {
+        let mut xml = format!("<member name=\"{}\"{}", self.name, self.member_type.attributes());
+        if self.key {
+            xml.push_str(" key=\"true\"");
+        }
+        xml.push_str("/>");
+        xml
+    }
+}
+
+/// The name of a type within its module: `Point` for `Geo::Point`.
+fn local_name(name: &str) -> &str {
+    name.rsplit("::").next().unwrap_or_default()
+}
+
+/// A `<struct>` type definition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructDefinition {
+    pub name: String,
+    pub base_type: Option<String>,
+    pub extensibility: Option<String>,
+    pub members: Vec<MemberDefinition>,
+}
+
+impl StructDefinition {
+    /// Renders the definition under its local name; [`TypeDefinitions`]
+    /// places it in its modules.
+    pub fn to_xml(&self) -> String {
+        let mut xml = format!("<struct name=\"{}\"", local_name(&self.name));
+        if let Some(base_type) = &self.base_type {
+            xml.push_str(&format!(" baseType=\"{}\"", base_type));
+        }
+        if let Some(extensibility) = &self.extensibility {
+            xml.push_str(&format!(" extensibility=\"{}\"", extensibility));
+        }
+        xml.push_str(">\n");
+        for member in &self.members {
+            xml.push_str(&format!("    {}\n", member.to_xml()));
+        }
+        xml.push_str("</struct>");
+        xml
+    }
+}
+
+/// An `<enum>` type definition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDefinition {
+    pub name: String,
+    pub extensibility: Option<String>,
+    pub enumerators: Vec<(String, i32)>,
+}
+
+impl EnumDefinition {
+    /// Renders the definition under its local name; [`TypeDefinitions`]
+    /// places it in its modules.
+    pub fn to_xml(&self) -> String {
+        let mut xml = format!("<enum name=\"{}\"", local_name(&self.name));
+        if let Some(extensibility) = &self.extensibility {
+            xml.push_str(&format!(" extensibility=\"{}\"", extensibility));
+        }
+        xml.push_str(">\n");
+        for (name, value) in &self.enumerators {
+            xml.push_str(&format!("    <enumerator name=\"{}\" value=\"{}\"/>\n", name, value));
+        }
+        xml.push_str("</enum>");
+        xml
+    }
+}
+
+/// A `<typedef>`, through which a sequence holds sequences or arrays.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedefDefinition {
+    pub name: String,
+    pub member_type: MemberType,
+}
+
+impl TypedefDefinition {
+    pub fn to_xml(&self) -> String {
+        format!("<typedef name=\"{}\"{}/>", self.name, self.member_type.attributes())
+    }
+}
+
+/// The type definitions collected for a `<types>` section, in dependency
+/// order.
+#[derive(Debug, Default)]
+pub struct TypeDefinitions {
+    seen: HashSet<String>,
+    /// The qualified name and rendering of each definition.
+    definitions: Vec<(String, String)>,
+}
+
+impl TypeDefinitions {
+    /// Marks `name` as being defined; returns `false` if it already was, so
+    /// recursive and shared types are only emitted once.
+    pub fn begin(&mut self, name: &str) -> bool {
+        self.seen.insert(name.to_string())
+    }
+
+    /// Appends the definition of `name`, rendered under its local name,
+    /// after the ones it depends on.
+    pub fn push(&mut self, name: &str, xml: String) {
+        self.definitions.push((name.to_string(), xml));
+    }
+
+    /// Renders the definitions with a `<module>` for every qualified name
+    /// (`Geo::Point`), shared by consecutive definitions of the same module.
+    pub fn to_xml(&self) -> String {
+        let indent = |depth: usize| "    ".repeat(depth);
+        let mut xml = String::from("<types>\n");
+        let mut open: Vec<&str> = Vec::new();
+        for (name, definition) in &self.definitions {
+            let mut modules: Vec<&str> = name.split("::").collect();
+            modules.pop();
+            let shared = open
+                .iter()
+                .zip(&modules)
+                .take_while(|(open, module)| open == module)
+                .count();
+            while open.len() > shared {
+                open.pop();
+                xml.push_str(&format!("{}</module>\n", indent(open.len() + 1)));
+            }
+            for module in &modules[shared..] {
+                xml.push_str(&format!(
+                    "{}<module name=\"{}\">\n",
+                    indent(open.len() + 1),
+                    module
+                ));
+                open.push(module);
+            }
+            for line in definition.lines() {
+                xml.push_str(&format!("{}{}\n", indent(open.len() + 1), line));
+            }
+        }
+        while open.pop().is_some() {
+            xml.push_str(&format!("{}</module>\n", indent(open.len() + 1)));
+        }
+        xml.push_str("</types>\n");
+        xml
+    }
+}
+
+macro_rules! basic_dds_type {
+    ($($ty:ty => $kind:literal),* $(,)?) => {
+        $(
+            impl DdsType for $ty {
+                fn member_type() -> MemberType {
+                    MemberType::basic($kind)
+                }
+            }
+        )*
+    };
+}
+
+basic_dds_type! {
+    bool => "boolean",
+    char => "char8",
+    i8 => "int8",
+    u8 => "octet",
+    i16 => "short",
+    u16 => "unsignedShort",
+    i32 => "long",
+    u32 => "unsignedLong",
+    i64 => "longLong",
+    u64 => "unsignedLongLong",
+    f32 => "float",
+    f64 => "double",
+    String => "string",
+}
+
+/// A sequence of sequences or of arrays refers to its elements through a
+/// typedef, since a `<member>` declares arrays of sequences.
+impl<T: DdsType> DdsType for Vec<T> {
+    fn member_type() -> MemberType {
+        let element = T::member_type();
+        let element = match element.is_collection() {
+            true => MemberType::non_basic(&element.typedef_name()),
+            false => element,
+        };
+        MemberType {
+            sequence_max_length: Some("-1".to_string()),
+            ..element
+        }
+    }
+
+    fn collect_definitions(types: &mut TypeDefinitions) {
+        T::collect_definitions(types);
+        let element = T::member_type();
+        if element.is_collection() && types.begin(&element.typedef_name()) {
+            let typedef = TypedefDefinition {
+                name: element.typedef_name(),
+                member_type: MemberType {
+                    optional: false,
+                    ..element
+                },
+            };
+            types.push(&typedef.name, typedef.to_xml());
+        }
+    }
+}
+
+impl<T: DdsType, const N: usize> DdsType for [T; N] {
+    fn member_type() -> MemberType {
+        let mut member_type = T::member_type();
+        member_type.array_dimensions.insert(0, N);
+        member_type
+    }
+
+    fn collect_definitions(types: &mut TypeDefinitions) {
+        T::collect_definitions(types)
+    }
+}
+
+impl<T: DdsType> DdsType for Option<T> {
+    fn member_type() -> MemberType {
+        MemberType {
+            optional: true,
+            ..T::member_type()
+        }
+    }
+
+    fn collect_definitions(types: &mut TypeDefinitions) {
+        T::collect_definitions(types)
+    }
+}
+
+impl<T: DdsType> DdsType for Box<T> {
+    fn member_type() -> MemberType {
+        T::member_type()
+    }
+
+    fn collect_definitions(types: &mut TypeDefinitions) {
+        T::collect_definitions(types)
+    }
+}
@@ -1,28 +1,885 @@
+use std::fmt;
+use std::sync::Arc;
+
+use rticonnector::config::types::{
+    Bound, Enum, Member, MemberType, Struct, TypeDefinition, TypeKind, Types,
+};
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use thiserror::Error;
+
+use crate::schema::{self, SchemaError};
+use crate::topic::TopicType;
+
 pub trait Entity {
     fn enable(&mut self);
+}
 
+/// Identifies an instance of a topic by the values of the key members of its
+/// type; every sample of an unkeyed type belongs to the same instance.
+///
+/// This is a local construct of the Rust API, not the DDS instance handle:
+/// the Connector does not expose the latter, so two handles are equal when
+/// their key members are.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InstanceHandle {
+    /// The key members as a JSON object, rendered once so that handles can
+    /// be compared and hashed.
+    key: String,
 }
 
+impl InstanceHandle {
+    /// The handle of the instance of `sample`, a sample rendered to JSON by
+    /// the Connector, of which only the `key_members` are read.
+    pub fn from_sample(key_members: &[String], sample: &Value) -> Self {
+        let mut key = Map::new();
+        for member in key_members {
+            key.insert(member.clone(), connector_json(&sample[member.as_str()]));
+        }
+        Self {
+            key: Value::Object(key).to_string(),
+        }
+    }
 
-pub struct InstanceHandle {
+    /// The handle of the instance of `value`, rendered the way the Connector
+    /// renders a sample so that it matches [`InstanceHandle::from_sample`].
+    pub fn from_value<S: Serialize>(
+        key_members: &[String],
+        value: &S,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self::from_sample(key_members, &serde_json::to_value(value)?))
+    }
+
+    /// The key members of the instance, as a JSON object that can be written
+    /// to dispose or unregister it.
+    pub fn key(&self) -> Value {
+        serde_json::from_str(&self.key).expect("the key is rendered from a JSON object")
+    }
+}
+
+/// `value` as the Connector renders it. The Connector holds every number as
+/// a double and writes the integral ones without a fraction, so `1.0` and
+/// `1` are the same key; enums of a [`TopicType`] already serialize as their
+/// enumerator value.
+fn connector_json(value: &Value) -> Value {
+    match value {
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or(f64::NAN);
+            if number.fract() == 0.0 && number.abs() < (1u64 << 53) as f64 {
+                Value::from(number as i64)
+            } else {
+                Number::from_f64(number).map_or(Value::Null, Value::Number)
+            }
+        }
+        Value::Array(elements) => Value::Array(elements.iter().map(connector_json).collect()),
+        Value::Object(members) => Value::Object(
+            members
+                .iter()
+                .map(|(name, member)| (name.clone(), connector_json(member)))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+/// The names of the key members of `T`, including those of its base types.
+/// A key member of a struct type is compared as a whole.
+pub fn key_members<T: TopicType>() -> Result<Vec<String>, SchemaError> {
+    let types = schema::rust_types::<T>()?;
+    let Some(definition) = types.get_struct(T::TYPE_NAME) else {
+        return Ok(Vec::new());
+    };
+    Ok(types
+        .all_members(definition)
+        .into_iter()
+        .filter(|member| member.key)
+        .map(|member| member.name.clone())
+        .collect())
+}
 
+/// Error returned when building or modifying a [`DynamicData`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DynamicDataError {
+    #[error("no type `{0}` in the types")]
+    UnknownType(String),
+    #[error("`{0}` is not a struct")]
+    NotAStruct(String),
+    #[error("`{type_name}` has no member `{member}`")]
+    UnknownMember { type_name: String, member: String },
+    #[error("`{type_name}` has no member at index {index}")]
+    UnknownIndex { type_name: String, index: usize },
+    #[error("`{member}`: expected {expected}, found {found}")]
+    Mismatch {
+        member: String,
+        expected: String,
+        found: String,
+    },
+    #[error("`{member}`: a length of {length} exceeds the bound of {bound}")]
+    Bound {
+        member: String,
+        bound: u32,
+        length: usize,
+    },
+    #[error("`{member}`: the array has {expected} elements, not {length}")]
+    ArrayLength {
+        member: String,
+        expected: u32,
+        length: usize,
+    },
+    #[error("`{member}`: no enumerator {value}")]
+    Enumerator { member: String, value: String },
+    #[error("`{0}` is not optional")]
+    NotOptional(String),
+    #[error("cannot resolve the bound `{0}`")]
+    Unresolved(String),
 }
 
-struct DynamicData;
+/// A sample of a struct type known only at run time, from its definition in
+/// an XML `<types>` section.
+///
+/// Every member holds a [`DynamicValue`] of its declared type: setting a
+/// value of another kind, too long for its bound or that is not an
+/// enumerator of its enum is an error, so a `DynamicData` can always be
+/// written. Members are in the order of
+/// [`Types::all_members`], inherited members first, and are addressed by
+/// name or by index in that order. Unset optional members are `None`.
+///
+/// [`to_json`](DynamicData::to_json) and
+/// [`from_json`](DynamicData::from_json) convert from and to the JSON the
+/// Connector reads and writes, with enums as integers:
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use connector_dds::util::{DynamicData, DynamicValue};
+/// use rticonnector::config::types::Types;
+///
+/// let types = Arc::new(Types::from_file("ShapeExample.xml").unwrap());
+/// let mut shape = DynamicData::new(types, "ShapeType").unwrap();
+/// shape.set("color", "BLUE".into()).unwrap();
+/// shape.set("x", DynamicValue::Long(10)).unwrap();
+/// assert!(shape.set("y", DynamicValue::Double(1.5)).is_err());
+/// println!("{}", shape.to_json());
+/// ```
+#[derive(Clone)]
+pub struct DynamicData {
+    types: Arc<Types>,
+    type_name: String,
+    values: Vec<Option<DynamicValue>>,
+}
 
 impl DynamicData {
-    pub fn to_cdr_buffer(sample: &Self) -> Vec<char> {
-        todo!()
+    /// A sample of the struct `type_name` of `types`, with every member set
+    /// to its default: zero, empty, the first enumerator, or unset when
+    /// optional.
+    pub fn new(types: Arc<Types>, type_name: &str) -> Result<Self, DynamicDataError> {
+        let s = struct_definition(&types, type_name)?;
+        Self::with_struct(types.clone(), s)
+    }
+
+    /// A sample of the struct `type_name` of `types` from its JSON
+    /// representation. Missing members keep their default.
+    pub fn from_json(
+        types: Arc<Types>,
+        type_name: &str,
+        json: &Value,
+    ) -> Result<Self, DynamicDataError> {
+        let s = struct_definition(&types, type_name)?;
+        Self::struct_from_json(types.clone(), s, json, "")
+    }
+
+    /// The JSON representation of the sample, without the unset optional
+    /// members.
+    pub fn to_json(&self) -> Value {
+        let mut object = Map::new();
+        for (member, value) in self.members().into_iter().zip(&self.values) {
+            if let Some(value) = value {
+                object.insert(member.name.clone(), value.to_json());
+            }
+        }
+        Value::Object(object)
+    }
+
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    pub fn types(&self) -> &Arc<Types> {
+        &self.types
+    }
+
+    pub fn info(&self) -> DynamicDataInfo {
+        DynamicDataInfo {
+            type_name: self.type_name.clone(),
+            member_count: self.values.len(),
+            set_member_count: self.values.iter().filter(|v| v.is_some()).count(),
+        }
+    }
+
+    pub fn member_count(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn member_index(&self, name: &str) -> Option<usize> {
+        self.members().iter().position(|m| m.name == name)
     }
 
-    
+    pub fn member_info(&self, index: usize) -> Option<DynamicDataMemberInfo> {
+        let member = *self.members().get(index)?;
+        Some(DynamicDataMemberInfo {
+            index,
+            name: member.name.clone(),
+            member_type: member.member_type.clone(),
+            key: member.key,
+            optional: member.optional,
+        })
+    }
+
+    /// The value of the member `name`; `None` when it is optional and unset.
+    pub fn get(&self, name: &str) -> Result<Option<&DynamicValue>, DynamicDataError> {
+        let index = self.index(name)?;
+        Ok(self.values[index].as_ref())
+    }
+
+    pub fn get_by_index(&self, index: usize) -> Result<Option<&DynamicValue>, DynamicDataError> {
+        self.check_index(index)?;
+        Ok(self.values[index].as_ref())
+    }
+
+    /// The member `name` of a struct type, to modify its own members.
+    pub fn get_struct_mut(&mut self, name: &str) -> Result<&mut DynamicData, DynamicDataError> {
+        let index = self.index(name)?;
+        match &mut self.values[index] {
+            Some(DynamicValue::Struct(data)) => Ok(data),
+            value => Err(DynamicDataError::Mismatch {
+                member: name.to_string(),
+                expected: "a struct".to_string(),
+                found: value
+                    .as_ref()
+                    .map_or("an unset member".to_string(), |v| v.kind_name()),
+            }),
+        }
+    }
+
+    /// Sets the member `name` to `value`, once checked against its type.
+    pub fn set(&mut self, name: &str, value: DynamicValue) -> Result<(), DynamicDataError> {
+        let index = self.index(name)?;
+        self.set_by_index(index, value)
+    }
+
+    pub fn set_by_index(
+        &mut self,
+        index: usize,
+        value: DynamicValue,
+    ) -> Result<(), DynamicDataError> {
+        self.check_index(index)?;
+        let member = self.members()[index];
+        check(&self.types, &member.member_type, &value, &member.name)?;
+        self.values[index] = Some(value);
+        Ok(())
+    }
+
+    /// Unsets the optional member `name`, or sets it back to its default.
+    pub fn clear(&mut self, name: &str) -> Result<(), DynamicDataError> {
+        let index = self.index(name)?;
+        let member = self.members()[index];
+        self.values[index] = if member.optional {
+            None
+        } else {
+            Some(default_value(&self.types, &member.member_type)?)
+        };
+        Ok(())
+    }
+
+    /// Unsets the optional member `name`; an error for the other members,
+    /// which always have a value.
+    pub fn unset(&mut self, name: &str) -> Result<(), DynamicDataError> {
+        let index = self.index(name)?;
+        if !self.members()[index].optional {
+            return Err(DynamicDataError::NotOptional(name.to_string()));
+        }
+        self.values[index] = None;
+        Ok(())
+    }
+
+    fn with_struct(types: Arc<Types>, s: &Struct) -> Result<Self, DynamicDataError> {
+        let values = types
+            .all_members(s)
+            .into_iter()
+            .map(|member| match member.optional {
+                true => Ok(None),
+                false => default_value(&types, &member.member_type).map(Some),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            type_name: s.name.clone(),
+            values,
+            types,
+        })
+    }
+
+    fn struct_from_json(
+        types: Arc<Types>,
+        s: &Struct,
+        json: &Value,
+        path: &str,
+    ) -> Result<Self, DynamicDataError> {
+        let Some(object) = json.as_object() else {
+            return Err(mismatch(path, &format!("struct `{}`", s.name), json));
+        };
+        let mut data = Self::with_struct(types.clone(), s)?;
+        let members = types.all_members(s);
+        for (name, json) in object {
+            let Some(index) = members.iter().position(|m| m.name == *name) else {
+                return Err(DynamicDataError::UnknownMember {
+                    type_name: s.name.clone(),
+                    member: name.clone(),
+                });
+            };
+            let member = members[index];
+            let path = join(path, name);
+            data.values[index] = if json.is_null() && member.optional {
+                None
+            } else {
+                let value = value_from_json(&types, &member.member_type, json, &path)?;
+                check(&types, &member.member_type, &value, &path)?;
+                Some(value)
+            };
+        }
+        Ok(data)
+    }
+
+    pub(crate) fn members(&self) -> Vec<&Member> {
+        let s = self
+            .types
+            .get_struct(&self.type_name)
+            .expect("the struct is checked when the data is created");
+        self.types.all_members(s)
+    }
+
+    fn index(&self, name: &str) -> Result<usize, DynamicDataError> {
+        self.member_index(name)
+            .ok_or_else(|| DynamicDataError::UnknownMember {
+                type_name: self.type_name.clone(),
+                member: name.to_string(),
+            })
+    }
+
+    fn check_index(&self, index: usize) -> Result<(), DynamicDataError> {
+        if index >= self.values.len() {
+            return Err(DynamicDataError::UnknownIndex {
+                type_name: self.type_name.clone(),
+                index,
+            });
+        }
+        Ok(())
+    }
 }
 
-struct DynamicDataInfo {}
+impl PartialEq for DynamicData {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_name == other.type_name && self.values == other.values
+    }
+}
 
-struct DynamicDataMemberInfo;
-struct DynamicDataProperty;
+impl fmt::Debug for DynamicData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicData")
+            .field("type_name", &self.type_name)
+            .field("values", &self.values)
+            .finish()
+    }
+}
 
-struct DynamicDataSeq {}
-struct DynamicDataSerializationSupport {}
-struct DynamicDataTypeProperty {}
\ No newline at end of file
+/// The value of a member of a [`DynamicData`].
+///
+/// Kinds the Connector does not tell apart share a variant: `Char` holds a
+/// `char8` or `char16`, `Octet` an `octet` or `uint8`, `Double` a `double`
+/// or `longDouble`, and `String` a `string` or `wstring`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DynamicValue {
+    Boolean(bool),
+    Char(char),
+    Octet(u8),
+    Int8(i8),
+    Short(i16),
+    UnsignedShort(u16),
+    Long(i32),
+    UnsignedLong(u32),
+    LongLong(i64),
+    UnsignedLongLong(u64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    /// The value of an enumerator.
+    Enum(i32),
+    Struct(DynamicData),
+    Sequence(Vec<DynamicValue>),
+    Array(Vec<DynamicValue>),
+}
+
+impl DynamicValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            DynamicValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// The value of an integer or enum, if it fits an `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        Some(match self {
+            DynamicValue::Octet(n) => (*n).into(),
+            DynamicValue::Int8(n) => (*n).into(),
+            DynamicValue::Short(n) => (*n).into(),
+            DynamicValue::UnsignedShort(n) => (*n).into(),
+            DynamicValue::Long(n) | DynamicValue::Enum(n) => (*n).into(),
+            DynamicValue::UnsignedLong(n) => (*n).into(),
+            DynamicValue::LongLong(n) => *n,
+            DynamicValue::UnsignedLongLong(n) => i64::try_from(*n).ok()?,
+            _ => return None,
+        })
+    }
+
+    /// The value of a number, possibly rounded.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            DynamicValue::Float(n) => Some((*n).into()),
+            DynamicValue::Double(n) => Some(*n),
+            DynamicValue::UnsignedLongLong(n) => Some(*n as f64),
+            other => other.as_i64().map(|n| n as f64),
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            DynamicValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_struct(&self) -> Option<&DynamicData> {
+        match self {
+            DynamicValue::Struct(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// The elements of a sequence or array.
+    pub fn as_slice(&self) -> Option<&[DynamicValue]> {
+        match self {
+            DynamicValue::Sequence(items) | DynamicValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// The JSON representation of the value, as the Connector renders it.
+    pub fn to_json(&self) -> Value {
+        match self {
+            DynamicValue::Boolean(b) => Value::Bool(*b),
+            DynamicValue::Char(c) => Value::String(c.to_string()),
+            DynamicValue::Octet(n) => (*n).into(),
+            DynamicValue::Int8(n) => (*n).into(),
+            DynamicValue::Short(n) => (*n).into(),
+            DynamicValue::UnsignedShort(n) => (*n).into(),
+            DynamicValue::Long(n) | DynamicValue::Enum(n) => (*n).into(),
+            DynamicValue::UnsignedLong(n) => (*n).into(),
+            DynamicValue::LongLong(n) => (*n).into(),
+            DynamicValue::UnsignedLongLong(n) => (*n).into(),
+            // Through its shortest decimal form, so that 0.1 is not rendered
+            // as 0.10000000149011612.
+            DynamicValue::Float(n) => {
+                Number::from_f64(n.to_string().parse().unwrap_or((*n).into()))
+                    .map_or(Value::Null, Value::Number)
+            }
+            DynamicValue::Double(n) => Number::from_f64(*n).map_or(Value::Null, Value::Number),
+            DynamicValue::String(s) => Value::String(s.clone()),
+            DynamicValue::Struct(data) => data.to_json(),
+            DynamicValue::Sequence(items) | DynamicValue::Array(items) => {
+                Value::Array(items.iter().map(DynamicValue::to_json).collect())
+            }
+        }
+    }
+
+    fn default_of(kind: &TypeKind) -> Self {
+        match kind {
+            TypeKind::Boolean => DynamicValue::Boolean(false),
+            TypeKind::Char8 | TypeKind::Char16 => DynamicValue::Char('\0'),
+            TypeKind::Octet | TypeKind::UInt8 => DynamicValue::Octet(0),
+            TypeKind::Int8 => DynamicValue::Int8(0),
+            TypeKind::Short => DynamicValue::Short(0),
+            TypeKind::UnsignedShort => DynamicValue::UnsignedShort(0),
+            TypeKind::Long => DynamicValue::Long(0),
+            TypeKind::UnsignedLong => DynamicValue::UnsignedLong(0),
+            TypeKind::LongLong => DynamicValue::LongLong(0),
+            TypeKind::UnsignedLongLong => DynamicValue::UnsignedLongLong(0),
+            TypeKind::Float => DynamicValue::Float(0.0),
+            TypeKind::Double | TypeKind::LongDouble => DynamicValue::Double(0.0),
+            TypeKind::String | TypeKind::WString | TypeKind::NonBasic(_) => {
+                DynamicValue::String(String::new())
+            }
+        }
+    }
+
+    /// Whether the value can be held by a member of the basic `kind`.
+    fn fits(&self, kind: &TypeKind) -> bool {
+        matches!(
+            (self, kind),
+            (DynamicValue::Boolean(_), TypeKind::Boolean)
+                | (DynamicValue::Char(_), TypeKind::Char8 | TypeKind::Char16)
+                | (DynamicValue::Octet(_), TypeKind::Octet | TypeKind::UInt8)
+                | (DynamicValue::Int8(_), TypeKind::Int8)
+                | (DynamicValue::Short(_), TypeKind::Short)
+                | (DynamicValue::UnsignedShort(_), TypeKind::UnsignedShort)
+                | (DynamicValue::Long(_), TypeKind::Long)
+                | (DynamicValue::UnsignedLong(_), TypeKind::UnsignedLong)
+                | (DynamicValue::LongLong(_), TypeKind::LongLong)
+                | (
+                    DynamicValue::UnsignedLongLong(_),
+                    TypeKind::UnsignedLongLong
+                )
+                | (DynamicValue::Float(_), TypeKind::Float)
+                | (
+                    DynamicValue::Double(_),
+                    TypeKind::Double | TypeKind::LongDouble
+                )
+                | (
+                    DynamicValue::String(_),
+                    TypeKind::String | TypeKind::WString
+                )
+        )
+    }
+
+    pub(crate) fn kind_name(&self) -> String {
+        match self {
+            DynamicValue::Boolean(_) => "boolean".to_string(),
+            DynamicValue::Char(_) => "char8".to_string(),
+            DynamicValue::Octet(_) => "octet".to_string(),
+            DynamicValue::Int8(_) => "int8".to_string(),
+            DynamicValue::Short(_) => "short".to_string(),
+            DynamicValue::UnsignedShort(_) => "unsignedShort".to_string(),
+            DynamicValue::Long(_) => "long".to_string(),
+            DynamicValue::UnsignedLong(_) => "unsignedLong".to_string(),
+            DynamicValue::LongLong(_) => "longLong".to_string(),
+            DynamicValue::UnsignedLongLong(_) => "unsignedLongLong".to_string(),
+            DynamicValue::Float(_) => "float".to_string(),
+            DynamicValue::Double(_) => "double".to_string(),
+            DynamicValue::String(_) => "string".to_string(),
+            DynamicValue::Enum(_) => "an enumerator".to_string(),
+            DynamicValue::Struct(data) => format!("struct `{}`", data.type_name),
+            DynamicValue::Sequence(_) => "a sequence".to_string(),
+            DynamicValue::Array(_) => "an array".to_string(),
+        }
+    }
+}
+
+macro_rules! dynamic_value_from {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for DynamicValue {
+                fn from(value: $ty) -> Self {
+                    DynamicValue::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+dynamic_value_from! {
+    bool => Boolean,
+    char => Char,
+    u8 => Octet,
+    i8 => Int8,
+    i16 => Short,
+    u16 => UnsignedShort,
+    i32 => Long,
+    u32 => UnsignedLong,
+    i64 => LongLong,
+    u64 => UnsignedLongLong,
+    f32 => Float,
+    f64 => Double,
+    String => String,
+    &str => String,
+    DynamicData => Struct,
+}
+
+/// A summary of a [`DynamicData`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicDataInfo {
+    pub type_name: String,
+    pub member_count: usize,
+    /// The members that have a value: all but the unset optional ones.
+    pub set_member_count: usize,
+}
+
+/// The declaration of a member of a [`DynamicData`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicDataMemberInfo {
+    pub index: usize,
+    pub name: String,
+    pub member_type: MemberType,
+    pub key: bool,
+    pub optional: bool,
+}
+
+/// One level of a member type: its array dimensions first, then its
+/// sequence, then its element, seen through typedefs.
+pub(crate) enum Layer<'t> {
+    Array(u32, MemberType),
+    Sequence(Option<u32>, MemberType),
+    Struct(&'t Struct),
+    Enum(&'t Enum),
+    /// A basic kind, with its string bound.
+    Basic(TypeKind, Option<u32>),
+}
+
+impl Layer<'_> {
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Layer::Array(length, _) => format!("an array of {}", length),
+            Layer::Sequence(..) => "a sequence".to_string(),
+            Layer::Struct(s) => format!("struct `{}`", s.name),
+            Layer::Enum(e) => format!("an enumerator of `{}`", e.name),
+            Layer::Basic(kind, _) => kind.xml_name().to_string(),
+        }
+    }
+}
+
+pub(crate) fn layer<'t>(
+    types: &'t Types,
+    member_type: &MemberType,
+) -> Result<Layer<'t>, DynamicDataError> {
+    if let Some((dimension, rest)) = member_type.array_dimensions.split_first() {
+        let length = types
+            .resolve_bound(dimension)
+            .ok_or_else(|| DynamicDataError::Unresolved(bound_name(dimension)))?;
+        let mut element = member_type.clone();
+        element.array_dimensions = rest.to_vec();
+        return Ok(Layer::Array(length, element));
+    }
+    if let Some(bound) = &member_type.sequence_max_length {
+        let mut element = member_type.clone();
+        element.sequence_max_length = None;
+        return Ok(Layer::Sequence(types.resolve_bound(bound), element));
+    }
+    match &member_type.kind {
+        TypeKind::NonBasic(name) => match types.get(name) {
+            Some(TypeDefinition::Struct(s)) => Ok(Layer::Struct(s)),
+            Some(TypeDefinition::Enum(e)) => Ok(Layer::Enum(e)),
+            Some(TypeDefinition::Typedef(t)) => layer(types, &t.member_type),
+            _ => Err(DynamicDataError::UnknownType(name.clone())),
+        },
+        kind => {
+            let bound = member_type.string_max_length.as_ref();
+            Ok(Layer::Basic(
+                kind.clone(),
+                bound.and_then(|b| types.resolve_bound(b)),
+            ))
+        }
+    }
+}
+
+fn default_value(
+    types: &Arc<Types>,
+    member_type: &MemberType,
+) -> Result<DynamicValue, DynamicDataError> {
+    Ok(match layer(types, member_type)? {
+        Layer::Array(length, element) => {
+            DynamicValue::Array(vec![default_value(types, &element)?; length as usize])
+        }
+        Layer::Sequence(..) => DynamicValue::Sequence(Vec::new()),
+        Layer::Struct(s) => DynamicValue::Struct(DynamicData::with_struct(types.clone(), s)?),
+        Layer::Enum(e) => DynamicValue::Enum(e.enumerators.first().map_or(0, |e| e.value)),
+        Layer::Basic(kind, _) => DynamicValue::default_of(&kind),
+    })
+}
+
+/// Checks that `value` can be held by a member of `member_type`; the members
+/// of a nested struct were checked when it was built.
+fn check(
+    types: &Types,
+    member_type: &MemberType,
+    value: &DynamicValue,
+    path: &str,
+) -> Result<(), DynamicDataError> {
+    match (layer(types, member_type)?, value) {
+        (Layer::Array(length, element), DynamicValue::Array(items)) => {
+            if items.len() != length as usize {
+                return Err(DynamicDataError::ArrayLength {
+                    member: path.to_string(),
+                    expected: length,
+                    length: items.len(),
+                });
+            }
+            for (index, item) in items.iter().enumerate() {
+                check(types, &element, item, &format!("{}[{}]", path, index))?;
+            }
+        }
+        (Layer::Sequence(bound, element), DynamicValue::Sequence(items)) => {
+            check_bound(path, bound, items.len())?;
+            for (index, item) in items.iter().enumerate() {
+                check(types, &element, item, &format!("{}[{}]", path, index))?;
+            }
+        }
+        (Layer::Struct(s), DynamicValue::Struct(data)) if data.type_name == s.name => {}
+        (Layer::Enum(e), DynamicValue::Enum(value)) => {
+            if !e.enumerators.iter().any(|e| e.value == *value) {
+                return Err(DynamicDataError::Enumerator {
+                    member: path.to_string(),
+                    value: value.to_string(),
+                });
+            }
+        }
+        (Layer::Basic(kind, bound), value) if value.fits(&kind) => {
+            // Bounds count the encoded units: UTF-8 bytes for a string, UTF-16
+            // code units for a wstring.
+            match (kind, value) {
+                (TypeKind::WString, DynamicValue::String(s)) => {
+                    check_bound(path, bound, s.encode_utf16().count())?
+                }
+                (_, DynamicValue::String(s)) => check_bound(path, bound, s.len())?,
+                _ => {}
+            }
+        }
+        (layer, value) => {
+            return Err(DynamicDataError::Mismatch {
+                member: path.to_string(),
+                expected: layer.describe(),
+                found: value.kind_name(),
+            })
+        }
+    }
+    Ok(())
+}
+
+fn check_bound(path: &str, bound: Option<u32>, length: usize) -> Result<(), DynamicDataError> {
+    match bound {
+        Some(bound) if length > bound as usize => Err(DynamicDataError::Bound {
+            member: path.to_string(),
+            bound,
+            length,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Reads a value of `member_type` from the JSON the Connector renders, which
+/// is checked against the bounds of the member by the caller.
+fn value_from_json(
+    types: &Arc<Types>,
+    member_type: &MemberType,
+    json: &Value,
+    path: &str,
+) -> Result<DynamicValue, DynamicDataError> {
+    let layer = layer(types, member_type)?;
+    let items = |element: &MemberType| -> Result<Vec<DynamicValue>, DynamicDataError> {
+        let Some(items) = json.as_array() else {
+            return Err(mismatch(path, &layer.describe(), json));
+        };
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                value_from_json(types, element, item, &format!("{}[{}]", path, index))
+            })
+            .collect()
+    };
+    match &layer {
+        Layer::Array(_, element) => Ok(DynamicValue::Array(items(element)?)),
+        Layer::Sequence(_, element) => Ok(DynamicValue::Sequence(items(element)?)),
+        Layer::Struct(s) => Ok(DynamicValue::Struct(DynamicData::struct_from_json(
+            types.clone(),
+            s,
+            json,
+            path,
+        )?)),
+        Layer::Enum(e) => {
+            let value = match json {
+                Value::String(name) => e
+                    .enumerators
+                    .iter()
+                    .find(|e| e.name == *name)
+                    .map(|e| e.value),
+                json => json.as_i64().and_then(|v| i32::try_from(v).ok()),
+            };
+            value
+                .map(DynamicValue::Enum)
+                .ok_or_else(|| DynamicDataError::Enumerator {
+                    member: path.to_string(),
+                    value: json.to_string(),
+                })
+        }
+        Layer::Basic(kind, _) => {
+            basic_from_json(kind, json).ok_or_else(|| mismatch(path, &layer.describe(), json))
+        }
+    }
+}
+
+fn basic_from_json(kind: &TypeKind, json: &Value) -> Option<DynamicValue> {
+    Some(match kind {
+        TypeKind::Boolean => DynamicValue::Boolean(json.as_bool()?),
+        TypeKind::Char8 | TypeKind::Char16 => {
+            let mut chars = json.as_str()?.chars();
+            let c = chars.next()?;
+            if chars.next().is_some() {
+                return None;
+            }
+            DynamicValue::Char(c)
+        }
+        TypeKind::Octet | TypeKind::UInt8 => DynamicValue::Octet(json.as_u64()?.try_into().ok()?),
+        TypeKind::Int8 => DynamicValue::Int8(json.as_i64()?.try_into().ok()?),
+        TypeKind::Short => DynamicValue::Short(json.as_i64()?.try_into().ok()?),
+        TypeKind::UnsignedShort => DynamicValue::UnsignedShort(json.as_u64()?.try_into().ok()?),
+        TypeKind::Long => DynamicValue::Long(json.as_i64()?.try_into().ok()?),
+        TypeKind::UnsignedLong => DynamicValue::UnsignedLong(json.as_u64()?.try_into().ok()?),
+        TypeKind::LongLong => DynamicValue::LongLong(json.as_i64()?),
+        TypeKind::UnsignedLongLong => DynamicValue::UnsignedLongLong(json.as_u64()?),
+        TypeKind::Float => DynamicValue::Float(json.as_f64()? as f32),
+        TypeKind::Double | TypeKind::LongDouble => DynamicValue::Double(json.as_f64()?),
+        TypeKind::String | TypeKind::WString => DynamicValue::String(json.as_str()?.to_string()),
+        TypeKind::NonBasic(_) => return None,
+    })
+}
+
+fn mismatch(path: &str, expected: &str, json: &Value) -> DynamicDataError {
+    let found = match json {
+        Value::Null => "null",
+        Value::Bool(_) => "a JSON boolean",
+        Value::Number(_) => "a JSON number",
+        Value::String(_) => "a JSON string",
+        Value::Array(_) => "a JSON array",
+        Value::Object(_) => "a JSON object",
+    };
+    DynamicDataError::Mismatch {
+        member: path.to_string(),
+        expected: expected.to_string(),
+        found: found.to_string(),
+    }
+}
+
+pub(crate) fn struct_definition<'t>(
+    types: &'t Types,
+    type_name: &str,
+) -> Result<&'t Struct, DynamicDataError> {
+    match types.get(type_name) {
+        Some(TypeDefinition::Struct(s)) => Ok(s),
+        Some(_) => Err(DynamicDataError::NotAStruct(type_name.to_string())),
+        None => Err(DynamicDataError::UnknownType(type_name.to_string())),
+    }
+}
+
+fn bound_name(bound: &Bound) -> String {
+    match bound {
+        Bound::Unbounded => "-1".to_string(),
+        Bound::Fixed(length) => length.to_string(),
+        Bound::Const(name) => name.clone(),
+    }
+}
+
+pub(crate) fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
@@ -0,0 +1,443 @@
+//! Golden XCDR vectors: each sample must encode to the bytes below, worked
+//! out from the DDS-XTypes 1.3 rules, and decode back to itself.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use connector_dds::cdr::{self, CdrError, Endianness, XcdrVersion};
+use connector_dds::util::DynamicData;
+use rticonnector::config::types::Types;
+use rticonnector::recorder::{Payload, Record, RecordedTopic, Recorder, Recording, Rotation};
+use serde_json::{json, Value};
+
+/// Types for the rules the ShapeType family does not use: mutable and final
+/// structs, optional members, and sequences of strings and enums.
+const EXTRA_TYPES: &str = r#"
+<dds>
+    <types>
+        <struct name="Point" extensibility="final">
+            <member name="x" type="short"/>
+            <member name="y" type="double"/>
+        </struct>
+        <struct name="Reading" extensibility="mutable">
+            <member name="id" type="unsignedLong" key="true"/>
+            <member name="origin" type="nonBasic" nonBasicTypeName="Point"/>
+            <member name="label" type="string" stringMaxLength="8" optional="true"/>
+            <member name="values" type="short" sequenceMaxLength="4"/>
+        </struct>
+        <enum name="Fill">
+            <enumerator name="SOLID" value="0"/>
+            <enumerator name="HATCH" value="1"/>
+            <enumerator name="DOTS" value="5"/>
+        </enum>
+        <struct name="Palette" extensibility="final">
+            <member name="fills" type="nonBasic" nonBasicTypeName="Fill" sequenceMaxLength="4"/>
+            <member name="corners" type="nonBasic" nonBasicTypeName="Fill" arrayDimensions="2"/>
+        </struct>
+        <struct name="Numbered" extensibility="mutable">
+            <member name="a" type="octet" id="10"/>
+            <member name="b" type="octet"/>
+        </struct>
+        <struct name="Tagged">
+            <member name="tags" type="string" sequenceMaxLength="-1"/>
+            <member name="note" type="string" optional="true"/>
+        </struct>
+    </types>
+</dds>
+"#;
+
+fn shape_types() -> Arc<Types> {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../rticonnector/examples/ShapeExample.xml");
+    Arc::new(Types::from_file(path).unwrap())
+}
+
+fn extra_types() -> Arc<Types> {
+    Arc::new(Types::from_xml(EXTRA_TYPES).unwrap())
+}
+
+fn sample(types: &Arc<Types>, type_name: &str, json: Value) -> DynamicData {
+    DynamicData::from_json(types.clone(), type_name, &json).unwrap()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let bytes: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    bytes.join(" ")
+}
+
+fn bytes(hex: &str) -> Vec<u8> {
+    hex.split_whitespace()
+        .map(|b| u8::from_str_radix(b, 16).unwrap())
+        .collect()
+}
+
+fn check(data: &DynamicData, version: XcdrVersion, endianness: Endianness, expected: &str) {
+    let encoded = data.to_cdr_buffer(version, endianness).unwrap();
+    assert_eq!(
+        hex(&encoded),
+        hex(&bytes(expected)),
+        "{:?} {:?}",
+        version,
+        endianness
+    );
+    let decoded = DynamicData::from_cdr_buffer(data.types().clone(), data.type_name(), &encoded);
+    assert_eq!(decoded.as_ref(), Ok(data));
+}
+
+fn shape() -> DynamicData {
+    let json = json!({"color": "BLUE", "x": 1, "y": 2, "shapesize": 30});
+    sample(&shape_types(), "ShapeType", json)
+}
+
+fn shape_extended() -> DynamicData {
+    let json = json!({
+        "color": "RED",
+        "x": 10,
+        "y": 20,
+        "shapesize": 30,
+        "fillKind": 2,
+        "angle": 45.0,
+    });
+    sample(&shape_types(), "ShapeTypeExtended", json)
+}
+
+#[test]
+fn shape_type_xcdr1() {
+    check(
+        &shape(),
+        XcdrVersion::Xcdr1,
+        Endianness::Little,
+        "00 01 00 00
+         05 00 00 00 42 4c 55 45 00 00 00 00
+         01 00 00 00
+         02 00 00 00
+         1e 00 00 00",
+    );
+    check(
+        &shape(),
+        XcdrVersion::Xcdr1,
+        Endianness::Big,
+        "00 00 00 00
+         00 00 00 05 42 4c 55 45 00 00 00 00
+         00 00 00 01
+         00 00 00 02
+         00 00 00 1e",
+    );
+}
+
+#[test]
+fn shape_type_xcdr2() {
+    check(
+        &shape(),
+        XcdrVersion::Xcdr2,
+        Endianness::Little,
+        "00 15 00 00
+         18 00 00 00
+         05 00 00 00 42 4c 55 45 00 00 00 00
+         01 00 00 00
+         02 00 00 00
+         1e 00 00 00",
+    );
+    check(
+        &shape(),
+        XcdrVersion::Xcdr2,
+        Endianness::Big,
+        "00 14 00 00
+         00 00 00 18
+         00 00 00 05 42 4c 55 45 00 00 00 00
+         00 00 00 01
+         00 00 00 02
+         00 00 00 1e",
+    );
+}
+
+#[test]
+fn shape_type_extended_xcdr1() {
+    check(
+        &shape_extended(),
+        XcdrVersion::Xcdr1,
+        Endianness::Little,
+        "00 01 00 00
+         04 00 00 00 52 45 44 00
+         0a 00 00 00
+         14 00 00 00
+         1e 00 00 00
+         02 00 00 00
+         00 00 34 42",
+    );
+}
+
+#[test]
+fn shape_type_extended_xcdr2() {
+    check(
+        &shape_extended(),
+        XcdrVersion::Xcdr2,
+        Endianness::Little,
+        "00 15 00 00
+         1c 00 00 00
+         04 00 00 00 52 45 44 00
+         0a 00 00 00
+         14 00 00 00
+         1e 00 00 00
+         02 00 00 00
+         00 00 34 42",
+    );
+    check(
+        &shape_extended(),
+        XcdrVersion::Xcdr2,
+        Endianness::Big,
+        "00 14 00 00
+         00 00 00 1c
+         00 00 00 04 52 45 44 00
+         00 00 00 0a
+         00 00 00 14
+         00 00 00 1e
+         00 00 00 02
+         42 34 00 00",
+    );
+}
+
+#[test]
+fn base_type_skips_appended_members() {
+    let encoded = shape_extended()
+        .to_cdr_buffer(XcdrVersion::Xcdr2, Endianness::Little)
+        .unwrap();
+    let decoded = DynamicData::from_cdr_buffer(shape_types(), "ShapeType", &encoded).unwrap();
+    let expected = json!({"color": "RED", "x": 10, "y": 20, "shapesize": 30});
+    assert_eq!(decoded.to_json(), expected);
+}
+
+#[test]
+fn mutable_xcdr1() {
+    let json = json!({"id": 7, "origin": {"x": 1, "y": 0.5}, "values": [3, 4]});
+    check(
+        &sample(&extra_types(), "Reading", json),
+        XcdrVersion::Xcdr1,
+        Endianness::Little,
+        "00 03 00 00
+         00 40 04 00 07 00 00 00
+         01 00 10 00 01 00 00 00 00 00 00 00 00 00 00 00 00 00 e0 3f
+         03 00 08 00 02 00 00 00 03 00 04 00
+         02 3f 00 00",
+    );
+}
+
+#[test]
+fn mutable_xcdr2() {
+    let json = json!({"id": 7, "origin": {"x": 1, "y": 0.5}, "values": [3, 4]});
+    check(
+        &sample(&extra_types(), "Reading", json),
+        XcdrVersion::Xcdr2,
+        Endianness::Little,
+        "00 13 00 00
+         2c 00 00 00
+         00 00 00 a0 07 00 00 00
+         01 00 00 40 0c 00 00 00 01 00 00 00 00 00 00 00 00 00 e0 3f
+         03 00 00 40 08 00 00 00 02 00 00 00 03 00 04 00",
+    );
+}
+
+#[test]
+fn explicit_member_ids() {
+    // `a` has the ID 10 and `b` the next one, 11.
+    let json = json!({"a": 1, "b": 2});
+    check(
+        &sample(&extra_types(), "Numbered", json.clone()),
+        XcdrVersion::Xcdr1,
+        Endianness::Little,
+        "00 03 00 00
+         0a 00 04 00 01 00 00 00
+         0b 00 04 00 02 00 00 00
+         02 3f 00 00",
+    );
+    check(
+        &sample(&extra_types(), "Numbered", json),
+        XcdrVersion::Xcdr2,
+        Endianness::Little,
+        "00 13 00 03
+         0d 00 00 00
+         0a 00 00 00 01 00 00 00
+         0b 00 00 00 02 00 00 00",
+    );
+}
+
+#[test]
+fn optional_and_string_sequence_xcdr1() {
+    let json = json!({"tags": ["a", "bc"], "note": "n"});
+    check(
+        &sample(&extra_types(), "Tagged", json),
+        XcdrVersion::Xcdr1,
+        Endianness::Little,
+        "00 01 00 00
+         02 00 00 00
+         02 00 00 00 61 00 00 00
+         03 00 00 00 62 63 00 00
+         01 00 08 00 02 00 00 00 6e 00 00 00",
+    );
+}
+
+#[test]
+fn optional_and_string_sequence_xcdr2() {
+    let json = json!({"tags": ["a", "bc"], "note": "n"});
+    check(
+        &sample(&extra_types(), "Tagged", json),
+        XcdrVersion::Xcdr2,
+        Endianness::Little,
+        "00 15 00 02
+         1e 00 00 00
+         13 00 00 00 02 00 00 00
+         02 00 00 00 61 00 00 00
+         03 00 00 00 62 63 00
+         01 02 00 00 00 6e 00
+         00 00",
+    );
+}
+
+#[test]
+fn unset_optional() {
+    let json = json!({"tags": []});
+    check(
+        &sample(&extra_types(), "Tagged", json.clone()),
+        XcdrVersion::Xcdr1,
+        Endianness::Little,
+        "00 01 00 00
+         00 00 00 00
+         01 00 00 00",
+    );
+    check(
+        &sample(&extra_types(), "Tagged", json),
+        XcdrVersion::Xcdr2,
+        Endianness::Little,
+        "00 15 00 03
+         09 00 00 00
+         04 00 00 00 00 00 00 00
+         00 00 00 00",
+    );
+}
+
+#[test]
+fn truncated_buffer() {
+    let mut encoded = shape()
+        .to_cdr_buffer(XcdrVersion::Xcdr1, Endianness::Little)
+        .unwrap();
+    encoded.truncate(14);
+    let decoded = DynamicData::from_cdr_buffer(shape_types(), "ShapeType", &encoded);
+    assert_eq!(decoded, Err(CdrError::UnexpectedEnd(14)));
+}
+
+#[test]
+fn truncated_at_member_boundary() {
+    // Only the members of an XCDR2 appendable struct may end early, where
+    // the DHEADER says so: here the sample stops after `x`.
+    let mut encoded = shape()
+        .to_cdr_buffer(XcdrVersion::Xcdr1, Endianness::Little)
+        .unwrap();
+    encoded.truncate(20);
+    let decoded = DynamicData::from_cdr_buffer(shape_types(), "ShapeType", &encoded);
+    assert_eq!(decoded, Err(CdrError::UnexpectedEnd(20)));
+}
+
+#[test]
+fn enum_collections_xcdr2() {
+    // Enums are primitive: no DHEADER before the sequence or the array.
+    let json = json!({"fills": [1, 5], "corners": [0, 1]});
+    check(
+        &sample(&extra_types(), "Palette", json),
+        XcdrVersion::Xcdr2,
+        Endianness::Little,
+        "00 11 00 00
+         02 00 00 00 01 00 00 00 05 00 00 00
+         00 00 00 00 01 00 00 00",
+    );
+}
+
+/// Samples of the RTI Shapes Demo, in the bytes its writers send: each must
+/// decode to the sample below and encode back to the same bytes.
+#[test]
+fn shapes_demo_payloads() {
+    let purple = json!({"color": "PURPLE", "x": 115, "y": 87, "shapesize": 30});
+    let purple = sample(&shape_types(), "ShapeType", purple);
+    check(
+        &purple,
+        XcdrVersion::Xcdr1,
+        Endianness::Little,
+        "00 01 00 00
+         07 00 00 00 50 55 52 50 4c 45 00 00
+         73 00 00 00
+         57 00 00 00
+         1e 00 00 00",
+    );
+    check(
+        &purple,
+        XcdrVersion::Xcdr2,
+        Endianness::Little,
+        "00 15 00 00
+         18 00 00 00
+         07 00 00 00 50 55 52 50 4c 45 00 00
+         73 00 00 00
+         57 00 00 00
+         1e 00 00 00",
+    );
+
+    let orange = json!({
+        "color": "ORANGE",
+        "x": 182,
+        "y": 201,
+        "shapesize": 45,
+        "fillKind": 3,
+        "angle": 12.5,
+    });
+    let orange = sample(&shape_types(), "ShapeTypeExtended", orange);
+    check(
+        &orange,
+        XcdrVersion::Xcdr2,
+        Endianness::Little,
+        "00 15 00 00
+         20 00 00 00
+         07 00 00 00 4f 52 41 4e 47 45 00 00
+         b6 00 00 00
+         c9 00 00 00
+         2d 00 00 00
+         03 00 00 00
+         00 00 48 41",
+    );
+}
+
+#[test]
+fn recorded_samples() {
+    let directory = std::env::temp_dir().join(format!("connector-dds-cdr-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("capture.jsonl");
+    let topic = RecordedTopic {
+        reader: "MySubscriber::MySquareReader".to_string(),
+        topic: "Square".to_string(),
+        type_name: "ShapeType".to_string(),
+        types: (*shape_types()).clone(),
+    };
+    let encoder = cdr::recording_encoder(XcdrVersion::Xcdr2, Endianness::Little);
+    let mut recorder = Recorder::create(&path, vec![topic], Rotation::default())
+        .unwrap()
+        .with_encoder(encoder);
+    let record = Record {
+        reader: "MySubscriber::MySquareReader".to_string(),
+        topic: "Square".to_string(),
+        reception_timestamp: None,
+        source_timestamp: None,
+        info: Default::default(),
+        payload: Payload::Json(shape().to_json()),
+    };
+    recorder.append(&record).unwrap();
+    recorder.flush().unwrap();
+
+    let recording = Recording::open(&path).unwrap();
+    let types = Arc::new(recording.topics()[0].types.clone());
+    let records: Vec<_> = recording.records().collect::<Result<_, _>>().unwrap();
+    let Payload::Binary(bytes) = &records[0].payload else {
+        panic!("expected a binary payload, got {:?}", records[0].payload);
+    };
+    let expected = shape().to_cdr_buffer(XcdrVersion::Xcdr2, Endianness::Little);
+    assert_eq!(Ok(bytes), expected.as_ref());
+    let decoded = cdr::from_bytes(types, "ShapeType", bytes).unwrap();
+    assert_eq!(decoded.to_json(), shape().to_json());
+}
@@ -0,0 +1,74 @@
+//! A participant that both writes and reads a topic, for the tests that need
+//! the native library. They are skipped when the participant cannot be
+//! created, as when `rticonnector-sys` is built against a stub of it.
+
+use std::time::Duration;
+
+use connector_dds::publisher::DataWriter;
+use connector_dds::topic::TopicType;
+use connector_dds::{DdsType, DomainParticipant, Error};
+use rticonnector::builder::BuildError;
+use rticonnector::Connector;
+use serde::{Deserialize, Serialize};
+
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DdsType)]
+#[dds(name = "ShapeType")]
+pub struct Shape {
+    #[dds(key, string_max_length = 128)]
+    pub color: String,
+    pub x: i32,
+    pub y: i32,
+}
+
+pub fn shape(color: &str, x: i32) -> Shape {
+    Shape {
+        color: color.to_string(),
+        x,
+        y: 2 * x,
+    }
+}
+
+/// A participant with the writer `Pub::Writer` and the reader `Sub::Reader`
+/// of the topic `topic`, or `None` when the C library cannot create it.
+pub fn participant(topic: &str) -> Option<DomainParticipant> {
+    let xml = format!(
+        r#"<dds>
+    {types}
+    <domain_participant_library name="Lib">
+        <domain_participant name="Participant" domain_id="0">
+            <register_type name="ShapeType" type_ref="ShapeType"/>
+            <topic name="{topic}" register_type_ref="ShapeType"/>
+            <publisher name="Pub">
+                <data_writer name="Writer" topic_ref="{topic}">
+                    <datawriter_qos base_name="BuiltinQosLibExp::Generic.KeepLastReliable"/>
+                </data_writer>
+            </publisher>
+            <subscriber name="Sub">
+                <data_reader name="Reader" topic_ref="{topic}">
+                    <datareader_qos base_name="BuiltinQosLibExp::Generic.KeepLastReliable"/>
+                </data_reader>
+            </subscriber>
+        </domain_participant>
+    </domain_participant_library>
+</dds>"#,
+        types = Shape::types_xml(),
+        topic = topic,
+    );
+    match DomainParticipant::from_builder(Connector::builder("Lib::Participant").config_str(&xml)) {
+        Ok(participant) => Some(participant),
+        Err(Error::Build(BuildError::Create { message, .. })) => {
+            eprintln!("skipped, the participant cannot be created: {}", message);
+            None
+        }
+        Err(error) => panic!("{}", error),
+    }
+}
+
+/// Waits for `writer` to be matched with the reader.
+pub fn wait_for_match(writer: &DataWriter<Shape>) {
+    if writer.matched_subscriptions().unwrap().is_empty() {
+        writer.wait_for_matched_subscription(TIMEOUT).unwrap();
+    }
+}
@@ -0,0 +1,249 @@
+//! The XML definitions and the serde representation `#[derive(DdsType)]`
+//! generates.
+
+use connector_dds::topic::{types_xml, TopicType};
+use connector_dds::{schema, DdsType};
+use rticonnector::config::types::Types;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, DdsType)]
+#[dds(name = "Level", extensibility = "final")]
+enum Level {
+    Low,
+    Mid,
+    High = 10,
+    Top,
+    Negative = -3,
+    #[dds(name = "RENAMED")]
+    Renamed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DdsType)]
+#[dds(extensibility = "mutable")]
+struct Grid {
+    #[dds(key)]
+    id: u32,
+    level: Level,
+    rows: Vec<Vec<i32>>,
+    tiles: Vec<[u8; 2]>,
+    layers: Vec<Vec<Vec<f64>>>,
+    corners: [Vec<i16>; 2],
+    levels: Vec<Level>,
+    note: Option<String>,
+}
+
+#[test]
+fn enum_xml() {
+    assert_eq!(
+        types_xml::<Level>(),
+        r#"<types>
+    <enum name="Level" extensibility="final">
+        <enumerator name="Low" value="0"/>
+        <enumerator name="Mid" value="1"/>
+        <enumerator name="High" value="10"/>
+        <enumerator name="Top" value="11"/>
+        <enumerator name="Negative" value="-3"/>
+        <enumerator name="RENAMED" value="-2"/>
+    </enum>
+</types>
+"#
+    );
+}
+
+#[test]
+fn enum_values() {
+    let values = [
+        (Level::Low, "0"),
+        (Level::High, "10"),
+        (Level::Top, "11"),
+        (Level::Negative, "-3"),
+        (Level::Renamed, "-2"),
+    ];
+    for (level, json) in values {
+        assert_eq!(serde_json::to_string(&level).unwrap(), json);
+        assert_eq!(serde_json::from_str::<Level>(json).unwrap(), level);
+    }
+    let error = serde_json::from_str::<Level>("2").unwrap_err();
+    assert_eq!(error.to_string(), "2 is not a valid Level");
+}
+
+#[test]
+fn nested_collections_xml() {
+    // A sequence refers to sequences and arrays through typedefs; an array
+    // of sequences is a single member.
+    assert_eq!(
+        types_xml::<Grid>(),
+        r#"<types>
+    <enum name="Level" extensibility="final">
+        <enumerator name="Low" value="0"/>
+        <enumerator name="Mid" value="1"/>
+        <enumerator name="High" value="10"/>
+        <enumerator name="Top" value="11"/>
+        <enumerator name="Negative" value="-3"/>
+        <enumerator name="RENAMED" value="-2"/>
+    </enum>
+    <typedef name="sequence_long" type="long" sequenceMaxLength="-1"/>
+    <typedef name="array_2_octet" type="octet" arrayDimensions="2"/>
+    <typedef name="sequence_double" type="double" sequenceMaxLength="-1"/>
+    <typedef name="sequence_sequence_double" type="nonBasic" nonBasicTypeName="sequence_double" sequenceMaxLength="-1"/>
+    <struct name="Grid" extensibility="mutable">
+        <member name="id" type="unsignedLong" key="true"/>
+        <member name="level" type="nonBasic" nonBasicTypeName="Level"/>
+        <member name="rows" type="nonBasic" nonBasicTypeName="sequence_long" sequenceMaxLength="-1"/>
+        <member name="tiles" type="nonBasic" nonBasicTypeName="array_2_octet" sequenceMaxLength="-1"/>
+        <member name="layers" type="nonBasic" nonBasicTypeName="sequence_sequence_double" sequenceMaxLength="-1"/>
+        <member name="corners" type="short" sequenceMaxLength="-1" arrayDimensions="2"/>
+        <member name="levels" type="nonBasic" nonBasicTypeName="Level" sequenceMaxLength="-1"/>
+        <member name="note" type="string" stringMaxLength="-1" optional="true"/>
+    </struct>
+</types>
+"#
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DdsType)]
+#[dds(name = "Geo::Point")]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DdsType)]
+#[dds(name = "Geo::Region")]
+struct Region {
+    corners: Vec<Point>,
+    grid: Vec<[Point; 2]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DdsType)]
+#[dds(name = "Fleet::Vehicle")]
+#[serde(rename_all = "camelCase")]
+struct Vehicle {
+    #[dds(key, string_max_length = 32)]
+    vehicle_id: String,
+    r#type: u8,
+    #[serde(rename = "home")]
+    home_region: Region,
+    #[dds(name = "position")]
+    #[serde(rename = "position")]
+    last_position: Point,
+}
+
+#[test]
+fn modules_xml() {
+    assert_eq!(
+        types_xml::<Vehicle>(),
+        r#"<types>
+    <module name="Geo">
+        <struct name="Point">
+            <member name="x" type="double"/>
+            <member name="y" type="double"/>
+        </struct>
+    </module>
+    <typedef name="array_2_Geo_Point" type="nonBasic" nonBasicTypeName="Geo::Point" arrayDimensions="2"/>
+    <module name="Geo">
+        <struct name="Region">
+            <member name="corners" type="nonBasic" nonBasicTypeName="Geo::Point" sequenceMaxLength="-1"/>
+            <member name="grid" type="nonBasic" nonBasicTypeName="array_2_Geo_Point" sequenceMaxLength="-1"/>
+        </struct>
+    </module>
+    <module name="Fleet">
+        <struct name="Vehicle">
+            <member name="vehicleId" type="string" stringMaxLength="32" key="true"/>
+            <member name="type" type="octet"/>
+            <member name="home" type="nonBasic" nonBasicTypeName="Geo::Region"/>
+            <member name="position" type="nonBasic" nonBasicTypeName="Geo::Point"/>
+        </struct>
+    </module>
+</types>
+"#
+    );
+    let types = Types::from_xml(&format!("<dds>{}</dds>", Vehicle::types_xml())).unwrap();
+    assert!(types.get_struct("Geo::Region").is_some());
+    schema::check::<Vehicle>(&types, "Fleet::Vehicle").unwrap();
+}
+
+#[test]
+fn rename_all() {
+    let vehicle = Vehicle {
+        vehicle_id: "V1".to_string(),
+        r#type: 2,
+        home_region: Region {
+            corners: vec![],
+            grid: vec![],
+        },
+        last_position: Point { x: 0.5, y: 1.0 },
+    };
+    // The member names are the ones serde uses.
+    assert_eq!(
+        vehicle.to_json().unwrap(),
+        r#"{"vehicleId":"V1","type":2,"home":{"corners":[],"grid":[]},"position":{"x":0.5,"y":1.0}}"#
+    );
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, DdsType)]
+#[serde(rename_all = "PascalCase")]
+struct Pascal {
+    vehicle_id: u32,
+    x: u32,
+    last_known_x2: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, DdsType)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct Screaming {
+    vehicle_id: u32,
+    x: u32,
+}
+
+/// The member names of the struct `T`, and the keys serde writes for it.
+fn member_names<T: TopicType + Default>() -> (Vec<String>, Vec<String>) {
+    let types = Types::from_xml(&format!("<dds>{}</dds>", T::types_xml())).unwrap();
+    let members = types.get_struct(T::TYPE_NAME).unwrap().members.iter();
+    let keys = match serde_json::to_value(T::default()).unwrap() {
+        serde_json::Value::Object(object) => object.keys().cloned().collect(),
+        _ => unreachable!(),
+    };
+    (members.map(|m| m.name.clone()).collect(), keys)
+}
+
+#[test]
+fn rename_rules() {
+    let (members, mut keys) = member_names::<Pascal>();
+    assert_eq!(members, ["VehicleId", "X", "LastKnownX2"]);
+    keys.sort();
+    let mut sorted = members.clone();
+    sorted.sort();
+    assert_eq!(sorted, keys);
+
+    let (members, mut keys) = member_names::<Screaming>();
+    assert_eq!(members, ["VEHICLE_ID", "X"]);
+    keys.sort();
+    assert_eq!(members, keys);
+}
+
+#[test]
+fn generated_xml_matches_the_type() {
+    let types = Types::from_xml(&format!("<dds>{}</dds>", Grid::types_xml())).unwrap();
+    schema::check::<Grid>(&types, "Grid").unwrap();
+}
+
+#[test]
+fn json_round_trip() {
+    let grid = Grid {
+        id: 4,
+        level: Level::Top,
+        rows: vec![vec![1, 2], vec![], vec![3]],
+        tiles: vec![[0, 255]],
+        layers: vec![vec![vec![0.5]]],
+        corners: [vec![-1], vec![]],
+        levels: vec![Level::Negative, Level::Mid],
+        note: None,
+    };
+    let json = grid.to_json().unwrap();
+    assert_eq!(
+        json,
+        r#"{"id":4,"level":11,"rows":[[1,2],[],[3]],"tiles":[[0,255]],"layers":[[[0.5]]],"corners":[[-1],[]],"levels":[-3,1],"note":null}"#
+    );
+    assert_eq!(Grid::from_json(&json).unwrap(), grid);
+}
@@ -0,0 +1,103 @@
+//! Participants whose configuration the Rust model cannot represent, or
+//! cannot read at all.
+
+use connector_dds::{DomainParticipant, Error};
+use rticonnector::builder::BuildError;
+use rticonnector::config::ConfigError;
+use rticonnector::Connector;
+
+const XML: &str = r#"<dds>
+    <types>
+        <union name="Value">
+            <discriminator type="long"/>
+            <case><caseDiscriminator value="0"/><member name="count" type="long"/></case>
+            <case><caseDiscriminator value="1"/><member name="ratio" type="double"/></case>
+        </union>
+        <struct name="Reading">
+            <member name="value" type="nonBasic" nonBasicTypeName="Value"/>
+        </struct>
+    </types>
+    <domain_participant_library name="Lib">
+        <domain_participant name="Participant">
+            <register_type name="Reading" type_ref="Reading"/>
+            <topic name="Readings" register_type_ref="Reading"/>
+            <publisher name="Pub">
+                <data_writer name="Writer" topic_ref="Readings"/>
+            </publisher>
+        </domain_participant>
+    </domain_participant_library>
+</dds>"#;
+
+#[test]
+fn unsupported_definitions_are_left_to_the_c_library() {
+    match DomainParticipant::from_builder(Connector::builder("Lib::Participant").config_str(XML)) {
+        Ok(participant) => {
+            assert!(participant.config().is_none());
+            assert!(participant.topic("Readings").is_none());
+            assert!(participant.topics().is_empty());
+            assert_eq!(participant.publishers().count(), 0);
+            let publisher = participant.publisher("Pub").unwrap();
+            assert!(publisher.writer_names().is_empty());
+        }
+        // Without the native library, creation fails in the C library rather
+        // than on the `<union>` the Rust parser rejects.
+        Err(error) => assert!(
+            matches!(error, Error::Build(BuildError::Create { .. })),
+            "{}",
+            error
+        ),
+    }
+}
+
+#[test]
+fn overrides_need_the_model() {
+    let builder = Connector::builder("Lib::Participant")
+        .config_str(XML)
+        .domain_id(3);
+    let error = DomainParticipant::from_builder(builder).err().unwrap();
+    assert!(
+        matches!(error, Error::Build(BuildError::Config(_))),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn other_errors_are_returned() {
+    let error = |xml: &str| {
+        DomainParticipant::from_builder(Connector::builder("Lib::Participant").config_str(xml))
+            .err()
+            .unwrap()
+    };
+    let malformed = error("<dds><types></dds>");
+    assert!(
+        matches!(
+            malformed,
+            Error::Build(BuildError::Config(ConfigError::Xml(_)))
+        ),
+        "{}",
+        malformed
+    );
+    let invalid = error(
+        r#"<dds><types><struct name="Reading"><member name="value"/></struct></types></dds>"#,
+    );
+    assert!(
+        matches!(
+            invalid,
+            Error::Build(BuildError::Config(ConfigError::Invalid { .. }))
+        ),
+        "{}",
+        invalid
+    );
+    let missing = DomainParticipant::new("Lib::Participant", "/nonexistent/Missing.xml")
+        .err()
+        .unwrap();
+    assert!(
+        matches!(
+            missing,
+            Error::Build(BuildError::Config(ConfigError::Io { .. }))
+        ),
+        "{}",
+        missing
+    );
+}
@@ -0,0 +1,217 @@
+//! Building and modifying samples with `DynamicData`, and the errors that
+//! keep them valid for their type.
+
+use std::sync::Arc;
+
+use connector_dds::util::{DynamicData, DynamicDataError, DynamicValue};
+use rticonnector::config::types::Types;
+use serde_json::json;
+
+const TYPES: &str = r#"
+<dds>
+    <types>
+        <const name="LABEL_LENGTH" type="long" value="4"/>
+        <enum name="Level">
+            <enumerator name="LOW" value="1"/>
+            <enumerator name="HIGH" value="8"/>
+        </enum>
+        <struct name="Point">
+            <member name="x" type="short"/>
+            <member name="y" type="short"/>
+        </struct>
+        <struct name="Base">
+            <member name="id" type="unsignedLong" key="true"/>
+        </struct>
+        <struct name="Reading" baseType="Base">
+            <member name="label" type="string" stringMaxLength="LABEL_LENGTH"/>
+            <member name="level" type="nonBasic" nonBasicTypeName="Level"/>
+            <member name="origin" type="nonBasic" nonBasicTypeName="Point"/>
+            <member name="values" type="double" sequenceMaxLength="2"/>
+            <member name="corners" type="octet" arrayDimensions="2"/>
+            <member name="note" type="string" optional="true"/>
+        </struct>
+    </types>
+</dds>
+"#;
+
+fn types() -> Arc<Types> {
+    Arc::new(Types::from_xml(TYPES).unwrap())
+}
+
+fn reading() -> DynamicData {
+    DynamicData::new(types(), "Reading").unwrap()
+}
+
+#[test]
+fn defaults() {
+    let data = reading();
+    assert_eq!(data.member_count(), 7);
+    assert_eq!(
+        data.to_json(),
+        json!({
+            "id": 0,
+            "label": "",
+            "level": 1,
+            "origin": {"x": 0, "y": 0},
+            "values": [],
+            "corners": [0, 0],
+        })
+    );
+    assert_eq!(data.get("note"), Ok(None));
+}
+
+#[test]
+fn get_and_set_by_name() {
+    let mut data = reading();
+    data.set("id", DynamicValue::UnsignedLong(7)).unwrap();
+    data.set("label", "abcd".into()).unwrap();
+    data.set("level", DynamicValue::Enum(8)).unwrap();
+    data.set("note", "n".into()).unwrap();
+    data.get_struct_mut("origin")
+        .unwrap()
+        .set("y", DynamicValue::Short(-2))
+        .unwrap();
+    assert_eq!(data.get("id"), Ok(Some(&DynamicValue::UnsignedLong(7))));
+    assert_eq!(
+        data.get("label").unwrap().and_then(|v| v.as_str()),
+        Some("abcd")
+    );
+    assert_eq!(data.get("level"), Ok(Some(&DynamicValue::Enum(8))));
+    let origin = data.get("origin").unwrap().and_then(|v| v.as_struct());
+    assert_eq!(origin.unwrap().get("y"), Ok(Some(&DynamicValue::Short(-2))));
+
+    data.unset("note").unwrap();
+    assert_eq!(data.get("note"), Ok(None));
+    data.clear("label").unwrap();
+    assert_eq!(
+        data.get("label").unwrap().and_then(|v| v.as_str()),
+        Some("")
+    );
+}
+
+#[test]
+fn get_and_set_by_index() {
+    let mut data = reading();
+    // The inherited `id` comes first.
+    assert_eq!(data.member_index("id"), Some(0));
+    assert_eq!(data.member_index("note"), Some(6));
+    let info = data.member_info(0).unwrap();
+    assert_eq!((info.name.as_str(), info.key), ("id", true));
+
+    let values = DynamicValue::Sequence(vec![DynamicValue::Double(0.5)]);
+    data.set_by_index(4, values.clone()).unwrap();
+    assert_eq!(data.get_by_index(4), Ok(Some(&values)));
+    assert_eq!(data.get("values"), Ok(Some(&values)));
+    assert_eq!(data.info().set_member_count, 6);
+}
+
+#[test]
+fn errors() {
+    let mut data = reading();
+    assert_eq!(
+        DynamicData::new(types(), "Missing").err(),
+        Some(DynamicDataError::UnknownType("Missing".to_string()))
+    );
+    assert_eq!(
+        DynamicData::new(types(), "Level").err(),
+        Some(DynamicDataError::NotAStruct("Level".to_string()))
+    );
+    assert_eq!(
+        data.set("speed", DynamicValue::Float(1.0)),
+        Err(DynamicDataError::UnknownMember {
+            type_name: "Reading".to_string(),
+            member: "speed".to_string(),
+        })
+    );
+    assert_eq!(
+        data.get_by_index(7),
+        Err(DynamicDataError::UnknownIndex {
+            type_name: "Reading".to_string(),
+            index: 7,
+        })
+    );
+    assert_eq!(
+        data.set("id", DynamicValue::Long(-1)),
+        Err(DynamicDataError::Mismatch {
+            member: "id".to_string(),
+            expected: "unsignedLong".to_string(),
+            found: "long".to_string(),
+        })
+    );
+    assert_eq!(
+        data.set("label", "abcde".into()),
+        Err(DynamicDataError::Bound {
+            member: "label".to_string(),
+            bound: 4,
+            length: 5,
+        })
+    );
+    let values = DynamicValue::Sequence(vec![DynamicValue::Double(0.0); 3]);
+    assert_eq!(
+        data.set("values", values),
+        Err(DynamicDataError::Bound {
+            member: "values".to_string(),
+            bound: 2,
+            length: 3,
+        })
+    );
+    assert_eq!(
+        data.set("corners", DynamicValue::Array(vec![DynamicValue::Octet(1)])),
+        Err(DynamicDataError::ArrayLength {
+            member: "corners".to_string(),
+            expected: 2,
+            length: 1,
+        })
+    );
+    assert_eq!(
+        data.set("level", DynamicValue::Enum(2)),
+        Err(DynamicDataError::Enumerator {
+            member: "level".to_string(),
+            value: "2".to_string(),
+        })
+    );
+    assert_eq!(
+        data.unset("label"),
+        Err(DynamicDataError::NotOptional("label".to_string()))
+    );
+
+    // Only constants with a literal value can bound an array.
+    let xml = r#"<dds><types>
+        <const name="SIZE" type="long" value="2 * 2"/>
+        <struct name="Bad">
+            <member name="a" type="long" arrayDimensions="SIZE"/>
+        </struct>
+    </types></dds>"#;
+    let types = Arc::new(Types::from_xml(xml).unwrap());
+    assert_eq!(
+        DynamicData::new(types, "Bad").err(),
+        Some(DynamicDataError::Unresolved("SIZE".to_string()))
+    );
+}
+
+#[test]
+fn json_round_trip() {
+    let json = json!({
+        "id": 3,
+        "label": "ab",
+        "level": 8,
+        "origin": {"x": 1, "y": -1},
+        "values": [0.25, 1e300],
+        "corners": [0, 255],
+        "note": "text",
+    });
+    let data = DynamicData::from_json(types(), "Reading", &json).unwrap();
+    assert_eq!(data.to_json(), json);
+    let back = DynamicData::from_json(types(), "Reading", &data.to_json()).unwrap();
+    assert_eq!(back, data);
+
+    // Missing members keep their default, and a null optional is unset.
+    let data = DynamicData::from_json(types(), "Reading", &json!({"note": null})).unwrap();
+    assert_eq!(data, reading());
+    let error = DynamicData::from_json(types(), "Reading", &json!({"origin": {"x": "1"}}));
+    assert!(
+        matches!(error, Err(DynamicDataError::Mismatch { ref member, .. }) if member == "origin.x"),
+        "{:?}",
+        error
+    );
+}
@@ -0,0 +1,135 @@
+//! Instance handles: the handle of a value written from Rust must match the
+//! handle of the sample the Connector renders for it, which is how a reader
+//! and a writer find the same instance.
+
+use connector_dds::util::{key_members, InstanceHandle};
+use connector_dds::DdsType;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, DdsType)]
+enum Level {
+    Low,
+    High = 10,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DdsType)]
+struct SensorId {
+    site: u16,
+    offset: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DdsType)]
+#[dds(name = "Reading")]
+struct Reading {
+    #[dds(key)]
+    id: SensorId,
+    #[dds(key)]
+    scale: f64,
+    #[dds(key)]
+    level: Level,
+    value: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DdsType)]
+#[dds(name = "CalibratedReading")]
+struct CalibratedReading {
+    #[dds(base)]
+    #[serde(flatten)]
+    base: Reading,
+    #[dds(key, string_max_length = 16)]
+    unit: String,
+    factor: f64,
+}
+
+fn reading(scale: f64, level: Level, value: i32) -> Reading {
+    Reading {
+        id: SensorId {
+            site: 3,
+            offset: 2.0,
+        },
+        scale,
+        level,
+        value,
+    }
+}
+
+/// `reading(1.0, High, ..)` as the Connector renders it: integral doubles
+/// without a fraction and enums as their enumerator value.
+fn rendered(value: i32) -> Value {
+    json!({
+        "id": { "site": 3, "offset": 2 },
+        "scale": 1,
+        "level": 10,
+        "value": value,
+    })
+}
+
+#[test]
+fn key_members_include_the_base_type() {
+    assert_eq!(key_members::<Reading>().unwrap(), ["id", "scale", "level"]);
+    assert_eq!(
+        key_members::<CalibratedReading>().unwrap(),
+        ["id", "scale", "level", "unit"]
+    );
+}
+
+#[test]
+fn values_match_rendered_samples() {
+    let keys = key_members::<Reading>().unwrap();
+    let handle = InstanceHandle::from_value(&keys, &reading(1.0, Level::High, 7)).unwrap();
+    assert_eq!(handle, InstanceHandle::from_sample(&keys, &rendered(7)));
+    // Only the key members are compared.
+    assert_eq!(handle, InstanceHandle::from_sample(&keys, &rendered(8)));
+    assert_eq!(
+        handle.key(),
+        json!({ "id": { "site": 3, "offset": 2 }, "scale": 1, "level": 10 })
+    );
+
+    let others = [
+        reading(1.5, Level::High, 7),
+        reading(1.0, Level::Low, 7),
+        Reading {
+            id: SensorId {
+                site: 3,
+                offset: 2.5,
+            },
+            ..reading(1.0, Level::High, 7)
+        },
+    ];
+    for other in others {
+        let other = InstanceHandle::from_value(&keys, &other).unwrap();
+        assert_ne!(other, handle, "{:?}", other.key());
+    }
+}
+
+#[test]
+fn base_type_keys() {
+    let keys = key_members::<CalibratedReading>().unwrap();
+    let value = CalibratedReading {
+        base: reading(1.0, Level::High, 7),
+        unit: "mV".to_string(),
+        factor: 0.5,
+    };
+    let mut sample = rendered(7);
+    sample["unit"] = json!("mV");
+    sample["factor"] = json!(0.5);
+    let handle = InstanceHandle::from_value(&keys, &value).unwrap();
+    assert_eq!(handle, InstanceHandle::from_sample(&keys, &sample));
+
+    sample["unit"] = json!("V");
+    assert_ne!(handle, InstanceHandle::from_sample(&keys, &sample));
+}
+
+#[test]
+fn unkeyed_types_have_one_instance() {
+    let keys = Vec::new();
+    assert_eq!(
+        InstanceHandle::from_sample(&keys, &json!({ "value": 1 })),
+        InstanceHandle::from_sample(&keys, &json!({ "value": 2 }))
+    );
+    assert_eq!(
+        InstanceHandle::from_sample(&keys, &json!({})).key(),
+        json!({})
+    );
+}
@@ -0,0 +1,155 @@
+//! Key hash vectors: the serialized key when it never takes more than 16
+//! bytes, its MD5 digest otherwise, checked against digests computed
+//! independently of this crate.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use connector_dds::cdr::CdrError;
+use connector_dds::util::{DynamicData, DynamicDataError};
+use rticonnector::config::types::Types;
+use serde_json::{json, Value};
+
+const KEYED_TYPES: &str = r#"
+<dds>
+    <types>
+        <struct name="Sensor" extensibility="mutable">
+            <member name="id" type="long" key="true"/>
+            <member name="channel" type="short" key="true"/>
+            <member name="value" type="double"/>
+            <member name="stamp" type="longLong" key="true"/>
+        </struct>
+        <struct name="Track">
+            <member name="fleet" type="unsignedLong" key="true"/>
+            <member name="name" type="string" stringMaxLength="8" key="true"/>
+            <member name="speed" type="float"/>
+        </struct>
+        <struct name="Label">
+            <member name="text" type="string" stringMaxLength="10" key="true"/>
+        </struct>
+        <struct name="Coord" extensibility="final">
+            <member name="row" type="short"/>
+            <member name="column" type="short"/>
+        </struct>
+        <struct name="Cell">
+            <member name="pos" type="nonBasic" nonBasicTypeName="Coord" key="true"/>
+            <member name="layer" type="octet" key="true"/>
+            <member name="level" type="long"/>
+        </struct>
+    </types>
+</dds>
+"#;
+
+fn shape_types() -> Arc<Types> {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../rticonnector/examples/ShapeExample.xml");
+    Arc::new(Types::from_file(path).unwrap())
+}
+
+fn keyed_types() -> Arc<Types> {
+    Arc::new(Types::from_xml(KEYED_TYPES).unwrap())
+}
+
+fn key_hash(types: &Arc<Types>, type_name: &str, json: Value) -> Result<String, CdrError> {
+    let data = DynamicData::from_json(types.clone(), type_name, &json).unwrap();
+    let hash = data.key_hash()?;
+    Ok(hash.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+#[test]
+fn string_key() {
+    // color is a string<128>: MD5 of 00000005 "BLUE\0".
+    let json = json!({"color": "BLUE", "x": 1, "y": 2, "shapesize": 30});
+    assert_eq!(
+        key_hash(&shape_types(), "ShapeType", json).unwrap(),
+        "cac217c318363f8ef1160eeedef9e886"
+    );
+    let json = json!({"color": "RED", "x": 1, "y": 2, "shapesize": 30});
+    assert_eq!(
+        key_hash(&shape_types(), "ShapeType", json).unwrap(),
+        "d36de865fac295155f18df7157b217e6"
+    );
+}
+
+#[test]
+fn inherited_key() {
+    let json =
+        json!({"color": "BLUE", "x": 5, "y": 6, "shapesize": 10, "fillKind": 1, "angle": 90.0});
+    assert_eq!(
+        key_hash(&shape_types(), "ShapeTypeExtended", json).unwrap(),
+        "cac217c318363f8ef1160eeedef9e886"
+    );
+}
+
+#[test]
+fn only_key_members_count() {
+    let blue = |x: i32| json!({"color": "BLUE", "x": x, "y": 2, "shapesize": 30});
+    assert_eq!(
+        key_hash(&shape_types(), "ShapeType", blue(1)).unwrap(),
+        key_hash(&shape_types(), "ShapeType", blue(99)).unwrap()
+    );
+}
+
+#[test]
+fn multiple_keys_within_16_bytes() {
+    // 00000001 0002 + padding to 4 + 0000000000000003: exactly 16 bytes, the
+    // long long aligned to 4 as in XCDR2, and no EMHEADER for the mutable
+    // struct.
+    let json = json!({"id": 1, "channel": 2, "value": 0.5, "stamp": 3});
+    assert_eq!(
+        key_hash(&keyed_types(), "Sensor", json).unwrap(),
+        "00000001000200000000000000000003"
+    );
+}
+
+#[test]
+fn multiple_keys_over_16_bytes() {
+    // At most 4 + 4 + 9 bytes: MD5 of 00000007 00000004 "abc\0".
+    let json = json!({"fleet": 7, "name": "abc", "speed": 1.5});
+    assert_eq!(
+        key_hash(&keyed_types(), "Track", json).unwrap(),
+        "d2356240fc332845d8486bc9fc65e093"
+    );
+}
+
+#[test]
+fn multibyte_string_key() {
+    // The bound counts UTF-8 bytes: 5 × "é" fill the string<10>, 0000000b
+    // then 10 bytes and the NUL.
+    let json = json!({"text": "ééééé"});
+    assert_eq!(
+        key_hash(&keyed_types(), "Label", json).unwrap(),
+        "0000000bc3a9c3a9c3a9c3a9c3a90000"
+    );
+    let json = json!({"text": "éééééééééé"});
+    let data = DynamicData::from_json(keyed_types(), "Label", &json);
+    assert_eq!(
+        data.err(),
+        Some(DynamicDataError::Bound {
+            member: "text".to_string(),
+            bound: 10,
+            length: 20,
+        })
+    );
+}
+
+#[test]
+fn nested_struct_key() {
+    // Coord has no key members, so both of its members are part of the key.
+    let json = json!({"pos": {"row": 1, "column": 2}, "layer": 3, "level": 4});
+    assert_eq!(
+        key_hash(&keyed_types(), "Cell", json).unwrap(),
+        "00010002030000000000000000000000"
+    );
+}
+
+#[test]
+fn unkeyed_type() {
+    let xml =
+        r#"<dds><types><struct name="Plain"><member name="a" type="long"/></struct></types></dds>"#;
+    let types = Arc::new(Types::from_xml(xml).unwrap());
+    assert_eq!(
+        key_hash(&types, "Plain", json!({"a": 1})),
+        Err(CdrError::NoKey("Plain".to_string()))
+    );
+}
@@ -0,0 +1,171 @@
+//! Typed data writers: the parameters of a write rendered for the C library,
+//! and samples written through a loopback participant.
+
+mod common;
+
+use common::{participant, shape, wait_for_match, Shape, TIMEOUT};
+use connector_dds::publisher::{MatchedSubscription, WriteParams};
+use connector_dds::subscriber::{InstanceState, SampleIdentity};
+use connector_dds::Error;
+use serde_json::{json, Value};
+
+const GUID: [u8; 16] = [7; 16];
+
+fn identity(sequence_number: i64) -> SampleIdentity {
+    SampleIdentity {
+        writer_guid: [7; 16],
+        sequence_number,
+    }
+}
+
+fn params_json(params: WriteParams, action: Option<&str>) -> Value {
+    serde_json::from_str(&params.to_json(action)).unwrap()
+}
+
+#[test]
+fn write_params() {
+    assert_eq!(params_json(WriteParams::default(), None), json!({}));
+    assert_eq!(
+        params_json(WriteParams::default(), Some("dispose")),
+        json!({ "action": "dispose" })
+    );
+    let params = WriteParams::default()
+        .with_source_timestamp(1_500_000_000)
+        .with_identity(identity(1))
+        .with_related_sample_identity(identity(2));
+    assert_eq!(
+        params_json(params, Some("unregister")),
+        json!({
+            "action": "unregister",
+            "source_timestamp": 1_500_000_000,
+            "identity": { "writer_guid": GUID, "sequence_number": 1 },
+            "related_sample_identity": { "writer_guid": GUID, "sequence_number": 2 },
+        })
+    );
+    assert_eq!(
+        params_json(WriteParams::default().with_source_timestamp(-1), None),
+        json!({ "source_timestamp": -1 })
+    );
+}
+
+#[test]
+fn matched_subscriptions_json() {
+    let json = r#"[{"name":"MySubscriber::MySquareReader"},{"name":null}]"#;
+    assert_eq!(
+        MatchedSubscription::from_json(json).unwrap(),
+        [
+            MatchedSubscription {
+                name: Some("MySubscriber::MySquareReader".to_string())
+            },
+            MatchedSubscription { name: None },
+        ]
+    );
+    assert!(MatchedSubscription::from_json("null").is_err());
+}
+
+#[test]
+fn write_with() {
+    let Some(participant) = participant("WriteWith") else {
+        return;
+    };
+    let writer = participant
+        .publisher("Pub")
+        .unwrap()
+        .datawriter::<Shape>("Writer")
+        .unwrap();
+    let reader = participant
+        .subscriber("Sub")
+        .unwrap()
+        .datareader::<Shape>("Reader")
+        .unwrap();
+    wait_for_match(&writer);
+    let names: Vec<_> = writer
+        .matched_subscriptions()
+        .unwrap()
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    assert_eq!(names, [Some("Sub::Reader".to_string())]);
+
+    writer.write(&shape("BLUE", 1)).unwrap();
+    let params = WriteParams::default()
+        .with_source_timestamp(1_500_000_000_000_000_000)
+        .with_identity(identity(42));
+    writer.write_with(&shape("RED", 2), params).unwrap();
+    writer.wait_for_acknowledgments(TIMEOUT).unwrap();
+    reader.wait(TIMEOUT).unwrap();
+
+    let taken = reader.take_with_info().unwrap();
+    assert_eq!(taken.len(), 2);
+    assert_eq!(taken[0].0, shape("BLUE", 1));
+    assert_ne!(taken[0].1.source_timestamp, params.source_timestamp);
+    assert_eq!(taken[1].0, shape("RED", 2));
+    assert_eq!(taken[1].1.source_timestamp, params.source_timestamp);
+    assert_eq!(taken[1].1.sample_identity, params.identity);
+}
+
+#[test]
+fn dispose_and_unregister() {
+    let Some(participant) = participant("DisposeAndUnregister") else {
+        return;
+    };
+    let writer = participant
+        .publisher("Pub")
+        .unwrap()
+        .datawriter::<Shape>("Writer")
+        .unwrap();
+    let reader = participant
+        .subscriber("Sub")
+        .unwrap()
+        .datareader::<Shape>("Reader")
+        .unwrap();
+    wait_for_match(&writer);
+    writer.write(&shape("BLUE", 1)).unwrap();
+    writer.write(&shape("RED", 2)).unwrap();
+    writer.dispose(&shape("BLUE", 0)).unwrap();
+    writer.unregister(&shape("RED", 0)).unwrap();
+    writer.wait_for_acknowledgments(TIMEOUT).unwrap();
+    reader.wait(TIMEOUT).unwrap();
+
+    // The samples without valid data are only reported by instance.
+    let instances = reader.read_instances().unwrap();
+    let state = |color: &str| {
+        let handle = reader.lookup_instance(&shape(color, 0)).unwrap().unwrap();
+        let instance = instances.iter().find(|i| i.handle == handle).unwrap();
+        instance.instance_state
+    };
+    assert_eq!(state("BLUE"), Some(InstanceState::NotAliveDisposed));
+    assert_eq!(state("RED"), Some(InstanceState::NotAliveNoWriters));
+    // The writer no longer knows the unregistered instance.
+    assert!(writer.lookup_instance(&shape("RED", 0)).unwrap().is_none());
+    assert!(writer.lookup_instance(&shape("BLUE", 0)).unwrap().is_some());
+}
+
+#[test]
+fn instance_handles() {
+    let Some(participant) = participant("InstanceHandles") else {
+        return;
+    };
+    let writer = participant
+        .publisher("Pub")
+        .unwrap()
+        .datawriter::<Shape>("Writer")
+        .unwrap();
+    assert!(writer.lookup_instance(&shape("BLUE", 0)).unwrap().is_none());
+    let blue = writer.register_instance(&shape("BLUE", 0)).unwrap();
+    // Only the key members identify the instance.
+    assert_eq!(
+        writer.lookup_instance(&shape("BLUE", 5)).unwrap(),
+        Some(blue.clone())
+    );
+    assert!(writer.lookup_instance(&shape("RED", 0)).unwrap().is_none());
+
+    wait_for_match(&writer);
+    writer.write_with_handle(&shape("BLUE", 1), &blue).unwrap();
+    match writer.write_with_handle(&shape("RED", 1), &blue) {
+        Err(Error::InstanceMismatch { writer }) => assert_eq!(writer, "Pub::Writer"),
+        other => panic!("expected an instance mismatch, got {:?}", other.err()),
+    }
+    writer.unregister_instance(&blue).unwrap();
+    assert!(writer.lookup_instance(&shape("BLUE", 0)).unwrap().is_none());
+}
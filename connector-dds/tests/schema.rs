@@ -0,0 +1,157 @@
+//! Differences `schema::check` reports between a Rust type and the DDS type
+//! of its topic.
+
+use connector_dds::schema::{self, Difference, SchemaError};
+use connector_dds::DdsType;
+use rticonnector::config::types::Types;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, DdsType)]
+enum Fill {
+    Solid,
+    Hatch,
+}
+
+#[derive(Debug, Serialize, Deserialize, DdsType)]
+#[dds(name = "Shape")]
+struct Shape {
+    #[dds(key, string_max_length = 16)]
+    color: String,
+    x: i32,
+    fill: Fill,
+}
+
+const SHAPE: &str = r#"
+<dds>
+    <types>
+        <enum name="Fill">
+            <enumerator name="Solid"/>
+            <enumerator name="Hatch"/>
+        </enum>
+        <struct name="Shape">
+            <member name="color" type="string" stringMaxLength="16" key="true"/>
+            <member name="x" type="long"/>
+            <member name="fill" type="nonBasic" nonBasicTypeName="Fill"/>
+        </struct>
+    </types>
+</dds>
+"#;
+
+/// The differences with the DDS types of `SHAPE` once `old` is replaced by
+/// `new`.
+fn differences(old: &str, new: &str) -> Vec<Difference> {
+    let xml = SHAPE.replace(old, new);
+    assert!(old == new || xml != SHAPE, "`{}` is not in the types", old);
+    let types = Types::from_xml(&xml).unwrap();
+    match schema::check::<Shape>(&types, "Shape") {
+        Ok(()) => Vec::new(),
+        Err(SchemaError::Mismatch(mismatch)) => mismatch.differences,
+        Err(error) => panic!("unexpected error: {}", error),
+    }
+}
+
+fn difference(path: &str, dds: Option<&str>, rust: Option<&str>) -> Difference {
+    Difference {
+        path: path.to_string(),
+        dds: dds.map(str::to_string),
+        rust: rust.map(str::to_string),
+    }
+}
+
+#[test]
+fn matching_types() {
+    assert_eq!(differences("", ""), []);
+}
+
+#[test]
+fn missing_member() {
+    assert_eq!(
+        differences(
+            r#"<member name="x" type="long"/>"#,
+            r#"<member name="y" type="long"/>"#
+        ),
+        [
+            difference("y", Some("long"), None),
+            difference("x", None, Some("long")),
+        ]
+    );
+}
+
+#[test]
+fn kind_mismatch() {
+    assert_eq!(
+        differences(
+            r#"<member name="x" type="long"/>"#,
+            r#"<member name="x" type="double"/>"#
+        ),
+        [difference("x", Some("double"), Some("long"))]
+    );
+}
+
+#[test]
+fn key_mismatch() {
+    assert_eq!(
+        differences(
+            r#"stringMaxLength="16" key="true""#,
+            r#"stringMaxLength="16""#
+        ),
+        [difference(
+            "color",
+            Some("string<16>"),
+            Some("string<16> @key")
+        )]
+    );
+}
+
+#[test]
+fn bounds() {
+    // A tighter Rust bound is accepted, a looser one is not.
+    assert_eq!(
+        differences(r#"stringMaxLength="16""#, r#"stringMaxLength="32""#),
+        []
+    );
+    assert_eq!(
+        differences(r#"stringMaxLength="16""#, r#"stringMaxLength="8""#),
+        [difference(
+            "color",
+            Some("string<8> @key"),
+            Some("string<16> @key")
+        )]
+    );
+}
+
+#[test]
+fn enum_value_mismatch() {
+    assert_eq!(
+        differences(
+            r#"<enumerator name="Hatch"/>"#,
+            r#"<enumerator name="Hatch" value="4"/>"#
+        ),
+        [difference("fill::Hatch", Some("= 4"), Some("= 1"))]
+    );
+    assert_eq!(
+        differences(
+            r#"<enumerator name="Hatch"/>"#,
+            r#"<enumerator name="Hatch"/>
+            <enumerator name="Dots"/>"#
+        ),
+        [difference("fill::Dots", Some("= 2"), None)]
+    );
+}
+
+#[test]
+fn different_definitions() {
+    let xml = r#"<dds><types><enum name="Shape"><enumerator name="A"/></enum></types></dds>"#;
+    let types = Types::from_xml(xml).unwrap();
+    match schema::check::<Shape>(&types, "Shape") {
+        Err(SchemaError::Mismatch(mismatch)) => assert_eq!(
+            mismatch.differences,
+            [difference(
+                "Shape",
+                Some("enum Shape"),
+                Some("struct Shape")
+            )]
+        ),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
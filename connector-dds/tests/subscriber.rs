@@ -0,0 +1,214 @@
+//! Typed data readers: the infos and matched publications parsed from the C
+//! library's JSON, and samples received through a loopback participant.
+
+mod common;
+
+use std::time::Duration;
+
+use common::{participant, shape, wait_for_match, Shape, TIMEOUT};
+use connector_dds::subscriber::{
+    InstanceState, MatchedPublication, SampleIdentity, SampleState, ViewState,
+};
+use connector_dds::{DdsType, DomainParticipant, Error};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::json;
+
+/// The same DDS type, with a Rust type that rejects negative `x`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DdsType)]
+#[dds(name = "ShapeType")]
+struct Positive {
+    #[dds(key, string_max_length = 128)]
+    color: String,
+    #[serde(deserialize_with = "positive")]
+    x: i32,
+    y: i32,
+}
+
+fn positive<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    let x = i32::deserialize(deserializer)?;
+    if x < 0 {
+        return Err(serde::de::Error::custom(format!("{} is negative", x)));
+    }
+    Ok(x)
+}
+
+/// Writes `shapes` and waits for the reader to have received them.
+fn send(participant: &DomainParticipant, shapes: &[Shape]) {
+    let publisher = participant.publisher("Pub").unwrap();
+    let writer = publisher.datawriter::<Shape>("Writer").unwrap();
+    wait_for_match(&writer);
+    for shape in shapes {
+        writer.write(shape).unwrap();
+    }
+    writer.wait_for_acknowledgments(TIMEOUT).unwrap();
+}
+
+#[test]
+fn states() {
+    assert_eq!(SampleState::parse("READ"), Some(SampleState::Read));
+    assert_eq!(SampleState::parse("NOT_READ"), Some(SampleState::NotRead));
+    assert_eq!(ViewState::parse("NEW"), Some(ViewState::New));
+    assert_eq!(ViewState::parse("NOT_NEW"), Some(ViewState::NotNew));
+    assert_eq!(InstanceState::parse("ALIVE"), Some(InstanceState::Alive));
+    assert_eq!(
+        InstanceState::parse("NOT_ALIVE_DISPOSED"),
+        Some(InstanceState::NotAliveDisposed)
+    );
+    assert_eq!(
+        InstanceState::parse("NOT_ALIVE_NO_WRITERS"),
+        Some(InstanceState::NotAliveNoWriters)
+    );
+    assert_eq!(SampleState::parse("read"), None);
+    assert_eq!(ViewState::parse(""), None);
+    assert_eq!(InstanceState::parse("NOT_ALIVE"), None);
+}
+
+#[test]
+fn sample_identity() {
+    let guid: Vec<u8> = (0..16).collect();
+    let identity = SampleIdentity::from_json(&json!({
+        "writer_guid": guid,
+        "sequence_number": 42,
+    }))
+    .unwrap();
+    assert_eq!(identity.writer_guid.to_vec(), guid);
+    assert_eq!(identity.sequence_number, 42);
+
+    let invalid = [
+        json!({ "writer_guid": [1, 2, 3], "sequence_number": 42 }),
+        json!({ "writer_guid": (0..16).map(|b| b * 20).collect::<Vec<_>>(), "sequence_number": 42 }),
+        json!({ "writer_guid": guid, "sequence_number": "42" }),
+        json!({ "sequence_number": 42 }),
+        json!(null),
+    ];
+    for json in invalid {
+        assert_eq!(SampleIdentity::from_json(&json), None, "{}", json);
+    }
+}
+
+#[test]
+fn matched_publications_json() {
+    let json = r#"[{"name":"MyPublisher::MySquareWriter"},{"name":null},{}]"#;
+    assert_eq!(
+        MatchedPublication::from_json(json).unwrap(),
+        [
+            MatchedPublication {
+                name: Some("MyPublisher::MySquareWriter".to_string())
+            },
+            MatchedPublication { name: None },
+            MatchedPublication { name: None },
+        ]
+    );
+    assert_eq!(MatchedPublication::from_json("[]").unwrap(), []);
+    assert!(MatchedPublication::from_json(r#"{"name":"Writer"}"#).is_err());
+}
+
+#[test]
+fn read_and_take() {
+    let Some(participant) = participant("ReadAndTake") else {
+        return;
+    };
+    let subscriber = participant.subscriber("Sub").unwrap();
+    let reader = subscriber.datareader::<Shape>("Reader").unwrap();
+    assert!(matches!(
+        reader.wait(Duration::from_millis(100)),
+        Err(Error::Timeout(_))
+    ));
+    let matched = reader.matched_publications().unwrap();
+    send(&participant, &[shape("BLUE", 1), shape("RED", 2)]);
+    reader.wait(TIMEOUT).unwrap();
+
+    let read = reader.read_with_info().unwrap();
+    assert_eq!(
+        read.iter().map(|(s, _)| s.clone()).collect::<Vec<_>>(),
+        [shape("BLUE", 1), shape("RED", 2)]
+    );
+    for (_, info) in &read {
+        assert!(info.valid_data);
+        assert_eq!(info.sample_state, Some(SampleState::NotRead));
+        assert_eq!(info.view_state, Some(ViewState::New));
+        assert_eq!(info.instance_state, Some(InstanceState::Alive));
+        assert!(info.source_timestamp.is_some());
+        assert!(info.sample_identity.is_some());
+    }
+    // Left in the cache, now read.
+    assert_eq!(reader.read().unwrap().len(), 2);
+    let taken = reader.take_with_info().unwrap();
+    assert_eq!(taken.len(), 2);
+    assert!(taken
+        .iter()
+        .all(|(_, info)| info.sample_state == Some(SampleState::Read)));
+    assert!(reader.take().unwrap().is_empty());
+
+    let names: Vec<_> = reader
+        .matched_publications()
+        .unwrap()
+        .into_iter()
+        .chain(matched)
+        .map(|p| p.name)
+        .collect();
+    assert!(
+        names.contains(&Some("Pub::Writer".to_string())),
+        "{:?}",
+        names
+    );
+}
+
+#[test]
+fn deserialize_error_names_the_member() {
+    let Some(participant) = participant("Negative") else {
+        return;
+    };
+    send(&participant, &[shape("BLUE", -1)]);
+    let subscriber = participant.subscriber("Sub").unwrap();
+    let reader = subscriber.datareader::<Positive>("Reader").unwrap();
+    reader.wait(TIMEOUT).unwrap();
+    match reader.take() {
+        Err(Error::Deserialize { reader, source }) => {
+            assert_eq!(reader, "Sub::Reader");
+            assert_eq!(source.field(), Some("x"));
+            assert!(source.to_string().contains("-1 is negative"), "{}", source);
+        }
+        other => panic!(
+            "expected a deserialization error, got {:?}",
+            other.map(|_| ())
+        ),
+    }
+}
+
+#[test]
+fn taken_instances_that_are_not_alive_are_forgotten() {
+    let Some(participant) = participant("Forgotten") else {
+        return;
+    };
+    let writer = participant
+        .publisher("Pub")
+        .unwrap()
+        .datawriter::<Shape>("Writer")
+        .unwrap();
+    let reader = participant
+        .subscriber("Sub")
+        .unwrap()
+        .datareader::<Shape>("Reader")
+        .unwrap();
+    wait_for_match(&writer);
+    writer.write(&shape("BLUE", 1)).unwrap();
+    writer.write(&shape("RED", 2)).unwrap();
+    writer.dispose(&shape("BLUE", 0)).unwrap();
+    writer.wait_for_acknowledgments(TIMEOUT).unwrap();
+    reader.wait(TIMEOUT).unwrap();
+
+    let lookup = |color: &str| reader.lookup_instance(&shape(color, 0)).unwrap();
+    // Reading leaves the samples of the disposed instance in the cache.
+    reader.read_instances().unwrap();
+    let blue = lookup("BLUE").unwrap();
+    assert_eq!(
+        reader.instance_state(&blue),
+        Some(InstanceState::NotAliveDisposed)
+    );
+    reader.take_instances().unwrap();
+    assert!(lookup("BLUE").is_none());
+    assert_eq!(reader.instance_state(&blue), None);
+    let red = lookup("RED").unwrap();
+    assert_eq!(reader.instance_state(&red), Some(InstanceState::Alive));
+}
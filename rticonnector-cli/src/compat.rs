@@ -0,0 +1,54 @@
+//! The `compat` subcommand.
+//!
+//! The types of the old file are the reader side and those of the new file
+//! the writer side; swapping the files checks the other direction.
+
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use rticonnector_config::assignability;
+use rticonnector_config::types::Types;
+
+use crate::error::Error;
+
+#[derive(Args)]
+pub struct CompatArgs {
+    /// The types of the readers.
+    old: PathBuf,
+    /// The types of the writers.
+    new: PathBuf,
+    /// The structs to check; every struct of the old types by default.
+    #[arg(long = "type", value_name = "NAME")]
+    types: Vec<String>,
+}
+
+/// Prints whether readers of each struct of the old types can read writers
+/// of the same struct of the new types, with the reasons when they cannot.
+pub fn compat(args: CompatArgs) -> Result<(), Error> {
+    let old = read_types(&args.old)?;
+    let new = read_types(&args.new)?;
+    let names = match args.types.is_empty() {
+        true => old.structs().map(|s| s.name.clone()).collect(),
+        false => args.types,
+    };
+
+    let mut incompatible = 0;
+    for name in &names {
+        let report = assignability::check(&old, name, &new, name);
+        if !report.is_compatible() {
+            incompatible += 1;
+        }
+        println!("{}", report);
+    }
+    match incompatible {
+        0 => Ok(()),
+        count => Err(Error::Incompatible(count)),
+    }
+}
+
+fn read_types(path: &Path) -> Result<Types, Error> {
+    Types::from_file(path).map_err(|source| Error::File {
+        path: path.to_path_buf(),
+        source,
+    })
+}
@@ -0,0 +1,64 @@
+//! Options shared by the subcommands to create the participant.
+
+use std::path::PathBuf;
+
+use clap::Args;
+use rticonnector::config::filter::ContentFilter;
+use rticonnector::{Connector, ConnectorBuilder};
+
+#[derive(Args)]
+pub struct ConnectionArgs {
+    /// A configuration file; repeat to combine shared types and QoS files.
+    #[arg(long = "config", value_name = "FILE", required = true)]
+    pub configs: Vec<PathBuf>,
+    /// The participant to create, as `Library::Participant`.
+    #[arg(long)]
+    pub participant: String,
+    /// Join this domain instead of the one of the configuration.
+    #[arg(long)]
+    pub domain: Option<u32>,
+    /// Place the publishers and subscribers in this partition; repeat for
+    /// several.
+    #[arg(long = "partition", value_name = "PARTITION")]
+    pub partitions: Vec<String>,
+}
+
+impl ConnectionArgs {
+    pub fn builder(&self) -> ConnectorBuilder {
+        let mut builder = Connector::builder(&self.participant).config_files(&self.configs);
+        if let Some(domain) = self.domain {
+            builder = builder.domain_id(domain);
+        }
+        if !self.partitions.is_empty() {
+            builder = builder.partitions(&self.partitions);
+        }
+        builder
+    }
+}
+
+#[derive(Args)]
+pub struct FilterArgs {
+    /// Only receive the samples matching this SQL expression, such as
+    /// `color = %0`.
+    #[arg(long)]
+    pub filter: Option<String>,
+    /// The value of `%0`, `%1`, ... in the filter; quote strings: `'BLUE'`.
+    #[arg(long = "param", value_name = "VALUE", requires = "filter")]
+    pub params: Vec<String>,
+}
+
+impl FilterArgs {
+    /// Filters every reader of `readers` (`"Subscriber::Reader"`).
+    pub fn apply(&self, mut builder: ConnectorBuilder, readers: &[String]) -> ConnectorBuilder {
+        if let Some(expression) = &self.filter {
+            for reader in readers {
+                // Filtered topics are named after their reader, which is
+                // unique within the participant.
+                let name = format!("{}Filter", reader.replace("::", "_"));
+                let filter = ContentFilter::new(&name, expression, &self.params);
+                builder = builder.content_filter(reader, filter);
+            }
+        }
+        builder
+    }
+}
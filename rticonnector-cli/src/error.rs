@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+#[cfg(feature = "native")]
+use rticonnector::builder::BuildError;
+#[cfg(feature = "native")]
+use rticonnector::recorder::RecordError;
+#[cfg(feature = "native")]
+use rticonnector::replay::ReplayError;
+use rticonnector_config::ConfigError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[cfg(feature = "native")]
+    #[error(transparent)]
+    Build(#[from] BuildError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[cfg(feature = "native")]
+    #[error(transparent)]
+    Record(#[from] RecordError),
+    #[cfg(feature = "native")]
+    #[error(transparent)]
+    Replay(#[from] ReplayError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("{}: {source}", path.display())]
+    File {
+        path: PathBuf,
+        #[source]
+        source: ConfigError,
+    },
+    #[error("{0} error(s) in the configuration")]
+    InvalidConfig(usize),
+    #[cfg(feature = "native")]
+    #[error("no participant `{0}` in the configuration")]
+    UnknownParticipant(String),
+    #[cfg(feature = "native")]
+    #[error("participant `{0}` has no data reader")]
+    NoReader(String),
+    #[cfg(feature = "native")]
+    #[error("participant `{0}` has no writer for the recorded topics")]
+    NoWriter(String),
+    #[cfg(feature = "native")]
+    #[error("no writer of the participant publishes topic `{0}`")]
+    NoTopicWriter(String),
+    #[cfg(feature = "native")]
+    #[error("`{entity}`: {message}")]
+    Entity { entity: String, message: String },
+    #[cfg(feature = "native")]
+    #[error("`{entity}` returned a sample that is not JSON: {source}")]
+    Sample {
+        entity: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[cfg(feature = "native")]
+    #[error("line {line} is not a JSON sample: {source}")]
+    Input {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("{0} type(s) cannot be read from the new types")]
+    Incompatible(usize),
+}
+
+#[cfg(feature = "native")]
+impl Error {
+    /// An error raised by the C library on the reader or writer `entity`.
+    pub fn entity(entity: &str, message: impl std::fmt::Display) -> Self {
+        let last_error = rticonnector::Connector::get_last_error();
+        let message = if last_error.is_empty() {
+            message.to_string()
+        } else {
+            format!("{} ({})", message, last_error.trim())
+        };
+        Error::Entity {
+            entity: entity.to_string(),
+            message,
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! The `inspect` subcommand.
+//!
+//! Only the Rust model of the configuration is used, so the check runs
+//! without creating any participant.
+
+use std::path::PathBuf;
+
+use clap::Args;
+use rticonnector_config::types::{Bound, MemberType, TypeDefinition, TypeKind, Types};
+use rticonnector_config::Config;
+
+use crate::error::Error;
+
+#[derive(Args)]
+pub struct InspectArgs {
+    /// The configuration files, combined in order.
+    #[arg(required = true, value_name = "FILE")]
+    configs: Vec<PathBuf>,
+}
+
+/// Prints the participants and types of the configuration, then reports
+/// its dangling references on stderr.
+pub fn inspect(args: InspectArgs) -> Result<(), Error> {
+    let mut builder = Config::builder();
+    for path in &args.configs {
+        let config = Config::from_file(path).map_err(|source| Error::File {
+            path: path.clone(),
+            source,
+        })?;
+        builder = builder.config(config);
+    }
+    let config = builder.build();
+
+    print_participants(&config);
+    print_types(&config.types);
+
+    let errors = config.validate();
+    for error in &errors {
+        // Lines can only be attributed to a file when there is one.
+        match args.configs.as_slice() {
+            [path] => eprintln!("{}: {}", path.display(), error),
+            _ => eprintln!("{}", error),
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::InvalidConfig(errors.len()))
+    }
+}
+
+fn print_participants(config: &Config) {
+    for (name, participant) in config.participants() {
+        println!("participant {}", name);
+        if let Some(domain_ref) = &participant.domain_ref {
+            match config.domain_id(participant) {
+                Some(domain_id) => println!("  domain {} (domain_id {})", domain_ref, domain_id),
+                None => println!("  domain {}", domain_ref),
+            }
+        }
+        let domain = participant
+            .domain_ref
+            .as_deref()
+            .and_then(|d| config.domain(d));
+        let topics = participant
+            .topics
+            .iter()
+            .chain(domain.into_iter().flat_map(|d| &d.topics));
+        for topic in topics {
+            let type_name = config.topic_type(participant, &topic.name).unwrap_or("?");
+            println!("  topic {}: {}", topic.name, type_name);
+        }
+        for (writer, data_writer) in participant.writers() {
+            println!("  writer {} -> {}", writer, data_writer.topic_ref);
+        }
+        for (reader, data_reader) in participant.readers() {
+            match &data_reader.content_filter {
+                Some(filter) => println!(
+                    "  reader {} -> {} where {}",
+                    reader, data_reader.topic_ref, filter.expression
+                ),
+                None => println!("  reader {} -> {}", reader, data_reader.topic_ref),
+            }
+        }
+    }
+}
+
+fn print_types(types: &Types) {
+    for definition in types.definitions() {
+        match definition {
+            TypeDefinition::Const(c) => {
+                println!("const {}: {} = {}", c.name, c.kind.xml_name(), c.value)
+            }
+            TypeDefinition::Struct(s) => {
+                match &s.base_type {
+                    Some(base_type) => println!("struct {} : {}", s.name, base_type),
+                    None => println!("struct {}", s.name),
+                }
+                for member in &s.members {
+                    let mut annotations = String::new();
+                    if member.key {
+                        annotations.push_str(" @key");
+                    }
+                    if member.optional {
+                        annotations.push_str(" @optional");
+                    }
+                    println!(
+                        "  {}{}: {}{}",
+                        member.name,
+                        dimensions(&member.member_type),
+                        type_name(&member.member_type),
+                        annotations
+                    );
+                }
+            }
+            TypeDefinition::Enum(e) => {
+                println!("enum {}", e.name);
+                for enumerator in &e.enumerators {
+                    println!("  {} = {}", enumerator.name, enumerator.value);
+                }
+            }
+            TypeDefinition::Typedef(t) => println!(
+                "typedef {}{} = {}",
+                t.name,
+                dimensions(&t.member_type),
+                type_name(&t.member_type)
+            ),
+        }
+    }
+}
+
+/// The type of a member without its array dimensions, such as
+/// `sequence<string<128>, 10>`.
+fn type_name(member_type: &MemberType) -> String {
+    let mut name = member_type.kind.xml_name().to_string();
+    if matches!(member_type.kind, TypeKind::String | TypeKind::WString) {
+        if let Some(bound) = member_type.string_max_length.as_ref().and_then(bound) {
+            name = format!("{}<{}>", name, bound);
+        }
+    }
+    match member_type.sequence_max_length.as_ref().map(bound) {
+        Some(Some(bound)) => format!("sequence<{}, {}>", name, bound),
+        Some(None) => format!("sequence<{}>", name),
+        None => name,
+    }
+}
+
+/// The array dimensions of a member, such as `[3][4]`.
+fn dimensions(member_type: &MemberType) -> String {
+    member_type
+        .array_dimensions
+        .iter()
+        .map(|dimension| format!("[{}]", dimension))
+        .collect()
+}
+
+/// A bound, or `None` when it is unbounded.
+fn bound(bound: &Bound) -> Option<String> {
+    match bound {
+        Bound::Unbounded => None,
+        bound => Some(bound.to_string()),
+    }
+}
@@ -0,0 +1,76 @@
+//! The subcommands of `rticonnector-cli`, parsed by [`Cli`] and run by
+//! [`Command::run`]; see the binary for their use.
+
+pub mod compat;
+#[cfg(feature = "native")]
+pub mod connection;
+pub mod error;
+pub mod inspect;
+#[cfg(feature = "native")]
+pub mod publish;
+#[cfg(feature = "native")]
+pub mod record;
+#[cfg(feature = "native")]
+pub mod replay;
+#[cfg(feature = "native")]
+pub mod spy;
+
+use clap::{Parser, Subcommand};
+
+use error::Error;
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Spy on and publish to the topics of a Connector XML configuration"
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Print the samples and infos received by the readers of a participant.
+    #[cfg(feature = "native")]
+    Spy(spy::SpyArgs),
+    /// Print the samples received by one reader.
+    #[cfg(feature = "native")]
+    Echo(spy::EchoArgs),
+    /// Write the JSON samples read from stdin or a file with one writer.
+    #[cfg(feature = "native")]
+    Pub(publish::PubArgs),
+    /// List the participants, entities and types of a configuration and
+    /// check its references.
+    Inspect(inspect::InspectArgs),
+    /// Append the samples and infos received by the readers of a participant
+    /// to a recording.
+    #[cfg(feature = "native")]
+    Record(record::RecordArgs),
+    /// Write the samples of a recording with the writers of a participant,
+    /// with their recorded timing.
+    #[cfg(feature = "native")]
+    Replay(replay::ReplayArgs),
+    /// Check that readers of the types of one file can read writers of the
+    /// types of another.
+    Compat(compat::CompatArgs),
+}
+
+impl Command {
+    pub fn run(self) -> Result<(), Error> {
+        match self {
+            #[cfg(feature = "native")]
+            Command::Spy(args) => spy::spy(args),
+            #[cfg(feature = "native")]
+            Command::Echo(args) => spy::echo(args),
+            #[cfg(feature = "native")]
+            Command::Pub(args) => publish::publish(args),
+            Command::Inspect(args) => inspect::inspect(args),
+            #[cfg(feature = "native")]
+            Command::Record(args) => record::record(args),
+            #[cfg(feature = "native")]
+            Command::Replay(args) => replay::replay(args),
+            Command::Compat(args) => compat::compat(args),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Command-line tools for the topics of a Connector XML configuration.
+//!
+//! ```text
+//! rticonnector-cli spy --config ShapeExample.xml --participant MyParticipantLibrary::MySubParticipant
+//! rticonnector-cli echo --config ShapeExample.xml --participant MyParticipantLibrary::MySubParticipant \
+//!     MySubscriber::MySquareReader --filter "color = %0" --param "'BLUE'"
+//! rticonnector-cli pub --config ShapeExample.xml --participant MyParticipantLibrary::MyPubParticipant \
+//!     MyPublisher::MySquareWriter --rate 2 < squares.jsonl
+//! ```
+//!
+//! Samples are read and written as JSON lines, so the output of `echo` can be
+//! replayed with `pub`. `inspect` checks a configuration without creating a
+//! participant, and exits with an error status when it has dangling
+//! references:
+//!
+//! ```text
+//! rticonnector-cli inspect types.xml ShapeExample.xml
+//! ```
+//!
+//! `record` keeps the samples and their infos for offline analysis, in files
+//! that carry the types of the recorded topics and that are read back with
+//! `rticonnector::recorder::Recording`:
+//!
+//! ```text
+//! rticonnector-cli record --config ShapeExample.xml --participant MyParticipantLibrary::MySubParticipant \
+//!     --output capture.jsonl --rotate-size 100000000 --duration 3600
+//! ```
+//!
+//! `replay` writes a recording back with the writers of the recorded topics,
+//! keeping the time between samples:
+//!
+//! ```text
+//! rticonnector-cli replay --config ShapeExample.xml --participant MyParticipantLibrary::MyPubParticipant \
+//!     capture.jsonl --speed 10 --from 60 --to 120
+//! ```
+//!
+//! Built without the default `native` feature, the tool only has `inspect`
+//! and `compat`, and does not link the native library: a CI gate on
+//! configurations can then run where the library is not available.
+//!
+//! `compat` tells whether readers of the types of one file can read writers
+//! of the types of another, under the XTypes assignability rules, and exits
+//! with an error status when some cannot:
+//!
+//! ```text
+//! rticonnector-cli compat old.xml new.xml --type ShapeType
+//! ```
+
+use std::process::ExitCode;
+
+use clap::Parser;
+use rticonnector_cli::Cli;
+
+fn main() -> ExitCode {
+    match Cli::parse().command.run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
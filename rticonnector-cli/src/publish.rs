@@ -0,0 +1,146 @@
+//! The `pub` subcommand.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use rticonnector::Connector;
+use serde_json::Value;
+
+use crate::connection::ConnectionArgs;
+use crate::error::Error;
+use crate::spy::parse_seconds;
+
+#[derive(Args)]
+pub struct PubArgs {
+    #[command(flatten)]
+    connection: ConnectionArgs,
+    /// The writer to publish with, as `Publisher::Writer`.
+    writer: String,
+    /// Read the samples from this file instead of stdin, one JSON object per
+    /// line.
+    #[arg(long)]
+    file: Option<PathBuf>,
+    /// Samples written per second.
+    #[arg(long, default_value = "1", value_parser = parse_rate)]
+    rate: f64,
+    /// Stop after this many samples.
+    #[arg(long)]
+    count: Option<usize>,
+    /// Write the samples again from the first once the input is exhausted.
+    #[arg(long)]
+    repeat: bool,
+    /// Wait up to this many seconds for a reader to match before writing.
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    wait_for_reader: Option<Duration>,
+}
+
+/// Writes samples at a fixed rate.
+struct Writer {
+    connector: Connector,
+    name: String,
+    period: Duration,
+    next: Instant,
+    written: usize,
+    count: Option<usize>,
+}
+
+impl Writer {
+    fn done(&self) -> bool {
+        self.count.is_some_and(|count| self.written >= count)
+    }
+
+    fn write(&mut self, sample: &str) -> Result<(), Error> {
+        let now = Instant::now();
+        if self.next > now {
+            thread::sleep(self.next - now);
+        }
+        self.next = self.next.max(now) + self.period;
+        let name = &self.name;
+        self.connector
+            .clear(name)
+            .map_err(|e| Error::entity(name, e))?;
+        self.connector
+            .set_json_instance(name, sample)
+            .map_err(|e| Error::entity(name, e))?;
+        self.connector
+            .write(name, "{}")
+            .map_err(|e| Error::entity(name, e))?;
+        self.written += 1;
+        Ok(())
+    }
+}
+
+pub fn publish(args: PubArgs) -> Result<(), Error> {
+    let input: Box<dyn BufRead> = match &args.file {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(io::stdin().lock()),
+    };
+    let connector = args.connection.builder().build()?;
+    if let Some(timeout) = args.wait_for_reader {
+        let data_writer = connector
+            .get_dynamic_datawriter(&args.writer)
+            .map_err(|e| Error::entity(&args.writer, e))?;
+        data_writer
+            .wait_for_matched_subscription(timeout)
+            .map_err(|e| Error::entity(&args.writer, e))?;
+    }
+    let mut writer = Writer {
+        connector,
+        name: args.writer,
+        // Representable, as checked by `parse_rate`.
+        period: Duration::from_secs_f64(1.0 / args.rate),
+        next: Instant::now(),
+        written: 0,
+        count: args.count,
+    };
+
+    // Samples are written as they are read, so the input can be a stream
+    // such as the output of `echo`.
+    let mut samples = Vec::new();
+    for (number, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let sample: Value = serde_json::from_str(&line).map_err(|source| Error::Input {
+            line: number + 1,
+            source,
+        })?;
+        if writer.done() {
+            return Ok(());
+        }
+        let sample = sample.to_string();
+        writer.write(&sample)?;
+        if args.repeat {
+            samples.push(sample);
+        }
+    }
+    while args.repeat && !samples.is_empty() {
+        for sample in &samples {
+            if writer.done() {
+                return Ok(());
+            }
+            writer.write(sample)?;
+        }
+    }
+    Ok(())
+}
+
+/// Parses a rate of samples per second, rejecting those whose period cannot
+/// be slept, such as `0`, `inf` or `1e-300`.
+pub fn parse_rate(rate: &str) -> Result<f64, String> {
+    let rate: f64 = rate.parse().map_err(|e| format!("{}", e))?;
+    if !(rate > 0.0 && rate.is_finite()) {
+        return Err(format!(
+            "the rate must be a positive number of samples per second, not {}",
+            rate
+        ));
+    }
+    Duration::try_from_secs_f64(1.0 / rate)
+        .map_err(|_| format!("a rate of {} is too low to wait between samples", rate))?;
+    Ok(rate)
+}
@@ -0,0 +1,108 @@
+//! The `record` subcommand.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use rticonnector::recorder::{RecordedTopic, Recorder, Rotation};
+use rticonnector::ConnectorError;
+use serde_json::Value;
+
+use crate::connection::{ConnectionArgs, FilterArgs};
+use crate::error::Error;
+use crate::spy::{parse_seconds, LimitArgs, POLL_INTERVAL};
+
+#[derive(Args)]
+pub struct RecordArgs {
+    #[command(flatten)]
+    connection: ConnectionArgs,
+    /// The first file of the recording; it must not exist.
+    #[arg(long, short)]
+    output: PathBuf,
+    /// A reader to record, as `Subscriber::Reader`; every reader of the
+    /// participant when omitted.
+    #[arg(long = "reader")]
+    readers: Vec<String>,
+    /// Start a new file rather than growing the current one past this many
+    /// bytes.
+    #[arg(long, value_name = "BYTES")]
+    rotate_size: Option<u64>,
+    /// Start a new file every this many seconds.
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    rotate_interval: Option<Duration>,
+    #[command(flatten)]
+    filter: FilterArgs,
+    #[command(flatten)]
+    limits: LimitArgs,
+}
+
+/// Appends the samples of the readers and their infos to a recording.
+pub fn record(args: RecordArgs) -> Result<(), Error> {
+    let builder = args.connection.builder();
+    let config = builder.resolve()?;
+    let participant = &args.connection.participant;
+    let readers = if args.readers.is_empty() {
+        config
+            .participant(participant)
+            .ok_or_else(|| Error::UnknownParticipant(participant.clone()))?
+            .readers()
+            .map(|(name, _)| name)
+            .collect()
+    } else {
+        args.readers
+    };
+    if readers.is_empty() {
+        return Err(Error::NoReader(participant.clone()));
+    }
+    let topics = readers
+        .iter()
+        .map(|reader| RecordedTopic::from_config(&config, participant, reader))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let connector = args.filter.apply(builder, &readers).build()?;
+    let inputs = readers
+        .iter()
+        .map(|name| {
+            connector
+                .get_input::<Value>(name)
+                .map_err(|e| Error::entity(name, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let rotation = Rotation {
+        max_bytes: args.rotate_size,
+        max_duration: args.rotate_interval,
+    };
+    let mut recorder = Recorder::create(&args.output, topics, rotation)?;
+
+    let started = Instant::now();
+    let mut recorded = 0;
+    'receive: while args.limits.count.is_none_or(|count| recorded < count) {
+        for input in &inputs {
+            if args
+                .limits
+                .duration
+                .is_some_and(|duration| started.elapsed() >= duration)
+            {
+                break 'receive;
+            }
+            match input.wait(POLL_INTERVAL) {
+                Ok(()) => {}
+                // Nothing to take yet.
+                Err(ConnectorError::Timeout) => continue,
+                Err(e) => return Err(Error::entity(input.name(), e)),
+            }
+            input.take().map_err(|e| Error::entity(input.name(), e))?;
+            // Every sample taken is recorded, so `--count` may be exceeded by
+            // the last batch.
+            recorded += recorder.record(input)?;
+            recorder.flush()?;
+        }
+    }
+    recorder.flush()?;
+    eprintln!(
+        "recorded {} sample(s) up to {}",
+        recorded,
+        recorder.current_path().display()
+    );
+    Ok(())
+}
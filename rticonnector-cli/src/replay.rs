@@ -0,0 +1,115 @@
+//! The `replay` subcommand.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Args;
+use rticonnector::recorder::Recording;
+use rticonnector::replay::{Replayer, SourceTimestamps};
+
+use crate::connection::ConnectionArgs;
+use crate::error::Error;
+use crate::spy::parse_seconds;
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    #[command(flatten)]
+    connection: ConnectionArgs,
+    /// The first file of the recording.
+    recording: PathBuf,
+    /// A recorded topic to replay; every recorded topic the participant has
+    /// a writer for when omitted.
+    #[arg(long = "topic")]
+    topics: Vec<String>,
+    /// Replay this many times faster than recorded.
+    #[arg(long, default_value_t = 1.0)]
+    speed: f64,
+    /// Skip the samples received less than this many seconds into the
+    /// recording.
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    from: Option<Duration>,
+    /// Skip the samples received this many seconds or more into the
+    /// recording.
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    to: Option<Duration>,
+    /// Replay the recording again once it is exhausted.
+    #[arg(long = "loop")]
+    looping: bool,
+    /// Stamp the samples with the time they are replayed rather than their
+    /// recorded source timestamp.
+    #[arg(long)]
+    rewrite_timestamps: bool,
+    /// Wait up to this many seconds for a reader to match each writer before
+    /// replaying.
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    wait_for_reader: Option<Duration>,
+}
+
+/// Writes the recorded samples with the writers of the participant that
+/// publish their topic.
+pub fn replay(args: ReplayArgs) -> Result<(), Error> {
+    let recording = Recording::open(&args.recording)?;
+    let builder = args.connection.builder();
+    let config = builder.resolve()?;
+    let participant = &args.connection.participant;
+    let participant_model = config
+        .participant(participant)
+        .ok_or_else(|| Error::UnknownParticipant(participant.clone()))?;
+
+    let explicit = !args.topics.is_empty();
+    let topics: Vec<String> = if explicit {
+        args.topics
+    } else {
+        recording.topics().iter().map(|t| t.topic.clone()).collect()
+    };
+    let mut writers = Vec::new();
+    for topic in topics {
+        let writer = participant_model
+            .writers()
+            .find(|(_, data_writer)| data_writer.topic_ref == topic)
+            .map(|(name, _)| name);
+        match writer {
+            Some(writer) if !writers.iter().any(|(t, _)| *t == topic) => {
+                writers.push((topic, writer))
+            }
+            Some(_) => {}
+            None if explicit => return Err(Error::NoTopicWriter(topic)),
+            None => eprintln!("skipping topic `{}`, which has no writer", topic),
+        }
+    }
+    if writers.is_empty() {
+        return Err(Error::NoWriter(participant.clone()));
+    }
+
+    let connector = builder.build()?;
+    let mut replayer = Replayer::new()
+        .with_speed(args.speed)
+        .with_loop(args.looping);
+    if let Some(from) = args.from {
+        replayer = replayer.with_start(from);
+    }
+    if let Some(to) = args.to {
+        replayer = replayer.with_end(to);
+    }
+    if args.rewrite_timestamps {
+        replayer = replayer.with_source_timestamps(SourceTimestamps::Now);
+    }
+    for (topic, writer) in &writers {
+        let output = connector
+            .get_output(writer)
+            .map_err(|e| Error::entity(writer, e))?;
+        if let Some(timeout) = args.wait_for_reader {
+            output
+                .writer()
+                .wait_for_matched_subscription(timeout)
+                .map_err(|e| Error::entity(writer, e))?;
+        }
+        replayer = replayer.with_output(topic, output);
+    }
+    let stats = replayer.replay(&recording)?;
+    eprintln!(
+        "replayed {} sample(s), skipped {} without JSON data",
+        stats.written, stats.skipped
+    );
+    Ok(())
+}
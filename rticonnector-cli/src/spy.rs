@@ -0,0 +1,187 @@
+//! The `spy` and `echo` subcommands.
+
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use rticonnector::{Connector, ConnectorError};
+use serde_json::{json, Map, Value};
+
+use crate::connection::{ConnectionArgs, FilterArgs};
+use crate::error::Error;
+
+/// How long each reader waits for data before the next one is polled.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The sample infos printed along with the data, when the C library
+/// provides them.
+const INFOS: [&str; 6] = [
+    "source_timestamp",
+    "reception_timestamp",
+    "sample_state",
+    "view_state",
+    "instance_state",
+    "sample_identity",
+];
+
+#[derive(Args)]
+pub struct SpyArgs {
+    #[command(flatten)]
+    connection: ConnectionArgs,
+    /// A reader to spy on, as `Subscriber::Reader`; every reader of the
+    /// participant when omitted.
+    #[arg(long = "reader")]
+    readers: Vec<String>,
+    #[command(flatten)]
+    filter: FilterArgs,
+    #[command(flatten)]
+    limits: LimitArgs,
+}
+
+#[derive(Args)]
+pub struct EchoArgs {
+    #[command(flatten)]
+    connection: ConnectionArgs,
+    /// The reader to print, as `Subscriber::Reader`.
+    reader: String,
+    /// Print the sample infos along with the data.
+    #[arg(long)]
+    infos: bool,
+    #[command(flatten)]
+    filter: FilterArgs,
+    #[command(flatten)]
+    limits: LimitArgs,
+}
+
+#[derive(Args)]
+pub struct LimitArgs {
+    /// Stop after this many samples.
+    #[arg(long)]
+    pub count: Option<usize>,
+    /// Stop after this many seconds.
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    pub duration: Option<Duration>,
+}
+
+/// Prints `{"reader": ..., "info": {...}, "data": {...}}` for every sample.
+pub fn spy(args: SpyArgs) -> Result<(), Error> {
+    let builder = args.connection.builder();
+    let readers = if args.readers.is_empty() {
+        let config = builder.resolve()?;
+        let participant = config
+            .participant(&args.connection.participant)
+            .ok_or_else(|| Error::UnknownParticipant(args.connection.participant.clone()))?;
+        participant.readers().map(|(name, _)| name).collect()
+    } else {
+        args.readers
+    };
+    if readers.is_empty() {
+        return Err(Error::NoReader(args.connection.participant));
+    }
+    let connector = args.filter.apply(builder, &readers).build()?;
+    receive(
+        &connector,
+        &readers,
+        &args.limits,
+        |reader, info, data| json!({ "reader": reader, "info": info, "data": data }),
+    )
+}
+
+/// Prints the data of every sample, or `{"info": {...}, "data": {...}}`
+/// with `--infos`.
+pub fn echo(args: EchoArgs) -> Result<(), Error> {
+    let readers = [args.reader];
+    let builder = args.filter.apply(args.connection.builder(), &readers);
+    let connector = builder.build()?;
+    receive(&connector, &readers, &args.limits, |_, info, data| {
+        if args.infos {
+            json!({ "info": info, "data": data })
+        } else {
+            data
+        }
+    })
+}
+
+/// Takes the samples of `readers` until a limit is reached, printing one
+/// JSON line per sample. Samples without valid data have a `null` data.
+fn receive(
+    connector: &Connector,
+    readers: &[String],
+    limits: &LimitArgs,
+    line: impl Fn(&str, Value, Value) -> Value,
+) -> Result<(), Error> {
+    let data_readers = readers
+        .iter()
+        .map(|name| {
+            connector
+                .get_dynamic_datareader(name)
+                .map_err(|e| Error::entity(name, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let started = Instant::now();
+    let mut received = 0;
+    if limits.count == Some(0) {
+        return Ok(());
+    }
+    loop {
+        for (name, data_reader) in readers.iter().zip(&data_readers) {
+            if limits
+                .duration
+                .is_some_and(|duration| started.elapsed() >= duration)
+            {
+                return Ok(());
+            }
+            match data_reader.wait_for_data_on_reader(POLL_INTERVAL) {
+                Ok(_) => {}
+                // Nothing to take yet.
+                Err(ConnectorError::Timeout) => continue,
+                Err(e) => return Err(Error::entity(name, e)),
+            }
+            connector.take(name).map_err(|e| Error::entity(name, e))?;
+            let count = connector
+                .get_sample_count(name)
+                .map_err(|e| Error::entity(name, e))? as usize;
+            for index in 1..=count {
+                let valid_data = connector
+                    .get_boolean_from_infos(name, index, "valid_data")
+                    .map_err(|e| Error::entity(name, e))?;
+                let data = if valid_data {
+                    let json = connector
+                        .get_json_sample(name, index)
+                        .map_err(|e| Error::entity(name, e))?;
+                    serde_json::from_str(&json).map_err(|source| Error::Sample {
+                        entity: name.clone(),
+                        source,
+                    })?
+                } else {
+                    Value::Null
+                };
+                let info = infos(connector, name, index, valid_data);
+                println!("{}", line(name, info, data));
+                received += 1;
+                if limits.count.is_some_and(|count| received >= count) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn infos(connector: &Connector, name: &str, index: usize, valid_data: bool) -> Value {
+    let mut infos = Map::new();
+    infos.insert("valid_data".to_string(), Value::Bool(valid_data));
+    for field in INFOS {
+        let value = connector
+            .get_json_from_infos(name, index, field)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok());
+        if let Some(value) = value {
+            infos.insert(field.to_string(), value);
+        }
+    }
+    Value::Object(infos)
+}
+
+pub fn parse_seconds(seconds: &str) -> Result<Duration, String> {
+    let seconds: f64 = seconds.parse().map_err(|e| format!("{}", e))?;
+    Duration::try_from_secs_f64(seconds).map_err(|e| e.to_string())
+}
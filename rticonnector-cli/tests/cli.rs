@@ -0,0 +1,181 @@
+//! Parsing of the command line, and the content filters `--filter` sets on
+//! the readers.
+
+use clap::Parser;
+use rticonnector_cli::Cli;
+
+fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+    Cli::try_parse_from(std::iter::once("rticonnector-cli").chain(args.iter().copied()))
+}
+
+/// The message of the error parsing `args`.
+fn error(args: &[&str]) -> String {
+    match parse(args) {
+        Ok(_) => panic!("{:?} was accepted", args),
+        Err(error) => error.to_string(),
+    }
+}
+
+#[test]
+fn configuration_commands() {
+    assert!(parse(&["inspect", "types.xml", "ShapeExample.xml"]).is_ok());
+    assert!(error(&["inspect"]).contains("<FILE>"));
+    assert!(parse(&["compat", "old.xml", "new.xml", "--type", "ShapeType"]).is_ok());
+    assert!(parse(&["compat", "old.xml"]).is_err());
+}
+
+#[cfg(feature = "native")]
+mod native {
+    use rticonnector::Connector;
+    use rticonnector_cli::connection::FilterArgs;
+
+    use super::{error, parse};
+
+    const CONNECTION: [&str; 4] = [
+        "--config",
+        "ShapeExample.xml",
+        "--participant",
+        "MyParticipantLibrary::MySubParticipant",
+    ];
+
+    const XML: &str = r#"<dds>
+    <types>
+        <struct name="ShapeType">
+            <member name="color" type="string" key="true"/>
+        </struct>
+    </types>
+    <domain_participant_library name="Lib">
+        <domain_participant name="Participant" domain_id="0">
+            <register_type name="ShapeType" type_ref="ShapeType"/>
+            <topic name="Square" register_type_ref="ShapeType"/>
+            <topic name="Circle" register_type_ref="ShapeType"/>
+            <subscriber name="Sub">
+                <data_reader name="Squares" topic_ref="Square"/>
+                <data_reader name="Circles" topic_ref="Circle"/>
+            </subscriber>
+        </domain_participant>
+    </domain_participant_library>
+</dds>"#;
+
+    fn with_connection(args: &[&str]) -> Vec<String> {
+        let mut all: Vec<String> = args[..1].iter().map(|a| a.to_string()).collect();
+        all.extend(CONNECTION.iter().map(|a| a.to_string()));
+        all.extend(args[1..].iter().map(|a| a.to_string()));
+        all
+    }
+
+    fn parses(args: &[&str]) -> bool {
+        let args = with_connection(args);
+        parse(&args.iter().map(String::as_str).collect::<Vec<_>>()).is_ok()
+    }
+
+    fn rejects(args: &[&str]) -> String {
+        let args = with_connection(args);
+        error(&args.iter().map(String::as_str).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn rates() {
+        assert!(parses(&["pub", "MyPublisher::MySquareWriter"]));
+        assert!(parses(&[
+            "pub",
+            "MyPublisher::MySquareWriter",
+            "--rate",
+            "0.5"
+        ]));
+        for rate in ["0", "-1", "inf", "NaN"] {
+            let rate = format!("--rate={}", rate);
+            let message = rejects(&["pub", "MyPublisher::MySquareWriter", &rate]);
+            assert!(message.contains("must be a positive number"), "{}", message);
+        }
+        // Its period would overflow a `Duration`.
+        let message = rejects(&["pub", "MyPublisher::MySquareWriter", "--rate", "1e-300"]);
+        assert!(message.contains("too low"), "{}", message);
+        assert!(!parses(&[
+            "pub",
+            "MyPublisher::MySquareWriter",
+            "--rate",
+            "fast"
+        ]));
+    }
+
+    #[test]
+    fn limits() {
+        assert!(parses(&["spy", "--count", "3", "--duration", "1.5"]));
+        assert!(!parses(&["spy", "--duration=-1"]));
+        assert!(!parses(&["spy", "--duration", "1e300"]));
+        assert!(!parses(&[
+            "echo",
+            "MySubscriber::MySquareReader",
+            "--count=-1"
+        ]));
+        assert!(parses(&[
+            "pub",
+            "MyPublisher::MySquareWriter",
+            "--wait-for-reader",
+            "2"
+        ]));
+    }
+
+    #[test]
+    fn filters() {
+        assert!(parses(&[
+            "echo",
+            "MySubscriber::MySquareReader",
+            "--filter",
+            "color = %0",
+            "--param",
+            "'BLUE'"
+        ]));
+        // A parameter needs a filter.
+        assert!(!parses(&["spy", "--param", "'BLUE'"]));
+        assert!(!parses(&["echo", "--filter", "x > 1"]));
+        assert!(error(&["spy"]).contains("--config"));
+    }
+
+    #[test]
+    fn filter_names() {
+        let readers = ["Sub::Squares".to_string(), "Sub::Circles".to_string()];
+        let filter = FilterArgs {
+            filter: Some("color = %0".to_string()),
+            params: vec!["'BLUE'".to_string()],
+        };
+        let builder = Connector::builder("Lib::Participant").config_str(XML);
+        let config = filter.apply(builder, &readers).resolve().unwrap();
+        let participant = config.participant("Lib::Participant").unwrap();
+        let filters: Vec<_> = participant
+            .readers()
+            .map(|(name, reader)| {
+                let filter = reader.content_filter.as_ref().unwrap();
+                (name, filter.name.clone(), filter.parameters.clone())
+            })
+            .collect();
+        assert_eq!(
+            filters,
+            [
+                (
+                    "Sub::Squares".to_string(),
+                    "Sub_SquaresFilter".to_string(),
+                    vec!["'BLUE'".to_string()]
+                ),
+                (
+                    "Sub::Circles".to_string(),
+                    "Sub_CirclesFilter".to_string(),
+                    vec!["'BLUE'".to_string()]
+                ),
+            ]
+        );
+
+        // Without `--filter` the readers are left as configured.
+        let unfiltered = FilterArgs {
+            filter: None,
+            params: Vec::new(),
+        };
+        let builder = Connector::builder("Lib::Participant").config_str(XML);
+        let config = unfiltered.apply(builder, &readers).resolve().unwrap();
+        let participant = config.participant("Lib::Participant").unwrap();
+        assert!(participant
+            .readers()
+            .all(|(_, reader)| reader.content_filter.is_none()));
+    }
+}
@@ -0,0 +1,456 @@
+//! Generates Rust types from the `<types>` section of a Connector XML
+//! configuration, for types whose source of truth is an XML file.
+//!
+//! Structs and enums are generated with serde support and
+//! `#[derive(connector_dds::DdsType)]`, so they implement
+//! `connector_dds::topic::TopicType`; the crate including the generated code
+//! needs `serde` (with `derive`) and `connector-dds` as dependencies. XML
+//! modules become Rust modules, consts become `pub const`s and typedefs
+//! become type aliases. Enums are (de)serialized as their integer value, the
+//! way the Connector represents them, by the `DdsType` derive.
+//!
+//! From a build script:
+//!
+//! ```no_run
+//! rticonnector_codegen::generate_to_out_dir("ShapeExample.xml", "shapes.rs").unwrap();
+//! ```
+//!
+//! and in the crate:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/shapes.rs"));
+//! ```
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use rticonnector_config::types::{
+    Bound, Const, Enum, MemberType, Struct, TypeDefinition, TypeKind, Typedef, Types,
+};
+use rticonnector_config::{Config, ConfigError};
+use thiserror::Error;
+
+mod participants;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error("failed to write {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("OUT_DIR is not set; generate_to_out_dir must run from a build script")]
+    OutDir,
+    #[error("`{name}`: {message}")]
+    Unsupported { name: String, message: String },
+}
+
+/// Generates Rust source for the types of an XML configuration document.
+pub fn generate(xml: &str) -> Result<String, Error> {
+    generate_types(&Types::from_xml(xml)?)
+}
+
+/// Generates Rust source for already parsed types.
+pub fn generate_types(types: &Types) -> Result<String, Error> {
+    let mut root = Module::default();
+    for definition in types.definitions() {
+        root.insert(definition);
+    }
+    let mut generator = Generator {
+        types,
+        out: String::from("// Generated by rticonnector-codegen. Do not edit.\n"),
+        scope: Vec::new(),
+        indent: 0,
+    };
+    generator.module(&root)?;
+    Ok(generator.out)
+}
+
+/// Generates Rust source for a whole configuration: its types in a `types`
+/// module, and a module per participant (nested in one per library) with a
+/// `Participant` that creates the Connector from `config_file` (through
+/// `rticonnector::ConnectorBuilder::build`, so a participant the C library
+/// rejects is a `BuildError`) and returns its readers and writers as
+/// `rticonnector::Input`s and `rticonnector::Output`s of the topic type.
+///
+/// `config_file` is embedded with `include_str!`, so it must be absolute or
+/// relative to the file the generated source is included in.
+///
+/// This is what `connector_dds::dds_config!` expands to.
+pub fn generate_config(config: &Config, config_file: &str) -> Result<String, Error> {
+    participants::generate(config, config_file)
+}
+
+/// Generates Rust source for the types of `input` into `output`.
+pub fn generate_file(input: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<(), Error> {
+    let source = generate_types(&Types::from_file(input)?)?;
+    let output = output.as_ref();
+    std::fs::write(output, source).map_err(|source| Error::Io {
+        path: output.to_path_buf(),
+        source,
+    })
+}
+
+/// Generates Rust source for the types of `xml_file` into `$OUT_DIR/file_name`
+/// and asks Cargo to rerun the build script when the XML changes.
+pub fn generate_to_out_dir(xml_file: impl AsRef<Path>, file_name: &str) -> Result<PathBuf, Error> {
+    let xml_file = xml_file.as_ref();
+    println!("cargo:rerun-if-changed={}", xml_file.display());
+    let output = PathBuf::from(std::env::var_os("OUT_DIR").ok_or(Error::OutDir)?).join(file_name);
+    generate_file(xml_file, &output)?;
+    Ok(output)
+}
+
+/// Definitions grouped by XML module.
+#[derive(Default)]
+struct Module<'a> {
+    name: String,
+    definitions: Vec<&'a TypeDefinition>,
+    modules: Vec<Module<'a>>,
+}
+
+impl<'a> Module<'a> {
+    fn insert(&mut self, definition: &'a TypeDefinition) {
+        let path: Vec<&str> = definition.name().split("::").collect();
+        let mut module = self;
+        for segment in &path[..path.len() - 1] {
+            let index = match module.modules.iter().position(|m| m.name == *segment) {
+                Some(index) => index,
+                None => {
+                    module.modules.push(Module {
+                        name: segment.to_string(),
+                        ..Module::default()
+                    });
+                    module.modules.len() - 1
+                }
+            };
+            module = &mut module.modules[index];
+        }
+        module.definitions.push(definition);
+    }
+}
+
+struct Generator<'a> {
+    types: &'a Types,
+    out: String,
+    /// The XML module path of the Rust module being generated.
+    scope: Vec<String>,
+    indent: usize,
+}
+
+impl Generator<'_> {
+    fn line(&mut self, line: &str) {
+        if !line.is_empty() {
+            for _ in 0..self.indent {
+                self.out.push_str("    ");
+            }
+        }
+        self.out.push_str(line);
+        self.out.push('\n');
+    }
+
+    fn module(&mut self, module: &Module) -> Result<(), Error> {
+        for definition in &module.definitions {
+            self.line("");
+            match definition {
+                TypeDefinition::Const(c) => self.constant(c)?,
+                TypeDefinition::Struct(s) => self.structure(s)?,
+                TypeDefinition::Enum(e) => self.enumeration(e),
+                TypeDefinition::Typedef(t) => self.typedef(t)?,
+            }
+        }
+        for child in &module.modules {
+            self.line("");
+            self.line(&format!("pub mod {} {{", module_ident(&child.name)));
+            self.scope.push(child.name.clone());
+            self.indent += 1;
+            self.module(child)?;
+            self.indent -= 1;
+            self.scope.pop();
+            self.line("}");
+        }
+        Ok(())
+    }
+
+    fn constant(&mut self, c: &Const) -> Result<(), Error> {
+        let value = c.value.trim();
+        let (ty, value) = match &c.kind {
+            TypeKind::String | TypeKind::WString => {
+                ("&str".to_string(), format!("{:?}", value.trim_matches('"')))
+            }
+            TypeKind::Char8 | TypeKind::Char16 => {
+                ("char".to_string(), format!("'{}'", value.trim_matches('\'')))
+            }
+            TypeKind::NonBasic(_) => {
+                return Err(Error::Unsupported {
+                    name: c.name.clone(),
+                    message: "only consts of a basic type are supported".to_string(),
+                })
+            }
+            kind => (self.basic_type(kind), value.to_string()),
+        };
+        let name = local_name(&c.name);
+        if name.chars().any(|c| c.is_ascii_lowercase()) {
+            self.line("#[allow(non_upper_case_globals)]");
+        }
+        self.line(&format!("pub const {}: {} = {};", name, ty, value));
+        Ok(())
+    }
+
+    fn structure(&mut self, s: &Struct) -> Result<(), Error> {
+        let mut derives = vec!["Debug", "Clone", "PartialEq"];
+        if !self.has_array(&TypeKind::NonBasic(s.name.clone()), &mut HashSet::new()) {
+            // Large arrays do not implement `Default`.
+            derives.push("Default");
+        }
+        derives.extend([
+            "::serde::Serialize",
+            "::serde::Deserialize",
+            "::connector_dds::DdsType",
+        ]);
+        self.line(&format!("#[derive({})]", derives.join(", ")));
+        self.line(&format!(
+            "#[dds(name = {:?}, extensibility = {:?})]",
+            s.name,
+            s.extensibility.xml_name()
+        ));
+        self.type_name_allow(&s.name);
+        self.line(&format!("pub struct {} {{", local_name(&s.name)));
+        self.indent += 1;
+
+        let field_names: HashSet<String> =
+            s.members.iter().map(|m| field_ident(&m.name)).collect();
+        if let Some(base_type) = &s.base_type {
+            let field = if field_names.contains("base") {
+                "base_"
+            } else {
+                "base"
+            };
+            let path = self.type_path(base_type);
+            self.line("#[dds(base)]");
+            self.line("#[serde(flatten)]");
+            self.line(&format!("pub {}: {},", field, path));
+        }
+        for member in &s.members {
+            let mut dds = Vec::new();
+            if member.key {
+                dds.push("key".to_string());
+            }
+            // A typedef becomes a type alias, which loses its bounds: they
+            // are set on the member instead.
+            let resolved = self.types.resolve_typedefs(&member.member_type);
+            if let Some(bound) = &resolved.string_max_length {
+                dds.push(format!("string_max_length = {}", self.bound_attribute(bound)));
+            }
+            match &resolved.sequence_max_length {
+                Some(Bound::Unbounded) | None => {}
+                Some(bound) => {
+                    dds.push(format!("sequence_max_length = {}", self.bound_attribute(bound)))
+                }
+            }
+            if !dds.is_empty() {
+                self.line(&format!("#[dds({})]", dds.join(", ")));
+            }
+            let field = field_ident(&member.name);
+            if field.trim_start_matches("r#") != member.name {
+                self.line(&format!("#[serde(rename = {:?})]", member.name));
+            }
+            let mut ty = self.member_type(&member.member_type, &s.name)?;
+            if member.optional {
+                ty = format!("Option<{}>", ty);
+            }
+            self.line(&format!("pub {}: {},", field, ty));
+        }
+
+        self.indent -= 1;
+        self.line("}");
+        Ok(())
+    }
+
+    fn enumeration(&mut self, e: &Enum) {
+        let name = local_name(&e.name);
+        let mut derives = vec!["Debug", "Clone", "Copy"];
+        if !e.enumerators.is_empty() {
+            derives.push("Default");
+        }
+        derives.extend(["PartialEq", "Eq", "Hash", "::connector_dds::DdsType"]);
+        self.line(&format!("#[derive({})]", derives.join(", ")));
+        self.line(&format!(
+            "#[dds(name = {:?}, extensibility = {:?})]",
+            e.name,
+            e.extensibility.xml_name()
+        ));
+        self.line("#[allow(non_camel_case_types, clippy::upper_case_acronyms)]");
+        self.line("#[repr(i32)]");
+        self.line(&format!("pub enum {} {{", name));
+        self.indent += 1;
+        for (i, enumerator) in e.enumerators.iter().enumerate() {
+            if i == 0 {
+                self.line("#[default]");
+            }
+            let variant = escape_keyword(enumerator.name.clone());
+            if variant.trim_start_matches("r#") != enumerator.name {
+                self.line(&format!("#[dds(name = {:?})]", enumerator.name));
+            }
+            self.line(&format!("{} = {},", variant, enumerator.value));
+        }
+        self.indent -= 1;
+        self.line("}");
+    }
+
+    fn typedef(&mut self, t: &Typedef) -> Result<(), Error> {
+        let ty = self.member_type(&t.member_type, &t.name)?;
+        self.type_name_allow(&t.name);
+        self.line(&format!("pub type {} = {};", local_name(&t.name), ty));
+        Ok(())
+    }
+
+    fn type_name_allow(&mut self, name: &str) {
+        let name = local_name(name);
+        if name.contains('_') || !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+            self.line("#[allow(non_camel_case_types)]");
+        }
+    }
+
+    fn basic_type(&self, kind: &TypeKind) -> String {
+        match kind {
+            TypeKind::Boolean => "bool",
+            TypeKind::Char8 | TypeKind::Char16 => "char",
+            TypeKind::Octet | TypeKind::UInt8 => "u8",
+            TypeKind::Int8 => "i8",
+            TypeKind::Short => "i16",
+            TypeKind::UnsignedShort => "u16",
+            TypeKind::Long => "i32",
+            TypeKind::UnsignedLong => "u32",
+            TypeKind::LongLong => "i64",
+            TypeKind::UnsignedLongLong => "u64",
+            TypeKind::Float => "f32",
+            TypeKind::Double | TypeKind::LongDouble => "f64",
+            TypeKind::String | TypeKind::WString => "String",
+            TypeKind::NonBasic(name) => return self.type_path(name),
+        }
+        .to_string()
+    }
+
+    fn member_type(&self, member_type: &MemberType, owner: &str) -> Result<String, Error> {
+        let mut ty = self.basic_type(&member_type.kind);
+        if member_type.is_sequence() {
+            ty = format!("Vec<{}>", ty);
+        }
+        for dimension in member_type.array_dimensions.iter().rev() {
+            let length = match dimension {
+                Bound::Fixed(length) => length.to_string(),
+                Bound::Const(name) => format!("{} as usize", self.type_path(name)),
+                Bound::Unbounded => {
+                    return Err(Error::Unsupported {
+                        name: owner.to_string(),
+                        message: "array dimensions must be bounded".to_string(),
+                    })
+                }
+            };
+            ty = format!("[{}; {}]", ty, length);
+        }
+        Ok(ty)
+    }
+
+    /// A bound as a `#[dds]` attribute value, with consts resolved so the
+    /// derived XML does not depend on the `<const>` declaration.
+    fn bound_attribute(&self, bound: &Bound) -> String {
+        match self.types.resolve_bound(bound) {
+            Some(length) => length.to_string(),
+            None => "\"-1\"".to_string(),
+        }
+    }
+
+    /// The Rust path to the definition `name` from the current module.
+    fn type_path(&self, name: &str) -> String {
+        let target: Vec<&str> = name.split("::").collect();
+        let (modules, item) = target.split_at(target.len() - 1);
+        let scope: Vec<&str> = self.scope.iter().map(String::as_str).collect();
+        let common = scope
+            .iter()
+            .zip(modules)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let mut path = "super::".repeat(scope.len() - common);
+        for module in &modules[common..] {
+            path.push_str(&module_ident(module));
+            path.push_str("::");
+        }
+        path.push_str(item[0]);
+        path
+    }
+
+    /// Whether `kind` contains an array, directly or through its members.
+    fn has_array(&self, kind: &TypeKind, visited: &mut HashSet<String>) -> bool {
+        let TypeKind::NonBasic(name) = kind else {
+            return false;
+        };
+        if !visited.insert(name.clone()) {
+            return false;
+        }
+        match self.types.get(name) {
+            Some(TypeDefinition::Struct(s)) => {
+                s.base_type
+                    .as_ref()
+                    .is_some_and(|b| self.has_array(&TypeKind::NonBasic(b.clone()), visited))
+                    || s.members.iter().any(|m| {
+                        m.member_type.is_array() || self.has_array(&m.member_type.kind, visited)
+                    })
+            }
+            Some(TypeDefinition::Typedef(t)) => {
+                t.member_type.is_array() || self.has_array(&t.member_type.kind, visited)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+fn module_ident(name: &str) -> String {
+    escape_keyword(snake_case(name))
+}
+
+fn field_ident(name: &str) -> String {
+    escape_keyword(snake_case(name))
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if previous_lower {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+            previous_lower = false;
+        } else {
+            snake.push(c);
+            previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        }
+    }
+    snake
+}
+
+fn escape_keyword(ident: String) -> String {
+    const KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do",
+        "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in",
+        "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+        "return", "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe",
+        "unsized", "use", "virtual", "where", "while", "yield",
+    ];
+    match ident.as_str() {
+        // These cannot be raw identifiers.
+        "self" | "Self" | "super" | "crate" => format!("{}_", ident),
+        keyword if KEYWORDS.contains(&keyword) => format!("r#{}", ident),
+        _ => ident,
+    }
+}
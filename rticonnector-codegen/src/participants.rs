@@ -0,0 +1,211 @@
+//! Generates a module per `<domain_participant>`, with accessors for its
+//! readers and writers bound to the generated topic types.
+
+use std::collections::HashMap;
+
+use rticonnector_config::participants::Participant;
+use rticonnector_config::{Config, ConfigError};
+
+use crate::{field_ident, generate_types, module_ident, Error};
+
+pub(crate) fn generate(config: &Config, config_file: &str) -> Result<String, Error> {
+    let mut out = String::from("// Generated by rticonnector-codegen. Do not edit.\n\n");
+    out.push_str("/// The configuration the participants are created from, embedded in the\n");
+    out.push_str("/// binary so it does not depend on where it was built.\n");
+    out.push_str(&format!("pub const CONFIG: &str = include_str!({:?});\n", config_file));
+
+    out.push_str("\npub mod types {");
+    for line in generate_types(&config.types)?.lines().skip(1) {
+        if !line.is_empty() {
+            out.push_str("\n    ");
+            out.push_str(line);
+        } else {
+            out.push('\n');
+        }
+    }
+    out.push_str("\n}\n");
+
+    for library in &config.participant_libraries {
+        out.push_str(&format!("\npub mod {} {{\n", module_ident(&library.name)));
+        for (i, participant) in library.participants.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            participant_module(&mut out, config, &library.name, participant)?;
+        }
+        out.push_str("}\n");
+    }
+    Ok(out)
+}
+
+/// A reader or writer accessor.
+struct Accessor<'a> {
+    group: &'a str,
+    name: &'a str,
+    topic: &'a str,
+    type_name: &'a str,
+    output: bool,
+}
+
+impl Accessor<'_> {
+    fn entity_name(&self) -> String {
+        format!("{}::{}", self.group, self.name)
+    }
+}
+
+fn participant_module(
+    out: &mut String,
+    config: &Config,
+    library: &str,
+    participant: &Participant,
+) -> Result<(), Error> {
+    let mut accessors = Vec::new();
+    for publisher in &participant.publishers {
+        for writer in &publisher.writers {
+            accessors.push(Accessor {
+                group: &publisher.name,
+                name: &writer.name,
+                topic: &writer.topic_ref,
+                type_name: topic_type(config, participant, &writer.topic_ref, writer.line)?,
+                output: true,
+            });
+        }
+    }
+    for subscriber in &participant.subscribers {
+        for reader in &subscriber.readers {
+            accessors.push(Accessor {
+                group: &subscriber.name,
+                name: &reader.name,
+                topic: &reader.topic_ref,
+                type_name: topic_type(config, participant, &reader.topic_ref, reader.line)?,
+                output: false,
+            });
+        }
+    }
+    // Entity names are only unique within their publisher or subscriber.
+    let mut counts = HashMap::new();
+    for accessor in &accessors {
+        *counts.entry(field_ident(accessor.name)).or_insert(0) += 1;
+    }
+
+    let name = format!("{}::{}", library, participant.name);
+    let lines = [
+        format!("pub mod {} {{", module_ident(&participant.name)),
+        format!("    pub const NAME: &str = {:?};", name),
+        String::new(),
+        format!("    /// The participant `{}`.", name),
+        "    pub struct Participant {".to_string(),
+        "        connector: ::rticonnector::Connector,".to_string(),
+        "    }".to_string(),
+        String::new(),
+        "    impl Participant {".to_string(),
+        "        /// Creates the participant, reporting the error of the C library".to_string(),
+        "        /// when it cannot be created.".to_string(),
+        "        pub fn new() -> Result<Self, ::rticonnector::builder::BuildError> {".to_string(),
+        "            Self::with_options(::rticonnector::RTIOptions::default())".to_string(),
+        "        }".to_string(),
+        String::new(),
+        "        pub fn with_options(".to_string(),
+        "            options: ::rticonnector::RTIOptions,".to_string(),
+        "        ) -> Result<Self, ::rticonnector::builder::BuildError> {".to_string(),
+        "            let connector = ::rticonnector::ConnectorBuilder::new(NAME)".to_string(),
+        "                .config_str(super::super::CONFIG)".to_string(),
+        "                .options(options)".to_string(),
+        "                .build()?;".to_string(),
+        "            Ok(Self { connector })".to_string(),
+        "        }".to_string(),
+        String::new(),
+        "        pub fn connector(&self) -> &::rticonnector::Connector {".to_string(),
+        "            &self.connector".to_string(),
+        "        }".to_string(),
+    ];
+    for line in lines {
+        push_line(out, &line);
+    }
+    for accessor in &accessors {
+        let mut function = field_ident(accessor.name);
+        if counts[&function] > 1 {
+            function = field_ident(&format!("{}_{}", accessor.group, accessor.name));
+        }
+        let (kind, getter, verb) = if accessor.output {
+            ("Output", "get_output", "Writes")
+        } else {
+            ("Input", "get_input", "Reads")
+        };
+        push_line(out, "");
+        push_line(
+            out,
+            &format!(
+                "        /// {} `{}` on the topic `{}`.",
+                verb, accessor.type_name, accessor.topic
+            ),
+        );
+        push_line(
+            out,
+            &format!(
+                "        pub fn {}(&self) -> Result<::rticonnector::{}<'_, super::super::types::{}>, ::rticonnector::ConnectorError> {{",
+                function,
+                kind,
+                type_path(accessor.type_name)
+            ),
+        );
+        push_line(
+            out,
+            &format!(
+                "            self.connector.{}({:?})",
+                getter,
+                accessor.entity_name()
+            ),
+        );
+        push_line(out, "        }");
+    }
+    push_line(out, "    }");
+    push_line(out, "}");
+    Ok(())
+}
+
+/// Pushes a line of a participant module, which sits inside its library's.
+fn push_line(out: &mut String, line: &str) {
+    if !line.is_empty() {
+        out.push_str("    ");
+    }
+    out.push_str(line);
+    out.push('\n');
+}
+
+fn topic_type<'a>(
+    config: &'a Config,
+    participant: &'a Participant,
+    topic_ref: &str,
+    line: u32,
+) -> Result<&'a str, Error> {
+    let type_name = config
+        .topic_type(participant, topic_ref)
+        .ok_or_else(|| ConfigError::Invalid {
+            line,
+            message: format!(
+                "topic `{}` is not declared in the participant `{}` or its domain",
+                topic_ref, participant.name
+            ),
+        })?;
+    if config.types.get(type_name).is_none() {
+        return Err(ConfigError::Invalid {
+            line,
+            message: format!(
+                "the type `{}` of topic `{}` is not defined in <types>",
+                type_name, topic_ref
+            ),
+        }
+        .into());
+    }
+    Ok(type_name.trim_start_matches("::"))
+}
+
+/// The path of a type from the generated `types` module.
+fn type_path(name: &str) -> String {
+    let mut segments: Vec<String> = name.split("::").map(str::to_string).collect();
+    let item = segments.pop().unwrap_or_default();
+    segments.iter_mut().for_each(|s| *s = module_ident(s));
+    segments.push(item);
+    segments.join("::")
+}
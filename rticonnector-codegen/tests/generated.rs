@@ -0,0 +1,121 @@
+//! The golden files must compile, and the types they declare must describe
+//! the same `<types>` as the XML they were generated from.
+
+use connector_dds::topic::types_xml;
+use rticonnector::builder::BuildError;
+use rticonnector_config::types::Types;
+
+#[allow(dead_code)]
+mod shape_example {
+    include!("golden/ShapeExample.rs");
+}
+
+#[allow(dead_code)]
+mod simple {
+    include!("golden/Simple.rs");
+}
+
+#[allow(dead_code)]
+mod modules {
+    include!("golden/Modules.rs");
+}
+
+#[allow(dead_code)]
+mod keywords {
+    include!("golden/Keywords.rs");
+}
+
+#[allow(dead_code)]
+mod shape_participants {
+    include!("golden/ShapeParticipants.rs");
+}
+
+fn derived<T: connector_dds::topic::DdsType>() -> Types {
+    Types::from_xml(&format!("<dds>{}</dds>", types_xml::<T>())).unwrap()
+}
+
+fn assert_same_struct(source: &Types, derived: &Types, name: &str) {
+    let expected = source.get_struct(name).unwrap();
+    let actual = derived.get_struct(name).unwrap();
+    assert_eq!(actual.base_type, expected.base_type);
+    assert_eq!(actual.extensibility, expected.extensibility);
+    let members = |types: &Types, s| {
+        types
+            .all_members(s)
+            .into_iter()
+            .map(|m| {
+                (
+                    m.name.clone(),
+                    m.member_type.kind.clone(),
+                    m.member_type
+                        .string_max_length
+                        .as_ref()
+                        .and_then(|b| types.resolve_bound(b)),
+                    m.key,
+                    m.optional,
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(members(derived, actual), members(source, expected));
+}
+
+#[test]
+fn shape_example_round_trips() {
+    let source = Types::from_file("../rticonnector/examples/ShapeExample.xml").unwrap();
+    let derived = derived::<shape_example::ShapeTypeExtended>();
+    assert_same_struct(&source, &derived, "ShapeType");
+    assert_same_struct(&source, &derived, "ShapeTypeExtended");
+    assert_eq!(
+        derived.get_enum("ShapeFillKind").unwrap().enumerators,
+        source.get_enum("ShapeFillKind").unwrap().enumerators
+    );
+}
+
+#[test]
+fn simple_round_trips() {
+    let source = Types::from_file("../rticonnector/examples/Simple.xml").unwrap();
+    assert_same_struct(&source, &derived::<simple::MyType>(), "MyType");
+}
+
+#[test]
+fn enums_use_their_integer_value() {
+    let json = serde_json::to_string(&modules::fleet::Status::MOVING).unwrap();
+    assert_eq!(json, "4");
+    let status: modules::fleet::Status = serde_json::from_str("5").unwrap();
+    assert_eq!(status, modules::fleet::Status::DOCKED);
+    assert!(serde_json::from_str::<modules::fleet::Status>("1").is_err());
+}
+
+#[test]
+fn keywords_keep_their_names() {
+    use keywords::r#match::{Lexeme, Token};
+
+    let source = Types::from_file("tests/fixtures/Keywords.xml").unwrap();
+    let derived = derived::<Lexeme>();
+    assert_same_struct(&source, &derived, "match::Lexeme");
+    assert_eq!(
+        derived.get_enum("match::Token").unwrap().enumerators,
+        source.get_enum("match::Token").unwrap().enumerators
+    );
+    assert_eq!(serde_json::to_string(&Token::Self_).unwrap(), "4");
+    let lexeme = Lexeme {
+        r#type: Token::r#match,
+        self_: "x".to_string(),
+    };
+    assert_eq!(
+        serde_json::to_value(&lexeme).unwrap(),
+        serde_json::json!({ "type": 3, "self": "x" })
+    );
+}
+
+#[test]
+fn participants_report_creation_errors() {
+    use shape_participants::my_participant_library::my_participant::{Participant, NAME};
+
+    // A connector the C library fails to create is an error, not a
+    // participant holding a null connector.
+    let _: fn() -> Result<Participant, BuildError> = Participant::new;
+    assert_eq!(NAME, "MyParticipantLibrary::MyParticipant");
+    assert!(shape_participants::CONFIG.contains("<domain_participant name=\"MyParticipant\""));
+}
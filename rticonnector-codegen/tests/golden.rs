@@ -0,0 +1,72 @@
+//! Golden-file tests: the Rust generated for each configuration must match
+//! `tests/golden/<name>.rs`. Run with `UPDATE_GOLDEN=1` to rewrite the golden
+//! files after an intended change to the generator.
+
+use std::path::{Path, PathBuf};
+
+fn check(xml: PathBuf) {
+    let source = std::fs::read_to_string(&xml).unwrap();
+    let generated = rticonnector_codegen::generate(&source).unwrap();
+    compare(
+        &xml,
+        generated,
+        xml.with_extension("rs").file_name().unwrap(),
+    );
+}
+
+fn compare(xml: &Path, generated: String, golden: impl AsRef<Path>) {
+    let golden = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(golden);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&golden, generated).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&golden).unwrap();
+    assert_eq!(
+        generated,
+        expected,
+        "generated code for {} differs from {}",
+        xml.display(),
+        golden.display()
+    );
+}
+
+fn example(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../rticonnector/examples")
+        .join(name)
+}
+
+#[test]
+fn shape_example() {
+    check(example("ShapeExample.xml"));
+}
+
+#[test]
+fn simple() {
+    check(example("Simple.xml"));
+}
+
+#[test]
+fn modules() {
+    check(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/Modules.xml"));
+}
+
+#[test]
+fn keywords() {
+    check(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/Keywords.xml"));
+}
+
+#[test]
+fn participants() {
+    let xml = example("ShapeExample.xml");
+    let config = rticonnector_config::Config::from_file(&xml).unwrap();
+    // Embedded relative to the golden file.
+    let generated = rticonnector_codegen::generate_config(
+        &config,
+        "../../../rticonnector/examples/ShapeExample.xml",
+    )
+    .unwrap();
+    compare(&xml, generated, "ShapeParticipants.rs");
+}
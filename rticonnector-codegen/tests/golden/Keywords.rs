@@ -0,0 +1,26 @@
+// Generated by rticonnector-codegen. Do not edit.
+
+pub mod r#match {
+
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, ::connector_dds::DdsType)]
+    #[dds(name = "match::Token", extensibility = "appendable")]
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    #[repr(i32)]
+    pub enum Token {
+        #[default]
+        r#type = 0,
+        r#match = 3,
+        #[dds(name = "Self")]
+        Self_ = 4,
+        IDENT = 5,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default, ::serde::Serialize, ::serde::Deserialize, ::connector_dds::DdsType)]
+    #[dds(name = "match::Lexeme", extensibility = "appendable")]
+    pub struct Lexeme {
+        pub r#type: Token,
+        #[dds(string_max_length = 16)]
+        #[serde(rename = "self")]
+        pub self_: String,
+    }
+}
@@ -0,0 +1,45 @@
+// Generated by rticonnector-codegen. Do not edit.
+
+pub const MAX_POINTS: i32 = 16;
+
+pub mod geo {
+
+    pub const DIMENSIONS: u16 = 3;
+
+    #[derive(Debug, Clone, PartialEq, ::serde::Serialize, ::serde::Deserialize, ::connector_dds::DdsType)]
+    #[dds(name = "Geo::Point", extensibility = "final")]
+    pub struct Point {
+        pub coordinates: [f64; DIMENSIONS as usize],
+    }
+
+    pub type Path = Vec<Point>;
+}
+
+pub mod fleet {
+
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, ::connector_dds::DdsType)]
+    #[dds(name = "Fleet::Status", extensibility = "appendable")]
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    #[repr(i32)]
+    pub enum Status {
+        #[default]
+        IDLE = 0,
+        MOVING = 4,
+        DOCKED = 5,
+    }
+
+    #[derive(Debug, Clone, PartialEq, ::serde::Serialize, ::serde::Deserialize, ::connector_dds::DdsType)]
+    #[dds(name = "Fleet::Vehicle", extensibility = "mutable")]
+    pub struct Vehicle {
+        #[dds(key)]
+        #[serde(rename = "vehicleId")]
+        pub vehicle_id: u32,
+        #[dds(string_max_length = 32)]
+        pub r#type: String,
+        pub status: Status,
+        #[dds(sequence_max_length = 16)]
+        pub route: super::geo::Path,
+        pub destination: Option<super::geo::Point>,
+        pub readings: Vec<f32>,
+    }
+}
@@ -0,0 +1,34 @@
+// Generated by rticonnector-codegen. Do not edit.
+
+#[derive(Debug, Clone, PartialEq, Default, ::serde::Serialize, ::serde::Deserialize, ::connector_dds::DdsType)]
+#[dds(name = "ShapeType", extensibility = "appendable")]
+pub struct ShapeType {
+    #[dds(key, string_max_length = 128)]
+    pub color: String,
+    pub x: i32,
+    pub y: i32,
+    pub shapesize: i32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, ::connector_dds::DdsType)]
+#[dds(name = "ShapeFillKind", extensibility = "appendable")]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[repr(i32)]
+pub enum ShapeFillKind {
+    #[default]
+    SOLID_FILL = 0,
+    TRANSPARENT_FILL = 1,
+    HORIZONTAL_HATCH_FILL = 2,
+    VERTICAL_HATCH_FILL = 3,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, ::serde::Serialize, ::serde::Deserialize, ::connector_dds::DdsType)]
+#[dds(name = "ShapeTypeExtended", extensibility = "appendable")]
+pub struct ShapeTypeExtended {
+    #[dds(base)]
+    #[serde(flatten)]
+    pub base: ShapeType,
+    #[serde(rename = "fillKind")]
+    pub fill_kind: ShapeFillKind,
+    pub angle: f32,
+}
@@ -0,0 +1,243 @@
+// Generated by rticonnector-codegen. Do not edit.
+
+/// The configuration the participants are created from, embedded in the
+/// binary so it does not depend on where it was built.
+pub const CONFIG: &str = include_str!("../../../rticonnector/examples/ShapeExample.xml");
+
+pub mod types {
+
+    #[derive(Debug, Clone, PartialEq, Default, ::serde::Serialize, ::serde::Deserialize, ::connector_dds::DdsType)]
+    #[dds(name = "ShapeType", extensibility = "appendable")]
+    pub struct ShapeType {
+        #[dds(key, string_max_length = 128)]
+        pub color: String,
+        pub x: i32,
+        pub y: i32,
+        pub shapesize: i32,
+    }
+
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, ::connector_dds::DdsType)]
+    #[dds(name = "ShapeFillKind", extensibility = "appendable")]
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    #[repr(i32)]
+    pub enum ShapeFillKind {
+        #[default]
+        SOLID_FILL = 0,
+        TRANSPARENT_FILL = 1,
+        HORIZONTAL_HATCH_FILL = 2,
+        VERTICAL_HATCH_FILL = 3,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default, ::serde::Serialize, ::serde::Deserialize, ::connector_dds::DdsType)]
+    #[dds(name = "ShapeTypeExtended", extensibility = "appendable")]
+    pub struct ShapeTypeExtended {
+        #[dds(base)]
+        #[serde(flatten)]
+        pub base: ShapeType,
+        #[serde(rename = "fillKind")]
+        pub fill_kind: ShapeFillKind,
+        pub angle: f32,
+    }
+}
+
+pub mod my_participant_library {
+    pub mod my_pub_participant {
+        pub const NAME: &str = "MyParticipantLibrary::MyPubParticipant";
+
+        /// The participant `MyParticipantLibrary::MyPubParticipant`.
+        pub struct Participant {
+            connector: ::rticonnector::Connector,
+        }
+
+        impl Participant {
+            /// Creates the participant, reporting the error of the C library
+            /// when it cannot be created.
+            pub fn new() -> Result<Self, ::rticonnector::builder::BuildError> {
+                Self::with_options(::rticonnector::RTIOptions::default())
+            }
+
+            pub fn with_options(
+                options: ::rticonnector::RTIOptions,
+            ) -> Result<Self, ::rticonnector::builder::BuildError> {
+                let connector = ::rticonnector::ConnectorBuilder::new(NAME)
+                    .config_str(super::super::CONFIG)
+                    .options(options)
+                    .build()?;
+                Ok(Self { connector })
+            }
+
+            pub fn connector(&self) -> &::rticonnector::Connector {
+                &self.connector
+            }
+
+            /// Writes `ShapeType` on the topic `Square`.
+            pub fn my_square_writer(&self) -> Result<::rticonnector::Output<'_, super::super::types::ShapeType>, ::rticonnector::ConnectorError> {
+                self.connector.get_output("MyPublisher::MySquareWriter")
+            }
+        }
+    }
+
+    pub mod my_sub_participant {
+        pub const NAME: &str = "MyParticipantLibrary::MySubParticipant";
+
+        /// The participant `MyParticipantLibrary::MySubParticipant`.
+        pub struct Participant {
+            connector: ::rticonnector::Connector,
+        }
+
+        impl Participant {
+            /// Creates the participant, reporting the error of the C library
+            /// when it cannot be created.
+            pub fn new() -> Result<Self, ::rticonnector::builder::BuildError> {
+                Self::with_options(::rticonnector::RTIOptions::default())
+            }
+
+            pub fn with_options(
+                options: ::rticonnector::RTIOptions,
+            ) -> Result<Self, ::rticonnector::builder::BuildError> {
+                let connector = ::rticonnector::ConnectorBuilder::new(NAME)
+                    .config_str(super::super::CONFIG)
+                    .options(options)
+                    .build()?;
+                Ok(Self { connector })
+            }
+
+            pub fn connector(&self) -> &::rticonnector::Connector {
+                &self.connector
+            }
+
+            /// Reads `ShapeType` on the topic `Square`.
+            pub fn my_square_reader(&self) -> Result<::rticonnector::Input<'_, super::super::types::ShapeType>, ::rticonnector::ConnectorError> {
+                self.connector.get_input("MySubscriber::MySquareReader")
+            }
+
+            /// Reads `ShapeType` on the topic `Circle`.
+            pub fn my_circle_reader(&self) -> Result<::rticonnector::Input<'_, super::super::types::ShapeType>, ::rticonnector::ConnectorError> {
+                self.connector.get_input("MySubscriber::MyCircleReader")
+            }
+
+            /// Reads `ShapeType` on the topic `Triangle`.
+            pub fn my_triangle_reader(&self) -> Result<::rticonnector::Input<'_, super::super::types::ShapeType>, ::rticonnector::ConnectorError> {
+                self.connector.get_input("MySubscriber::MyTriangleReader")
+            }
+        }
+    }
+
+    pub mod my_participant {
+        pub const NAME: &str = "MyParticipantLibrary::MyParticipant";
+
+        /// The participant `MyParticipantLibrary::MyParticipant`.
+        pub struct Participant {
+            connector: ::rticonnector::Connector,
+        }
+
+        impl Participant {
+            /// Creates the participant, reporting the error of the C library
+            /// when it cannot be created.
+            pub fn new() -> Result<Self, ::rticonnector::builder::BuildError> {
+                Self::with_options(::rticonnector::RTIOptions::default())
+            }
+
+            pub fn with_options(
+                options: ::rticonnector::RTIOptions,
+            ) -> Result<Self, ::rticonnector::builder::BuildError> {
+                let connector = ::rticonnector::ConnectorBuilder::new(NAME)
+                    .config_str(super::super::CONFIG)
+                    .options(options)
+                    .build()?;
+                Ok(Self { connector })
+            }
+
+            pub fn connector(&self) -> &::rticonnector::Connector {
+                &self.connector
+            }
+
+            /// Writes `ShapeType` on the topic `Square`.
+            pub fn my_square_writer(&self) -> Result<::rticonnector::Output<'_, super::super::types::ShapeType>, ::rticonnector::ConnectorError> {
+                self.connector.get_output("MyPublisher::MySquareWriter")
+            }
+
+            /// Reads `ShapeType` on the topic `Square`.
+            pub fn my_square_reader(&self) -> Result<::rticonnector::Input<'_, super::super::types::ShapeType>, ::rticonnector::ConnectorError> {
+                self.connector.get_input("MySubscriber::MySquareReader")
+            }
+        }
+    }
+
+    pub mod transformation_participant {
+        pub const NAME: &str = "MyParticipantLibrary::TransformationParticipant";
+
+        /// The participant `MyParticipantLibrary::TransformationParticipant`.
+        pub struct Participant {
+            connector: ::rticonnector::Connector,
+        }
+
+        impl Participant {
+            /// Creates the participant, reporting the error of the C library
+            /// when it cannot be created.
+            pub fn new() -> Result<Self, ::rticonnector::builder::BuildError> {
+                Self::with_options(::rticonnector::RTIOptions::default())
+            }
+
+            pub fn with_options(
+                options: ::rticonnector::RTIOptions,
+            ) -> Result<Self, ::rticonnector::builder::BuildError> {
+                let connector = ::rticonnector::ConnectorBuilder::new(NAME)
+                    .config_str(super::super::CONFIG)
+                    .options(options)
+                    .build()?;
+                Ok(Self { connector })
+            }
+
+            pub fn connector(&self) -> &::rticonnector::Connector {
+                &self.connector
+            }
+
+            /// Writes `ShapeType` on the topic `Circle`.
+            pub fn my_circle_writer(&self) -> Result<::rticonnector::Output<'_, super::super::types::ShapeType>, ::rticonnector::ConnectorError> {
+                self.connector.get_output("MyPublisher::MyCircleWriter")
+            }
+
+            /// Reads `ShapeType` on the topic `Square`.
+            pub fn my_square_reader(&self) -> Result<::rticonnector::Input<'_, super::super::types::ShapeType>, ::rticonnector::ConnectorError> {
+                self.connector.get_input("MySubscriber::MySquareReader")
+            }
+        }
+    }
+
+    pub mod circle_sub_participant {
+        pub const NAME: &str = "MyParticipantLibrary::CircleSubParticipant";
+
+        /// The participant `MyParticipantLibrary::CircleSubParticipant`.
+        pub struct Participant {
+            connector: ::rticonnector::Connector,
+        }
+
+        impl Participant {
+            /// Creates the participant, reporting the error of the C library
+            /// when it cannot be created.
+            pub fn new() -> Result<Self, ::rticonnector::builder::BuildError> {
+                Self::with_options(::rticonnector::RTIOptions::default())
+            }
+
+            pub fn with_options(
+                options: ::rticonnector::RTIOptions,
+            ) -> Result<Self, ::rticonnector::builder::BuildError> {
+                let connector = ::rticonnector::ConnectorBuilder::new(NAME)
+                    .config_str(super::super::CONFIG)
+                    .options(options)
+                    .build()?;
+                Ok(Self { connector })
+            }
+
+            pub fn connector(&self) -> &::rticonnector::Connector {
+                &self.connector
+            }
+
+            /// Reads `ShapeType` on the topic `Circle`.
+            pub fn my_circle_reader(&self) -> Result<::rticonnector::Input<'_, super::super::types::ShapeType>, ::rticonnector::ConnectorError> {
+                self.connector.get_input("MySubscriber::MyCircleReader")
+            }
+        }
+    }
+}
@@ -0,0 +1,10 @@
+// Generated by rticonnector-codegen. Do not edit.
+
+pub const MAX_LEN: i32 = 128;
+
+#[derive(Debug, Clone, PartialEq, Default, ::serde::Serialize, ::serde::Deserialize, ::connector_dds::DdsType)]
+#[dds(name = "MyType", extensibility = "appendable")]
+pub struct MyType {
+    #[dds(key, string_max_length = 128)]
+    pub message: String,
+}
@@ -0,0 +1,486 @@
+//! XTypes assignability between two versions of a type.
+//!
+//! A reader matches a writer when the type of the reader is assignable from
+//! the type of the writer, as defined by DDS-XTypes 1.3 with the default
+//! `DISCARD` try-construct behavior:
+//!
+//! - Structs and enums must keep their extensibility, and structs their key
+//!   members.
+//! - A final struct must keep its members. An appendable one may gain or
+//!   lose members at its end, and a mutable one anywhere, as long as the
+//!   remaining members keep their member ID: their `id` attribute, or the
+//!   ID following the one of the previous member.
+//! - Members must keep their kind, optionality and array dimensions; nested
+//!   types are compared by structure, so they may be renamed.
+//! - A final enum must keep its enumerators. An appendable one may gain or
+//!   lose some, as long as names and values keep going together.
+//!
+//! Changes that keep the types assignable but affect some samples, such as a
+//! bound that the writer widened, are reported as notes.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use super::types::{
+    Bound, Enum, Extensibility, Member, MemberType, Struct, TypeDefinition, TypeKind, Types,
+};
+
+/// The result of [`check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub reader_type: String,
+    pub writer_type: String,
+    /// Why the reader cannot read the writer; empty when it can.
+    pub incompatibilities: Vec<Change>,
+    /// The changes that keep the types assignable but drop or default some
+    /// values.
+    pub notes: Vec<Change>,
+}
+
+impl Report {
+    pub fn is_compatible(&self) -> bool {
+        self.incompatibilities.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = match self.reader_type == self.writer_type {
+            true => format!("`{}`", self.reader_type),
+            false => format!("`{}` from `{}`", self.reader_type, self.writer_type),
+        };
+        match self.is_compatible() {
+            true => write!(f, "{}: compatible", names)?,
+            false => write!(f, "{}: incompatible", names)?,
+        }
+        for change in &self.incompatibilities {
+            write!(f, "\n  - {}", change)?;
+        }
+        for change in &self.notes {
+            write!(f, "\n  note: {}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// A difference between the reader and the writer type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// Dot-separated from the checked type, such as `pos.x`; empty for the
+    /// checked type itself.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path.is_empty() {
+            true => write!(f, "{}", self.message),
+            false => write!(f, "{}: {}", self.path, self.message),
+        }
+    }
+}
+
+/// Checks whether a reader of `reader_type`, defined in `reader`, can read
+/// the samples of a writer of `writer_type`, defined in `writer`.
+pub fn check(reader: &Types, reader_type: &str, writer: &Types, writer_type: &str) -> Report {
+    let mut comparison = Comparison {
+        reader,
+        writer,
+        visited: HashSet::new(),
+        incompatibilities: Vec::new(),
+        notes: Vec::new(),
+    };
+    match (
+        reader.get_struct(reader_type),
+        writer.get_struct(writer_type),
+    ) {
+        (Some(_), Some(_)) => comparison.definitions("", false, reader_type, writer_type),
+        (None, _) => comparison.incompatible(
+            "",
+            format!("no struct `{}` in the reader types", reader_type),
+        ),
+        (_, None) => comparison.incompatible(
+            "",
+            format!("no struct `{}` in the writer types", writer_type),
+        ),
+    }
+    Report {
+        reader_type: reader_type.to_string(),
+        writer_type: writer_type.to_string(),
+        incompatibilities: comparison.incompatibilities,
+        notes: comparison.notes,
+    }
+}
+
+struct Comparison<'a> {
+    reader: &'a Types,
+    writer: &'a Types,
+    /// The pairs of definitions already compared, so shared and recursive
+    /// types are compared once.
+    visited: HashSet<(String, String)>,
+    incompatibilities: Vec<Change>,
+    notes: Vec<Change>,
+}
+
+impl Comparison<'_> {
+    fn incompatible(&mut self, path: &str, message: impl Into<String>) {
+        self.incompatibilities.push(Change {
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn note(&mut self, path: &str, message: impl Into<String>) {
+        self.notes.push(Change {
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    /// Compares the definitions `reader` and `writer`, the type of a key
+    /// member when `key` is set.
+    fn definitions(&mut self, path: &str, key: bool, reader: &str, writer: &str) {
+        if !self
+            .visited
+            .insert((reader.to_string(), writer.to_string()))
+        {
+            return;
+        }
+        match (self.reader.get(reader), self.writer.get(writer)) {
+            (Some(TypeDefinition::Struct(r)), Some(TypeDefinition::Struct(w))) => {
+                self.structs(path, r, w)
+            }
+            (Some(TypeDefinition::Enum(r)), Some(TypeDefinition::Enum(w))) => {
+                self.enums(path, r, w)
+            }
+            // Typedefs that resolve_typedefs leaves, such as a sequence of a
+            // sequence typedef.
+            (Some(TypeDefinition::Typedef(r)), Some(TypeDefinition::Typedef(w))) => {
+                self.member_types(path, key, &r.member_type, &w.member_type)
+            }
+            (r, w) => {
+                let message = format!("{} in the reader, {} in the writer", what(r), what(w));
+                self.incompatible(path, message);
+            }
+        }
+    }
+
+    fn structs(&mut self, path: &str, reader: &Struct, writer: &Struct) {
+        if reader.extensibility != writer.extensibility {
+            let message = format!(
+                "{} in the reader, {} in the writer",
+                reader.extensibility.xml_name(),
+                writer.extensibility.xml_name()
+            );
+            self.incompatible(path, message);
+            return;
+        }
+        let reader_members = self.reader.all_members(reader);
+        let writer_members = self.writer.all_members(writer);
+        let reader_keys = key_names(&reader_members);
+        let writer_keys = key_names(&writer_members);
+        if reader_keys != writer_keys {
+            let message = format!(
+                "the key is ({}) in the reader, ({}) in the writer",
+                reader_keys.join(", "),
+                writer_keys.join(", ")
+            );
+            self.incompatible(path, message);
+        }
+
+        if reader.extensibility == Extensibility::Mutable {
+            let reader_ids = self.reader.member_ids(reader);
+            let writer_ids = self.writer.member_ids(writer);
+            for (member, id) in reader_members.iter().zip(reader_ids) {
+                let member_path = join(path, &member.name);
+                match writer_members.iter().position(|m| m.name == member.name) {
+                    Some(index) if writer_ids[index] != id => {
+                        let message = format!(
+                            "member ID {} in the reader, {} in the writer",
+                            id, writer_ids[index]
+                        );
+                        self.incompatible(&member_path, message);
+                    }
+                    Some(index) => self.members(&member_path, member, writer_members[index]),
+                    None => self.missing(&member_path, member),
+                }
+            }
+            for member in &writer_members {
+                if !reader_members.iter().any(|m| m.name == member.name) {
+                    self.note(
+                        &join(path, &member.name),
+                        "added by the writer, ignored by the reader",
+                    );
+                }
+            }
+            return;
+        }
+
+        for (reader_member, writer_member) in reader_members.iter().zip(&writer_members) {
+            if reader_member.name != writer_member.name {
+                let message = format!(
+                    "`{}` in the reader, `{}` in the writer at the same position",
+                    reader_member.name, writer_member.name
+                );
+                self.incompatible(&join(path, &reader_member.name), message);
+                continue;
+            }
+            self.members(
+                &join(path, &reader_member.name),
+                reader_member,
+                writer_member,
+            );
+        }
+        let common = reader_members.len().min(writer_members.len());
+        if reader.extensibility == Extensibility::Final
+            && reader_members.len() != writer_members.len()
+        {
+            let message = format!(
+                "{} members in the reader, {} in the writer, which a final struct cannot change",
+                reader_members.len(),
+                writer_members.len()
+            );
+            self.incompatible(path, message);
+            return;
+        }
+        for member in &reader_members[common..] {
+            self.missing(&join(path, &member.name), member);
+        }
+        for member in &writer_members[common..] {
+            self.note(
+                &join(path, &member.name),
+                "added by the writer, ignored by the reader",
+            );
+        }
+    }
+
+    /// A member of the reader that the writer does not send.
+    fn missing(&mut self, path: &str, member: &Member) {
+        match member.optional {
+            true => self.note(path, "missing from the writer, left unset by the reader"),
+            false => self.note(path, "missing from the writer, read as its default"),
+        }
+    }
+
+    fn members(&mut self, path: &str, reader: &Member, writer: &Member) {
+        if reader.optional != writer.optional {
+            let message = match reader.optional {
+                true => "optional in the reader only",
+                false => "optional in the writer only",
+            };
+            self.incompatible(path, message);
+        }
+        self.member_types(path, reader.key, &reader.member_type, &writer.member_type);
+    }
+
+    fn member_types(&mut self, path: &str, key: bool, reader: &MemberType, writer: &MemberType) {
+        let reader_type = self.reader.resolve_typedefs(reader);
+        let writer_type = self.writer.resolve_typedefs(writer);
+
+        let reader_dimensions = dimensions(self.reader, &reader_type);
+        let writer_dimensions = dimensions(self.writer, &writer_type);
+        if reader_dimensions != writer_dimensions {
+            let message = format!(
+                "array {} in the reader, {} in the writer",
+                describe_dimensions(&reader_dimensions),
+                describe_dimensions(&writer_dimensions)
+            );
+            self.incompatible(path, message);
+            return;
+        }
+        if reader_type.is_sequence() != writer_type.is_sequence() {
+            let message = match reader_type.is_sequence() {
+                true => "a sequence in the reader only",
+                false => "a sequence in the writer only",
+            };
+            self.incompatible(path, message);
+            return;
+        }
+        self.bounds(
+            path,
+            key,
+            "sequence",
+            &reader_type.sequence_max_length,
+            &writer_type.sequence_max_length,
+        );
+
+        match (&reader_type.kind, &writer_type.kind) {
+            (TypeKind::NonBasic(r), TypeKind::NonBasic(w)) => self.definitions(path, key, r, w),
+            (r, w) if r == w => self.bounds(
+                path,
+                key,
+                "string",
+                &reader_type.string_max_length,
+                &writer_type.string_max_length,
+            ),
+            (r, w) => {
+                let message = format!(
+                    "{} in the reader, {} in the writer",
+                    r.xml_name(),
+                    w.xml_name()
+                );
+                self.incompatible(path, message);
+            }
+        }
+    }
+
+    /// Compares the bounds of a sequence or a string. A writer bound wider
+    /// than the reader one only drops the longer samples, unless it bounds a
+    /// key, which both sides must hash the same way.
+    fn bounds(
+        &mut self,
+        path: &str,
+        key: bool,
+        what: &str,
+        reader: &Option<Bound>,
+        writer: &Option<Bound>,
+    ) {
+        let reader_length = reader.as_ref().and_then(|b| self.reader.resolve_bound(b));
+        let writer_length = writer.as_ref().and_then(|b| self.writer.resolve_bound(b));
+        if reader_length == writer_length {
+            return;
+        }
+        let describe = |length: Option<u32>| match length {
+            Some(length) => length.to_string(),
+            None => "unbounded".to_string(),
+        };
+        let message = format!(
+            "{} bound {} in the reader, {} in the writer",
+            what,
+            describe(reader_length),
+            describe(writer_length)
+        );
+        let widened = match (reader_length, writer_length) {
+            (Some(reader), Some(writer)) => writer > reader,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if key {
+            self.incompatible(path, format!("{}, on a key member", message));
+        } else if widened {
+            self.note(
+                path,
+                format!("{}: the reader drops the longer samples", message),
+            );
+        }
+    }
+
+    fn enums(&mut self, path: &str, reader: &Enum, writer: &Enum) {
+        if reader.extensibility != writer.extensibility {
+            let message = format!(
+                "{} in the reader, {} in the writer",
+                reader.extensibility.xml_name(),
+                writer.extensibility.xml_name()
+            );
+            self.incompatible(path, message);
+            return;
+        }
+        let final_enum = reader.extensibility == Extensibility::Final;
+        for enumerator in &reader.enumerators {
+            let enumerator_path = format!("{}::{}", path, enumerator.name);
+            let by_name = writer
+                .enumerators
+                .iter()
+                .find(|e| e.name == enumerator.name);
+            let by_value = writer
+                .enumerators
+                .iter()
+                .find(|e| e.value == enumerator.value);
+            match (by_name, by_value) {
+                (Some(w), _) if w.value != enumerator.value => {
+                    let message = format!(
+                        "= {} in the reader, = {} in the writer",
+                        enumerator.value, w.value
+                    );
+                    self.incompatible(&enumerator_path, message);
+                }
+                (Some(_), _) => {}
+                (None, Some(w)) => {
+                    let message =
+                        format!("value {} is `{}` in the writer", enumerator.value, w.name);
+                    self.incompatible(&enumerator_path, message);
+                }
+                (None, None) if final_enum => {
+                    self.incompatible(
+                        &enumerator_path,
+                        "missing from the writer, which a final enum cannot change",
+                    );
+                }
+                (None, None) => self.note(
+                    &enumerator_path,
+                    "missing from the writer, which never sends it",
+                ),
+            }
+        }
+        for enumerator in &writer.enumerators {
+            let known = reader
+                .enumerators
+                .iter()
+                .any(|e| e.name == enumerator.name || e.value == enumerator.value);
+            if known {
+                continue;
+            }
+            let enumerator_path = format!("{}::{}", path, enumerator.name);
+            match final_enum {
+                true => self.incompatible(
+                    &enumerator_path,
+                    "added by the writer, which a final enum cannot change",
+                ),
+                false => self.note(
+                    &enumerator_path,
+                    "added by the writer: the reader drops the samples with it",
+                ),
+            }
+        }
+    }
+}
+
+fn what(definition: Option<&TypeDefinition>) -> &'static str {
+    match definition {
+        Some(TypeDefinition::Struct(_)) => "a struct",
+        Some(TypeDefinition::Enum(_)) => "an enum",
+        Some(TypeDefinition::Typedef(_)) => "a typedef",
+        Some(TypeDefinition::Const(_)) => "a const",
+        None => "an unknown type",
+    }
+}
+
+fn key_names<'m>(members: &[&'m Member]) -> Vec<&'m str> {
+    members
+        .iter()
+        .filter(|m| m.key)
+        .map(|m| m.name.as_str())
+        .collect()
+}
+
+/// The resolved array dimensions of a member; `None` for a dimension naming
+/// an unknown const.
+fn dimensions(types: &Types, member_type: &MemberType) -> Vec<Option<u32>> {
+    member_type
+        .array_dimensions
+        .iter()
+        .map(|d| types.resolve_bound(d))
+        .collect()
+}
+
+fn describe_dimensions(dimensions: &[Option<u32>]) -> String {
+    if dimensions.is_empty() {
+        return "none".to_string();
+    }
+    dimensions
+        .iter()
+        .map(|d| match d {
+            Some(d) => format!("[{}]", d),
+            None => "[?]".to_string(),
+        })
+        .collect()
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
@@ -0,0 +1,144 @@
+//! Building a configuration in Rust rather than templating XML.
+//!
+//! ```
+//! use rticonnector_config::domains::Domain;
+//! use rticonnector_config::participants::{DataWriter, Participant, Publisher};
+//! use rticonnector_config::types::{Member, MemberType, Struct, TypeKind};
+//! use rticonnector_config::Config;
+//!
+//! let config = Config::builder()
+//!     .type_definition(
+//!         Struct::new("ShapeType")
+//!             .with_member(Member::new("color", MemberType::new(TypeKind::String)).with_key())
+//!             .with_member(Member::new("x", MemberType::new(TypeKind::Long))),
+//!     )
+//!     .domain(
+//!         "MyDomainLibrary",
+//!         Domain::new("MyDomain", 7)
+//!             .with_register_type("ShapeType", "ShapeType")
+//!             .with_topic("Square", "ShapeType"),
+//!     )
+//!     .participant(
+//!         "MyParticipantLibrary",
+//!         Participant::new("MyPubParticipant", "MyDomainLibrary::MyDomain").with_publisher(
+//!             Publisher::new("MyPublisher").with_writer(DataWriter::new("MySquareWriter", "Square")),
+//!         ),
+//!     )
+//!     .build();
+//! assert!(config.validate().is_empty());
+//! println!("{}", config.to_xml());
+//! ```
+
+use super::domains::{Domain, DomainLibrary};
+use super::participants::{Participant, ParticipantLibrary};
+use super::qos::{QosLibrary, QosProfile};
+use super::types::{TypeDefinition, Types};
+use super::Config;
+
+/// Builds a [`Config`], creating libraries as they are first named.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a QoS profile to the QoS library `library`.
+    pub fn qos_profile(mut self, library: &str, profile: QosProfile) -> Self {
+        library_entry(&mut self.config.qos_libraries, library, |l| &l.name, QosLibrary::new)
+            .profiles
+            .push(profile);
+        self
+    }
+
+    /// Adds a type definition; its name and references must be fully
+    /// qualified (`"Geo::Point"`).
+    pub fn type_definition(mut self, definition: impl Into<TypeDefinition>) -> Self {
+        self.config.types.push(definition);
+        self
+    }
+
+    /// Adds every definition of `types`, such as the ones parsed from a
+    /// shared `<types>` file.
+    pub fn types(mut self, types: Types) -> Self {
+        self.config.types.extend(types);
+        self
+    }
+
+    /// Adds a domain to the domain library `library`.
+    pub fn domain(mut self, library: &str, domain: Domain) -> Self {
+        library_entry(&mut self.config.domain_libraries, library, |l| &l.name, DomainLibrary::new)
+            .domains
+            .push(domain);
+        self
+    }
+
+    /// Adds a participant to the participant library `library`.
+    pub fn participant(mut self, library: &str, participant: Participant) -> Self {
+        library_entry(
+            &mut self.config.participant_libraries,
+            library,
+            |l| &l.name,
+            ParticipantLibrary::new,
+        )
+        .participants
+        .push(participant);
+        self
+    }
+
+    /// Adds every library and type of `config`, such as a parsed file,
+    /// merging libraries of the same name.
+    pub fn config(mut self, config: Config) -> Self {
+        for library in config.qos_libraries {
+            merge_library(&mut self.config.qos_libraries, library, |l| &l.name, |l| &mut l.profiles);
+        }
+        self.config.types.extend(config.types);
+        for library in config.domain_libraries {
+            merge_library(&mut self.config.domain_libraries, library, |l| &l.name, |l| &mut l.domains);
+        }
+        for library in config.participant_libraries {
+            merge_library(
+                &mut self.config.participant_libraries,
+                library,
+                |l| &l.name,
+                |l| &mut l.participants,
+            );
+        }
+        self
+    }
+
+    pub fn build(self) -> Config {
+        self.config
+    }
+}
+
+fn library_entry<'a, L>(
+    libraries: &'a mut Vec<L>,
+    name: &str,
+    library_name: impl Fn(&L) -> &String,
+    new: impl FnOnce(&str) -> L,
+) -> &'a mut L {
+    let index = match libraries.iter().position(|l| library_name(l) == name) {
+        Some(index) => index,
+        None => {
+            libraries.push(new(name));
+            libraries.len() - 1
+        }
+    };
+    &mut libraries[index]
+}
+
+fn merge_library<L, T>(
+    libraries: &mut Vec<L>,
+    mut library: L,
+    library_name: impl Fn(&L) -> &String,
+    entries: impl Fn(&mut L) -> &mut Vec<T>,
+) {
+    match libraries.iter_mut().find(|l| library_name(l) == library_name(&library)) {
+        Some(existing) => entries(existing).append(entries(&mut library)),
+        None => libraries.push(library),
+    }
+}
@@ -0,0 +1,208 @@
+//! The `<domain_library>` sections of a configuration.
+//!
+//! A domain registers types under a name and binds topics to them;
+//! participants refer to a domain with `domain_ref="Library::Domain"`.
+
+use roxmltree::Node;
+
+use super::{attribute, element, invalid, line, required_attribute, ConfigError};
+
+/// A `<domain_library>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainLibrary {
+    pub name: String,
+    pub domains: Vec<Domain>,
+    pub line: u32,
+}
+
+/// A `<domain>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Domain {
+    pub name: String,
+    pub domain_id: u32,
+    pub register_types: Vec<RegisterType>,
+    pub topics: Vec<Topic>,
+    pub line: u32,
+}
+
+/// A `<register_type>`: a type of the `<types>` section registered under
+/// `name`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisterType {
+    pub name: String,
+    /// The qualified name of the type; `name` when `type_ref` is absent.
+    pub type_ref: String,
+    pub line: u32,
+}
+
+/// A `<topic>` and the registered type it carries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Topic {
+    pub name: String,
+    pub register_type_ref: String,
+    pub line: u32,
+}
+
+impl DomainLibrary {
+    pub(crate) fn parse(node: Node) -> Result<Self, ConfigError> {
+        Ok(Self {
+            name: required_attribute(node, "name")?.to_string(),
+            domains: node
+                .children()
+                .filter(|n| n.has_tag_name("domain"))
+                .map(Domain::parse)
+                .collect::<Result<_, _>>()?,
+            line: line(node),
+        })
+    }
+
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            domains: Vec::new(),
+            line: 0,
+        }
+    }
+
+    pub fn domain(&self, name: &str) -> Option<&Domain> {
+        self.domains.iter().find(|d| d.name == name)
+    }
+
+    pub fn to_xml(&self) -> String {
+        let domains: String = self.domains.iter().map(Domain::to_xml).collect();
+        element("domain_library", &attribute("name", &self.name), &domains)
+    }
+}
+
+impl Domain {
+    fn parse(node: Node) -> Result<Self, ConfigError> {
+        let (register_types, topics) = parse_registrations(node)?;
+        Ok(Self {
+            name: required_attribute(node, "name")?.to_string(),
+            domain_id: parse_domain_id(node)?.unwrap_or(0),
+            register_types,
+            topics,
+            line: line(node),
+        })
+    }
+
+    pub fn new(name: &str, domain_id: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            domain_id,
+            register_types: Vec::new(),
+            topics: Vec::new(),
+            line: 0,
+        }
+    }
+
+    /// Registers the type `type_ref` of the `<types>` section as `name`.
+    pub fn with_register_type(mut self, name: &str, type_ref: &str) -> Self {
+        self.register_types.push(RegisterType::new(name, type_ref));
+        self
+    }
+
+    /// Declares the topic `name` carrying the registered type
+    /// `register_type_ref`.
+    pub fn with_topic(mut self, name: &str, register_type_ref: &str) -> Self {
+        self.topics.push(Topic::new(name, register_type_ref));
+        self
+    }
+
+    pub fn topic(&self, name: &str) -> Option<&Topic> {
+        self.topics.iter().find(|t| t.name == name)
+    }
+
+    pub fn register_type(&self, name: &str) -> Option<&RegisterType> {
+        self.register_types.iter().find(|r| r.name == name)
+    }
+
+    pub fn to_xml(&self) -> String {
+        let attributes =
+            attribute("name", &self.name) + &attribute("domain_id", &self.domain_id.to_string());
+        element(
+            "domain",
+            &attributes,
+            &registrations_xml(&self.register_types, &self.topics),
+        )
+    }
+}
+
+impl RegisterType {
+    pub fn new(name: &str, type_ref: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            type_ref: type_ref.to_string(),
+            line: 0,
+        }
+    }
+}
+
+impl Topic {
+    pub fn new(name: &str, register_type_ref: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            register_type_ref: register_type_ref.to_string(),
+            line: 0,
+        }
+    }
+}
+
+/// Parses the `domain_id` attribute of a domain or participant.
+pub(crate) fn parse_domain_id(node: Node) -> Result<Option<u32>, ConfigError> {
+    node.attribute("domain_id")
+        .map(|id| {
+            id.trim()
+                .parse()
+                .map_err(|_| invalid(node, format!("`{}` is not a valid domain_id", id)))
+        })
+        .transpose()
+}
+
+/// Renders `<register_type>`s followed by `<topic>`s.
+pub(crate) fn registrations_xml(register_types: &[RegisterType], topics: &[Topic]) -> String {
+    let mut xml = String::new();
+    for register_type in register_types {
+        xml.push_str(&format!(
+            "<register_type{}{}/>\n",
+            attribute("name", &register_type.name),
+            attribute("type_ref", &register_type.type_ref)
+        ));
+    }
+    for topic in topics {
+        xml.push_str(&format!(
+            "<topic{}{}/>\n",
+            attribute("name", &topic.name),
+            attribute("register_type_ref", &topic.register_type_ref)
+        ));
+    }
+    xml
+}
+
+/// Parses the `<register_type>` and `<topic>` children of a domain or
+/// participant.
+pub(crate) fn parse_registrations(
+    node: Node,
+) -> Result<(Vec<RegisterType>, Vec<Topic>), ConfigError> {
+    let mut register_types = Vec::new();
+    let mut topics = Vec::new();
+    for child in node.children().filter(Node::is_element) {
+        match child.tag_name().name() {
+            "register_type" => {
+                let name = required_attribute(child, "name")?;
+                register_types.push(RegisterType {
+                    name: name.to_string(),
+                    type_ref: child.attribute("type_ref").unwrap_or(name).to_string(),
+                    line: line(child),
+                });
+            }
+            "topic" => topics.push(Topic {
+                name: required_attribute(child, "name")?.to_string(),
+                register_type_ref: required_attribute(child, "register_type_ref")?.to_string(),
+                line: line(child),
+            }),
+            _ => {}
+        }
+    }
+    Ok((register_types, topics))
+}
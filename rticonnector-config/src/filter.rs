@@ -0,0 +1,579 @@
+//! Content filters on data readers.
+//!
+//! A `<content_filter>` makes the middleware drop the samples a reader is
+//! not interested in before they reach the application, using an SQL-like
+//! expression over the members of the topic type:
+//!
+//! ```
+//! use rticonnector_config::filter::ContentFilter;
+//!
+//! let filter = ContentFilter::new("BlueSquares", "color = %0 AND x > 50", &["'BLUE'"]);
+//! assert!(filter.check().is_ok());
+//! ```
+//!
+//! String parameters are quoted, as they would be in the expression itself.
+
+use roxmltree::Node;
+use thiserror::Error;
+
+use super::types::{Enum, MemberType, Struct, TypeDefinition, TypeKind, Types};
+use super::{attribute, element, escape, line, required_attribute, ConfigError};
+
+/// An invalid filter expression.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum FilterError {
+    #[error("syntax error at offset {offset}: {message}")]
+    Syntax { offset: usize, message: String },
+    #[error("`%{index}` is used but only {count} parameter(s) are given")]
+    MissingParameter { index: usize, count: usize },
+    #[error("`{member}` is not a member of `{type_name}`")]
+    UnknownMember { member: String, type_name: String },
+    #[error("`%{index}` is compared to the string `{member}` and must be quoted: `'{value}'`")]
+    UnquotedParameter {
+        index: usize,
+        member: String,
+        value: String,
+    },
+}
+
+/// A `<content_filter>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentFilter {
+    /// The name of the filtered topic, unique within the participant.
+    pub name: String,
+    /// The filter class; `builtin.sql` unless set otherwise.
+    pub kind: String,
+    pub expression: String,
+    /// The values of `%0`, `%1`, ...
+    pub parameters: Vec<String>,
+    pub line: u32,
+}
+
+impl ContentFilter {
+    /// Parses the `<content_filter>` child of a data reader, if any.
+    pub(crate) fn parse(node: Node) -> Result<Option<Self>, ConfigError> {
+        let filter = match node.children().find(|n| n.has_tag_name("content_filter")) {
+            Some(filter) => filter,
+            None => return Ok(None),
+        };
+        let text = |tag| {
+            filter
+                .children()
+                .find(|n| n.has_tag_name(tag))
+                .and_then(|n| n.text())
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        };
+        let parameters = filter
+            .children()
+            .filter(|n| n.has_tag_name("expression_parameters"))
+            .flat_map(|n| n.children().filter(|n| n.has_tag_name("element")))
+            .map(|n| n.text().unwrap_or_default().trim().to_string())
+            .collect();
+        Ok(Some(Self {
+            name: required_attribute(filter, "name")?.to_string(),
+            kind: filter
+                .attribute("kind")
+                .unwrap_or("builtin.sql")
+                .to_string(),
+            expression: text("expression"),
+            parameters,
+            line: line(filter),
+        }))
+    }
+
+    pub fn new<S: AsRef<str>>(name: &str, expression: &str, parameters: &[S]) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: "builtin.sql".to_string(),
+            expression: expression.to_string(),
+            parameters: parameters.iter().map(|p| p.as_ref().to_string()).collect(),
+            line: 0,
+        }
+    }
+
+    pub fn to_xml(&self) -> String {
+        let attributes = attribute("name", &self.name) + &attribute("kind", &self.kind);
+        let mut children = format!("<expression>{}</expression>\n", escape(&self.expression));
+        if !self.parameters.is_empty() {
+            let elements: String = self
+                .parameters
+                .iter()
+                .map(|p| format!("<element>{}</element>\n", escape(p)))
+                .collect();
+            children.push_str(&element("expression_parameters", "", &elements));
+        }
+        element("content_filter", &attributes, &children)
+    }
+
+    /// Checks the syntax of the expression and that every parameter it uses
+    /// is given. Filters of other kinds than `builtin.sql` are not checked.
+    pub fn check(&self) -> Result<(), FilterError> {
+        self.predicates().map(|_| ())
+    }
+
+    /// Like [`check`](Self::check), and also checks the members the
+    /// expression refers to against the topic type `topic_type`.
+    pub fn check_against(&self, types: &Types, topic_type: &Struct) -> Result<(), FilterError> {
+        for predicate in self.predicates()? {
+            let resolved: Vec<_> = predicate
+                .iter()
+                .map(|operand| match operand {
+                    Operand::Field(path) => resolve(types, topic_type, path),
+                    _ => None,
+                })
+                .collect();
+            for (operand, field) in predicate.iter().zip(&resolved) {
+                let path = match (operand, field) {
+                    (Operand::Field(path), None) => path,
+                    _ => continue,
+                };
+                // An enumerator compared to a member of its enum.
+                let enumerator = match path.as_slice() {
+                    [Segment::Name(name)] => resolved.iter().flatten().any(|r| match r {
+                        Resolved::Enum(e) => e.enumerators.iter().any(|e| &e.name == name),
+                        _ => false,
+                    }),
+                    _ => false,
+                };
+                if !enumerator {
+                    return Err(FilterError::UnknownMember {
+                        member: path_name(path),
+                        type_name: topic_type.name.clone(),
+                    });
+                }
+            }
+            let string_member =
+                predicate
+                    .iter()
+                    .zip(&resolved)
+                    .find_map(|(operand, r)| match (operand, r) {
+                        (
+                            Operand::Field(path),
+                            Some(Resolved::Basic(TypeKind::String | TypeKind::WString)),
+                        ) => Some(path),
+                        _ => None,
+                    });
+            if let Some(member) = string_member {
+                for operand in &predicate {
+                    if let Operand::Parameter(index) = *operand {
+                        let value = &self.parameters[index];
+                        if !value.starts_with('\'') {
+                            return Err(FilterError::UnquotedParameter {
+                                index,
+                                member: path_name(member),
+                                value: value.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn predicates(&self) -> Result<Vec<Vec<Operand>>, FilterError> {
+        if self.kind != "builtin.sql" {
+            return Ok(Vec::new());
+        }
+        let mut parser = Parser {
+            tokens: tokenize(&self.expression)?,
+            position: 0,
+            end: self.expression.len(),
+            predicates: Vec::new(),
+        };
+        parser.condition()?;
+        if let Some((offset, token)) = parser.tokens.get(parser.position) {
+            return Err(syntax(*offset, format!("unexpected {}", token)));
+        }
+        for operand in parser.predicates.iter().flatten() {
+            if let Operand::Parameter(index) = *operand {
+                if index >= self.parameters.len() {
+                    return Err(FilterError::MissingParameter {
+                        index,
+                        count: self.parameters.len(),
+                    });
+                }
+            }
+        }
+        Ok(parser.predicates)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    Number,
+    String,
+    Parameter(usize),
+    Operator(&'static str),
+    LeftParenthesis,
+    RightParenthesis,
+    Dot,
+    LeftBracket,
+    RightBracket,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Identifier(name) => write!(f, "`{}`", name),
+            Token::Number => write!(f, "number"),
+            Token::String => write!(f, "string"),
+            Token::Parameter(index) => write!(f, "`%{}`", index),
+            Token::Operator(operator) => write!(f, "`{}`", operator),
+            Token::LeftParenthesis => write!(f, "`(`"),
+            Token::RightParenthesis => write!(f, "`)`"),
+            Token::Dot => write!(f, "`.`"),
+            Token::LeftBracket => write!(f, "`[`"),
+            Token::RightBracket => write!(f, "`]`"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Name(String),
+    Index,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Field(Vec<Segment>),
+    Parameter(usize),
+    Literal,
+}
+
+fn syntax(offset: usize, message: impl Into<String>) -> FilterError {
+    FilterError::Syntax {
+        offset,
+        message: message.into(),
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<(usize, Token)>, FilterError> {
+    const OPERATORS: [&str; 8] = ["<>", "!=", ">=", "<=", "=", ">", "<", "-"];
+    let mut tokens = Vec::new();
+    let mut chars = expression.char_indices().peekable();
+    while let Some(&(offset, c)) = chars.peek() {
+        let rest = &expression[offset..];
+        let taken =
+            |predicate: fn(char) -> bool| rest.find(|c: char| !predicate(c)).unwrap_or(rest.len());
+        let (length, token) = if c.is_whitespace() {
+            (c.len_utf8(), None)
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let length = taken(|c| c.is_ascii_alphanumeric() || c == '_');
+            (length, Some(Token::Identifier(rest[..length].to_string())))
+        } else if c.is_ascii_digit() {
+            let length = number_length(rest);
+            // A number only runs into a name or another number through a typo.
+            let word = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '.';
+            if rest[length..].starts_with(word) {
+                let end = length
+                    + rest[length..]
+                        .find(|c| !word(c))
+                        .unwrap_or(rest.len() - length);
+                return Err(syntax(offset, format!("invalid number `{}`", &rest[..end])));
+            }
+            (length, Some(Token::Number))
+        } else if c == '\'' || c == '`' {
+            let end = rest[1..]
+                .find('\'')
+                .ok_or_else(|| syntax(offset, "unterminated string"))?;
+            (end + 2, Some(Token::String))
+        } else if c == '%' {
+            let length = 1 + rest[1..]
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len() - 1);
+            let index = rest[1..length]
+                .parse()
+                .map_err(|_| syntax(offset, "expected a parameter index after `%`"))?;
+            (length, Some(Token::Parameter(index)))
+        } else if let Some(operator) = OPERATORS.iter().find(|o| rest.starts_with(*o)) {
+            (operator.len(), Some(Token::Operator(operator)))
+        } else {
+            let token = match c {
+                '(' => Token::LeftParenthesis,
+                ')' => Token::RightParenthesis,
+                '.' => Token::Dot,
+                '[' => Token::LeftBracket,
+                ']' => Token::RightBracket,
+                c => return Err(syntax(offset, format!("unexpected `{}`", c))),
+            };
+            (1, Some(token))
+        };
+        if let Some(token) = token {
+            tokens.push((offset, token));
+        }
+        while chars.peek().is_some_and(|&(o, _)| o < offset + length) {
+            chars.next();
+        }
+    }
+    Ok(tokens)
+}
+
+/// The length of the number at the start of `rest`: a hexadecimal integer
+/// (`0x1F`), or a decimal one with an optional fraction and exponent
+/// (`12`, `1.5`, `1e-5`).
+fn number_length(rest: &str) -> usize {
+    let digits = |from: usize, hex: bool| {
+        from + rest[from..]
+            .find(|c: char| !(c.is_ascii_digit() || hex && c.is_ascii_hexdigit()))
+            .unwrap_or(rest.len() - from)
+    };
+    if rest.starts_with("0x") || rest.starts_with("0X") {
+        let end = digits(2, true);
+        // `0x` alone is the number 0 followed by `x`.
+        return if end > 2 { end } else { 1 };
+    }
+    let mut end = digits(0, false);
+    if rest[end..].starts_with('.') {
+        end = digits(end + 1, false);
+    }
+    if rest[end..].starts_with(['e', 'E']) {
+        let sign = usize::from(rest[end + 1..].starts_with(['+', '-']));
+        let exponent = digits(end + 1 + sign, false);
+        if exponent > end + 1 + sign {
+            end = exponent;
+        }
+    }
+    end
+}
+
+/// A recursive descent parser of the `builtin.sql` grammar:
+///
+/// ```text
+/// condition  = and_term { OR and_term }
+/// and_term   = not_term { AND not_term }
+/// not_term   = NOT not_term | "(" condition ")" | predicate
+/// predicate  = operand ( relop operand | [NOT] BETWEEN operand AND operand )
+/// relop      = "=" | "<>" | "!=" | ">" | ">=" | "<" | "<=" | LIKE | MATCH
+/// operand    = field | parameter | number | string | TRUE | FALSE
+/// field      = identifier { "." identifier | "[" number "]" }
+/// ```
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    end: usize,
+    predicates: Vec<Vec<Operand>>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map_or(self.end, |(offset, _)| *offset)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Identifier(name)) if name.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, expected: Token, description: &str) -> Result<(), FilterError> {
+        if self.peek() == Some(&expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected(description))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> FilterError {
+        let found = match self.peek() {
+            Some(token) => token.to_string(),
+            None => "the end of the expression".to_string(),
+        };
+        syntax(
+            self.offset(),
+            format!("expected {}, found {}", expected, found),
+        )
+    }
+
+    fn condition(&mut self) -> Result<(), FilterError> {
+        self.and_term()?;
+        while self.keyword("OR") {
+            self.and_term()?;
+        }
+        Ok(())
+    }
+
+    fn and_term(&mut self) -> Result<(), FilterError> {
+        self.not_term()?;
+        while self.keyword("AND") {
+            self.not_term()?;
+        }
+        Ok(())
+    }
+
+    fn not_term(&mut self) -> Result<(), FilterError> {
+        if self.keyword("NOT") {
+            return self.not_term();
+        }
+        if self.peek() == Some(&Token::LeftParenthesis) {
+            self.position += 1;
+            self.condition()?;
+            return self.expect(Token::RightParenthesis, "`)`");
+        }
+        self.predicate()
+    }
+
+    fn predicate(&mut self) -> Result<(), FilterError> {
+        let offset = self.offset();
+        let mut operands = vec![self.operand()?];
+        let negated = self.keyword("NOT");
+        if self.keyword("BETWEEN") {
+            operands.push(self.operand()?);
+            if !self.keyword("AND") {
+                return Err(self.unexpected("`AND`"));
+            }
+            operands.push(self.operand()?);
+        } else if negated {
+            return Err(self.unexpected("`BETWEEN`"));
+        } else {
+            let operator = matches!(self.peek(), Some(Token::Operator(o)) if *o != "-");
+            if operator {
+                self.position += 1;
+            } else if !self.keyword("LIKE") && !self.keyword("MATCH") {
+                return Err(self.unexpected("a comparison operator"));
+            }
+            operands.push(self.operand()?);
+        }
+        if !operands.iter().any(|o| matches!(o, Operand::Field(_))) {
+            return Err(syntax(offset, "a comparison must involve a member"));
+        }
+        self.predicates.push(operands);
+        Ok(())
+    }
+
+    fn operand(&mut self) -> Result<Operand, FilterError> {
+        let token = self.peek().cloned();
+        match token {
+            Some(Token::Parameter(index)) => {
+                self.position += 1;
+                Ok(Operand::Parameter(index))
+            }
+            Some(Token::Number | Token::String) => {
+                self.position += 1;
+                Ok(Operand::Literal)
+            }
+            Some(Token::Operator("-")) => {
+                self.position += 1;
+                self.expect(Token::Number, "a number")?;
+                Ok(Operand::Literal)
+            }
+            Some(Token::Identifier(name))
+                if name.eq_ignore_ascii_case("TRUE") || name.eq_ignore_ascii_case("FALSE") =>
+            {
+                self.position += 1;
+                Ok(Operand::Literal)
+            }
+            Some(Token::Identifier(name)) => {
+                self.position += 1;
+                let mut path = vec![Segment::Name(name)];
+                loop {
+                    match self.peek() {
+                        Some(Token::Dot) => {
+                            self.position += 1;
+                            match self.peek().cloned() {
+                                Some(Token::Identifier(name)) => {
+                                    self.position += 1;
+                                    path.push(Segment::Name(name));
+                                }
+                                _ => return Err(self.unexpected("a member name")),
+                            }
+                        }
+                        Some(Token::LeftBracket) => {
+                            self.position += 1;
+                            self.expect(Token::Number, "an index")?;
+                            self.expect(Token::RightBracket, "`]`")?;
+                            path.push(Segment::Index);
+                        }
+                        _ => return Ok(Operand::Field(path)),
+                    }
+                }
+            }
+            _ => Err(self.unexpected("a member, parameter or value")),
+        }
+    }
+}
+
+/// What a member path designates.
+enum Resolved<'a> {
+    Struct,
+    Enum(&'a Enum),
+    Basic(&'a TypeKind),
+}
+
+/// Resolves a member path from `root`, or `None` when it names no member.
+fn resolve<'a>(types: &'a Types, root: &'a Struct, path: &[Segment]) -> Option<Resolved<'a>> {
+    let mut current = Some(root);
+    let mut resolved = Resolved::Struct;
+    // Indexes the current member still accepts: array dimensions and
+    // sequences.
+    let mut collections = 0;
+    for segment in path {
+        match segment {
+            Segment::Name(name) => {
+                let s = current.filter(|_| collections == 0)?;
+                let member = types.all_members(s).into_iter().find(|m| &m.name == name)?;
+                collections = dimensions(&member.member_type);
+                let mut kind = &member.member_type.kind;
+                current = None;
+                resolved = loop {
+                    match kind {
+                        TypeKind::NonBasic(type_name) => match types.get(type_name)? {
+                            TypeDefinition::Struct(s) => {
+                                current = Some(s);
+                                break Resolved::Struct;
+                            }
+                            TypeDefinition::Enum(e) => break Resolved::Enum(e),
+                            TypeDefinition::Typedef(t) => {
+                                collections += dimensions(&t.member_type);
+                                kind = &t.member_type.kind;
+                            }
+                            TypeDefinition::Const(_) => return None,
+                        },
+                        kind => break Resolved::Basic(kind),
+                    }
+                };
+            }
+            Segment::Index => {
+                if collections == 0 {
+                    return None;
+                }
+                collections -= 1;
+            }
+        }
+    }
+    Some(resolved)
+}
+
+fn dimensions(member_type: &MemberType) -> usize {
+    member_type.array_dimensions.len() + member_type.is_sequence() as usize
+}
+
+fn path_name(path: &[Segment]) -> String {
+    let mut name = String::new();
+    for segment in path {
+        match segment {
+            Segment::Name(segment) if name.is_empty() => name.push_str(segment),
+            Segment::Name(segment) => {
+                name.push('.');
+                name.push_str(segment);
+            }
+            Segment::Index => name.push_str("[]"),
+        }
+    }
+    name
+}
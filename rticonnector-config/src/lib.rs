@@ -0,0 +1,654 @@
+//! Rust-side model of a Connector XML configuration.
+//!
+//! The model is built without the native library, so a configuration can be
+//! inspected before it is handed to `rticonnector::Connector::new`.
+//!
+//! ```no_run
+//! use rticonnector_config::Config;
+//!
+//! let config = Config::from_file("examples/ShapeExample.xml").unwrap();
+//! for (name, participant) in config.participants() {
+//!     for (reader, data_reader) in participant.readers() {
+//!         let type_name = config.topic_type(participant, &data_reader.topic_ref);
+//!         println!("{} {} {:?}", name, reader, type_name);
+//!     }
+//! }
+//! for error in config.validate() {
+//!     eprintln!("{}", error);
+//! }
+//! ```
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use roxmltree::{Document, Node};
+use thiserror::Error;
+
+pub mod assignability;
+pub mod builder;
+pub mod domains;
+pub mod filter;
+pub mod participants;
+pub mod policies;
+pub mod qos;
+pub mod types;
+
+pub use builder::ConfigBuilder;
+
+use domains::{Domain, DomainLibrary};
+use participants::{Participant, ParticipantLibrary};
+use qos::{is_builtin_profile, EntityQos, QosLibrary, QosProfile};
+use types::{Struct, Types};
+
+/// Error returned for a configuration that cannot be read or parsed.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("malformed XML: {0}")]
+    Xml(#[from] roxmltree::Error),
+    #[error("line {line}: {message}")]
+    Invalid { line: u32, message: String },
+    /// A definition the model does not support, such as a `<union>`, which
+    /// the C library may accept.
+    #[error("line {line}: unsupported type definition <{element}>")]
+    Unsupported { line: u32, element: String },
+}
+
+impl ConfigError {
+    /// The line of the document the error points to, if known.
+    pub fn line(&self) -> Option<u32> {
+        match self {
+            ConfigError::Io { .. } => None,
+            ConfigError::Xml(error) => Some(error.pos().row),
+            ConfigError::Invalid { line, .. } | ConfigError::Unsupported { line, .. } => {
+                Some(*line)
+            }
+        }
+    }
+}
+
+/// A configuration document: its QoS profiles, types, domains and
+/// participants.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    pub qos_libraries: Vec<QosLibrary>,
+    pub types: Types,
+    pub domain_libraries: Vec<DomainLibrary>,
+    pub participant_libraries: Vec<ParticipantLibrary>,
+}
+
+impl Config {
+    pub fn from_xml(xml: &str) -> Result<Self, ConfigError> {
+        let document = parse_document(xml)?;
+        let dds = document.root_element();
+        if !dds.has_tag_name("dds") {
+            return Err(invalid(
+                dds,
+                format!("expected <dds> as the root element, found <{}>", dds.tag_name().name()),
+            ));
+        }
+        let mut config = Config {
+            types: Types::parse(dds)?,
+            ..Config::default()
+        };
+        for child in dds.children().filter(Node::is_element) {
+            match child.tag_name().name() {
+                "qos_library" => config.qos_libraries.push(QosLibrary::parse(child)?),
+                "domain_library" => config.domain_libraries.push(DomainLibrary::parse(child)?),
+                "domain_participant_library" | "participant_library" => config
+                    .participant_libraries
+                    .push(ParticipantLibrary::parse(child)?),
+                _ => {}
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Self::from_xml(&read_file(path.as_ref())?)
+    }
+
+    /// Parses and merges the sources of a URL as
+    /// `rticonnector::Connector::new` takes it: file paths and
+    /// `str://"<dds>...</dds>"` documents, separated by `;`.
+    pub fn from_url(url: &str) -> Result<Self, ConfigError> {
+        let mut builder = Config::builder();
+        let mut rest = url.trim_start();
+        while !rest.is_empty() {
+            let config = match rest.strip_prefix("str://\"") {
+                // Quotes are escaped inside inline documents.
+                Some(inline) => {
+                    let end = inline.find('"').unwrap_or(inline.len());
+                    rest = inline.get(end + 1..).unwrap_or_default();
+                    Config::from_xml(&inline[..end])?
+                }
+                None => {
+                    let end = rest.find(';').unwrap_or(rest.len());
+                    let path = rest[..end].trim();
+                    rest = &rest[end..];
+                    Config::from_file(path)?
+                }
+            };
+            builder = builder.config(config);
+            rest = rest.trim_start_matches(|c: char| c == ';' || c.is_whitespace());
+        }
+        Ok(builder.build())
+    }
+
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::new()
+    }
+
+    /// Renders the configuration as a Connector XML document.
+    pub fn to_xml(&self) -> String {
+        let mut sections = String::new();
+        for library in &self.qos_libraries {
+            sections.push_str(&library.to_xml());
+        }
+        if !self.types.is_empty() {
+            sections.push_str(&self.types.to_xml());
+        }
+        for library in &self.domain_libraries {
+            sections.push_str(&library.to_xml());
+        }
+        for library in &self.participant_libraries {
+            sections.push_str(&library.to_xml());
+        }
+        format!("<?xml version=\"1.0\"?>\n{}", element("dds", "", &sections))
+    }
+
+    /// Checks that [`to_xml`](Self::to_xml) renders every element of `xml`,
+    /// a document the configuration was parsed from or merged with. Fails
+    /// on the first element the model does not represent, such as an
+    /// `<application_library>` or a `<topic_qos>`, which rendering drops.
+    pub fn check_round_trip(&self, xml: &str) -> Result<(), ConfigError> {
+        let rendered = self.to_xml();
+        let rendered: HashSet<String> = parse_document(&rendered)?
+            .descendants()
+            .filter(Node::is_element)
+            .map(element_path)
+            .collect();
+        let source = parse_document(xml)?;
+        // Descendants come in document order, so the outermost element
+        // dropped is found before the ones it holds.
+        let dropped = source.descendants().find(|node| {
+            let content = node.attributes().next().is_some()
+                || node
+                    .children()
+                    .any(|c| c.is_element() || c.text().is_some_and(|t| !t.trim().is_empty()));
+            node.is_element() && content && !rendered.contains(&element_path(*node))
+        });
+        match dropped {
+            Some(node) => Err(invalid(
+                node,
+                format!("<{}> is not represented by the configuration model", node.tag_name().name()),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Looks up a QoS profile by its `"Library::Profile"` name.
+    pub fn qos_profile(&self, name: &str) -> Option<&QosProfile> {
+        let (library, profile) = name.split_once("::")?;
+        self.qos_libraries
+            .iter()
+            .find(|l| l.name == library)?
+            .profile(profile)
+    }
+
+    /// Looks up a domain by its `"Library::Domain"` name.
+    pub fn domain(&self, name: &str) -> Option<&Domain> {
+        let (library, domain) = name.split_once("::")?;
+        self.domain_libraries
+            .iter()
+            .find(|l| l.name == library)?
+            .domain(domain)
+    }
+
+    /// Looks up a participant by its `"Library::Participant"` name.
+    pub fn participant(&self, name: &str) -> Option<&Participant> {
+        let (library, participant) = name.split_once("::")?;
+        self.participant_libraries
+            .iter()
+            .find(|l| l.name == library)?
+            .participant(participant)
+    }
+
+    /// Looks up a participant by its `"Library::Participant"` name, to modify
+    /// it before the configuration is rendered.
+    pub fn participant_mut(&mut self, name: &str) -> Option<&mut Participant> {
+        let (library, participant) = name.split_once("::")?;
+        self.participant_libraries
+            .iter_mut()
+            .find(|l| l.name == library)?
+            .participants
+            .iter_mut()
+            .find(|p| p.name == participant)
+    }
+
+    /// The domain ID `participant` joins: its own `domain_id`, or the one of
+    /// its domain.
+    pub fn domain_id(&self, participant: &Participant) -> Option<u32> {
+        participant.domain_id.or_else(|| {
+            let domain = self.domain(participant.domain_ref.as_deref()?)?;
+            Some(domain.domain_id)
+        })
+    }
+
+    /// Every participant with its `"Library::Participant"` name, the name
+    /// `rticonnector::Connector::new` expects.
+    pub fn participants(&self) -> impl Iterator<Item = (String, &Participant)> {
+        self.participant_libraries.iter().flat_map(|library| {
+            library
+                .participants
+                .iter()
+                .map(move |participant| (format!("{}::{}", library.name, participant.name), participant))
+        })
+    }
+
+    /// The qualified name of the type carried by the topic `topic_ref`, as
+    /// seen from `participant`: its own topics and registered types first,
+    /// then the ones of its domain.
+    pub fn topic_type<'a>(
+        &'a self,
+        participant: &'a Participant,
+        topic_ref: &str,
+    ) -> Option<&'a str> {
+        let domain = participant.domain_ref.as_deref().and_then(|d| self.domain(d));
+        let topic = participant
+            .topics
+            .iter()
+            .chain(domain.into_iter().flat_map(|d| &d.topics))
+            .find(|t| t.name == topic_ref)?;
+        participant
+            .register_types
+            .iter()
+            .chain(domain.into_iter().flat_map(|d| &d.register_types))
+            .find(|r| r.name == topic.register_type_ref)
+            .map(|r| r.type_ref.as_str())
+    }
+
+    /// The topic type of the writer `"Publisher::Writer"` or reader
+    /// `"Subscriber::Reader"` of the participant `config_name`.
+    pub fn entity_type(&self, config_name: &str, entity_name: &str) -> Option<&Struct> {
+        let participant = self.participant(config_name)?;
+        let writers = participant.writers().map(|(name, w)| (name, &w.topic_ref));
+        let readers = participant.readers().map(|(name, r)| (name, &r.topic_ref));
+        let (_, topic_ref) = writers
+            .chain(readers)
+            .find(|(name, _)| name == entity_name)?;
+        self.types
+            .get_struct(self.topic_type(participant, topic_ref)?)
+    }
+
+    /// Checks the references between the sections of the configuration:
+    /// `domain_ref`, `topic_ref`, `register_type_ref`, `type_ref` and QoS
+    /// `base_name`s, the content filters of readers against their topic
+    /// types, and the uniqueness of member IDs. Returns every error, by line;
+    /// an empty list means the configuration is consistent.
+    ///
+    /// Names of RTI's built-in QoS profiles are checked against
+    /// [`BuiltinProfile`](policies::BuiltinProfile).
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+        let mut error = |line, message: String| errors.push(ConfigError::Invalid { line, message });
+
+        for library in &self.qos_libraries {
+            for profile in &library.profiles {
+                if let Some(base_name) = &profile.base_name {
+                    let qualified = if base_name.contains("::") {
+                        base_name.clone()
+                    } else {
+                        qualify(&library.name, base_name)
+                    };
+                    if !is_builtin_profile(base_name) && self.qos_profile(&qualified).is_none() {
+                        error(profile.line, format!("unknown QoS profile `{}`", base_name));
+                    }
+                }
+            }
+        }
+
+        for s in self.types.structs() {
+            let members = self.types.all_members(s);
+            let ids = self.types.member_ids(s);
+            // Inherited members are checked with their own struct.
+            let inherited = members.len() - s.members.len();
+            for (index, member) in members.iter().enumerate().skip(inherited) {
+                if ids[..index].contains(&ids[index]) {
+                    error(
+                        member.line,
+                        format!("member ID {} of `{}` is used twice", ids[index], s.name),
+                    );
+                }
+            }
+        }
+
+        for domain in self.domain_libraries.iter().flat_map(|l| &l.domains) {
+            for register_type in &domain.register_types {
+                if self.types.get(&register_type.type_ref).is_none() {
+                    error(
+                        register_type.line,
+                        format!("unknown type `{}`", register_type.type_ref),
+                    );
+                }
+            }
+            for topic in &domain.topics {
+                if domain.register_type(&topic.register_type_ref).is_none() {
+                    error(
+                        topic.line,
+                        format!(
+                            "`{}` is not a type registered in domain `{}`",
+                            topic.register_type_ref, domain.name
+                        ),
+                    );
+                }
+            }
+        }
+
+        for (_, participant) in self.participants() {
+            let domain = match &participant.domain_ref {
+                Some(domain_ref) => {
+                    let domain = self.domain(domain_ref);
+                    if domain.is_none() {
+                        error(participant.line, format!("unknown domain `{}`", domain_ref));
+                    }
+                    domain
+                }
+                None => None,
+            };
+            for register_type in &participant.register_types {
+                if self.types.get(&register_type.type_ref).is_none() {
+                    error(
+                        register_type.line,
+                        format!("unknown type `{}`", register_type.type_ref),
+                    );
+                }
+            }
+            let registered = |name: &str| {
+                participant.register_types.iter().any(|r| r.name == name)
+                    || domain.is_some_and(|d| d.register_type(name).is_some())
+            };
+            for topic in &participant.topics {
+                if !registered(&topic.register_type_ref) {
+                    error(
+                        topic.line,
+                        format!(
+                            "`{}` is not a type registered in participant `{}` or its domain",
+                            topic.register_type_ref, participant.name
+                        ),
+                    );
+                }
+            }
+            let has_topic = |name: &str| {
+                participant.topics.iter().any(|t| t.name == name)
+                    || domain.is_some_and(|d| d.topic(name).is_some())
+            };
+            let writers = participant.writers().map(|(_, w)| (&w.topic_ref, w.line));
+            let readers = participant.readers().map(|(_, r)| (&r.topic_ref, r.line));
+            for (topic_ref, line) in writers.chain(readers) {
+                if !has_topic(topic_ref) {
+                    error(
+                        line,
+                        format!(
+                            "unknown topic `{}` in participant `{}`",
+                            topic_ref, participant.name
+                        ),
+                    );
+                }
+            }
+
+            for (name, reader) in participant.readers() {
+                let filter = match &reader.content_filter {
+                    Some(filter) => filter,
+                    None => continue,
+                };
+                let topic_type = self
+                    .topic_type(participant, &reader.topic_ref)
+                    .and_then(|t| self.types.get_struct(t));
+                let checked = match topic_type {
+                    Some(topic_type) => filter.check_against(&self.types, topic_type),
+                    None => filter.check(),
+                };
+                if let Err(e) = checked {
+                    error(filter.line, format!("content filter of `{}`: {}", name, e));
+                }
+            }
+
+            let qos = participant
+                .qos
+                .iter()
+                .chain(participant.publishers.iter().flat_map(|p| &p.qos))
+                .chain(participant.subscribers.iter().flat_map(|s| &s.qos))
+                .chain(participant.writers().flat_map(|(_, w)| &w.qos))
+                .chain(participant.readers().flat_map(|(_, r)| &r.qos));
+            for EntityQos { base_name, line, .. } in qos {
+                if let Some(base_name) = base_name {
+                    if !is_builtin_profile(base_name) && self.qos_profile(base_name).is_none() {
+                        error(*line, format!("unknown QoS profile `{}`", base_name));
+                    }
+                }
+            }
+        }
+
+        errors.sort_by_key(|e| e.line());
+        errors
+    }
+}
+
+/// The `str://"..."` URL that passes `xml` to the Connector inline.
+///
+/// The document cannot contain `"` inside the URL, so attribute values are
+/// re-quoted with `'`, and quotes in text and CDATA sections are replaced by
+/// entities. A byte order mark and the XML declaration are dropped: the
+/// string is already decoded.
+pub fn inline_url(xml: &str) -> String {
+    let mut rest = xml.trim_start_matches('\u{feff}').trim_start();
+    let declaration = rest
+        .strip_prefix("<?xml")
+        .filter(|d| d.starts_with(char::is_whitespace));
+    if let Some(declaration) = declaration {
+        if let Some(end) = declaration.find("?>") {
+            rest = declaration[end + 2..].trim_start();
+        }
+    }
+    let mut url = String::with_capacity(rest.len() + 8);
+    url.push_str("str://\"");
+    while let Some(c) = rest.chars().next() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            // Quotes in comments are not significant.
+            let end = comment.find("-->").map_or(comment.len(), |end| end + 3);
+            url.push_str("<!--");
+            url.push_str(&comment[..end].replace('"', "'"));
+            rest = &comment[end..];
+        } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").map_or(cdata.len(), |end| end + 3);
+            url.push_str("<![CDATA[");
+            url.push_str(&cdata[..end].replace('"', "]]>&quot;<![CDATA["));
+            rest = &cdata[end..];
+        } else if c == '<' {
+            let mut delimiter = None;
+            let mut end = rest.len();
+            for (i, c) in rest.char_indices() {
+                match (delimiter, c) {
+                    (None, '>') => {
+                        url.push('>');
+                        end = i + 1;
+                        break;
+                    }
+                    (None, '"' | '\'') => {
+                        delimiter = Some(c);
+                        url.push('\'');
+                    }
+                    (Some(d), c) if c == d => {
+                        delimiter = None;
+                        url.push('\'');
+                    }
+                    (Some(_), '\'') => url.push_str("&apos;"),
+                    (Some(_), '"') => url.push_str("&quot;"),
+                    (_, c) => url.push(c),
+                }
+            }
+            rest = &rest[end..];
+        } else {
+            match c {
+                '"' => url.push_str("&quot;"),
+                c => url.push(c),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    url.push('"');
+    url
+}
+
+pub(crate) fn read_file(path: &Path) -> Result<String, ConfigError> {
+    std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+pub(crate) fn parse_document(xml: &str) -> Result<Document<'_>, ConfigError> {
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..roxmltree::ParsingOptions::default()
+    };
+    Ok(Document::parse_with_options(xml, options)?)
+}
+
+/// The XML between the start and end tags of `node`, from its first to its
+/// last child element, with the indentation of the document removed.
+pub(crate) fn inner_xml(node: Node) -> String {
+    let mut elements = node.children().filter(Node::is_element);
+    let (first, last) = match elements.next() {
+        Some(first) => (first, elements.next_back().unwrap_or(first)),
+        None => return String::new(),
+    };
+    let document = node.document();
+    let text = &document.input_text()[first.range().start..last.range().end];
+    let indent = document.text_pos_at(first.range().start).col as usize - 1;
+    let mut lines = text.lines();
+    let mut xml = lines.next().unwrap_or_default().to_string();
+    for line in lines {
+        let trimmed = line.trim_start();
+        let removed = (line.len() - trimmed.len()).min(indent);
+        xml.push('\n');
+        xml.push_str(&line[removed..]);
+    }
+    xml
+}
+
+/// Renders ` name="value"`, escaping the value.
+pub(crate) fn attribute(name: &str, value: &str) -> String {
+    format!(" {}=\"{}\"", name, escape(value))
+}
+
+/// Escapes `value` for use in text or an attribute value.
+pub(crate) fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Indents every non-empty line of `xml` by one level.
+pub(crate) fn indent(xml: &str) -> String {
+    let mut indented = String::with_capacity(xml.len());
+    for line in xml.lines() {
+        if !line.is_empty() {
+            indented.push_str("    ");
+        }
+        indented.push_str(line);
+        indented.push('\n');
+    }
+    indented
+}
+
+/// Renders an element whose children are already rendered, or an empty
+/// element when there are none.
+pub(crate) fn element(tag: &str, attributes: &str, children: &str) -> String {
+    if children.is_empty() {
+        format!("<{}{}/>\n", tag, attributes)
+    } else {
+        format!("<{}{}>\n{}</{}>\n", tag, attributes, indent(children), tag)
+    }
+}
+
+/// The tags and names of `node` and its ancestors, such as
+/// `dds/domain_library[Lib]/domain[Shapes]`, with the tags the parser
+/// accepts under two names spelled as they are rendered.
+fn element_path(node: Node) -> String {
+    let mut path: Vec<String> = node
+        .ancestors()
+        .filter(Node::is_element)
+        .map(|node| {
+            let tag = match node.tag_name().name() {
+                "participant_library" => "domain_participant_library",
+                "participant_qos" => "domain_participant_qos",
+                tag => tag,
+            };
+            match node.attribute("name") {
+                Some(name) => format!("{}[{}]", tag, name),
+                None => tag.to_string(),
+            }
+        })
+        .collect();
+    path.reverse();
+    path.join("/")
+}
+
+/// The line a node starts on.
+pub(crate) fn line(node: Node) -> u32 {
+    node.document().text_pos_at(node.range().start).row
+}
+
+pub(crate) fn invalid(node: Node, message: impl Into<String>) -> ConfigError {
+    ConfigError::Invalid {
+        line: line(node),
+        message: message.into(),
+    }
+}
+
+pub(crate) fn required_attribute<'a>(node: Node<'a, '_>, name: &str) -> Result<&'a str, ConfigError> {
+    node.attribute(name).ok_or_else(|| {
+        invalid(
+            node,
+            format!("<{}> is missing the `{}` attribute", node.tag_name().name(), name),
+        )
+    })
+}
+
+pub(crate) fn bool_attribute(node: Node, name: &str) -> Result<bool, ConfigError> {
+    match node.attribute(name) {
+        None => Ok(false),
+        Some(value) => match value.trim() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            other => Err(invalid(
+                node,
+                format!("`{}` must be `true` or `false`, found `{}`", name, other),
+            )),
+        },
+    }
+}
+
+/// Joins a name to the module scope it is declared in.
+pub(crate) fn qualify(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}::{}", scope, name)
+    }
+}
@@ -0,0 +1,409 @@
+//! The participant libraries of a configuration.
+//!
+//! Both spellings, `<domain_participant_library>` and `<participant_library>`,
+//! are accepted. The Connector names a participant `"Library::Participant"`
+//! and its readers and writers `"Subscriber::Reader"` /
+//! `"Publisher::Writer"`.
+
+use roxmltree::Node;
+
+use super::domains::{parse_domain_id, parse_registrations, registrations_xml, RegisterType, Topic};
+use super::filter::ContentFilter;
+use super::qos::EntityQos;
+use super::{attribute, element, escape, line, required_attribute, ConfigError};
+
+/// A `<domain_participant_library>` or `<participant_library>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticipantLibrary {
+    pub name: String,
+    pub participants: Vec<Participant>,
+    pub line: u32,
+}
+
+/// A `<domain_participant>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Participant {
+    pub name: String,
+    /// The `"Library::Domain"` the participant joins.
+    pub domain_ref: Option<String>,
+    /// The domain ID the participant joins, overriding the one of its domain.
+    pub domain_id: Option<u32>,
+    /// Types registered by the participant itself, in addition to the ones of
+    /// its domain.
+    pub register_types: Vec<RegisterType>,
+    /// Topics declared by the participant itself, in addition to the ones of
+    /// its domain.
+    pub topics: Vec<Topic>,
+    pub publishers: Vec<Publisher>,
+    pub subscribers: Vec<Subscriber>,
+    pub qos: Option<EntityQos>,
+    pub line: u32,
+}
+
+/// A `<publisher>` and its `<data_writer>`s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Publisher {
+    pub name: String,
+    pub writers: Vec<DataWriter>,
+    pub qos: Option<EntityQos>,
+    pub line: u32,
+}
+
+/// A `<subscriber>` and its `<data_reader>`s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscriber {
+    pub name: String,
+    pub readers: Vec<DataReader>,
+    pub qos: Option<EntityQos>,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataWriter {
+    pub name: String,
+    pub topic_ref: String,
+    pub qos: Option<EntityQos>,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataReader {
+    pub name: String,
+    pub topic_ref: String,
+    pub qos: Option<EntityQos>,
+    pub content_filter: Option<ContentFilter>,
+    pub line: u32,
+}
+
+impl ParticipantLibrary {
+    pub(crate) fn parse(node: Node) -> Result<Self, ConfigError> {
+        Ok(Self {
+            name: required_attribute(node, "name")?.to_string(),
+            participants: node
+                .children()
+                .filter(|n| n.has_tag_name("domain_participant"))
+                .map(Participant::parse)
+                .collect::<Result<_, _>>()?,
+            line: line(node),
+        })
+    }
+
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            participants: Vec::new(),
+            line: 0,
+        }
+    }
+
+    pub fn participant(&self, name: &str) -> Option<&Participant> {
+        self.participants.iter().find(|p| p.name == name)
+    }
+
+    /// Renders the library as a `<domain_participant_library>`.
+    pub fn to_xml(&self) -> String {
+        let participants: String = self.participants.iter().map(Participant::to_xml).collect();
+        element(
+            "domain_participant_library",
+            &attribute("name", &self.name),
+            &participants,
+        )
+    }
+}
+
+impl Participant {
+    fn parse(node: Node) -> Result<Self, ConfigError> {
+        let (register_types, topics) = parse_registrations(node)?;
+        let mut publishers = Vec::new();
+        let mut subscribers = Vec::new();
+        for child in node.children().filter(Node::is_element) {
+            match child.tag_name().name() {
+                "publisher" => publishers.push(Publisher {
+                    name: required_attribute(child, "name")?.to_string(),
+                    writers: child
+                        .children()
+                        .filter(|n| n.has_tag_name("data_writer"))
+                        .map(|writer| {
+                            Ok(DataWriter {
+                                name: required_attribute(writer, "name")?.to_string(),
+                                topic_ref: required_attribute(writer, "topic_ref")?.to_string(),
+                                qos: EntityQos::parse(writer, &["datawriter_qos"]),
+                                line: line(writer),
+                            })
+                        })
+                        .collect::<Result<_, ConfigError>>()?,
+                    qos: EntityQos::parse(child, &["publisher_qos"]),
+                    line: line(child),
+                }),
+                "subscriber" => subscribers.push(Subscriber {
+                    name: required_attribute(child, "name")?.to_string(),
+                    readers: child
+                        .children()
+                        .filter(|n| n.has_tag_name("data_reader"))
+                        .map(|reader| {
+                            Ok(DataReader {
+                                name: required_attribute(reader, "name")?.to_string(),
+                                topic_ref: required_attribute(reader, "topic_ref")?.to_string(),
+                                qos: EntityQos::parse(reader, &["datareader_qos"]),
+                                content_filter: ContentFilter::parse(reader)?,
+                                line: line(reader),
+                            })
+                        })
+                        .collect::<Result<_, ConfigError>>()?,
+                    qos: EntityQos::parse(child, &["subscriber_qos"]),
+                    line: line(child),
+                }),
+                _ => {}
+            }
+        }
+        Ok(Self {
+            name: required_attribute(node, "name")?.to_string(),
+            domain_ref: node.attribute("domain_ref").map(str::to_string),
+            domain_id: parse_domain_id(node)?,
+            register_types,
+            topics,
+            publishers,
+            subscribers,
+            qos: EntityQos::parse(node, &["domain_participant_qos", "participant_qos"]),
+            line: line(node),
+        })
+    }
+
+    /// A participant joining the domain `domain_ref` (`"Library::Domain"`).
+    pub fn new(name: &str, domain_ref: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            domain_ref: Some(domain_ref.to_string()),
+            domain_id: None,
+            register_types: Vec::new(),
+            topics: Vec::new(),
+            publishers: Vec::new(),
+            subscribers: Vec::new(),
+            qos: None,
+            line: 0,
+        }
+    }
+
+    pub fn with_qos(mut self, qos: EntityQos) -> Self {
+        self.qos = Some(qos);
+        self
+    }
+
+    /// Joins `domain_id` instead of the domain ID of the participant's domain.
+    pub fn with_domain_id(mut self, domain_id: u32) -> Self {
+        self.domain_id = Some(domain_id);
+        self
+    }
+
+    pub fn with_register_type(mut self, name: &str, type_ref: &str) -> Self {
+        self.register_types.push(RegisterType::new(name, type_ref));
+        self
+    }
+
+    pub fn with_topic(mut self, name: &str, register_type_ref: &str) -> Self {
+        self.topics.push(Topic::new(name, register_type_ref));
+        self
+    }
+
+    pub fn with_publisher(mut self, publisher: Publisher) -> Self {
+        self.publishers.push(publisher);
+        self
+    }
+
+    pub fn with_subscriber(mut self, subscriber: Subscriber) -> Self {
+        self.subscribers.push(subscriber);
+        self
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut attributes = attribute("name", &self.name);
+        if let Some(domain_ref) = &self.domain_ref {
+            attributes.push_str(&attribute("domain_ref", domain_ref));
+        }
+        if let Some(domain_id) = self.domain_id {
+            attributes.push_str(&attribute("domain_id", &domain_id.to_string()));
+        }
+        let mut children = String::new();
+        if let Some(qos) = &self.qos {
+            children.push_str(&qos.to_xml("domain_participant_qos"));
+        }
+        children.push_str(&registrations_xml(&self.register_types, &self.topics));
+        for publisher in &self.publishers {
+            children.push_str(&publisher.to_xml());
+        }
+        for subscriber in &self.subscribers {
+            children.push_str(&subscriber.to_xml());
+        }
+        element("domain_participant", &attributes, &children)
+    }
+
+    /// Sets the name the participant announces to the other participants
+    /// (the `participant_name` policy).
+    pub fn set_participant_name(&mut self, name: &str) -> Result<(), ConfigError> {
+        let policy = element("participant_name", "", &format!("<name>{}</name>\n", escape(name)));
+        self.qos
+            .get_or_insert_with(EntityQos::default)
+            .set_policy("participant_name", &policy)
+    }
+
+    /// Places every publisher and subscriber of the participant in
+    /// `partitions`, replacing the partitions their QoS sets.
+    pub fn set_partitions<S: AsRef<str>>(&mut self, partitions: &[S]) -> Result<(), ConfigError> {
+        let elements: String = partitions
+            .iter()
+            .map(|p| format!("<element>{}</element>\n", escape(p.as_ref())))
+            .collect();
+        let policy = element("partition", "", &element("name", "", &elements));
+        let qos = self
+            .publishers
+            .iter_mut()
+            .map(|p| &mut p.qos)
+            .chain(self.subscribers.iter_mut().map(|s| &mut s.qos));
+        for qos in qos {
+            qos.get_or_insert_with(EntityQos::default)
+                .set_policy("partition", &policy)?;
+        }
+        Ok(())
+    }
+
+    /// The participant's data writers and their `"Publisher::Writer"` names.
+    pub fn writers(&self) -> impl Iterator<Item = (String, &DataWriter)> {
+        self.publishers.iter().flat_map(|publisher| {
+            publisher
+                .writers
+                .iter()
+                .map(move |writer| (format!("{}::{}", publisher.name, writer.name), writer))
+        })
+    }
+
+    /// The participant's data readers and their `"Subscriber::Reader"` names.
+    pub fn readers(&self) -> impl Iterator<Item = (String, &DataReader)> {
+        self.subscribers.iter().flat_map(|subscriber| {
+            subscriber
+                .readers
+                .iter()
+                .map(move |reader| (format!("{}::{}", subscriber.name, reader.name), reader))
+        })
+    }
+}
+
+impl Publisher {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            writers: Vec::new(),
+            qos: None,
+            line: 0,
+        }
+    }
+
+    pub fn with_qos(mut self, qos: EntityQos) -> Self {
+        self.qos = Some(qos);
+        self
+    }
+
+    pub fn with_writer(mut self, writer: DataWriter) -> Self {
+        self.writers.push(writer);
+        self
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut children = String::new();
+        if let Some(qos) = &self.qos {
+            children.push_str(&qos.to_xml("publisher_qos"));
+        }
+        for writer in &self.writers {
+            children.push_str(&writer.to_xml());
+        }
+        element("publisher", &attribute("name", &self.name), &children)
+    }
+}
+
+impl Subscriber {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            readers: Vec::new(),
+            qos: None,
+            line: 0,
+        }
+    }
+
+    pub fn with_qos(mut self, qos: EntityQos) -> Self {
+        self.qos = Some(qos);
+        self
+    }
+
+    pub fn with_reader(mut self, reader: DataReader) -> Self {
+        self.readers.push(reader);
+        self
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut children = String::new();
+        if let Some(qos) = &self.qos {
+            children.push_str(&qos.to_xml("subscriber_qos"));
+        }
+        for reader in &self.readers {
+            children.push_str(&reader.to_xml());
+        }
+        element("subscriber", &attribute("name", &self.name), &children)
+    }
+}
+
+impl DataWriter {
+    pub fn new(name: &str, topic_ref: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            topic_ref: topic_ref.to_string(),
+            qos: None,
+            line: 0,
+        }
+    }
+
+    pub fn with_qos(mut self, qos: EntityQos) -> Self {
+        self.qos = Some(qos);
+        self
+    }
+
+    pub fn to_xml(&self) -> String {
+        let attributes = attribute("name", &self.name) + &attribute("topic_ref", &self.topic_ref);
+        let qos = self.qos.as_ref().map(|qos| qos.to_xml("datawriter_qos"));
+        element("data_writer", &attributes, qos.as_deref().unwrap_or_default())
+    }
+}
+
+impl DataReader {
+    pub fn new(name: &str, topic_ref: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            topic_ref: topic_ref.to_string(),
+            qos: None,
+            content_filter: None,
+            line: 0,
+        }
+    }
+
+    pub fn with_qos(mut self, qos: EntityQos) -> Self {
+        self.qos = Some(qos);
+        self
+    }
+
+    /// Only delivers the samples matching `filter` to the reader.
+    pub fn with_content_filter(mut self, filter: ContentFilter) -> Self {
+        self.content_filter = Some(filter);
+        self
+    }
+
+    pub fn to_xml(&self) -> String {
+        let attributes = attribute("name", &self.name) + &attribute("topic_ref", &self.topic_ref);
+        let mut children = String::new();
+        if let Some(qos) = &self.qos {
+            children.push_str(&qos.to_xml("datareader_qos"));
+        }
+        if let Some(filter) = &self.content_filter {
+            children.push_str(&filter.to_xml());
+        }
+        element("data_reader", &attributes, &children)
+    }
+}
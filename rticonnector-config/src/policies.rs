@@ -0,0 +1,424 @@
+//! Typed QoS policies, rendered as the content of a `<qos_profile>`.
+//!
+//! Only the policies most applications tune are modelled; everything else
+//! is inherited from the profile's `base_name`, typically one of RTI's
+//! [`BuiltinProfile`]s.
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use rticonnector_config::policies::{BuiltinProfile, History, QosPolicies, Reliability};
+//! use rticonnector_config::qos::QosProfile;
+//!
+//! let policies = QosPolicies::new()
+//!     .with_reliability(Reliability::Reliable)
+//!     .with_history(History::KeepLast(10))
+//!     .with_deadline(Duration::from_millis(500));
+//! assert!(policies.validate().is_empty());
+//! let profile = QosProfile::new("Telemetry")
+//!     .with_base_name(BuiltinProfile::KeepLastReliable.name())
+//!     .with_policies(&policies);
+//! println!("{}", profile.to_xml());
+//! ```
+
+use std::time::Duration;
+
+use thiserror::Error;
+
+use super::element;
+
+/// An inconsistent combination of policies.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum PolicyError {
+    #[error("KEEP_LAST history needs a depth of at least 1")]
+    ZeroDepth,
+    #[error("history depth {depth} exceeds max_samples_per_instance {max_samples_per_instance}")]
+    DepthExceedsLimit {
+        depth: u32,
+        max_samples_per_instance: u32,
+    },
+    #[error("max_samples {max_samples} is below max_samples_per_instance {max_samples_per_instance}")]
+    SamplesBelowPerInstance {
+        max_samples: u32,
+        max_samples_per_instance: u32,
+    },
+    #[error("KEEP_ALL history cannot keep more than {0} sample(s) per instance; use KEEP_LAST")]
+    KeepAllLimited(u32),
+    #[error("{0:?} durability needs RELIABLE reliability to deliver historical samples")]
+    DurabilityNeedsReliability(Durability),
+    #[error("the liveliness lease_duration must not be zero")]
+    ZeroLeaseDuration,
+    #[error("the deadline period must not be zero")]
+    ZeroDeadline,
+    #[error("transport_builtin enables no transport")]
+    NoTransport,
+}
+
+/// Profiles of the `BuiltinQosLibExp` library RTI ships with the Connector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinProfile {
+    StrictReliable,
+    KeepLastReliable,
+    BestEffort,
+    StrictReliableHighThroughput,
+    StrictReliableLowLatency,
+    PeriodicData,
+    Streaming,
+    ReliableStreaming,
+    Event,
+    AlarmEvent,
+    Status,
+    AlarmStatus,
+    LastValueCache,
+}
+
+impl BuiltinProfile {
+    pub const ALL: [BuiltinProfile; 13] = [
+        BuiltinProfile::StrictReliable,
+        BuiltinProfile::KeepLastReliable,
+        BuiltinProfile::BestEffort,
+        BuiltinProfile::StrictReliableHighThroughput,
+        BuiltinProfile::StrictReliableLowLatency,
+        BuiltinProfile::PeriodicData,
+        BuiltinProfile::Streaming,
+        BuiltinProfile::ReliableStreaming,
+        BuiltinProfile::Event,
+        BuiltinProfile::AlarmEvent,
+        BuiltinProfile::Status,
+        BuiltinProfile::AlarmStatus,
+        BuiltinProfile::LastValueCache,
+    ];
+
+    /// The profile named `name`, as returned by [`BuiltinProfile::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|profile| profile.name() == name)
+    }
+
+    /// The `"Library::Profile"` name to use as a `base_name`.
+    pub fn name(self) -> &'static str {
+        match self {
+            BuiltinProfile::StrictReliable => "BuiltinQosLibExp::Generic.StrictReliable",
+            BuiltinProfile::KeepLastReliable => "BuiltinQosLibExp::Generic.KeepLastReliable",
+            BuiltinProfile::BestEffort => "BuiltinQosLibExp::Generic.BestEffort",
+            BuiltinProfile::StrictReliableHighThroughput => {
+                "BuiltinQosLibExp::Generic.StrictReliable.HighThroughput"
+            }
+            BuiltinProfile::StrictReliableLowLatency => {
+                "BuiltinQosLibExp::Generic.StrictReliable.LowLatency"
+            }
+            BuiltinProfile::PeriodicData => "BuiltinQosLibExp::Pattern.PeriodicData",
+            BuiltinProfile::Streaming => "BuiltinQosLibExp::Pattern.Streaming",
+            BuiltinProfile::ReliableStreaming => "BuiltinQosLibExp::Pattern.ReliableStreaming",
+            BuiltinProfile::Event => "BuiltinQosLibExp::Pattern.Event",
+            BuiltinProfile::AlarmEvent => "BuiltinQosLibExp::Pattern.AlarmEvent",
+            BuiltinProfile::Status => "BuiltinQosLibExp::Pattern.Status",
+            BuiltinProfile::AlarmStatus => "BuiltinQosLibExp::Pattern.AlarmStatus",
+            BuiltinProfile::LastValueCache => "BuiltinQosLibExp::Pattern.LastValueCache",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reliability {
+    BestEffort,
+    Reliable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Durability {
+    Volatile,
+    TransientLocal,
+    Transient,
+    Persistent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum History {
+    /// Keeps the last `depth` samples of every instance.
+    KeepLast(u32),
+    /// Keeps every sample, within the resource limits.
+    KeepAll,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LivelinessKind {
+    Automatic,
+    ManualByParticipant,
+    ManualByTopic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Liveliness {
+    pub kind: LivelinessKind,
+    pub lease_duration: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ownership {
+    Shared,
+    /// Only the writer with the highest strength updates an instance;
+    /// `strength` applies to writers.
+    Exclusive { strength: i32 },
+}
+
+/// Resource limits of a writer or reader. Unset limits are inherited;
+/// limits above `i32::MAX`, such as [`ResourceLimits::UNLIMITED`], are
+/// unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub max_samples: Option<u32>,
+    pub max_instances: Option<u32>,
+    pub max_samples_per_instance: Option<u32>,
+}
+
+impl ResourceLimits {
+    /// A limit rendered as `LENGTH_UNLIMITED`.
+    pub const UNLIMITED: u32 = u32::MAX;
+}
+
+/// The builtin transports a participant enables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransportBuiltin {
+    pub udpv4: bool,
+    pub udpv6: bool,
+    pub shmem: bool,
+}
+
+/// The policies of a QoS profile. Unset policies are inherited.
+///
+/// Policies of writers and readers are rendered in both `<datawriter_qos>`
+/// and `<datareader_qos>`; the transports in `<domain_participant_qos>`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QosPolicies {
+    pub reliability: Option<Reliability>,
+    pub durability: Option<Durability>,
+    pub history: Option<History>,
+    /// Durations of `i32::MAX` seconds or more, such as [`Duration::MAX`],
+    /// are infinite.
+    pub deadline: Option<Duration>,
+    pub liveliness: Option<Liveliness>,
+    pub ownership: Option<Ownership>,
+    pub resource_limits: Option<ResourceLimits>,
+    pub transport_builtin: Option<TransportBuiltin>,
+}
+
+impl QosPolicies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_reliability(mut self, reliability: Reliability) -> Self {
+        self.reliability = Some(reliability);
+        self
+    }
+
+    pub fn with_durability(mut self, durability: Durability) -> Self {
+        self.durability = Some(durability);
+        self
+    }
+
+    pub fn with_history(mut self, history: History) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    pub fn with_deadline(mut self, period: Duration) -> Self {
+        self.deadline = Some(period);
+        self
+    }
+
+    pub fn with_liveliness(mut self, kind: LivelinessKind, lease_duration: Duration) -> Self {
+        self.liveliness = Some(Liveliness {
+            kind,
+            lease_duration,
+        });
+        self
+    }
+
+    pub fn with_ownership(mut self, ownership: Ownership) -> Self {
+        self.ownership = Some(ownership);
+        self
+    }
+
+    pub fn with_resource_limits(mut self, resource_limits: ResourceLimits) -> Self {
+        self.resource_limits = Some(resource_limits);
+        self
+    }
+
+    pub fn with_transport_builtin(mut self, transport_builtin: TransportBuiltin) -> Self {
+        self.transport_builtin = Some(transport_builtin);
+        self
+    }
+
+    /// Checks for combinations the middleware rejects or that defeat their
+    /// purpose. An empty list means none was found.
+    pub fn validate(&self) -> Vec<PolicyError> {
+        let mut errors = Vec::new();
+        let limits = self.resource_limits.unwrap_or_default();
+        match self.history {
+            Some(History::KeepLast(0)) => errors.push(PolicyError::ZeroDepth),
+            Some(History::KeepLast(depth)) => {
+                if let Some(max) = limits.max_samples_per_instance.filter(|&max| depth > max) {
+                    errors.push(PolicyError::DepthExceedsLimit {
+                        depth,
+                        max_samples_per_instance: max,
+                    });
+                }
+            }
+            Some(History::KeepAll) => {
+                let kept = match (limits.max_samples, limits.max_samples_per_instance) {
+                    (Some(samples), Some(per_instance)) => Some(samples.min(per_instance)),
+                    (samples, per_instance) => samples.or(per_instance),
+                };
+                if let Some(kept) = kept.filter(|&kept| kept <= 1) {
+                    errors.push(PolicyError::KeepAllLimited(kept));
+                }
+            }
+            None => {}
+        }
+        if let (Some(max_samples), Some(per_instance)) =
+            (limits.max_samples, limits.max_samples_per_instance)
+        {
+            if max_samples < per_instance {
+                errors.push(PolicyError::SamplesBelowPerInstance {
+                    max_samples,
+                    max_samples_per_instance: per_instance,
+                });
+            }
+        }
+        if let (Some(durability), Some(Reliability::BestEffort)) =
+            (self.durability, self.reliability)
+        {
+            if durability != Durability::Volatile {
+                errors.push(PolicyError::DurabilityNeedsReliability(durability));
+            }
+        }
+        if self.deadline == Some(Duration::ZERO) {
+            errors.push(PolicyError::ZeroDeadline);
+        }
+        if self.liveliness.is_some_and(|l| l.lease_duration.is_zero()) {
+            errors.push(PolicyError::ZeroLeaseDuration);
+        }
+        if self.transport_builtin.is_some_and(|t| !(t.udpv4 || t.udpv6 || t.shmem)) {
+            errors.push(PolicyError::NoTransport);
+        }
+        errors
+    }
+
+    /// Renders the policies as the content of a `<qos_profile>`.
+    pub fn to_xml(&self) -> String {
+        let endpoint = self.endpoint_xml();
+        let mut writer = endpoint.clone();
+        if let Some(Ownership::Exclusive { strength }) = self.ownership {
+            writer.push_str(&element("ownership_strength", "", &value("value", strength)));
+        }
+        let mut xml = String::new();
+        if !writer.is_empty() {
+            xml.push_str(&element("datawriter_qos", "", &writer));
+        }
+        if !endpoint.is_empty() {
+            xml.push_str(&element("datareader_qos", "", &endpoint));
+        }
+        if let Some(transport_builtin) = self.transport_builtin {
+            let transport = element("transport_builtin", "", &value("mask", transport_builtin.mask()));
+            xml.push_str(&element("domain_participant_qos", "", &transport));
+        }
+        xml
+    }
+
+    /// The policies shared by writers and readers.
+    fn endpoint_xml(&self) -> String {
+        let mut xml = String::new();
+        if let Some(reliability) = self.reliability {
+            let kind = match reliability {
+                Reliability::BestEffort => "BEST_EFFORT_RELIABILITY_QOS",
+                Reliability::Reliable => "RELIABLE_RELIABILITY_QOS",
+            };
+            xml.push_str(&element("reliability", "", &value("kind", kind)));
+        }
+        if let Some(durability) = self.durability {
+            let kind = match durability {
+                Durability::Volatile => "VOLATILE_DURABILITY_QOS",
+                Durability::TransientLocal => "TRANSIENT_LOCAL_DURABILITY_QOS",
+                Durability::Transient => "TRANSIENT_DURABILITY_QOS",
+                Durability::Persistent => "PERSISTENT_DURABILITY_QOS",
+            };
+            xml.push_str(&element("durability", "", &value("kind", kind)));
+        }
+        if let Some(history) = self.history {
+            let history = match history {
+                History::KeepLast(depth) => {
+                    value("kind", "KEEP_LAST_HISTORY_QOS") + &value("depth", depth)
+                }
+                History::KeepAll => value("kind", "KEEP_ALL_HISTORY_QOS"),
+            };
+            xml.push_str(&element("history", "", &history));
+        }
+        if let Some(period) = self.deadline {
+            xml.push_str(&element("deadline", "", &duration("period", period)));
+        }
+        if let Some(liveliness) = self.liveliness {
+            let kind = match liveliness.kind {
+                LivelinessKind::Automatic => "AUTOMATIC_LIVELINESS_QOS",
+                LivelinessKind::ManualByParticipant => "MANUAL_BY_PARTICIPANT_LIVELINESS_QOS",
+                LivelinessKind::ManualByTopic => "MANUAL_BY_TOPIC_LIVELINESS_QOS",
+            };
+            let liveliness =
+                value("kind", kind) + &duration("lease_duration", liveliness.lease_duration);
+            xml.push_str(&element("liveliness", "", &liveliness));
+        }
+        if let Some(ownership) = self.ownership {
+            let kind = match ownership {
+                Ownership::Shared => "SHARED_OWNERSHIP_QOS",
+                Ownership::Exclusive { .. } => "EXCLUSIVE_OWNERSHIP_QOS",
+            };
+            xml.push_str(&element("ownership", "", &value("kind", kind)));
+        }
+        if let Some(limits) = self.resource_limits {
+            let limit = |tag, limit: Option<u32>| match limit {
+                Some(limit) if limit > i32::MAX as u32 => value(tag, "LENGTH_UNLIMITED"),
+                Some(limit) => value(tag, limit),
+                None => String::new(),
+            };
+            let limits = limit("max_samples", limits.max_samples)
+                + &limit("max_instances", limits.max_instances)
+                + &limit("max_samples_per_instance", limits.max_samples_per_instance);
+            if !limits.is_empty() {
+                xml.push_str(&element("resource_limits", "", &limits));
+            }
+        }
+        xml
+    }
+}
+
+impl TransportBuiltin {
+    /// The `<mask>` of the policy, such as `UDPv4|SHMEM`.
+    pub fn mask(&self) -> String {
+        let transports: Vec<&str> = [
+            (self.udpv4, "UDPv4"),
+            (self.udpv6, "UDPv6"),
+            (self.shmem, "SHMEM"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, name)| enabled.then_some(name))
+        .collect();
+        if transports.is_empty() {
+            "MASK_NONE".to_string()
+        } else {
+            transports.join("|")
+        }
+    }
+}
+
+fn value(tag: &str, value: impl std::fmt::Display) -> String {
+    format!("<{}>{}</{}>\n", tag, value, tag)
+}
+
+fn duration(tag: &str, duration: Duration) -> String {
+    let children = if duration.as_secs() >= i32::MAX as u64 {
+        value("sec", "DURATION_INFINITE_SEC") + &value("nanosec", "DURATION_INFINITE_NSEC")
+    } else {
+        value("sec", duration.as_secs()) + &value("nanosec", duration.subsec_nanos())
+    };
+    element(tag, "", &children)
+}
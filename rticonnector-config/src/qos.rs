@@ -0,0 +1,217 @@
+//! The `<qos_library>` sections of a configuration, and the QoS set on
+//! individual entities.
+//!
+//! Policies are kept as the XML they were written in: the model records
+//! where QoS comes from (`base_name`, `is_default_qos`) rather than
+//! interpreting every policy.
+
+use roxmltree::Node;
+
+use super::policies::{BuiltinProfile, QosPolicies};
+use super::{
+    attribute, bool_attribute, element, inner_xml, line, parse_document, required_attribute,
+    ConfigError,
+};
+
+/// A `<qos_library>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QosLibrary {
+    pub name: String,
+    pub profiles: Vec<QosProfile>,
+    pub line: u32,
+}
+
+/// A `<qos_profile>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QosProfile {
+    pub name: String,
+    /// The `"Library::Profile"` this profile inherits from.
+    pub base_name: Option<String>,
+    pub is_default_qos: bool,
+    pub is_default_participant_factory_profile: bool,
+    /// The policies of the profile (`<datawriter_qos>`, ...), as written.
+    pub content: String,
+    pub line: u32,
+}
+
+/// The QoS element of an entity, such as `<datareader_qos>`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntityQos {
+    /// The `"Library::Profile"` the entity's QoS inherits from.
+    pub base_name: Option<String>,
+    /// The policies set on the entity, as written.
+    pub content: String,
+    pub line: u32,
+}
+
+impl QosLibrary {
+    pub(crate) fn parse(node: Node) -> Result<Self, ConfigError> {
+        Ok(Self {
+            name: required_attribute(node, "name")?.to_string(),
+            profiles: node
+                .children()
+                .filter(|n| n.has_tag_name("qos_profile"))
+                .map(QosProfile::parse)
+                .collect::<Result<_, _>>()?,
+            line: line(node),
+        })
+    }
+
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            profiles: Vec::new(),
+            line: 0,
+        }
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&QosProfile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    pub fn to_xml(&self) -> String {
+        let profiles: String = self.profiles.iter().map(QosProfile::to_xml).collect();
+        element("qos_library", &attribute("name", &self.name), &profiles)
+    }
+}
+
+impl QosProfile {
+    fn parse(node: Node) -> Result<Self, ConfigError> {
+        Ok(Self {
+            name: required_attribute(node, "name")?.to_string(),
+            base_name: node.attribute("base_name").map(str::to_string),
+            is_default_qos: bool_attribute(node, "is_default_qos")?,
+            is_default_participant_factory_profile: bool_attribute(
+                node,
+                "is_default_participant_factory_profile",
+            )?,
+            content: inner_xml(node),
+            line: line(node),
+        })
+    }
+
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            base_name: None,
+            is_default_qos: false,
+            is_default_participant_factory_profile: false,
+            content: String::new(),
+            line: 0,
+        }
+    }
+
+    pub fn with_base_name(mut self, base_name: &str) -> Self {
+        self.base_name = Some(base_name.to_string());
+        self
+    }
+
+    /// Makes this profile the default QoS of the entities created by the
+    /// Connector.
+    pub fn with_default_qos(mut self) -> Self {
+        self.is_default_qos = true;
+        self
+    }
+
+    /// Sets the policies of the profile, such as a `<datawriter_qos>` element.
+    pub fn with_content(mut self, content: &str) -> Self {
+        self.content = content.trim().to_string();
+        self
+    }
+
+    /// Sets the policies of the profile from typed [`QosPolicies`].
+    pub fn with_policies(self, policies: &QosPolicies) -> Self {
+        self.with_content(&policies.to_xml())
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut attributes = attribute("name", &self.name);
+        if let Some(base_name) = &self.base_name {
+            attributes.push_str(&attribute("base_name", base_name));
+        }
+        if self.is_default_qos {
+            attributes.push_str(&attribute("is_default_qos", "true"));
+        }
+        if self.is_default_participant_factory_profile {
+            attributes.push_str(&attribute("is_default_participant_factory_profile", "true"));
+        }
+        element("qos_profile", &attributes, &self.content)
+    }
+}
+
+impl EntityQos {
+    /// Parses the first child of `node` named one of `tags`.
+    pub(crate) fn parse(node: Node, tags: &[&str]) -> Option<Self> {
+        let qos = node
+            .children()
+            .find(|n| tags.contains(&n.tag_name().name()))?;
+        Some(Self {
+            base_name: qos.attribute("base_name").map(str::to_string),
+            content: inner_xml(qos),
+            line: line(qos),
+        })
+    }
+
+    /// QoS inherited from the profile `base_name` (`"Library::Profile"`).
+    pub fn from_profile(base_name: &str) -> Self {
+        Self {
+            base_name: Some(base_name.to_string()),
+            content: String::new(),
+            line: 0,
+        }
+    }
+
+    /// QoS set by the given policies.
+    pub fn from_content(content: &str) -> Self {
+        Self {
+            base_name: None,
+            content: content.trim().to_string(),
+            line: 0,
+        }
+    }
+
+    /// Sets the policy `tag`, such as `partition`, to the element `xml`,
+    /// replacing the one already set on the entity. Fails when the policies
+    /// already set are not well-formed, as the one to replace cannot be
+    /// found.
+    pub fn set_policy(&mut self, tag: &str, xml: &str) -> Result<(), ConfigError> {
+        const ROOT: &str = "<qos>";
+        let wrapped = format!("{}{}</qos>", ROOT, self.content);
+        let document = parse_document(&wrapped).map_err(|e| ConfigError::Invalid {
+            line: self.line,
+            message: format!("cannot set <{}> in the QoS: {}", tag, e),
+        })?;
+        let existing = document
+            .root_element()
+            .children()
+            .find(|n| n.has_tag_name(tag))
+            .map(|policy| policy.range());
+        let xml = xml.trim();
+        match existing {
+            Some(range) => self
+                .content
+                .replace_range(range.start - ROOT.len()..range.end - ROOT.len(), xml),
+            None if self.content.is_empty() => self.content = xml.to_string(),
+            None => {
+                self.content.push('\n');
+                self.content.push_str(xml);
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders the QoS as the element `tag`, such as `datareader_qos`.
+    pub fn to_xml(&self, tag: &str) -> String {
+        let attributes = match &self.base_name {
+            Some(base_name) => attribute("base_name", base_name),
+            None => String::new(),
+        };
+        element(tag, &attributes, &self.content)
+    }
+}
+
+/// Whether `name` is one of the [`BuiltinProfile`]s RTI ships with the
+/// library, such as `BuiltinQosLibExp::Generic.StrictReliable`.
+pub fn is_builtin_profile(name: &str) -> bool {
+    BuiltinProfile::from_name(name).is_some()
+}
@@ -0,0 +1,984 @@
+//! The `<types>` section of a configuration.
+//!
+//! Consts, structs, enums and typedefs may be nested in `<module>`s; every
+//! name in the model is fully qualified (`Geo::Position`), including the
+//! references between types, which are resolved against the enclosing modules
+//! while parsing.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use roxmltree::Node;
+
+use super::{
+    attribute, bool_attribute, element, invalid, line, parse_document, qualify, read_file,
+    required_attribute, ConfigError,
+};
+
+/// The type definitions of one or more `<types>` sections, in document order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Types {
+    definitions: Vec<TypeDefinition>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeDefinition {
+    Const(Const),
+    Struct(Struct),
+    Enum(Enum),
+    Typedef(Typedef),
+}
+
+impl TypeDefinition {
+    pub fn name(&self) -> &str {
+        match self {
+            TypeDefinition::Const(c) => &c.name,
+            TypeDefinition::Struct(s) => &s.name,
+            TypeDefinition::Enum(e) => &e.name,
+            TypeDefinition::Typedef(t) => &t.name,
+        }
+    }
+
+    /// The line the definition starts on; `0` for definitions built in Rust.
+    pub fn line(&self) -> u32 {
+        match self {
+            TypeDefinition::Const(c) => c.line,
+            TypeDefinition::Struct(s) => s.line,
+            TypeDefinition::Enum(e) => e.line,
+            TypeDefinition::Typedef(t) => t.line,
+        }
+    }
+}
+
+/// A `<const>`, usable as a bound.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Const {
+    pub name: String,
+    pub kind: TypeKind,
+    pub value: String,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Struct {
+    pub name: String,
+    pub base_type: Option<String>,
+    pub extensibility: Extensibility,
+    pub members: Vec<Member>,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enum {
+    pub name: String,
+    pub extensibility: Extensibility,
+    pub enumerators: Vec<Enumerator>,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enumerator {
+    pub name: String,
+    pub value: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Typedef {
+    pub name: String,
+    pub member_type: MemberType,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub name: String,
+    pub member_type: MemberType,
+    pub key: bool,
+    pub optional: bool,
+    /// The member ID given by the `id` attribute; see [`Types::member_ids`].
+    pub id: Option<u32>,
+    pub line: u32,
+}
+
+/// The type of a member or typedef, with its bounds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberType {
+    pub kind: TypeKind,
+    pub string_max_length: Option<Bound>,
+    /// Set when the member is a sequence of `kind`.
+    pub sequence_max_length: Option<Bound>,
+    /// Set when the member is an array of `kind` (or of a sequence of `kind`).
+    pub array_dimensions: Vec<Bound>,
+}
+
+impl MemberType {
+    pub fn is_sequence(&self) -> bool {
+        self.sequence_max_length.is_some()
+    }
+
+    pub fn is_array(&self) -> bool {
+        !self.array_dimensions.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeKind {
+    Boolean,
+    Char8,
+    Char16,
+    Octet,
+    Int8,
+    UInt8,
+    Short,
+    UnsignedShort,
+    Long,
+    UnsignedLong,
+    LongLong,
+    UnsignedLongLong,
+    Float,
+    Double,
+    LongDouble,
+    String,
+    WString,
+    /// A struct, enum or typedef, by qualified name.
+    NonBasic(std::string::String),
+}
+
+impl TypeKind {
+    fn parse(node: Node, type_name: &str) -> Result<Self, ConfigError> {
+        Ok(match type_name {
+            "boolean" => TypeKind::Boolean,
+            "char8" | "char" => TypeKind::Char8,
+            "char16" | "wchar" => TypeKind::Char16,
+            "octet" | "byte" => TypeKind::Octet,
+            "int8" => TypeKind::Int8,
+            "uint8" => TypeKind::UInt8,
+            "short" | "int16" => TypeKind::Short,
+            "unsignedShort" | "uint16" => TypeKind::UnsignedShort,
+            "long" | "int32" => TypeKind::Long,
+            "unsignedLong" | "uint32" => TypeKind::UnsignedLong,
+            "longLong" | "int64" => TypeKind::LongLong,
+            "unsignedLongLong" | "uint64" => TypeKind::UnsignedLongLong,
+            "float" | "float32" => TypeKind::Float,
+            "double" | "float64" => TypeKind::Double,
+            "longDouble" | "float128" => TypeKind::LongDouble,
+            "string" => TypeKind::String,
+            "wstring" => TypeKind::WString,
+            "nonBasic" => {
+                TypeKind::NonBasic(required_attribute(node, "nonBasicTypeName")?.to_string())
+            }
+            other => TypeKind::NonBasic(other.to_string()),
+        })
+    }
+
+    /// The name used for this kind in the XML `type` attribute.
+    pub fn xml_name(&self) -> &str {
+        match self {
+            TypeKind::Boolean => "boolean",
+            TypeKind::Char8 => "char8",
+            TypeKind::Char16 => "char16",
+            TypeKind::Octet => "octet",
+            TypeKind::Int8 => "int8",
+            TypeKind::UInt8 => "uint8",
+            TypeKind::Short => "short",
+            TypeKind::UnsignedShort => "unsignedShort",
+            TypeKind::Long => "long",
+            TypeKind::UnsignedLong => "unsignedLong",
+            TypeKind::LongLong => "longLong",
+            TypeKind::UnsignedLongLong => "unsignedLongLong",
+            TypeKind::Float => "float",
+            TypeKind::Double => "double",
+            TypeKind::LongDouble => "longDouble",
+            TypeKind::String => "string",
+            TypeKind::WString => "wstring",
+            TypeKind::NonBasic(name) => name,
+        }
+    }
+}
+
+/// A length bound: unbounded (`-1`), a number, or the name of a `<const>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Bound {
+    Unbounded,
+    Fixed(u32),
+    Const(String),
+}
+
+impl Bound {
+    fn parse(node: Node, value: &str) -> Result<Self, ConfigError> {
+        let value = value.trim();
+        if value == "-1" {
+            return Ok(Bound::Unbounded);
+        }
+        if let Ok(length) = value.parse() {
+            return Ok(Bound::Fixed(length));
+        }
+        if value.is_empty() || value.starts_with(|c: char| c.is_ascii_digit() || c == '-') {
+            return Err(invalid(node, format!("`{}` is not a valid bound", value)));
+        }
+        Ok(Bound::Const(value.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Extensibility {
+    Final,
+    /// `appendable`, also spelled `extensible`; the default.
+    #[default]
+    Appendable,
+    Mutable,
+}
+
+impl Extensibility {
+    fn parse(node: Node) -> Result<Self, ConfigError> {
+        match node.attribute("extensibility") {
+            None => Ok(Extensibility::default()),
+            Some("final") => Ok(Extensibility::Final),
+            Some("appendable") | Some("extensible") => Ok(Extensibility::Appendable),
+            Some("mutable") => Ok(Extensibility::Mutable),
+            Some(other) => Err(invalid(
+                node,
+                format!("unknown extensibility `{}`", other),
+            )),
+        }
+    }
+
+    pub fn xml_name(&self) -> &'static str {
+        match self {
+            Extensibility::Final => "final",
+            Extensibility::Appendable => "appendable",
+            Extensibility::Mutable => "mutable",
+        }
+    }
+}
+
+impl Types {
+    /// Parses the `<types>` sections of a configuration document.
+    pub fn from_xml(xml: &str) -> Result<Self, ConfigError> {
+        let document = parse_document(xml)?;
+        Self::parse(document.root_element())
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Self::from_xml(&read_file(path.as_ref())?)
+    }
+
+    /// Parses the `<types>` children of the `<dds>` element.
+    pub(crate) fn parse(dds: Node) -> Result<Self, ConfigError> {
+        let mut parser = Parser::default();
+        for types in dds.children().filter(|n| n.has_tag_name("types")) {
+            parser.parse_scope(types, "")?;
+        }
+        parser.resolve()
+    }
+
+    pub fn definitions(&self) -> &[TypeDefinition] {
+        &self.definitions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    /// Looks up a definition by qualified name; a leading `::` is ignored.
+    pub fn get(&self, name: &str) -> Option<&TypeDefinition> {
+        let name = name.trim_start_matches("::");
+        self.definitions.iter().find(|d| d.name() == name)
+    }
+
+    pub fn get_struct(&self, name: &str) -> Option<&Struct> {
+        match self.get(name) {
+            Some(TypeDefinition::Struct(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn get_enum(&self, name: &str) -> Option<&Enum> {
+        match self.get(name) {
+            Some(TypeDefinition::Enum(e)) => Some(e),
+            _ => None,
+        }
+    }
+
+    pub fn get_const(&self, name: &str) -> Option<&Const> {
+        match self.get(name) {
+            Some(TypeDefinition::Const(c)) => Some(c),
+            _ => None,
+        }
+    }
+
+    pub fn structs(&self) -> impl Iterator<Item = &Struct> {
+        self.definitions.iter().filter_map(|d| match d {
+            TypeDefinition::Struct(s) => Some(s),
+            _ => None,
+        })
+    }
+
+    /// The numeric value of a bound, or `None` if it is unbounded.
+    pub fn resolve_bound(&self, bound: &Bound) -> Option<u32> {
+        match bound {
+            Bound::Unbounded => None,
+            Bound::Fixed(length) => Some(*length),
+            Bound::Const(name) => self
+                .get_const(name)
+                .and_then(|c| c.value.trim().parse().ok()),
+        }
+    }
+
+    /// The members of a struct, inherited members first.
+    pub fn all_members<'a>(&'a self, s: &'a Struct) -> Vec<&'a Member> {
+        let mut members = match s.base_type.as_deref().and_then(|b| self.get_struct(b)) {
+            Some(base) => self.all_members(base),
+            None => Vec::new(),
+        };
+        members.extend(&s.members);
+        members
+    }
+
+    /// The type of the member `field` of `s`, named the way the Connector
+    /// names fields (`pos.x`, `points[1].y`), with its typedefs resolved.
+    /// Each index takes an element of an array dimension or a sequence.
+    pub fn field_type(&self, s: &Struct, field: &str) -> Option<MemberType> {
+        let mut s = s;
+        let mut segments = field.split('.').peekable();
+        while let Some(segment) = segments.next() {
+            let name = segment.split('[').next().unwrap_or_default();
+            let member = self.all_members(s).into_iter().find(|m| m.name == name)?;
+            let mut member_type = self.resolve_typedefs(&member.member_type);
+            for _ in 0..segment.matches('[').count() {
+                if !member_type.array_dimensions.is_empty() {
+                    member_type.array_dimensions.remove(0);
+                } else if member_type.sequence_max_length.take().is_none() {
+                    return None;
+                }
+                member_type = self.resolve_typedefs(&member_type);
+            }
+            if segments.peek().is_none() {
+                return Some(member_type);
+            }
+            match &member_type.kind {
+                TypeKind::NonBasic(name) if !member_type.is_array() && !member_type.is_sequence() => {
+                    s = self.get_struct(name)?;
+                }
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// The member IDs of [`Types::all_members`]: the `id` of a member when it
+    /// has one, otherwise the ID of the previous member plus one, starting
+    /// at 0.
+    pub fn member_ids(&self, s: &Struct) -> Vec<u32> {
+        let mut next = 0;
+        self.all_members(s)
+            .iter()
+            .map(|member| {
+                let id = member.id.unwrap_or(next);
+                next = id.wrapping_add(1);
+                id
+            })
+            .collect()
+    }
+
+    /// The key members of a struct, inherited keys first.
+    pub fn key_members<'a>(&'a self, s: &'a Struct) -> Vec<&'a Member> {
+        self.all_members(s).into_iter().filter(|m| m.key).collect()
+    }
+
+    /// `member_type` with the typedefs it refers to replaced by their
+    /// definitions, bounds and array dimensions included. A typedef that
+    /// cannot be flattened into one member type, such as a sequence of a
+    /// sequence typedef, is left as a reference.
+    pub fn resolve_typedefs(&self, member_type: &MemberType) -> MemberType {
+        let mut resolved = member_type.clone();
+        let mut seen = HashSet::new();
+        while let TypeKind::NonBasic(name) = &resolved.kind {
+            let Some(TypeDefinition::Typedef(t)) = self.get(name) else {
+                break;
+            };
+            let typedef = &t.member_type;
+            let nested = resolved.is_sequence() && (typedef.is_sequence() || typedef.is_array());
+            if nested || !seen.insert(t.name.as_str()) {
+                break;
+            }
+            resolved.kind = typedef.kind.clone();
+            resolved.string_max_length = typedef.string_max_length.clone();
+            if typedef.is_sequence() {
+                resolved.sequence_max_length = typedef.sequence_max_length.clone();
+            }
+            resolved
+                .array_dimensions
+                .extend(typedef.array_dimensions.iter().cloned());
+        }
+        resolved
+    }
+
+    /// The definition `name` and every definition it refers to, directly or
+    /// through other types: base types, member types and bound consts. The
+    /// definitions keep their document order; unknown names are left out.
+    pub fn subset(&self, name: &str) -> Types {
+        let mut needed = HashSet::new();
+        let mut pending = vec![name.trim_start_matches("::").to_string()];
+        while let Some(name) = pending.pop() {
+            let Some(definition) = self.get(&name) else {
+                continue;
+            };
+            if !needed.insert(definition.name()) {
+                continue;
+            }
+            let member_types: Vec<&MemberType> = match definition {
+                TypeDefinition::Const(_) | TypeDefinition::Enum(_) => Vec::new(),
+                TypeDefinition::Struct(s) => {
+                    pending.extend(s.base_type.clone());
+                    s.members.iter().map(|m| &m.member_type).collect()
+                }
+                TypeDefinition::Typedef(t) => vec![&t.member_type],
+            };
+            for member_type in member_types {
+                if let TypeKind::NonBasic(name) = &member_type.kind {
+                    pending.push(name.clone());
+                }
+                let bounds = member_type
+                    .string_max_length
+                    .iter()
+                    .chain(&member_type.sequence_max_length)
+                    .chain(&member_type.array_dimensions);
+                for bound in bounds {
+                    if let Bound::Const(name) = bound {
+                        pending.push(name.clone());
+                    }
+                }
+            }
+        }
+        Types {
+            definitions: self
+                .definitions
+                .iter()
+                .filter(|d| needed.contains(d.name()))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+impl Types {
+    /// Appends a definition; its name and references must be fully qualified.
+    pub fn push(&mut self, definition: impl Into<TypeDefinition>) {
+        self.definitions.push(definition.into());
+    }
+
+    /// Appends the definitions of `other`.
+    pub fn extend(&mut self, other: Types) {
+        self.definitions.extend(other.definitions);
+    }
+
+    /// Renders the `<types>` section, with a `<module>` for every qualified
+    /// name.
+    pub fn to_xml(&self) -> String {
+        let mut root = ModuleXml::default();
+        for definition in &self.definitions {
+            root.insert(definition);
+        }
+        element("types", "", &root.to_xml())
+    }
+}
+
+/// Definitions grouped by module, in document order.
+#[derive(Default)]
+struct ModuleXml<'a> {
+    name: &'a str,
+    items: Vec<ModuleItem<'a>>,
+}
+
+enum ModuleItem<'a> {
+    Definition(&'a TypeDefinition),
+    Module(ModuleXml<'a>),
+}
+
+impl<'a> ModuleXml<'a> {
+    fn insert(&mut self, definition: &'a TypeDefinition) {
+        let mut module = self;
+        let mut segments: Vec<&str> = definition.name().split("::").collect();
+        segments.pop();
+        for segment in segments {
+            // Reuse the module only if it is the last item, to keep the order.
+            if !matches!(module.items.last(), Some(ModuleItem::Module(m)) if m.name == segment) {
+                module.items.push(ModuleItem::Module(ModuleXml {
+                    name: segment,
+                    items: Vec::new(),
+                }));
+            }
+            match module.items.last_mut() {
+                Some(ModuleItem::Module(m)) => module = m,
+                _ => unreachable!(),
+            }
+        }
+        module.items.push(ModuleItem::Definition(definition));
+    }
+
+    fn to_xml(&self) -> String {
+        let mut xml = String::new();
+        for item in &self.items {
+            xml.push_str(&match item {
+                ModuleItem::Definition(definition) => definition.to_xml(),
+                ModuleItem::Module(module) => {
+                    element("module", &attribute("name", module.name), &module.to_xml())
+                }
+            });
+        }
+        xml
+    }
+}
+
+impl TypeDefinition {
+    /// Renders the definition under its local name.
+    pub fn to_xml(&self) -> String {
+        let name = self.name().rsplit("::").next().unwrap_or_default();
+        match self {
+            TypeDefinition::Const(c) => format!(
+                "<const{}{}{}/>\n",
+                attribute("name", name),
+                attribute("type", c.kind.xml_name()),
+                attribute("value", &c.value)
+            ),
+            TypeDefinition::Struct(s) => {
+                let mut attributes = attribute("name", name);
+                if let Some(base_type) = &s.base_type {
+                    attributes.push_str(&attribute("baseType", base_type));
+                }
+                attributes.push_str(&attribute("extensibility", s.extensibility.xml_name()));
+                let members: String = s.members.iter().map(Member::to_xml).collect();
+                element("struct", &attributes, &members)
+            }
+            TypeDefinition::Enum(e) => {
+                let enumerators: String = e
+                    .enumerators
+                    .iter()
+                    .map(|enumerator| {
+                        format!(
+                            "<enumerator{}{}/>\n",
+                            attribute("name", &enumerator.name),
+                            attribute("value", &enumerator.value.to_string())
+                        )
+                    })
+                    .collect();
+                let attributes = attribute("name", name)
+                    + &attribute("extensibility", e.extensibility.xml_name());
+                element("enum", &attributes, &enumerators)
+            }
+            TypeDefinition::Typedef(t) => format!(
+                "<typedef{}{}/>\n",
+                attribute("name", name),
+                t.member_type.attributes()
+            ),
+        }
+    }
+}
+
+impl Member {
+    pub fn new(name: &str, member_type: MemberType) -> Self {
+        Self {
+            name: name.to_string(),
+            member_type,
+            key: false,
+            optional: false,
+            id: None,
+            line: 0,
+        }
+    }
+
+    pub fn with_key(mut self) -> Self {
+        self.key = true;
+        self
+    }
+
+    pub fn with_optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    pub fn with_id(mut self, id: u32) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut attributes = attribute("name", &self.name) + &self.member_type.attributes();
+        if self.key {
+            attributes.push_str(&attribute("key", "true"));
+        }
+        if self.optional {
+            attributes.push_str(&attribute("optional", "true"));
+        }
+        if let Some(id) = self.id {
+            attributes.push_str(&attribute("id", &id.to_string()));
+        }
+        format!("<member{}/>\n", attributes)
+    }
+}
+
+impl MemberType {
+    pub fn new(kind: TypeKind) -> Self {
+        Self {
+            kind,
+            string_max_length: None,
+            sequence_max_length: None,
+            array_dimensions: Vec::new(),
+        }
+    }
+
+    pub fn with_string_max_length(mut self, bound: impl Into<Bound>) -> Self {
+        self.string_max_length = Some(bound.into());
+        self
+    }
+
+    /// Makes this a sequence of the type.
+    pub fn with_sequence_max_length(mut self, bound: impl Into<Bound>) -> Self {
+        self.sequence_max_length = Some(bound.into());
+        self
+    }
+
+    /// Makes this an array of the type.
+    pub fn with_array_dimensions(mut self, dimensions: Vec<Bound>) -> Self {
+        self.array_dimensions = dimensions;
+        self
+    }
+
+    /// The `type`, `nonBasicTypeName` and bound attributes.
+    fn attributes(&self) -> String {
+        let mut attributes = match &self.kind {
+            TypeKind::NonBasic(name) => {
+                attribute("type", "nonBasic") + &attribute("nonBasicTypeName", name)
+            }
+            kind => attribute("type", kind.xml_name()),
+        };
+        if let Some(bound) = &self.string_max_length {
+            attributes.push_str(&attribute("stringMaxLength", &bound.to_string()));
+        }
+        if let Some(bound) = &self.sequence_max_length {
+            attributes.push_str(&attribute("sequenceMaxLength", &bound.to_string()));
+        }
+        if !self.array_dimensions.is_empty() {
+            let dimensions: Vec<String> =
+                self.array_dimensions.iter().map(Bound::to_string).collect();
+            attributes.push_str(&attribute("arrayDimensions", &dimensions.join(",")));
+        }
+        attributes
+    }
+}
+
+impl From<u32> for Bound {
+    fn from(length: u32) -> Self {
+        Bound::Fixed(length)
+    }
+}
+
+impl std::fmt::Display for Bound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Bound::Unbounded => f.write_str("-1"),
+            Bound::Fixed(length) => write!(f, "{}", length),
+            Bound::Const(name) => f.write_str(name),
+        }
+    }
+}
+
+impl Const {
+    pub fn new(name: &str, kind: TypeKind, value: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            value: value.to_string(),
+            line: 0,
+        }
+    }
+}
+
+impl Struct {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            base_type: None,
+            extensibility: Extensibility::default(),
+            members: Vec::new(),
+            line: 0,
+        }
+    }
+
+    pub fn with_base_type(mut self, base_type: &str) -> Self {
+        self.base_type = Some(base_type.to_string());
+        self
+    }
+
+    pub fn with_extensibility(mut self, extensibility: Extensibility) -> Self {
+        self.extensibility = extensibility;
+        self
+    }
+
+    pub fn with_member(mut self, member: Member) -> Self {
+        self.members.push(member);
+        self
+    }
+}
+
+impl Enum {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            extensibility: Extensibility::default(),
+            enumerators: Vec::new(),
+            line: 0,
+        }
+    }
+
+    pub fn with_extensibility(mut self, extensibility: Extensibility) -> Self {
+        self.extensibility = extensibility;
+        self
+    }
+
+    pub fn with_enumerator(mut self, name: &str, value: i32) -> Self {
+        self.enumerators.push(Enumerator {
+            name: name.to_string(),
+            value,
+        });
+        self
+    }
+}
+
+impl Typedef {
+    pub fn new(name: &str, member_type: MemberType) -> Self {
+        Self {
+            name: name.to_string(),
+            member_type,
+            line: 0,
+        }
+    }
+}
+
+impl From<Const> for TypeDefinition {
+    fn from(c: Const) -> Self {
+        TypeDefinition::Const(c)
+    }
+}
+
+impl From<Struct> for TypeDefinition {
+    fn from(s: Struct) -> Self {
+        TypeDefinition::Struct(s)
+    }
+}
+
+impl From<Enum> for TypeDefinition {
+    fn from(e: Enum) -> Self {
+        TypeDefinition::Enum(e)
+    }
+}
+
+impl From<Typedef> for TypeDefinition {
+    fn from(t: Typedef) -> Self {
+        TypeDefinition::Typedef(t)
+    }
+}
+
+/// Collects definitions along with the module scope they were declared in,
+/// so references can be resolved once every name is known.
+#[derive(Default)]
+struct Parser {
+    definitions: Vec<(TypeDefinition, String)>,
+}
+
+impl Parser {
+    fn parse_scope(&mut self, node: Node, scope: &str) -> Result<(), ConfigError> {
+        for child in node.children().filter(Node::is_element) {
+            let name = || required_attribute(child, "name").map(|name| qualify(scope, name));
+            let definition = match child.tag_name().name() {
+                "module" => {
+                    self.parse_scope(child, &name()?)?;
+                    continue;
+                }
+                "const" => TypeDefinition::Const(Const {
+                    name: name()?,
+                    kind: TypeKind::parse(child, required_attribute(child, "type")?)?,
+                    value: required_attribute(child, "value")?.to_string(),
+                    line: line(child),
+                }),
+                "struct" => TypeDefinition::Struct(Struct {
+                    name: name()?,
+                    base_type: child.attribute("baseType").map(str::to_string),
+                    extensibility: Extensibility::parse(child)?,
+                    members: child
+                        .children()
+                        .filter(Node::is_element)
+                        .map(parse_member)
+                        .collect::<Result<_, _>>()?,
+                    line: line(child),
+                }),
+                "enum" => TypeDefinition::Enum(parse_enum(child, name()?)?),
+                "typedef" => TypeDefinition::Typedef(Typedef {
+                    name: name()?,
+                    member_type: parse_member_type(child)?,
+                    line: line(child),
+                }),
+                other => {
+                    return Err(ConfigError::Unsupported {
+                        line: line(child),
+                        element: other.to_string(),
+                    })
+                }
+            };
+            self.definitions.push((definition, scope.to_string()));
+        }
+        Ok(())
+    }
+
+    fn resolve(self) -> Result<Types, ConfigError> {
+        let names: HashSet<String> = self
+            .definitions
+            .iter()
+            .map(|(d, _)| d.name().to_string())
+            .collect();
+        let resolve = |name: &mut String, scope: &str, line: u32| -> Result<(), ConfigError> {
+            match lookup(&names, name, scope) {
+                Some(qualified) => {
+                    *name = qualified;
+                    Ok(())
+                }
+                None => Err(ConfigError::Invalid {
+                    line,
+                    message: format!("unknown type or const `{}`", name),
+                }),
+            }
+        };
+        let resolve_member_type =
+            |member_type: &mut MemberType, scope: &str, line: u32| -> Result<(), ConfigError> {
+                if let TypeKind::NonBasic(name) = &mut member_type.kind {
+                    resolve(name, scope, line)?;
+                }
+                let bounds = member_type
+                    .string_max_length
+                    .iter_mut()
+                    .chain(member_type.sequence_max_length.iter_mut())
+                    .chain(member_type.array_dimensions.iter_mut());
+                for bound in bounds {
+                    if let Bound::Const(name) = bound {
+                        resolve(name, scope, line)?;
+                    }
+                }
+                Ok(())
+            };
+
+        let mut definitions = Vec::with_capacity(self.definitions.len());
+        for (mut definition, scope) in self.definitions {
+            match &mut definition {
+                TypeDefinition::Struct(s) => {
+                    if let Some(base_type) = &mut s.base_type {
+                        resolve(base_type, &scope, s.line)?;
+                    }
+                    for member in &mut s.members {
+                        resolve_member_type(&mut member.member_type, &scope, member.line)?;
+                    }
+                }
+                TypeDefinition::Typedef(t) => resolve_member_type(&mut t.member_type, &scope, t.line)?,
+                TypeDefinition::Const(_) | TypeDefinition::Enum(_) => {}
+            }
+            definitions.push(definition);
+        }
+        Ok(Types { definitions })
+    }
+}
+
+/// Finds `name` from the innermost enclosing module outwards.
+fn lookup(names: &HashSet<String>, name: &str, scope: &str) -> Option<String> {
+    if let Some(absolute) = name.strip_prefix("::") {
+        return names.contains(absolute).then(|| absolute.to_string());
+    }
+    let mut scope = scope;
+    loop {
+        let candidate = qualify(scope, name);
+        if names.contains(&candidate) {
+            return Some(candidate);
+        }
+        if scope.is_empty() {
+            return None;
+        }
+        scope = scope.rsplit_once("::").map_or("", |(parent, _)| parent);
+    }
+}
+
+fn parse_member(node: Node) -> Result<Member, ConfigError> {
+    if !node.has_tag_name("member") {
+        return Err(invalid(
+            node,
+            format!("unexpected <{}> in <struct>", node.tag_name().name()),
+        ));
+    }
+    Ok(Member {
+        name: required_attribute(node, "name")?.to_string(),
+        member_type: parse_member_type(node)?,
+        key: bool_attribute(node, "key")?,
+        optional: bool_attribute(node, "optional")?,
+        id: node
+            .attribute("id")
+            .map(|id| {
+                id.trim()
+                    .parse()
+                    .map_err(|_| invalid(node, format!("`{}` is not a valid member ID", id)))
+            })
+            .transpose()?,
+        line: line(node),
+    })
+}
+
+fn parse_member_type(node: Node) -> Result<MemberType, ConfigError> {
+    let bound = |name| node.attribute(name).map(|value| Bound::parse(node, value)).transpose();
+    let array_dimensions = match node.attribute("arrayDimensions") {
+        Some(dimensions) => dimensions
+            .split(',')
+            .map(|dimension| Bound::parse(node, dimension))
+            .collect::<Result<_, _>>()?,
+        None => Vec::new(),
+    };
+    Ok(MemberType {
+        kind: TypeKind::parse(node, required_attribute(node, "type")?)?,
+        string_max_length: bound("stringMaxLength")?,
+        sequence_max_length: bound("sequenceMaxLength")?,
+        array_dimensions,
+    })
+}
+
+fn parse_enum(node: Node, name: String) -> Result<Enum, ConfigError> {
+    let mut enumerators = Vec::new();
+    // `None` after an enumerator of value `i32::MAX`, which only an explicit
+    // value may follow.
+    let mut next_value = Some(0);
+    for child in node.children().filter(Node::is_element) {
+        if !child.has_tag_name("enumerator") {
+            return Err(invalid(
+                child,
+                format!("unexpected <{}> in <enum>", child.tag_name().name()),
+            ));
+        }
+        let value = match child.attribute("value") {
+            Some(value) => value.trim().parse().map_err(|_| {
+                invalid(child, format!("`{}` is not a valid enumerator value", value))
+            })?,
+            None => next_value.ok_or_else(|| {
+                invalid(
+                    child,
+                    "the enumerator follows one of value 2147483647 and needs an explicit value",
+                )
+            })?,
+        };
+        enumerators.push(Enumerator {
+            name: required_attribute(child, "name")?.to_string(),
+            value,
+        });
+        next_value = value.checked_add(1);
+    }
+    Ok(Enum {
+        name,
+        extensibility: Extensibility::parse(node)?,
+        enumerators,
+        line: line(node),
+    })
+}
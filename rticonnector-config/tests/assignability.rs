@@ -0,0 +1,238 @@
+//! Assignability of a reader type from a writer type that evolved from it.
+
+use rticonnector_config::assignability::{check, Report};
+use rticonnector_config::types::Types;
+
+const OLD: &str = r#"
+<dds>
+    <types>
+        <enum name="Fill">
+            <enumerator name="SOLID" value="0"/>
+            <enumerator name="HATCH" value="1"/>
+        </enum>
+        <struct name="Shape">
+            <member name="color" type="string" stringMaxLength="16" key="true"/>
+            <member name="x" type="long"/>
+            <member name="fill" type="nonBasic" nonBasicTypeName="Fill"/>
+        </struct>
+        <struct name="Point" extensibility="final">
+            <member name="x" type="long"/>
+            <member name="y" type="long"/>
+        </struct>
+        <struct name="Status" extensibility="mutable">
+            <member name="id" type="unsignedLong" key="true"/>
+            <member name="label" type="string" stringMaxLength="32"/>
+            <member name="level" type="short"/>
+        </struct>
+    </types>
+</dds>
+"#;
+
+fn check_change(type_name: &str, old: &str, new: &str) -> Report {
+    let changed = OLD.replace(old, new);
+    assert!(
+        old == new || changed != OLD,
+        "`{}` is not in the types",
+        old
+    );
+    let reader = Types::from_xml(OLD).unwrap();
+    let writer = Types::from_xml(&changed).unwrap();
+    check(&reader, type_name, &writer, type_name)
+}
+
+fn messages(report: &Report) -> Vec<String> {
+    report
+        .incompatibilities
+        .iter()
+        .map(|change| change.to_string())
+        .collect()
+}
+
+#[test]
+fn identical_types() {
+    for type_name in ["Shape", "Point", "Status"] {
+        let report = check_change(type_name, "", "");
+        assert!(report.is_compatible(), "{}", report);
+        assert!(report.notes.is_empty(), "{}", report);
+    }
+}
+
+#[test]
+fn appended_member() {
+    let report = check_change(
+        "Shape",
+        r#"<member name="fill" type="nonBasic" nonBasicTypeName="Fill"/>"#,
+        r#"<member name="fill" type="nonBasic" nonBasicTypeName="Fill"/>
+            <member name="angle" type="float"/>"#,
+    );
+    assert!(report.is_compatible(), "{}", report);
+    assert_eq!(report.notes.len(), 1);
+    assert_eq!(report.notes[0].path, "angle");
+}
+
+#[test]
+fn inserted_member() {
+    let report = check_change(
+        "Shape",
+        r#"<member name="x" type="long"/>"#,
+        r#"<member name="y" type="long"/>
+            <member name="x" type="long"/>"#,
+    );
+    assert_eq!(
+        messages(&report),
+        [
+            "x: `x` in the reader, `y` in the writer at the same position",
+            "fill: `fill` in the reader, `x` in the writer at the same position",
+        ]
+    );
+}
+
+#[test]
+fn final_struct_members() {
+    let report = check_change(
+        "Point",
+        r#"<member name="y" type="long"/>"#,
+        r#"<member name="y" type="long"/>
+            <member name="z" type="long"/>"#,
+    );
+    assert_eq!(
+        messages(&report),
+        ["2 members in the reader, 3 in the writer, which a final struct cannot change"]
+    );
+}
+
+#[test]
+fn mutable_struct_members() {
+    let report = check_change(
+        "Status",
+        r#"<member name="level" type="short"/>"#,
+        r#"<member name="level" type="short"/>
+            <member name="note" type="string" optional="true"/>"#,
+    );
+    assert!(report.is_compatible(), "{}", report);
+
+    let report = check_change(
+        "Status",
+        r#"<member name="label" type="string" stringMaxLength="32"/>"#,
+        "",
+    );
+    assert_eq!(
+        messages(&report),
+        ["level: member ID 2 in the reader, 1 in the writer"]
+    );
+}
+
+#[test]
+fn explicit_member_ids() {
+    // The removed member keeps the IDs of the next ones.
+    let report = check_change(
+        "Status",
+        r#"<member name="label" type="string" stringMaxLength="32"/>
+            <member name="level" type="short"/>"#,
+        r#"<member name="level" type="short" id="2"/>"#,
+    );
+    assert!(report.is_compatible(), "{}", report);
+
+    let report = check_change(
+        "Status",
+        r#"<member name="level" type="short"/>"#,
+        r#"<member name="level" type="short" id="7"/>"#,
+    );
+    assert_eq!(
+        messages(&report),
+        ["level: member ID 2 in the reader, 7 in the writer"]
+    );
+}
+
+#[test]
+fn extensibility() {
+    let report = check_change(
+        "Point",
+        r#"<struct name="Point" extensibility="final">"#,
+        r#"<struct name="Point" extensibility="appendable">"#,
+    );
+    assert_eq!(
+        messages(&report),
+        ["final in the reader, appendable in the writer"]
+    );
+}
+
+#[test]
+fn widened_bound() {
+    let report = check_change(
+        "Status",
+        r#"stringMaxLength="32""#,
+        r#"stringMaxLength="64""#,
+    );
+    assert!(report.is_compatible(), "{}", report);
+    assert_eq!(
+        report.notes[0].to_string(),
+        "label: string bound 32 in the reader, 64 in the writer: the reader drops the longer samples"
+    );
+
+    let report = check_change(
+        "Shape",
+        r#"stringMaxLength="16""#,
+        r#"stringMaxLength="128""#,
+    );
+    assert_eq!(
+        messages(&report),
+        ["color: string bound 16 in the reader, 128 in the writer, on a key member"]
+    );
+}
+
+#[test]
+fn member_kind() {
+    let report = check_change(
+        "Status",
+        r#"<member name="level" type="short"/>"#,
+        r#"<member name="level" type="long"/>"#,
+    );
+    assert_eq!(
+        messages(&report),
+        ["level: short in the reader, long in the writer"]
+    );
+}
+
+#[test]
+fn enum_changes() {
+    let report = check_change(
+        "Shape",
+        r#"<enumerator name="HATCH" value="1"/>"#,
+        r#"<enumerator name="HATCH" value="1"/>
+            <enumerator name="DOTS" value="2"/>"#,
+    );
+    assert!(report.is_compatible(), "{}", report);
+    assert_eq!(report.notes[0].path, "fill::DOTS");
+
+    let report = check_change(
+        "Shape",
+        r#"<enumerator name="HATCH" value="1"/>"#,
+        r#"<enumerator name="HATCH" value="2"/>"#,
+    );
+    assert_eq!(
+        messages(&report),
+        ["fill::HATCH: = 1 in the reader, = 2 in the writer"]
+    );
+}
+
+#[test]
+fn key_change() {
+    let report = check_change(
+        "Status",
+        r#"<member name="level" type="short"/>"#,
+        r#"<member name="level" type="short" key="true"/>"#,
+    );
+    assert_eq!(
+        messages(&report),
+        ["the key is (id) in the reader, (id, level) in the writer"]
+    );
+}
+
+#[test]
+fn renamed_nested_type() {
+    let reader = Types::from_xml(OLD).unwrap();
+    let writer = Types::from_xml(&OLD.replace("Fill", "FillKind")).unwrap();
+    let report = check(&reader, "Shape", &writer, "Shape");
+    assert!(report.is_compatible(), "{}", report);
+}
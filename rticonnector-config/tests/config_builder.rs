@@ -0,0 +1,148 @@
+//! Snapshot tests of the XML rendered from the configuration model. Run with
+//! `UPDATE_SNAPSHOTS=1` to rewrite `tests/snapshots/` after an intended
+//! change to the output.
+
+use std::path::Path;
+
+use rticonnector_config::domains::Domain;
+use rticonnector_config::participants::{
+    DataReader, DataWriter, Participant, Publisher, Subscriber,
+};
+use rticonnector_config::qos::{EntityQos, QosProfile};
+use rticonnector_config::types::{Enum, Extensibility, Member, MemberType, Struct, TypeKind};
+use rticonnector_config::Config;
+
+fn assert_snapshot(name: &str, xml: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(name);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, xml).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(xml, expected, "rendered XML differs from {}", path.display());
+}
+
+fn shape_example() -> Config {
+    let shape_type = Struct::new("ShapeType")
+        .with_member(
+            Member::new("color", MemberType::new(TypeKind::String).with_string_max_length(128))
+                .with_key(),
+        )
+        .with_member(Member::new("x", MemberType::new(TypeKind::Long)))
+        .with_member(Member::new("y", MemberType::new(TypeKind::Long)))
+        .with_member(Member::new("shapesize", MemberType::new(TypeKind::Long)));
+    let fill_kind = Enum::new("ShapeFillKind")
+        .with_enumerator("SOLID_FILL", 0)
+        .with_enumerator("TRANSPARENT_FILL", 1)
+        .with_enumerator("HORIZONTAL_HATCH_FILL", 2)
+        .with_enumerator("VERTICAL_HATCH_FILL", 3);
+    let extended = Struct::new("ShapeTypeExtended")
+        .with_base_type("ShapeType")
+        .with_extensibility(Extensibility::Mutable)
+        .with_member(Member::new(
+            "fillKind",
+            MemberType::new(TypeKind::NonBasic("ShapeFillKind".to_string())),
+        ))
+        .with_member(Member::new("angle", MemberType::new(TypeKind::Float)));
+
+    Config::builder()
+        .qos_profile(
+            "QosLibrary",
+            QosProfile::new("DefaultProfile")
+                .with_base_name("BuiltinQosLibExp::Generic.StrictReliable")
+                .with_default_qos(),
+        )
+        .type_definition(shape_type)
+        .type_definition(fill_kind)
+        .type_definition(extended)
+        .domain(
+            "MyDomainLibrary",
+            Domain::new("MyDomain", 0)
+                .with_register_type("ShapeType", "ShapeType")
+                .with_topic("Square", "ShapeType")
+                .with_topic("Circle", "ShapeType"),
+        )
+        .participant(
+            "MyParticipantLibrary",
+            Participant::new("MyPubParticipant", "MyDomainLibrary::MyDomain").with_publisher(
+                Publisher::new("MyPublisher").with_writer(
+                    DataWriter::new("MySquareWriter", "Square")
+                        .with_qos(EntityQos::from_profile("QosLibrary::DefaultProfile")),
+                ),
+            ),
+        )
+        .participant(
+            "MyParticipantLibrary",
+            Participant::new("MySubParticipant", "MyDomainLibrary::MyDomain").with_subscriber(
+                Subscriber::new("MySubscriber")
+                    .with_reader(DataReader::new("MySquareReader", "Square"))
+                    .with_reader(DataReader::new("MyCircleReader", "Circle").with_qos(
+                        EntityQos::from_content("<history>\n    <depth>10</depth>\n</history>"),
+                    )),
+            ),
+        )
+        .build()
+}
+
+#[test]
+fn builder_renders_shape_example() {
+    let config = shape_example();
+    assert!(config.validate().is_empty(), "{:?}", config.validate());
+    assert_snapshot("shape_example.xml", &config.to_xml());
+}
+
+#[test]
+fn rendered_xml_parses_back() {
+    let xml = shape_example().to_xml();
+    let config = Config::from_xml(&xml).unwrap();
+    assert!(config.validate().is_empty());
+    assert_eq!(config.to_xml(), xml);
+}
+
+#[test]
+fn parsed_examples_render_stably() {
+    let examples = [
+        ("ShapeExample.xml", "parsed_shape_example.xml"),
+        ("Simple.xml", "parsed_simple.xml"),
+    ];
+    for (example, snapshot) in examples {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../rticonnector/examples")
+            .join(example);
+        let xml = Config::from_file(path).unwrap().to_xml();
+        assert_eq!(Config::from_xml(&xml).unwrap().to_xml(), xml);
+        assert_snapshot(snapshot, &xml);
+    }
+}
+
+#[test]
+fn round_trip_keeps_every_element() {
+    for example in ["ShapeExample.xml", "Simple.xml"] {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../rticonnector/examples")
+            .join(example);
+        let xml = std::fs::read_to_string(path).unwrap();
+        let config = Config::from_xml(&xml).unwrap();
+        assert!(config.check_round_trip(&xml).is_ok(), "{}", example);
+    }
+
+    let xml = r#"<dds>
+    <domain_library name="Domains">
+        <domain name="Shapes" domain_id="0">
+            <topic name="Square" register_type_ref="ShapeType">
+                <topic_qos>
+                    <durability><kind>TRANSIENT_LOCAL_DURABILITY_QOS</kind></durability>
+                </topic_qos>
+            </topic>
+        </domain>
+    </domain_library>
+</dds>"#;
+    let error = Config::from_xml(xml)
+        .unwrap()
+        .check_round_trip(xml)
+        .unwrap_err();
+    assert_eq!(error.line(), Some(5));
+    assert!(error.to_string().contains("<topic_qos>"), "{}", error);
+}
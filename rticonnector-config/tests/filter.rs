@@ -0,0 +1,137 @@
+//! Checks of content filter expressions, and their errors.
+
+use rticonnector_config::filter::{ContentFilter, FilterError};
+use rticonnector_config::Config;
+
+const TYPES: &str = r#"<dds>
+    <types>
+        <enum name="Color">
+            <enumerator name="RED"/>
+            <enumerator name="BLUE"/>
+        </enum>
+        <struct name="Point">
+            <member name="x" type="long"/>
+            <member name="y" type="long"/>
+        </struct>
+        <struct name="Shape">
+            <member name="color" type="string" stringMaxLength="128"/>
+            <member name="fill" type="nonBasic" nonBasicTypeName="Color"/>
+            <member name="size" type="double"/>
+            <member name="center" type="nonBasic" nonBasicTypeName="Point"/>
+            <member name="points" type="nonBasic" nonBasicTypeName="Point" sequenceMaxLength="4"/>
+        </struct>
+    </types>
+</dds>"#;
+
+fn check(expression: &str, parameters: &[&str]) -> Result<(), FilterError> {
+    let config = Config::from_xml(TYPES).unwrap();
+    let shape = config.types.get_struct("Shape").unwrap();
+    ContentFilter::new("Filtered", expression, parameters).check_against(&config.types, shape)
+}
+
+fn syntax_error(expression: &str) -> (usize, String) {
+    match check(expression, &[]) {
+        Err(FilterError::Syntax { offset, message }) => (offset, message),
+        other => panic!("{}: expected a syntax error, got {:?}", expression, other),
+    }
+}
+
+#[test]
+fn valid_expressions() {
+    for expression in [
+        "color = 'BLUE' AND size > 1.5",
+        "size > 1e-5 OR size < 2E+3 OR size = 1e3",
+        "center.x BETWEEN -10 AND 0x1F",
+        "points[1].y <> 3 AND NOT (fill = RED)",
+        "color LIKE 'B%' OR color MATCH 'R.*'",
+    ] {
+        assert_eq!(check(expression, &[]), Ok(()), "{}", expression);
+    }
+    assert_eq!(check("color = %0 AND size > %1", &["'BLUE'", "2"]), Ok(()));
+}
+
+#[test]
+fn numbers() {
+    assert_eq!(
+        syntax_error("size > 1e"),
+        (7, "invalid number `1e`".to_string())
+    );
+    assert_eq!(
+        syntax_error("size > 0xZZ"),
+        (7, "invalid number `0xZZ`".to_string())
+    );
+    assert_eq!(
+        syntax_error("size > 1.2.3"),
+        (7, "invalid number `1.2.3`".to_string())
+    );
+    assert_eq!(
+        syntax_error("size > 12abc"),
+        (7, "invalid number `12abc`".to_string())
+    );
+}
+
+#[test]
+fn syntax_errors() {
+    assert_eq!(
+        syntax_error("color = 'BLUE"),
+        (8, "unterminated string".to_string())
+    );
+    assert_eq!(
+        syntax_error("color = %x"),
+        (8, "expected a parameter index after `%`".to_string())
+    );
+    assert_eq!(syntax_error("color = #"), (8, "unexpected `#`".to_string()));
+    let (offset, _) = syntax_error("color = 'BLUE' size > 1");
+    assert_eq!(offset, 15);
+    let (offset, _) = syntax_error("(color = 'BLUE'");
+    assert_eq!(offset, 15);
+    let (offset, _) = syntax_error("size BETWEEN 1 OR 2");
+    assert_eq!(offset, 15);
+}
+
+#[test]
+fn missing_parameter() {
+    assert_eq!(
+        check("color = %0 AND size > %2", &["'BLUE'", "1"]),
+        Err(FilterError::MissingParameter { index: 2, count: 2 })
+    );
+}
+
+#[test]
+fn unknown_member() {
+    assert_eq!(
+        check("center.z > 1", &[]),
+        Err(FilterError::UnknownMember {
+            member: "center.z".to_string(),
+            type_name: "Shape".to_string(),
+        })
+    );
+    // An enumerator is only accepted next to a member of its enum.
+    assert_eq!(
+        check("size = RED", &[]),
+        Err(FilterError::UnknownMember {
+            member: "RED".to_string(),
+            type_name: "Shape".to_string(),
+        })
+    );
+}
+
+#[test]
+fn unquoted_parameter() {
+    assert_eq!(
+        check("color = %0", &["BLUE"]),
+        Err(FilterError::UnquotedParameter {
+            index: 0,
+            member: "color".to_string(),
+            value: "BLUE".to_string(),
+        })
+    );
+}
+
+#[test]
+fn other_kinds_are_not_checked() {
+    let mut filter = ContentFilter::new("Filtered", "color = = 'BLUE'", &[] as &[&str]);
+    assert!(filter.check().is_err());
+    filter.kind = "builtin.stringMatch".to_string();
+    assert_eq!(filter.check(), Ok(()));
+}
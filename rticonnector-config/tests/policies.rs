@@ -0,0 +1,387 @@
+//! Typed QoS policies rendered as a `<qos_profile>`, and the inconsistent
+//! combinations `validate` reports.
+
+use std::time::Duration;
+
+use rticonnector_config::policies::{
+    Durability, History, LivelinessKind, Ownership, PolicyError, QosPolicies, Reliability,
+    ResourceLimits, TransportBuiltin,
+};
+
+fn limits(max_samples: Option<u32>, max_samples_per_instance: Option<u32>) -> ResourceLimits {
+    ResourceLimits {
+        max_samples,
+        max_instances: None,
+        max_samples_per_instance,
+    }
+}
+
+/// The XML of a policy shared by writers and readers, `policy` being the
+/// content of their QoS.
+fn endpoint(policy: &str) -> String {
+    let policy: String = policy
+        .lines()
+        .map(|line| format!("    {}\n", line))
+        .collect();
+    format!(
+        "<datawriter_qos>\n{}</datawriter_qos>\n<datareader_qos>\n{}</datareader_qos>\n",
+        policy, policy
+    )
+}
+
+#[test]
+fn unset_resource_limits_are_inherited() {
+    let limits = ResourceLimits {
+        max_samples: Some(ResourceLimits::UNLIMITED),
+        max_instances: None,
+        max_samples_per_instance: Some(8),
+    };
+    let policies = QosPolicies::new()
+        .with_history(History::KeepLast(8))
+        .with_resource_limits(limits);
+    assert!(policies.validate().is_empty(), "{:?}", policies.validate());
+    let xml = policies.to_xml();
+    assert!(
+        xml.contains("<max_samples>LENGTH_UNLIMITED</max_samples>"),
+        "{}",
+        xml
+    );
+    assert!(
+        xml.contains("<max_samples_per_instance>8</max_samples_per_instance>"),
+        "{}",
+        xml
+    );
+    assert!(!xml.contains("max_instances"), "{}", xml);
+
+    let policies = QosPolicies::new().with_resource_limits(ResourceLimits::default());
+    assert_eq!(policies.to_xml(), "");
+}
+
+#[test]
+fn depth_exceeds_max_samples_per_instance() {
+    let policies = QosPolicies::new()
+        .with_history(History::KeepLast(10))
+        .with_resource_limits(limits(None, Some(4)));
+    assert_eq!(
+        policies.validate(),
+        [PolicyError::DepthExceedsLimit {
+            depth: 10,
+            max_samples_per_instance: 4
+        }]
+    );
+    let policies = policies.with_history(History::KeepLast(4));
+    assert!(policies.validate().is_empty());
+}
+
+#[test]
+fn max_samples_below_max_samples_per_instance() {
+    let policies = QosPolicies::new().with_resource_limits(limits(Some(2), Some(4)));
+    assert_eq!(
+        policies.validate(),
+        [PolicyError::SamplesBelowPerInstance {
+            max_samples: 2,
+            max_samples_per_instance: 4
+        }]
+    );
+    let policies = QosPolicies::new().with_resource_limits(limits(Some(4), Some(4)));
+    assert!(policies.validate().is_empty());
+}
+
+#[test]
+fn keep_all_with_tiny_limits() {
+    let keep_all = QosPolicies::new().with_history(History::KeepAll);
+    let cases = [
+        (limits(Some(1), None), Some(1)),
+        (limits(None, Some(0)), Some(0)),
+        (limits(Some(1), Some(100)), Some(1)),
+        (limits(Some(100), Some(1)), Some(1)),
+        (limits(Some(2), None), None),
+        (limits(None, None), None),
+    ];
+    for (limits, kept) in cases {
+        let errors = keep_all.clone().with_resource_limits(limits).validate();
+        let limited: Vec<_> = errors
+            .into_iter()
+            .filter(|e| matches!(e, PolicyError::KeepAllLimited(_)))
+            .collect();
+        assert_eq!(
+            limited,
+            kept.map(PolicyError::KeepAllLimited)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            "{:?}",
+            limits
+        );
+    }
+}
+
+#[test]
+fn zero_depth() {
+    let policies = QosPolicies::new()
+        .with_history(History::KeepLast(0))
+        .with_resource_limits(limits(None, Some(4)));
+    assert_eq!(policies.validate(), [PolicyError::ZeroDepth]);
+}
+
+#[test]
+fn durability_needs_reliability() {
+    for durability in [
+        Durability::TransientLocal,
+        Durability::Transient,
+        Durability::Persistent,
+    ] {
+        let policies = QosPolicies::new()
+            .with_reliability(Reliability::BestEffort)
+            .with_durability(durability);
+        assert_eq!(
+            policies.validate(),
+            [PolicyError::DurabilityNeedsReliability(durability)]
+        );
+        let reliable = policies.with_reliability(Reliability::Reliable);
+        assert!(reliable.validate().is_empty());
+    }
+    let volatile = QosPolicies::new()
+        .with_reliability(Reliability::BestEffort)
+        .with_durability(Durability::Volatile);
+    assert!(volatile.validate().is_empty());
+}
+
+#[test]
+fn zero_deadline() {
+    let policies = QosPolicies::new().with_deadline(Duration::ZERO);
+    assert_eq!(policies.validate(), [PolicyError::ZeroDeadline]);
+    let policies = QosPolicies::new().with_deadline(Duration::from_nanos(1));
+    assert!(policies.validate().is_empty());
+}
+
+#[test]
+fn zero_lease_duration() {
+    let policies = QosPolicies::new().with_liveliness(LivelinessKind::Automatic, Duration::ZERO);
+    assert_eq!(policies.validate(), [PolicyError::ZeroLeaseDuration]);
+}
+
+#[test]
+fn no_transport() {
+    let none = TransportBuiltin {
+        udpv4: false,
+        udpv6: false,
+        shmem: false,
+    };
+    let policies = QosPolicies::new().with_transport_builtin(none);
+    assert_eq!(policies.validate(), [PolicyError::NoTransport]);
+    let shmem = TransportBuiltin {
+        shmem: true,
+        ..none
+    };
+    assert!(QosPolicies::new()
+        .with_transport_builtin(shmem)
+        .validate()
+        .is_empty());
+}
+
+#[test]
+fn errors_are_all_reported() {
+    let policies = QosPolicies::new()
+        .with_reliability(Reliability::BestEffort)
+        .with_durability(Durability::TransientLocal)
+        .with_deadline(Duration::ZERO)
+        .with_liveliness(LivelinessKind::ManualByTopic, Duration::ZERO);
+    assert_eq!(
+        policies.validate(),
+        [
+            PolicyError::DurabilityNeedsReliability(Durability::TransientLocal),
+            PolicyError::ZeroDeadline,
+            PolicyError::ZeroLeaseDuration,
+        ]
+    );
+}
+
+#[test]
+fn reliability_xml() {
+    let cases = [
+        (Reliability::BestEffort, "BEST_EFFORT_RELIABILITY_QOS"),
+        (Reliability::Reliable, "RELIABLE_RELIABILITY_QOS"),
+    ];
+    for (reliability, kind) in cases {
+        assert_eq!(
+            QosPolicies::new().with_reliability(reliability).to_xml(),
+            endpoint(&format!(
+                "<reliability>\n    <kind>{}</kind>\n</reliability>",
+                kind
+            ))
+        );
+    }
+}
+
+#[test]
+fn durability_xml() {
+    let cases = [
+        (Durability::Volatile, "VOLATILE_DURABILITY_QOS"),
+        (Durability::TransientLocal, "TRANSIENT_LOCAL_DURABILITY_QOS"),
+        (Durability::Transient, "TRANSIENT_DURABILITY_QOS"),
+        (Durability::Persistent, "PERSISTENT_DURABILITY_QOS"),
+    ];
+    for (durability, kind) in cases {
+        assert_eq!(
+            QosPolicies::new().with_durability(durability).to_xml(),
+            endpoint(&format!(
+                "<durability>\n    <kind>{}</kind>\n</durability>",
+                kind
+            ))
+        );
+    }
+}
+
+#[test]
+fn history_xml() {
+    assert_eq!(
+        QosPolicies::new()
+            .with_history(History::KeepLast(5))
+            .to_xml(),
+        endpoint(
+            "<history>
+    <kind>KEEP_LAST_HISTORY_QOS</kind>
+    <depth>5</depth>
+</history>"
+        )
+    );
+    assert_eq!(
+        QosPolicies::new().with_history(History::KeepAll).to_xml(),
+        endpoint("<history>\n    <kind>KEEP_ALL_HISTORY_QOS</kind>\n</history>")
+    );
+}
+
+#[test]
+fn deadline_xml() {
+    assert_eq!(
+        QosPolicies::new()
+            .with_deadline(Duration::from_millis(1500))
+            .to_xml(),
+        endpoint(
+            "<deadline>
+    <period>
+        <sec>1</sec>
+        <nanosec>500000000</nanosec>
+    </period>
+</deadline>"
+        )
+    );
+    assert_eq!(
+        QosPolicies::new().with_deadline(Duration::MAX).to_xml(),
+        endpoint(
+            "<deadline>
+    <period>
+        <sec>DURATION_INFINITE_SEC</sec>
+        <nanosec>DURATION_INFINITE_NSEC</nanosec>
+    </period>
+</deadline>"
+        )
+    );
+}
+
+#[test]
+fn liveliness_xml() {
+    let cases = [
+        (LivelinessKind::Automatic, "AUTOMATIC_LIVELINESS_QOS"),
+        (
+            LivelinessKind::ManualByParticipant,
+            "MANUAL_BY_PARTICIPANT_LIVELINESS_QOS",
+        ),
+        (
+            LivelinessKind::ManualByTopic,
+            "MANUAL_BY_TOPIC_LIVELINESS_QOS",
+        ),
+    ];
+    for (kind, name) in cases {
+        let policies = QosPolicies::new().with_liveliness(kind, Duration::from_secs(2));
+        assert_eq!(
+            policies.to_xml(),
+            endpoint(&format!(
+                "<liveliness>
+    <kind>{}</kind>
+    <lease_duration>
+        <sec>2</sec>
+        <nanosec>0</nanosec>
+    </lease_duration>
+</liveliness>",
+                name
+            ))
+        );
+    }
+}
+
+#[test]
+fn ownership_xml() {
+    assert_eq!(
+        QosPolicies::new()
+            .with_ownership(Ownership::Shared)
+            .to_xml(),
+        endpoint("<ownership>\n    <kind>SHARED_OWNERSHIP_QOS</kind>\n</ownership>")
+    );
+    // The strength only applies to writers.
+    assert_eq!(
+        QosPolicies::new()
+            .with_ownership(Ownership::Exclusive { strength: 7 })
+            .to_xml(),
+        "<datawriter_qos>
+    <ownership>
+        <kind>EXCLUSIVE_OWNERSHIP_QOS</kind>
+    </ownership>
+    <ownership_strength>
+        <value>7</value>
+    </ownership_strength>
+</datawriter_qos>
+<datareader_qos>
+    <ownership>
+        <kind>EXCLUSIVE_OWNERSHIP_QOS</kind>
+    </ownership>
+</datareader_qos>
+"
+    );
+}
+
+#[test]
+fn resource_limits_xml() {
+    let limits = ResourceLimits {
+        max_samples: Some(100),
+        max_instances: Some(ResourceLimits::UNLIMITED),
+        max_samples_per_instance: Some(10),
+    };
+    assert_eq!(
+        QosPolicies::new().with_resource_limits(limits).to_xml(),
+        endpoint(
+            "<resource_limits>
+    <max_samples>100</max_samples>
+    <max_instances>LENGTH_UNLIMITED</max_instances>
+    <max_samples_per_instance>10</max_samples_per_instance>
+</resource_limits>"
+        )
+    );
+}
+
+#[test]
+fn transport_builtin_xml() {
+    let transports = TransportBuiltin {
+        udpv4: true,
+        udpv6: false,
+        shmem: true,
+    };
+    assert_eq!(transports.mask(), "UDPv4|SHMEM");
+    assert_eq!(
+        QosPolicies::new()
+            .with_transport_builtin(transports)
+            .to_xml(),
+        "<domain_participant_qos>
+    <transport_builtin>
+        <mask>UDPv4|SHMEM</mask>
+    </transport_builtin>
+</domain_participant_qos>
+"
+    );
+    let none = TransportBuiltin {
+        udpv4: false,
+        udpv6: false,
+        shmem: false,
+    };
+    assert_eq!(none.mask(), "MASK_NONE");
+}
@@ -0,0 +1,157 @@
+//! Errors reported by `Config::validate`, or while parsing, each on the line
+//! of the element at fault.
+
+use rticonnector_config::{Config, ConfigError};
+
+fn errors(xml: &str) -> Vec<(u32, String)> {
+    let config = Config::from_xml(xml).unwrap();
+    config
+        .validate()
+        .into_iter()
+        .map(|error| match error {
+            ConfigError::Invalid { line, message } => (line, message),
+            error => panic!("unexpected error: {}", error),
+        })
+        .collect()
+}
+
+#[test]
+fn duplicate_member_id() {
+    let xml = r#"<dds>
+    <types>
+        <struct name="Base" extensibility="mutable">
+            <member name="a" type="long" id="3"/>
+        </struct>
+        <struct name="Derived" baseType="Base" extensibility="mutable">
+            <member name="b" type="long" id="2"/>
+            <member name="c" type="long"/>
+            <member name="d" type="long" id="2"/>
+        </struct>
+    </types>
+</dds>"#;
+    assert_eq!(
+        errors(xml),
+        [
+            (8, "member ID 3 of `Derived` is used twice".to_string()),
+            (9, "member ID 2 of `Derived` is used twice".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn builtin_profiles() {
+    let xml = r#"<dds>
+    <qos_library name="Lib">
+        <qos_profile name="Reliable" base_name="BuiltinQosLibExp::Generic.StrictReliable"/>
+        <qos_profile name="Typo" base_name="BuiltinQosLibExp::Generic.StrictReliabel"/>
+        <qos_profile name="Unknown" base_name="BuiltinQosLibExp::Pattern.Unknown"/>
+    </qos_library>
+</dds>"#;
+    assert_eq!(
+        errors(xml),
+        [
+            (
+                4,
+                "unknown QoS profile `BuiltinQosLibExp::Generic.StrictReliabel`".to_string()
+            ),
+            (
+                5,
+                "unknown QoS profile `BuiltinQosLibExp::Pattern.Unknown`".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn unknown_references() {
+    let xml = r#"<dds>
+    <types>
+        <struct name="ShapeType">
+            <member name="x" type="long"/>
+        </struct>
+    </types>
+    <qos_library name="Lib">
+        <qos_profile name="Base"/>
+        <qos_profile name="Local" base_name="Base"/>
+        <qos_profile name="Missing" base_name="Bsae"/>
+    </qos_library>
+    <domain_library name="Domains">
+        <domain name="Shapes">
+            <register_type name="ShapeType" type_ref="ShapeType"/>
+            <register_type name="CircleType" type_ref="CircleType"/>
+            <topic name="Square" register_type_ref="ShapeType"/>
+            <topic name="Circle" register_type_ref="Circle"/>
+        </domain>
+    </domain_library>
+    <domain_participant_library name="Participants">
+        <domain_participant name="Participant" domain_ref="Domains::Shapes">
+            <register_type name="Triangle" type_ref="TriangleType"/>
+            <topic name="Triangle" register_type_ref="Triangle"/>
+            <topic name="Star" register_type_ref="Star"/>
+            <publisher name="Pub">
+                <data_writer name="Square" topic_ref="Square">
+                    <datawriter_qos base_name="Lib::Base"/>
+                </data_writer>
+                <data_writer name="Pentagon" topic_ref="Pentagon"/>
+            </publisher>
+            <subscriber name="Sub">
+                <data_reader name="Hexagon" topic_ref="Hexagon">
+                    <datareader_qos base_name="Lib::Absent"/>
+                </data_reader>
+            </subscriber>
+        </domain_participant>
+        <domain_participant name="Elsewhere" domain_ref="Domains::Other"/>
+    </domain_participant_library>
+</dds>"#;
+    let expected = [
+        (10, "unknown QoS profile `Bsae`"),
+        (15, "unknown type `CircleType`"),
+        (17, "`Circle` is not a type registered in domain `Shapes`"),
+        (22, "unknown type `TriangleType`"),
+        (
+            24,
+            "`Star` is not a type registered in participant `Participant` or its domain",
+        ),
+        (29, "unknown topic `Pentagon` in participant `Participant`"),
+        (32, "unknown topic `Hexagon` in participant `Participant`"),
+        (33, "unknown QoS profile `Lib::Absent`"),
+        (37, "unknown domain `Domains::Other`"),
+    ];
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(line, message)| (line, message.to_string()))
+        .collect();
+    assert_eq!(errors(xml), expected);
+}
+
+#[test]
+fn enumerator_values() {
+    let xml = |last: &str| {
+        format!(
+            r#"<dds>
+    <types>
+        <enum name="Level">
+            <enumerator name="LOW" value="2147483646"/>
+            <enumerator name="HIGH"/>
+            {}
+        </enum>
+    </types>
+</dds>"#,
+            last
+        )
+    };
+    // Only an enumerator relying on the implicit value past i32::MAX fails.
+    let config = Config::from_xml(&xml(r#"<enumerator name="MIN" value="-2147483648"/>"#)).unwrap();
+    let level = config.types.get_enum("Level").unwrap();
+    let values: Vec<_> = level.enumerators.iter().map(|e| e.value).collect();
+    assert_eq!(values, [i32::MAX - 1, i32::MAX, i32::MIN]);
+    assert!(Config::from_xml(&xml("")).is_ok());
+
+    match Config::from_xml(&xml(r#"<enumerator name="OVER"/>"#)) {
+        Err(ConfigError::Invalid { line, message }) => {
+            assert_eq!(line, 6);
+            assert!(message.contains("explicit value"), "{}", message);
+        }
+        other => panic!("unexpected result: {:?}", other.map(drop)),
+    }
+}
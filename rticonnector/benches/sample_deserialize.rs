@@ -0,0 +1,116 @@
+//! Compares reading a sample through `get_json_sample` + `serde_json` with the
+//! member-by-member `SampleDeserializer`.
+//!
+//! Both readers share one loopback participant from `benches/Bench.xml`; each
+//! sample is written once and kept in the reader queue with `read`.
+
+use std::hint::black_box;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rticonnector::Connector;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+const CONFIG: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/benches/Bench.xml");
+
+#[derive(Serialize, Deserialize)]
+struct ShapeType {
+    color: String,
+    x: i32,
+    y: i32,
+    shapesize: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Position {
+    latitude: f64,
+    longitude: f64,
+    altitude: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Track {
+    id: u32,
+    callsign: String,
+    position: Position,
+    history: Vec<Position>,
+    active: bool,
+}
+
+fn position(step: f64) -> Position {
+    Position {
+        latitude: 48.85 + step,
+        longitude: 2.35 - step,
+        altitude: 1200.0,
+    }
+}
+
+/// Writes `sample` and reads it back, leaving it at index 1 of `reader`.
+fn loopback<T: Serialize>(connector: &mut Connector, writer: &str, reader: &str, sample: &T) {
+    connector
+        .set_json_instance(writer, &serde_json::to_string(sample).unwrap())
+        .unwrap();
+    connector.write(writer, "{}").unwrap();
+    connector
+        .get_dynamic_datareader(reader)
+        .unwrap()
+        .wait_for_data_on_reader(Duration::from_secs(5))
+        .unwrap();
+    connector.read(reader).unwrap();
+}
+
+fn bench_reader<T: DeserializeOwned>(
+    c: &mut Criterion,
+    connector: &Connector,
+    group_name: &str,
+    reader: &str,
+) {
+    let mut group = c.benchmark_group(group_name);
+    group.bench_function("json", |b| {
+        b.iter(|| {
+            let json = connector.get_json_sample(reader, 1).unwrap();
+            black_box(serde_json::from_str::<T>(&json).unwrap())
+        })
+    });
+    group.bench_function("sample_deserializer", |b| {
+        b.iter(|| black_box(connector.deserialize_sample::<T>(reader, 1).unwrap()))
+    });
+    group.finish();
+}
+
+fn sample_deserialize(c: &mut Criterion) {
+    let mut connector = Connector::new("BenchParticipantLibrary::Loopback", CONFIG, &[]);
+
+    let shape = ShapeType {
+        color: "BLUE".to_string(),
+        x: 10,
+        y: 20,
+        shapesize: 30,
+    };
+    let track = Track {
+        id: 7,
+        callsign: "AF1234".to_string(),
+        position: position(0.0),
+        history: (1..=8).map(|step| position(step as f64 * 0.01)).collect(),
+        active: true,
+    };
+    loopback(
+        &mut connector,
+        "BenchPublisher::SquareWriter",
+        "BenchSubscriber::SquareReader",
+        &shape,
+    );
+    loopback(
+        &mut connector,
+        "BenchPublisher::TrackWriter",
+        "BenchSubscriber::TrackReader",
+        &track,
+    );
+
+    bench_reader::<ShapeType>(c, &connector, "ShapeType", "BenchSubscriber::SquareReader");
+    bench_reader::<Track>(c, &connector, "Track", "BenchSubscriber::TrackReader");
+}
+
+criterion_group!(benches, sample_deserialize);
+criterion_main!(benches);
@@ -25,7 +25,9 @@ impl Default for ShapeFillKind {
 }
 #[derive(Serialize, Deserialize, Default)]
 struct ShapeTypeExtended {
-    baseType: ShapeType,
+    // `ShapeTypeExtended` inherits from `ShapeType`, so its members are not nested.
+    #[serde(flatten)]
+    base: ShapeType,
     fillKind: ShapeFillKind,
     angle: f32,
 }
@@ -0,0 +1,339 @@
+//! Creating a [`Connector`] from several configuration sources.
+//!
+//! The C library takes a single URL listing its sources, separated by `;`:
+//! file paths, and documents passed inline as `str://"<dds>...</dds>"`.
+//! [`ConnectorBuilder`] writes that URL, so a configuration can be embedded
+//! with `include_str!` and combined with shared files without writing
+//! anything to disk.
+//!
+//! Overrides such as [`ConnectorBuilder::domain_id`] let one configuration
+//! serve several environments, and [`ConnectorBuilder::content_filter`]
+//! filters a reader defined in XML. When one is set, the sources are parsed into
+//! a [`Config`], the participant is modified, and the result is passed to
+//! the C library inline. A source holding elements the [`config`] model
+//! does not represent is rejected with [`BuildError::Unrepresented`] rather
+//! than passed without them.
+//!
+//! ```
+//! use rticonnector::ConnectorBuilder;
+//!
+//! let builder = ConnectorBuilder::new("MyParticipantLibrary::MyParticipant")
+//!     .config_files(&["types.xml", "qos.xml"])
+//!     .config_str(r#"<dds><domain_library name="MyDomainLibrary"/></dds>"#);
+//! assert_eq!(
+//!     builder.url().unwrap(),
+//!     r#"types.xml;qos.xml;str://"<dds><domain_library name='MyDomainLibrary'/></dds>""#
+//! );
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::config::filter::{ContentFilter, FilterError};
+use crate::config::{self, Config};
+use crate::{Connector, RTIOptions};
+
+/// Errors raised while creating a [`Connector`] with a [`ConnectorBuilder`].
+#[derive(Debug, Error)]
+pub enum BuildError {
+    #[error("no configuration file or document was given")]
+    NoConfiguration,
+    #[error("cannot pass {path} to the Connector: {reason}")]
+    InvalidPath { path: PathBuf, reason: &'static str },
+    #[error("an inline document contains a NUL byte")]
+    NulInDocument,
+    #[error("`{0}` is not a valid participant name")]
+    InvalidName(String),
+    #[error("cannot read the configuration: {0}")]
+    Config(#[from] config::ConfigError),
+    #[error("cannot apply the overrides to {document}: {source}")]
+    Unrepresented {
+        /// The path of the file, or `inline document`.
+        document: String,
+        #[source]
+        source: config::ConfigError,
+    },
+    #[error("cannot apply the overrides: no participant `{0}` in the configuration")]
+    UnknownParticipant(String),
+    #[error("cannot apply the overrides: no reader `{0}` in the participant")]
+    UnknownReader(String),
+    #[error("invalid content filter on `{reader}`: {source}")]
+    ContentFilter {
+        reader: String,
+        #[source]
+        source: FilterError,
+    },
+    #[error("failed to create the participant `{config_name}`: {message}")]
+    Create {
+        config_name: String,
+        /// The last error reported by the C library.
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    File(PathBuf),
+    Inline(String),
+    Config(Config),
+}
+
+/// Builds a [`Connector`] from XML files and documents, loaded in the order
+/// they are added.
+#[derive(Clone)]
+pub struct ConnectorBuilder {
+    config_name: String,
+    sources: Vec<Source>,
+    options: RTIOptions,
+    domain_id: Option<u32>,
+    partitions: Option<Vec<String>>,
+    participant_name: Option<String>,
+    content_filters: Vec<(String, ContentFilter)>,
+}
+
+impl ConnectorBuilder {
+    /// A builder for the participant `config_name` (`"Library::Participant"`).
+    pub fn new(config_name: &str) -> Self {
+        Self {
+            config_name: config_name.to_string(),
+            sources: Vec::new(),
+            options: RTIOptions::default(),
+            domain_id: None,
+            partitions: None,
+            participant_name: None,
+            content_filters: Vec::new(),
+        }
+    }
+
+    /// The participant the connector is built for (`"Library::Participant"`).
+    pub fn config_name(&self) -> &str {
+        &self.config_name
+    }
+
+    /// Adds a configuration file.
+    pub fn config_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.sources.push(Source::File(path.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Adds configuration files, such as shared type definitions followed by
+    /// the participants of the application.
+    pub fn config_files<P: AsRef<Path>>(self, paths: &[P]) -> Self {
+        paths
+            .iter()
+            .fold(self, |builder, path| builder.config_file(path))
+    }
+
+    /// Adds a `<dds>` document passed to the Connector inline, such as one
+    /// embedded with `include_str!`. Quotes are escaped as the URL requires.
+    pub fn config_str(mut self, xml: &str) -> Self {
+        let xml = xml.trim_start_matches('\u{feff}');
+        self.sources.push(Source::Inline(xml.to_string()));
+        self
+    }
+
+    /// Adds a configuration built or parsed in Rust.
+    pub fn config(mut self, config: &Config) -> Self {
+        self.sources.push(Source::Config(config.clone()));
+        self
+    }
+
+    pub fn options(mut self, options: RTIOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Joins `domain_id` instead of the domain ID of the configuration.
+    pub fn domain_id(mut self, domain_id: u32) -> Self {
+        self.domain_id = Some(domain_id);
+        self
+    }
+
+    /// Places every publisher and subscriber of the participant in
+    /// `partitions` instead of the ones of the configuration.
+    pub fn partitions<S: AsRef<str>>(mut self, partitions: &[S]) -> Self {
+        self.partitions = Some(partitions.iter().map(|p| p.as_ref().to_string()).collect());
+        self
+    }
+
+    /// Sets the name the participant announces to the other participants.
+    pub fn participant_name(mut self, name: &str) -> Self {
+        self.participant_name = Some(name.to_string());
+        self
+    }
+
+    /// Only delivers the samples matching `filter` to the reader
+    /// `"Subscriber::Reader"`, whose [`Input`](crate::Input) then reports
+    /// it. The filter is checked against the topic type when the connector
+    /// is built.
+    ///
+    /// The parameters are substituted into the expression as written, so a
+    /// string parameter is quoted: `color = %0` takes `["'BLUE'"]`, and
+    /// `["BLUE"]` is rejected with
+    /// [`FilterError::UnquotedParameter`](crate::config::filter::FilterError::UnquotedParameter).
+    pub fn content_filter(mut self, reader: &str, filter: ContentFilter) -> Self {
+        self.content_filters.push((reader.to_string(), filter));
+        self
+    }
+
+    /// The URL passed to the C library: every source, separated by `;`, or
+    /// a single inline document when overrides are set.
+    pub fn url(&self) -> Result<String, BuildError> {
+        if self.sources.is_empty() {
+            return Err(BuildError::NoConfiguration);
+        }
+        if self.has_overrides() {
+            let url = config::inline_url(&self.resolve()?.to_xml());
+            return Ok(url);
+        }
+        let sources = self
+            .sources
+            .iter()
+            .map(|source| match source {
+                Source::File(path) => file_url(path),
+                Source::Inline(xml) if xml.contains('\0') => Err(BuildError::NulInDocument),
+                Source::Inline(xml) => Ok(config::inline_url(xml)),
+                Source::Config(config) => Ok(config::inline_url(&config.to_xml())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(sources.join(";"))
+    }
+
+    /// Parses and merges every source, then applies the overrides to the
+    /// participant.
+    pub fn resolve(&self) -> Result<Config, BuildError> {
+        let mut builder = Config::builder();
+        for source in &self.sources {
+            let config = match source {
+                Source::File(path) => {
+                    let xml = std::fs::read_to_string(path).map_err(|source| {
+                        config::ConfigError::Io {
+                            path: path.clone(),
+                            source,
+                        }
+                    })?;
+                    self.parse(&xml, || path.display().to_string())?
+                }
+                Source::Inline(xml) => self.parse(xml, || "inline document".to_string())?,
+                Source::Config(config) => config.clone(),
+            };
+            builder = builder.config(config);
+        }
+        let mut config = builder.build();
+        if self.has_overrides() {
+            let participant = config
+                .participant_mut(&self.config_name)
+                .ok_or_else(|| BuildError::UnknownParticipant(self.config_name.clone()))?;
+            if let Some(domain_id) = self.domain_id {
+                participant.domain_id = Some(domain_id);
+            }
+            if let Some(partitions) = &self.partitions {
+                participant.set_partitions(partitions)?;
+            }
+            if let Some(name) = &self.participant_name {
+                participant.set_participant_name(name)?;
+            }
+            for (name, filter) in &self.content_filters {
+                let reader = name.split_once("::").and_then(|(subscriber, reader)| {
+                    let subscriber = participant
+                        .subscribers
+                        .iter_mut()
+                        .find(|s| s.name == subscriber)?;
+                    subscriber.readers.iter_mut().find(|r| r.name == reader)
+                });
+                let reader = reader.ok_or_else(|| BuildError::UnknownReader(name.clone()))?;
+                reader.content_filter = Some(filter.clone());
+            }
+            check_content_filters(&config, &self.config_name)?;
+        }
+        Ok(config)
+    }
+
+    /// Parses the source `xml`. With overrides set, the configuration is
+    /// rendered back for the C library, so `xml` must not hold elements the
+    /// rendering drops.
+    fn parse(&self, xml: &str, document: impl FnOnce() -> String) -> Result<Config, BuildError> {
+        let config = Config::from_xml(xml)?;
+        if self.has_overrides() {
+            config
+                .check_round_trip(xml)
+                .map_err(|source| BuildError::Unrepresented {
+                    document: document(),
+                    source,
+                })?;
+        }
+        Ok(config)
+    }
+
+    fn has_overrides(&self) -> bool {
+        self.domain_id.is_some()
+            || self.partitions.is_some()
+            || self.participant_name.is_some()
+            || !self.content_filters.is_empty()
+    }
+
+    /// Creates the [`Connector`], reporting the error of the C library when
+    /// the configuration is rejected.
+    pub fn build(self) -> Result<Connector, BuildError> {
+        let url = self.url()?;
+        if self.config_name.contains('\0') {
+            return Err(BuildError::InvalidName(self.config_name));
+        }
+        let connector = Connector::new(&self.config_name, &url, &[self.options]);
+        if connector.connector.is_null() {
+            return Err(BuildError::Create {
+                config_name: self.config_name,
+                message: Connector::get_last_error(),
+            });
+        }
+        Ok(connector)
+    }
+}
+
+/// Checks the content filters of the participant's readers against their
+/// topic types.
+fn check_content_filters(config: &Config, config_name: &str) -> Result<(), BuildError> {
+    let participant = match config.participant(config_name) {
+        Some(participant) => participant,
+        None => return Ok(()),
+    };
+    for (name, reader) in participant.readers() {
+        let filter = match &reader.content_filter {
+            Some(filter) => filter,
+            None => continue,
+        };
+        let topic_type = config
+            .topic_type(participant, &reader.topic_ref)
+            .and_then(|t| config.types.get_struct(t));
+        let checked = match topic_type {
+            Some(topic_type) => filter.check_against(&config.types, topic_type),
+            None => filter.check(),
+        };
+        checked.map_err(|source| BuildError::ContentFilter {
+            reader: name,
+            source,
+        })?;
+    }
+    Ok(())
+}
+
+fn file_url(path: &Path) -> Result<String, BuildError> {
+    let invalid = |reason| BuildError::InvalidPath {
+        path: path.to_path_buf(),
+        reason,
+    };
+    let url = path
+        .to_str()
+        .ok_or_else(|| invalid("the path is not UTF-8"))?;
+    if url.contains(';') {
+        return Err(invalid("`;` separates the files of the URL"));
+    }
+    if url.contains('\0') {
+        return Err(invalid("the path contains a NUL byte"));
+    }
+    if url.starts_with("str://") {
+        return Err(invalid("the path would be read as an inline document"));
+    }
+    Ok(url.to_string())
+}
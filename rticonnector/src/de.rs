@@ -0,0 +1,563 @@
+//! A serde [`Deserializer`] that reads a received sample member by member.
+//!
+//! [`Connector::get_json_sample`] renders the whole sample to JSON in the C
+//! library, which then has to be parsed again on the Rust side. The
+//! [`SampleDeserializer`] instead walks the Rust type and asks the Connector
+//! for each member through `get_number_from_sample`, `get_string_from_sample`
+//! and `get_boolean_from_sample`, building the field name (`"pos.x"`,
+//! `"points[1].y"`, ...) as it goes.
+//!
+//! DDS enums are read as their integer value and mapped to the variant named
+//! like the enumerator with that value, looked up with
+//! [`Connector::enumeration`]; enums deriving `DdsType` deserialize themselves
+//! from the value already.
+//!
+//! The deserializer reads the members through the [`Sample`] trait, which
+//! [`ReaderSample`] implements over a data reader of a [`Connector`].
+
+use std::fmt;
+use std::sync::Arc;
+
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use serde::Deserializer;
+use thiserror::Error;
+
+use crate::config::types::Enum;
+use crate::{Connector, ConnectorError};
+
+/// Deserializes the sample at `index` of the reader `entity_name`.
+pub fn from_sample<T>(connector: &Connector, entity_name: &str, index: usize) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let mut deserializer = SampleDeserializer::new(connector, entity_name, index);
+    T::deserialize(&mut deserializer)
+}
+
+/// Error returned while deserializing a sample, with the field it occurred on.
+#[derive(Debug)]
+pub struct Error {
+    field: Option<String>,
+    kind: ErrorKind,
+}
+
+#[derive(Debug, Error)]
+pub enum ErrorKind {
+    #[error("{0}")]
+    Connector(#[from] ConnectorError),
+    #[error("{value} is not a valid {expected}")]
+    InvalidNumber { value: f64, expected: &'static str },
+    #[error("expected a single character, found {0:?}")]
+    InvalidChar(String),
+    /// The member is not a DDS enum, or the configuration could not be
+    /// parsed to find out.
+    #[error("`{0}` needs a DDS enum member")]
+    NotAnEnum(&'static str),
+    #[error("the DDS enum has no enumerator with value {0}")]
+    UnknownEnumerator(i32),
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{0}")]
+    Custom(String),
+}
+
+impl Error {
+    /// The field name of the offending member, if the error is tied to one.
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    fn at(mut self, field: &str) -> Self {
+        if self.field.is_none() && !field.is_empty() {
+            self.field = Some(field.to_string());
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "field `{}`: {}", field, self.kind),
+            None => self.kind.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.kind.source()
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self {
+            field: None,
+            kind: ErrorKind::Custom(msg.to_string()),
+        }
+    }
+}
+
+/// A received sample whose members are read by their field name
+/// (`"pos.x"`, `"points[1].y"`, `"points#"` for a length).
+pub trait Sample {
+    fn number(&self, field: &str) -> Result<f64, ConnectorError>;
+    fn string(&self, field: &str) -> Result<String, ConnectorError>;
+    fn boolean(&self, field: &str) -> Result<bool, ConnectorError>;
+    /// The member `field` rendered to JSON; the whole sample when `field` is
+    /// empty.
+    fn json(&self, field: &str) -> Result<String, ConnectorError>;
+    /// Whether the elements of a sequence are addressed starting at `1`.
+    fn one_based_sequence_indexing(&self) -> bool;
+    /// The DDS enum of the member `field`, if it is one.
+    fn enumeration(&self, field: &str) -> Option<Arc<Enum>>;
+}
+
+/// The sample at `index` of the reader `entity_name` of a [`Connector`].
+pub struct ReaderSample<'a> {
+    connector: &'a Connector,
+    entity_name: &'a str,
+    index: usize,
+}
+
+impl<'a> ReaderSample<'a> {
+    pub fn new(connector: &'a Connector, entity_name: &'a str, index: usize) -> Self {
+        Self {
+            connector,
+            entity_name,
+            index,
+        }
+    }
+}
+
+impl Sample for ReaderSample<'_> {
+    fn number(&self, field: &str) -> Result<f64, ConnectorError> {
+        self.connector
+            .get_number_from_sample(self.entity_name, self.index as i32, field)
+    }
+
+    fn string(&self, field: &str) -> Result<String, ConnectorError> {
+        self.connector
+            .get_string_from_sample(self.entity_name, self.index as i32, field)
+    }
+
+    fn boolean(&self, field: &str) -> Result<bool, ConnectorError> {
+        self.connector
+            .get_boolean_from_sample(self.entity_name, self.index as i32, field)
+    }
+
+    fn json(&self, field: &str) -> Result<String, ConnectorError> {
+        if field.is_empty() {
+            self.connector.get_json_sample(self.entity_name, self.index)
+        } else {
+            self.connector
+                .get_json_member(self.entity_name, self.index, field)
+        }
+    }
+
+    fn one_based_sequence_indexing(&self) -> bool {
+        self.connector.one_based_sequence_indexing()
+    }
+
+    fn enumeration(&self, field: &str) -> Option<Arc<Enum>> {
+        self.connector.enumeration(self.entity_name, field)
+    }
+}
+
+/// Deserializer over one sample, such as a [`ReaderSample`].
+pub struct SampleDeserializer<S> {
+    sample: S,
+    /// Field name of the member currently being deserialized; empty at the root.
+    path: String,
+}
+
+impl<'a> SampleDeserializer<ReaderSample<'a>> {
+    pub fn new(connector: &'a Connector, entity_name: &'a str, index: usize) -> Self {
+        Self::from_sample(ReaderSample::new(connector, entity_name, index))
+    }
+}
+
+impl<S: Sample> SampleDeserializer<S> {
+    pub fn from_sample(sample: S) -> Self {
+        Self {
+            sample,
+            path: String::new(),
+        }
+    }
+
+    fn error(&self, kind: impl Into<ErrorKind>) -> Error {
+        Error {
+            field: None,
+            kind: kind.into(),
+        }
+        .at(&self.path)
+    }
+
+    fn number(&self) -> Result<f64, Error> {
+        self.sample.number(&self.path).map_err(|e| self.error(e))
+    }
+
+    fn string(&self) -> Result<String, Error> {
+        self.sample.string(&self.path).map_err(|e| self.error(e))
+    }
+
+    fn boolean(&self) -> Result<bool, Error> {
+        self.sample.boolean(&self.path).map_err(|e| self.error(e))
+    }
+
+    fn json(&self) -> Result<serde_json::Value, Error> {
+        let json = self.sample.json(&self.path).map_err(|e| self.error(e))?;
+        serde_json::from_str(&json).map_err(|e| self.error(e))
+    }
+
+    /// Reads an integer in `min..end`. The end is exclusive because the
+    /// maximum of a 64-bit integer rounds up to a power of two as an `f64`.
+    fn integer(&self, min: f64, end: f64, expected: &'static str) -> Result<f64, Error> {
+        let value = self.number()?;
+        if value.fract() != 0.0 || value < min || value >= end {
+            return Err(self.error(ErrorKind::InvalidNumber { value, expected }));
+        }
+        Ok(value)
+    }
+
+    /// Whether the optional member at the current path holds a value.
+    ///
+    /// The getters report an unset optional member with `NoData`; since the
+    /// member kind is not known here, each getter is tried until one of them
+    /// accepts the member.
+    fn is_set(&self) -> bool {
+        let probes = [
+            self.sample.number(&self.path).map(drop),
+            self.sample.string(&self.path).map(drop),
+            self.sample.boolean(&self.path).map(drop),
+        ];
+        for probe in probes {
+            match probe {
+                Ok(()) => return true,
+                Err(ConnectorError::NoData) => return false,
+                Err(_) => {}
+            }
+        }
+        // Complex members are rendered as `null` when unset.
+        self.sample
+            .json(&self.path)
+            .is_ok_and(|json| json != "null")
+    }
+
+    fn element_path(&mut self, index: usize) {
+        let base = if self.sample.one_based_sequence_indexing() {
+            1
+        } else {
+            0
+        };
+        self.path.push_str(&format!("[{}]", index + base));
+    }
+
+    fn member_path(&mut self, name: &str) {
+        if !self.path.is_empty() {
+            self.path.push('.');
+        }
+        self.path.push_str(name);
+    }
+}
+
+macro_rules! deserialize_integer {
+    ($method:ident, $visit:ident, $ty:ty) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            let end = <$ty>::MAX as f64 + 1.0;
+            let value = self.integer(<$ty>::MIN as f64, end, stringify!($ty))?;
+            visitor.$visit(value as $ty)
+        }
+    };
+}
+
+impl<'de, S: Sample> Deserializer<'de> for &mut SampleDeserializer<S> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        // Without type information the member is read through its JSON form.
+        self.json()?
+            .deserialize_any(visitor)
+            .map_err(|e| self.error(e))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bool(self.boolean()?)
+    }
+
+    deserialize_integer!(deserialize_i8, visit_i8, i8);
+    deserialize_integer!(deserialize_i16, visit_i16, i16);
+    deserialize_integer!(deserialize_i32, visit_i32, i32);
+    deserialize_integer!(deserialize_i64, visit_i64, i64);
+    deserialize_integer!(deserialize_u8, visit_u8, u8);
+    deserialize_integer!(deserialize_u16, visit_u16, u16);
+    deserialize_integer!(deserialize_u32, visit_u32, u32);
+    deserialize_integer!(deserialize_u64, visit_u64, u64);
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f32(self.number()? as f32)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f64(self.number()?)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let value = self.string()?;
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(self.error(ErrorKind::InvalidChar(value))),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.string()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.string()?)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.is_set() {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        // `member#` is the Connector syntax for the length of a sequence.
+        let len = {
+            let parent_len = self.path.len();
+            self.path.push('#');
+            let len = self.integer(0.0, u32::MAX as f64 + 1.0, "sequence length");
+            self.path.truncate(parent_len);
+            len? as usize
+        };
+        visitor.visit_seq(Elements::new(self, len))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(Elements::new(self, len))
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        // Maps (and `#[serde(flatten)]` structs) have no fixed set of members
+        // to walk, so they fall back to the JSON form of the member.
+        self.json()?
+            .deserialize_map(visitor)
+            .map_err(|e| self.error(e))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_map(Members {
+            parent_len: self.path.len(),
+            de: self,
+            fields: fields.iter(),
+            current: None,
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let enumeration = self
+            .sample
+            .enumeration(&self.path)
+            .ok_or_else(|| self.error(ErrorKind::NotAnEnum(name)))?;
+        let value = self.integer(i32::MIN as f64, i32::MAX as f64 + 1.0, "enumerator")? as i32;
+        let enumerator = enumeration
+            .enumerators
+            .iter()
+            .find(|e| e.value == value)
+            .ok_or_else(|| self.error(ErrorKind::UnknownEnumerator(value)))?;
+        visitor.visit_enum(Enumerator(enumerator.name.clone()))
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+}
+
+/// Walks the members of a struct in declaration order.
+struct Members<'s, S> {
+    de: &'s mut SampleDeserializer<S>,
+    fields: std::slice::Iter<'static, &'static str>,
+    current: Option<&'static str>,
+    parent_len: usize,
+}
+
+impl<'de, S: Sample> MapAccess<'de> for Members<'_, S> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.current = self.fields.next().copied();
+        match self.current {
+            Some(field) => seed.deserialize(field.into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let field = self
+            .current
+            .take()
+            .ok_or_else(|| de::Error::custom("value requested before its member name"))?;
+        self.de.member_path(field);
+        let value = seed.deserialize(&mut *self.de).map_err(|e| e.at(&self.de.path));
+        self.de.path.truncate(self.parent_len);
+        value
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len())
+    }
+}
+
+/// Walks the elements of a sequence or array.
+struct Elements<'s, S> {
+    de: &'s mut SampleDeserializer<S>,
+    len: usize,
+    next: usize,
+    parent_len: usize,
+}
+
+impl<'s, S> Elements<'s, S> {
+    fn new(de: &'s mut SampleDeserializer<S>, len: usize) -> Self {
+        Self {
+            parent_len: de.path.len(),
+            de,
+            len,
+            next: 0,
+        }
+    }
+}
+
+impl<'de, S: Sample> SeqAccess<'de> for Elements<'_, S> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.next == self.len {
+            return Ok(None);
+        }
+        self.de.element_path(self.next);
+        self.next += 1;
+        let value = seed.deserialize(&mut *self.de).map_err(|e| e.at(&self.de.path));
+        self.de.path.truncate(self.parent_len);
+        value.map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.next)
+    }
+}
+
+/// A DDS enumerator, identified by its name.
+struct Enumerator(String);
+
+impl<'de> de::EnumAccess<'de> for Enumerator {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self), Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.0.as_str().into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Enumerator {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        Err(de::Error::custom("DDS enums only have unit variants"))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value, Error> {
+        Err(de::Error::custom("DDS enums only have unit variants"))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error> {
+        Err(de::Error::custom("DDS enums only have unit variants"))
+    }
+}
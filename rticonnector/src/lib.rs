@@ -7,15 +7,29 @@
 //! without exposing any unsafe code,and nothing more.
 
 use core::panic;
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::ffi::{c_char, c_double, c_int, c_void, CStr};
 use std::ptr;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
 use std::time::Duration;
 
 use rticonnector_sys::*;
 
 use thiserror::Error;
 
+pub mod builder;
+pub mod de;
+pub mod recorder;
+pub mod replay;
+pub mod ser;
+pub mod typed;
+
+pub use builder::ConnectorBuilder;
+/// The configuration model, which does not need the native library.
+pub use rticonnector_config as config;
+pub use typed::{Input, Output};
+
 #[derive(Clone)]
 pub struct RTIOptions {
     options: RTI_Connector_Options,
@@ -45,31 +59,161 @@ impl Default for RTIOptions {
 
 pub struct Connector {
     connector: *mut RTI_Connector,
+    one_based_sequence_indexing: bool,
+    config_name: String,
+    config_file: String,
+    /// The configuration parsed from `config_file` when the types are first
+    /// needed; `None` when it cannot be parsed.
+    config: OnceLock<Option<config::Config>>,
+    /// The enums found by [`Connector::enumeration`], by entity and field
+    /// name without its indices.
+    enumerations: Mutex<HashMap<(String, String), EnumerationEntry>>,
 }
 
+type EnumerationEntry = Option<Arc<config::types::Enum>>;
+
 impl Connector {
     /// Creates a new [`Connector`].
     ///
-    /// Panics if the configuration file is not valid or `&[RTIOptions]`
-    /// exceeds a length of 128.
+    /// Only the first entry of `options` is used; [`RTIOptions::default`] is
+    /// applied when it is empty.
     pub fn new(config_name: &str, config_file: &str, options: &[RTIOptions]) -> Self {
-        let rti_options: [RTI_Connector_Options; 128] = match options
-            .iter()
-            .map(|o| o.options)
-            .collect::<Vec<_>>()
-            .try_into()
-        {
-            Ok(options) => options,
-            Err(_) => panic!("Failed to convert options to array"),
-        };
+        let rti_options = options.first().cloned().unwrap_or_default().options;
         let connector = unsafe {
             RTI_Connector_new(
                 CString::new(config_name).unwrap().as_ptr(),
                 CString::new(config_file).unwrap().as_ptr(),
-                rti_options.as_ptr(),
+                &rti_options,
             )
         };
-        Self { connector }
+        Self {
+            connector,
+            one_based_sequence_indexing: rti_options.one_based_sequence_indexing != 0,
+            config_name: config_name.to_string(),
+            config_file: config_file.to_string(),
+            config: OnceLock::new(),
+            enumerations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates a new [`Connector`] for the participant `config_name`
+    /// (`"Library::Participant"`) of a configuration built or parsed in Rust,
+    /// passed to the C library inline rather than through a file.
+    pub fn from_config(config_name: &str, config: &config::Config, options: &[RTIOptions]) -> Self {
+        let connector = Self::new(config_name, &config::inline_url(&config.to_xml()), options);
+        let _ = connector.config.set(Some(config.clone()));
+        connector
+    }
+
+    /// A [`ConnectorBuilder`] for the participant `config_name`, to combine
+    /// configuration files and inline documents.
+    pub fn builder(config_name: &str) -> ConnectorBuilder {
+        ConnectorBuilder::new(config_name)
+    }
+
+    /// Deserializes the sample at `index` of the reader `entity_name` directly
+    /// from the native sample, without rendering it to JSON first.
+    ///
+    /// See [`de::from_sample`].
+    pub fn deserialize_sample<T>(&self, entity_name: &str, index: usize) -> Result<T, de::Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        de::from_sample(self, entity_name, index)
+    }
+
+    /// Sets every member of `value` into the instance of the writer
+    /// `entity_name`, without going through JSON.
+    ///
+    /// See [`ser::to_instance`].
+    pub fn serialize_instance<T>(&self, entity_name: &str, value: &T) -> Result<(), ser::Error>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        ser::to_instance(self, entity_name, value)
+    }
+
+    /// Looks up the data reader `entity_name` and binds it to `T`.
+    pub fn get_input<T>(&self, entity_name: &str) -> Result<Input<'_, T>, ConnectorError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        Input::new(self, entity_name)
+    }
+
+    /// Looks up the data writer `entity_name` and binds it to `T`.
+    pub fn get_output<T>(&self, entity_name: &str) -> Result<Output<'_, T>, ConnectorError>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        Output::new(self, entity_name)
+    }
+
+    /// Returns whether sequence elements in field names are addressed starting
+    /// at `1` (`"seq[1]"`) rather than `0`.
+    pub fn one_based_sequence_indexing(&self) -> bool {
+        self.one_based_sequence_indexing
+    }
+
+    /// The configuration the connector was created from, parsed when first
+    /// needed; `None` when it cannot be parsed in Rust.
+    pub fn config(&self) -> Option<&config::Config> {
+        self.config
+            .get_or_init(|| config::Config::from_url(&self.config_file).ok())
+            .as_ref()
+    }
+
+    /// The `<content_filter>` the reader `entity_name` was created with,
+    /// looked up in the configuration the connector was created from.
+    pub fn content_filter(&self, entity_name: &str) -> Option<&config::filter::ContentFilter> {
+        self.config()?
+            .participant(&self.config_name)?
+            .readers()
+            .find(|(name, _)| name == entity_name)?
+            .1
+            .content_filter
+            .as_ref()
+    }
+
+    /// The DDS enum of the member `field` of the writer or reader
+    /// `entity_name`, looked up in the configuration the connector was
+    /// created from. `None` when the configuration cannot be parsed in Rust
+    /// or the member is not an enum.
+    ///
+    /// The result is cached for the entity and the member, whatever the
+    /// indices of `field` (`"palette[1]"` and `"palette[2]"` share one).
+    pub fn enumeration(&self, entity_name: &str, field: &str) -> Option<Arc<config::types::Enum>> {
+        let member: String = field
+            .split('[')
+            .enumerate()
+            .map(|(i, part)| match i {
+                0 => part,
+                _ => part.trim_start_matches(|c: char| c.is_ascii_digit()),
+            })
+            .collect::<Vec<_>>()
+            .join("[");
+        let mut enumerations = self
+            .enumerations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        enumerations
+            .entry((entity_name.to_string(), member))
+            .or_insert_with(|| self.find_enumeration(entity_name, field).map(Arc::new))
+            .clone()
+    }
+
+    fn find_enumeration(&self, entity_name: &str, field: &str) -> Option<config::types::Enum> {
+        let config = self.config()?;
+        let topic_type = config.entity_type(&self.config_name, entity_name)?;
+        let member_type = config.types.field_type(topic_type, field)?;
+        match &member_type.kind {
+            config::types::TypeKind::NonBasic(name)
+                if !member_type.is_array() && !member_type.is_sequence() =>
+            {
+                config.types.get_enum(name).cloned()
+            }
+            _ => None,
+        }
     }
 
     /// Safe wrapper for `RTI_Connector_get_sample_count`
@@ -525,7 +669,7 @@ impl Connector {
 
     /// Safe wrapper for `RTI_Connector_get_number_from_sample`
     pub fn get_number_from_sample(
-        &mut self,
+        &self,
         entity_name: &str,
         index: i32,
         field_name: &str,
@@ -555,7 +699,11 @@ impl Connector {
             )
         };
 
-        // Check if the function call was successful
+        // Check if the function call was successful; unset optional members
+        // report `DDS_RETCODE_NO_DATA`
+        if result == DDSError::NoData as c_int {
+            return Err(ConnectorError::NoData);
+        }
         if result != 0 {
             return Err(ConnectorError::FfiError);
         }
@@ -565,7 +713,7 @@ impl Connector {
 
     /// Safe wrapper for `RTI_Connector_get_boolean_from_sample`
     pub fn get_boolean_from_sample(
-        &mut self,
+        &self,
         entity_name: &str,
         index: i32,
         field_name: &str,
@@ -595,7 +743,11 @@ impl Connector {
             )
         };
 
-        // Check if the function call was successful
+        // Check if the function call was successful; unset optional members
+        // report `DDS_RETCODE_NO_DATA`
+        if result == DDSError::NoData as c_int {
+            return Err(ConnectorError::NoData);
+        }
         if result != 0 {
             return Err(ConnectorError::FfiError);
         }
@@ -606,7 +758,7 @@ impl Connector {
 
     /// Safe wrapper for `RTI_Connector_get_string_from_sample`
     pub fn get_string_from_sample(
-        &mut self,
+        &self,
         entity_name: &str,
         index: i32,
         field_name: &str,
@@ -636,7 +788,11 @@ impl Connector {
             )
         };
 
-        // Check if the function call was successful
+        // Check if the function call was successful; unset optional members
+        // report `DDS_RETCODE_NO_DATA`
+        if result == DDSError::NoData as c_int {
+            return Err(ConnectorError::NoData);
+        }
         if result != 0 {
             return Err(ConnectorError::FfiError);
         }
@@ -713,7 +869,7 @@ impl Connector {
 
     /// Safe wrapper for `RTI_Connector_get_datawriter`
     pub fn get_dynamic_datawriter(
-        &mut self,
+        &self,
         entity_name: &str,
     ) -> Result<DynamicDataWriter, ConnectorError> {
         // Ensure the connector is not None (null pointer check)
@@ -819,10 +975,17 @@ impl Connector {
         Ok(())
     }
 
+    /// Returns the message of the last error raised by the C library, or an
+    /// empty string when there is none.
     pub fn get_last_error() -> String {
         unsafe {
             let result = RTI_Connector_get_last_error_message();
-            String::from(CString::from_raw(result).to_str().unwrap())
+            if result.is_null() {
+                return String::new();
+            }
+            let message = CStr::from_ptr(result).to_string_lossy().into_owned();
+            RTI_Connector_free_string(result);
+            message
         }
     }
 
@@ -851,6 +1014,11 @@ impl DynamicDataReader<'_> {
         let result = unsafe {
             RTI_Connector_wait_for_data_on_reader(self.data_reader, timeout.as_millis() as c_int)
         };
+        // No data within the timeout is `DDS_RETCODE_TIMEOUT`, unlike a
+        // failure of the wait.
+        if result == DDSError::Timeout as c_int {
+            return Err(ConnectorError::Timeout);
+        }
         if result != 0 {
             Err(ConnectorError::FfiError)
         } else {
@@ -922,6 +1090,12 @@ pub struct DynamicDataWriter<'a> {
 }
 
 impl DynamicDataWriter<'_> {
+    /// The native `DDS_DynamicDataWriter`, for the `RTI_Connector_*`
+    /// functions that take a writer.
+    pub fn as_ptr(&self) -> *mut c_void {
+        self.data_writer
+    }
+
     /// Safe wrapper for `RTI_Connector_wait_for_acknowledgments`
     pub fn wait_for_acknowledgments(&self, timeout: Duration) -> Result<i32, &'static str> {
         // Call the unsafe FFI function
@@ -995,7 +1169,10 @@ impl DynamicDataWriter<'_> {
 
 impl Drop for Connector {
     fn drop(&mut self) {
-        self.delete();
+        // A connector that failed to be created has nothing to delete.
+        if !self.connector.is_null() {
+            self.delete();
+        }
     }
 }
 
@@ -1027,4 +1204,8 @@ pub enum ConnectorError {
     MemberNotFound,
     #[error("Error: Invalid String")]
     InvalidString,
+    #[error("Error: No Data")]
+    NoData,
+    #[error("Error: Timeout")]
+    Timeout,
 }
@@ -0,0 +1,669 @@
+//! Recording of received samples to files, for offline analysis.
+//!
+//! A recording is a sequence of JSON-lines files. Each file starts with a
+//! header naming the format and describing the recorded readers — their topic,
+//! type name and the `<types>` XML of that type — so a file can be read
+//! without the configuration it was recorded with. Every following line is a
+//! [`Record`]: one sample with its infos and timestamps, and its data as JSON,
+//! as bytes, or nothing for the samples that only notify a change of instance
+//! state. Samples are recorded as JSON, or as bytes when the [`Recorder`] has
+//! an [`Encoder`], such as one writing CDR with `connector_dds::cdr`.
+//!
+//! A [`Recorder`] starts a new file when the current one reaches the size or
+//! age of its [`Rotation`]. The files are named after the first one:
+//! `capture.jsonl`, then `capture.1.jsonl`, `capture.2.jsonl`, ..., and a
+//! [`Recording`] reads them back in that order.
+//!
+//! ```no_run
+//! use rticonnector::config::Config;
+//! use rticonnector::recorder::{RecordedTopic, Recorder, Recording, Rotation};
+//! use rticonnector::Connector;
+//! use serde_json::Value;
+//!
+//! let participant = "MyParticipantLibrary::MySubParticipant";
+//! let reader = "MySubscriber::MySquareReader";
+//! let config = Config::from_file("ShapeExample.xml").unwrap();
+//! let topic = RecordedTopic::from_config(&config, participant, reader).unwrap();
+//!
+//! let connector = Connector::from_config(participant, &config, &[]);
+//! let input = connector.get_input::<Value>(reader).unwrap();
+//! let rotation = Rotation::default().with_max_bytes(64 << 20);
+//! let mut recorder = Recorder::create("capture.jsonl", vec![topic], rotation).unwrap();
+//! for _ in 0..10 {
+//!     if input.wait(std::time::Duration::from_secs(1)).is_ok() {
+//!         input.take().unwrap();
+//!         recorder.record(&input).unwrap();
+//!     }
+//! }
+//! recorder.flush().unwrap();
+//!
+//! for record in Recording::open("capture.jsonl").unwrap().records() {
+//!     let record = record.unwrap();
+//!     println!("{} {:?}", record.topic, record.source_timestamp);
+//! }
+//! ```
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::config::types::Types;
+use crate::config::{Config, ConfigError};
+use crate::typed::Input;
+use crate::{Connector, ConnectorError};
+
+/// The `format` of the header line of every recording file.
+pub const FORMAT: &str = "rticonnector-recording";
+
+/// The version of the format written by [`Recorder`].
+pub const VERSION: u64 = 1;
+
+/// The sample infos stored in [`Record::info`], when the C library provides
+/// them; `valid_data` is always stored.
+pub const INFOS: [&str; 6] = [
+    "source_timestamp",
+    "reception_timestamp",
+    "sample_state",
+    "view_state",
+    "instance_state",
+    "sample_identity",
+];
+
+/// Error returned by [`Recorder`] and [`Recording`].
+#[derive(Debug, Error)]
+pub enum RecordError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Connector(#[from] ConnectorError),
+    #[error("{}: already exists", .0.display())]
+    Exists(PathBuf),
+    #[error("no participant `{0}` in the configuration")]
+    UnknownParticipant(String),
+    #[error("no reader `{0}` in the participant")]
+    UnknownReader(String),
+    #[error("the topic of reader `{0}` has no type in the configuration")]
+    UnknownType(String),
+    #[error("reader `{0}` is not recorded")]
+    NotRecorded(String),
+    #[error("`{reader}`: cannot encode the sample: {message}")]
+    Encode { reader: String, message: String },
+    #[error("`{reader}` returned a sample that is not JSON: {source}")]
+    Sample {
+        reader: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("{}:{line}: {message}", path.display())]
+    Format {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+/// A recorded reader, described in the header of every file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedTopic {
+    /// The reader, as `"Subscriber::Reader"`.
+    pub reader: String,
+    pub topic: String,
+    /// The qualified name of the type of the topic.
+    pub type_name: String,
+    /// The type and every definition it refers to.
+    pub types: Types,
+}
+
+impl RecordedTopic {
+    /// Describes the reader `reader` of `participant`
+    /// (`"Library::Participant"`) from the configuration it is created with.
+    pub fn from_config(
+        config: &Config,
+        participant: &str,
+        reader: &str,
+    ) -> Result<Self, RecordError> {
+        let participant_model = config
+            .participant(participant)
+            .ok_or_else(|| RecordError::UnknownParticipant(participant.to_string()))?;
+        let (_, data_reader) = participant_model
+            .readers()
+            .find(|(name, _)| name == reader)
+            .ok_or_else(|| RecordError::UnknownReader(reader.to_string()))?;
+        let type_name = config
+            .topic_type(participant_model, &data_reader.topic_ref)
+            .ok_or_else(|| RecordError::UnknownType(reader.to_string()))?;
+        Ok(Self {
+            reader: reader.to_string(),
+            topic: data_reader.topic_ref.clone(),
+            type_name: type_name.to_string(),
+            types: config.types.subset(type_name),
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "reader": self.reader,
+            "topic": self.topic,
+            "type": self.type_name,
+            "types": self.types.to_xml(),
+        })
+    }
+
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let field = |name: &str| {
+            value[name]
+                .as_str()
+                .ok_or_else(|| format!("topic without a `{}`", name))
+        };
+        let types = format!("<dds>{}</dds>", field("types")?);
+        Ok(Self {
+            reader: field("reader")?.to_string(),
+            topic: field("topic")?.to_string(),
+            type_name: field("type")?.to_string(),
+            types: Types::from_xml(&types).map_err(|e: ConfigError| e.to_string())?,
+        })
+    }
+}
+
+/// The data of a [`Record`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Payload {
+    /// The sample as returned by [`Connector::get_json_sample`].
+    Json(Value),
+    /// A serialized sample, stored in base64.
+    Binary(Vec<u8>),
+    /// A sample without valid data.
+    None,
+}
+
+/// One recorded sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    /// The reader the sample was received on, as `"Subscriber::Reader"`.
+    pub reader: String,
+    pub topic: String,
+    /// Nanoseconds since the Unix epoch, when the C library provides it.
+    pub reception_timestamp: Option<i64>,
+    /// Nanoseconds since the Unix epoch, when the C library provides it.
+    pub source_timestamp: Option<i64>,
+    /// The [`INFOS`] of the sample, and `valid_data`.
+    pub info: Map<String, Value>,
+    pub payload: Payload,
+}
+
+impl Record {
+    fn to_json(&self) -> Value {
+        let mut record = Map::new();
+        record.insert("reader".to_string(), json!(self.reader));
+        record.insert("topic".to_string(), json!(self.topic));
+        record.insert(
+            "reception_timestamp".to_string(),
+            json!(self.reception_timestamp),
+        );
+        record.insert("source_timestamp".to_string(), json!(self.source_timestamp));
+        record.insert("info".to_string(), Value::Object(self.info.clone()));
+        match &self.payload {
+            Payload::Json(data) => record.insert("data".to_string(), data.clone()),
+            Payload::Binary(bytes) => {
+                record.insert("binary".to_string(), json!(base64_encode(bytes)))
+            }
+            Payload::None => record.insert("data".to_string(), Value::Null),
+        };
+        Value::Object(record)
+    }
+
+    fn from_json(value: Value) -> Result<Self, String> {
+        let Value::Object(mut record) = value else {
+            return Err("a record must be an object".to_string());
+        };
+        let mut string = |name: &str| match record.remove(name) {
+            Some(Value::String(s)) => Ok(s),
+            _ => Err(format!("record without a `{}`", name)),
+        };
+        let reader = string("reader")?;
+        let topic = string("topic")?;
+        let timestamp = |value: Option<&Value>| value.and_then(Value::as_i64);
+        let payload = match (record.remove("data"), record.remove("binary")) {
+            (_, Some(Value::String(binary))) => Payload::Binary(
+                base64_decode(&binary).ok_or_else(|| "`binary` is not base64".to_string())?,
+            ),
+            (Some(Value::Null) | None, None) => Payload::None,
+            (Some(data), None) => Payload::Json(data),
+            (_, Some(_)) => return Err("`binary` must be a string".to_string()),
+        };
+        Ok(Self {
+            reader,
+            topic,
+            reception_timestamp: timestamp(record.get("reception_timestamp")),
+            source_timestamp: timestamp(record.get("source_timestamp")),
+            info: match record.remove("info") {
+                Some(Value::Object(info)) => info,
+                _ => Map::new(),
+            },
+            payload,
+        })
+    }
+}
+
+/// When a [`Recorder`] starts a new file. Both limits are unset by default:
+/// everything is recorded to one file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rotation {
+    pub max_bytes: Option<u64>,
+    pub max_duration: Option<Duration>,
+}
+
+impl Rotation {
+    /// Starts a new file rather than growing the current one past
+    /// `max_bytes`. A file always holds at least one record.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Starts a new file for the records that come `max_duration` after the
+    /// current one was created.
+    pub fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+}
+
+/// Encodes the JSON data of a sample of a recorded topic, for the
+/// [`Payload::Binary`] records of a [`Recorder`].
+pub type Encoder = Box<dyn FnMut(&RecordedTopic, &Value) -> Result<Vec<u8>, String>>;
+
+/// Appends records to a recording, rotating its files.
+pub struct Recorder {
+    path: PathBuf,
+    topics: Vec<RecordedTopic>,
+    rotation: Rotation,
+    encoder: Option<Encoder>,
+    file: BufWriter<File>,
+    /// The index of the current file; `0` for the first one.
+    index: usize,
+    bytes: u64,
+    records: usize,
+    created: Instant,
+}
+
+impl Recorder {
+    /// Creates the first file of a recording of `topics` at `path`. Neither
+    /// it nor the files it would rotate to may exist, so an earlier
+    /// recording is never mixed with this one.
+    pub fn create(
+        path: impl AsRef<Path>,
+        topics: Vec<RecordedTopic>,
+        rotation: Rotation,
+    ) -> Result<Self, RecordError> {
+        let path = path.as_ref().to_path_buf();
+        if path.exists() || file_path(&path, 1).exists() {
+            return Err(RecordError::Exists(path));
+        }
+        let (file, bytes) = open(&path, &topics, 0)?;
+        Ok(Self {
+            path,
+            topics,
+            rotation,
+            encoder: None,
+            file,
+            index: 0,
+            bytes,
+            records: 0,
+            created: Instant::now(),
+        })
+    }
+
+    /// Stores the JSON data of the records appended from now on as bytes,
+    /// encoded by `encoder`.
+    pub fn with_encoder(mut self, encoder: Encoder) -> Self {
+        self.encoder = Some(encoder);
+        self
+    }
+
+    /// The file records are currently appended to.
+    pub fn current_path(&self) -> PathBuf {
+        file_path(&self.path, self.index)
+    }
+
+    /// Appends the samples loaded by the last [`Input::read`] or
+    /// [`Input::take`] of `input`, with their infos. Returns the number of
+    /// records appended.
+    pub fn record<T: DeserializeOwned>(
+        &mut self,
+        input: &Input<'_, T>,
+    ) -> Result<usize, RecordError> {
+        let (connector, reader) = (input.connector(), input.name());
+        let topic = self
+            .topics
+            .iter()
+            .find(|t| t.reader == reader)
+            .ok_or_else(|| RecordError::NotRecorded(reader.to_string()))?
+            .topic
+            .clone();
+        let count = connector
+            .get_sample_count(reader)
+            .map_err(|_| ConnectorError::FfiError)? as usize;
+        for index in 1..=count {
+            let record = sample_record(connector, reader, &topic, index)?;
+            self.append(&record)?;
+        }
+        Ok(count)
+    }
+
+    /// Appends one record, starting a new file first if the current one
+    /// reached its [`Rotation`] limits. With an [`Encoder`], JSON data is
+    /// stored encoded.
+    pub fn append(&mut self, record: &Record) -> Result<(), RecordError> {
+        let topic = self
+            .topics
+            .iter()
+            .find(|t| t.reader == record.reader)
+            .ok_or_else(|| RecordError::NotRecorded(record.reader.clone()))?;
+        let mut line = match (&mut self.encoder, &record.payload) {
+            (Some(encoder), Payload::Json(data)) => {
+                let bytes = encoder(topic, data).map_err(|message| RecordError::Encode {
+                    reader: record.reader.clone(),
+                    message,
+                })?;
+                let record = Record {
+                    payload: Payload::Binary(bytes),
+                    ..record.clone()
+                };
+                record.to_json().to_string()
+            }
+            _ => record.to_json().to_string(),
+        };
+        line.push('\n');
+        let full = self
+            .rotation
+            .max_bytes
+            .is_some_and(|max_bytes| self.bytes + line.len() as u64 > max_bytes);
+        let old = self
+            .rotation
+            .max_duration
+            .is_some_and(|max_duration| self.created.elapsed() >= max_duration);
+        if self.records > 0 && (full || old) {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.bytes += line.len() as u64;
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Writes the buffered records to the current file.
+    pub fn flush(&mut self) -> Result<(), RecordError> {
+        Ok(self.file.flush()?)
+    }
+
+    fn rotate(&mut self) -> Result<(), RecordError> {
+        self.file.flush()?;
+        let (file, bytes) = open(&self.path, &self.topics, self.index + 1)?;
+        self.file = file;
+        self.index += 1;
+        self.bytes = bytes;
+        self.records = 0;
+        self.created = Instant::now();
+        Ok(())
+    }
+}
+
+/// The path of the file `index` of the recording starting at `path`:
+/// `capture.jsonl`, `capture.1.jsonl`, ...
+fn file_path(path: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, index, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, index),
+    };
+    path.with_file_name(name)
+}
+
+/// Creates the file `index` and writes its header.
+fn open(
+    path: &Path,
+    topics: &[RecordedTopic],
+    index: usize,
+) -> Result<(BufWriter<File>, u64), RecordError> {
+    let path = file_path(path, index);
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => RecordError::Exists(path.clone()),
+            _ => RecordError::Io(e),
+        })?;
+    let header = json!({
+        "format": FORMAT,
+        "version": VERSION,
+        "index": index,
+        "topics": topics.iter().map(RecordedTopic::to_json).collect::<Vec<_>>(),
+    });
+    let mut line = header.to_string();
+    line.push('\n');
+    let mut file = BufWriter::new(file);
+    file.write_all(line.as_bytes())?;
+    Ok((file, line.len() as u64))
+}
+
+/// Reads the sample `index` (1-based) loaded on `reader`.
+fn sample_record(
+    connector: &Connector,
+    reader: &str,
+    topic: &str,
+    index: usize,
+) -> Result<Record, RecordError> {
+    let valid_data = connector
+        .get_boolean_from_infos(reader, index, "valid_data")
+        .map_err(|_| ConnectorError::FieldNotFound)?;
+    let payload = if valid_data {
+        let json = connector.get_json_sample(reader, index)?;
+        Payload::Json(
+            serde_json::from_str(&json).map_err(|source| RecordError::Sample {
+                reader: reader.to_string(),
+                source,
+            })?,
+        )
+    } else {
+        Payload::None
+    };
+    let mut info = Map::new();
+    info.insert("valid_data".to_string(), Value::Bool(valid_data));
+    for field in INFOS {
+        let value = connector
+            .get_json_from_infos(reader, index, field)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok());
+        if let Some(value) = value {
+            info.insert(field.to_string(), value);
+        }
+    }
+    Ok(Record {
+        reader: reader.to_string(),
+        topic: topic.to_string(),
+        reception_timestamp: info.get("reception_timestamp").and_then(Value::as_i64),
+        source_timestamp: info.get("source_timestamp").and_then(Value::as_i64),
+        info,
+        payload,
+    })
+}
+
+/// The files of a recording, read back in order.
+pub struct Recording {
+    paths: Vec<PathBuf>,
+    topics: Vec<RecordedTopic>,
+}
+
+impl Recording {
+    /// Opens the recording whose first file is `path`, along with the files
+    /// it was rotated to.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RecordError> {
+        let path = path.as_ref();
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        let topics = read_header(path, &mut lines)?;
+        let paths = (0..)
+            .map(|index| file_path(path, index))
+            .take_while(|path| path.exists())
+            .collect();
+        Ok(Self { paths, topics })
+    }
+
+    /// The files of the recording, in order.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// The recorded readers, from the header of the first file.
+    pub fn topics(&self) -> &[RecordedTopic] {
+        &self.topics
+    }
+
+    pub fn topic(&self, reader: &str) -> Option<&RecordedTopic> {
+        self.topics.iter().find(|t| t.reader == reader)
+    }
+
+    /// Iterates over the records of every file. An error ends the iteration.
+    pub fn records(&self) -> Records {
+        Records {
+            paths: self.paths.clone().into_iter(),
+            current: None,
+            failed: false,
+        }
+    }
+}
+
+/// The iterator returned by [`Recording::records`].
+pub struct Records {
+    paths: std::vec::IntoIter<PathBuf>,
+    /// The file being read, its path and the number of its last line read.
+    current: Option<(PathBuf, usize, Lines<BufReader<File>>)>,
+    failed: bool,
+}
+
+impl Records {
+    fn next_record(&mut self) -> Result<Option<Record>, RecordError> {
+        loop {
+            let Some((path, number, lines)) = &mut self.current else {
+                let Some(path) = self.paths.next() else {
+                    return Ok(None);
+                };
+                let mut lines = BufReader::new(File::open(&path)?).lines();
+                read_header(&path, &mut lines)?;
+                self.current = Some((path, 1, lines));
+                continue;
+            };
+            let Some(line) = lines.next() else {
+                self.current = None;
+                continue;
+            };
+            *number += 1;
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let error = |message: String| RecordError::Format {
+                path: path.clone(),
+                line: *number,
+                message,
+            };
+            let value = serde_json::from_str(&line).map_err(|e| error(e.to_string()))?;
+            return Record::from_json(value).map(Some).map_err(error);
+        }
+    }
+}
+
+impl Iterator for Records {
+    type Item = Result<Record, RecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let record = self.next_record().transpose();
+        self.failed = matches!(record, Some(Err(_)));
+        record
+    }
+}
+
+/// Reads and checks the header line of the file at `path`.
+fn read_header(
+    path: &Path,
+    lines: &mut Lines<BufReader<File>>,
+) -> Result<Vec<RecordedTopic>, RecordError> {
+    let error = |message: String| RecordError::Format {
+        path: path.to_path_buf(),
+        line: 1,
+        message,
+    };
+    let line = lines
+        .next()
+        .transpose()?
+        .ok_or_else(|| error("empty file".to_string()))?;
+    let header: Value = serde_json::from_str(&line).map_err(|e| error(e.to_string()))?;
+    if header["format"] != FORMAT {
+        return Err(error(format!("not a `{}` file", FORMAT)));
+    }
+    match header["version"].as_u64() {
+        Some(VERSION) => {}
+        version => return Err(error(format!("unsupported version {:?}", version))),
+    }
+    header["topics"]
+        .as_array()
+        .ok_or_else(|| error("header without `topics`".to_string()))?
+        .iter()
+        .map(|topic| RecordedTopic::from_json(topic).map_err(error))
+        .collect()
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard base64, with padding.
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn base64_decode(encoded: &str) -> Option<Vec<u8>> {
+    let encoded = encoded.as_bytes();
+    if !encoded.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(encoded.len() / 4 * 3);
+    for (n, chunk) in encoded.chunks(4).enumerate() {
+        let last = n == encoded.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return None;
+        }
+        let mut group = 0u32;
+        for &c in &chunk[..4 - padding] {
+            let value = BASE64.iter().position(|&b| b == c)? as u32;
+            group = group << 6 | value;
+        }
+        group <<= 6 * padding as u32;
+        bytes.extend_from_slice(&group.to_be_bytes()[1..4 - padding]);
+    }
+    Some(bytes)
+}
@@ -0,0 +1,247 @@
+//! Replay of a [`Recording`] through data writers.
+//!
+//! A [`Replayer`] writes the JSON records of the topics it has an [`Output`],
+//! or another [`Sink`], for, keeping the time between samples as it was received, divided by a
+//! speed factor. The records of the other topics are left out, as are the
+//! records without JSON data: binary payloads cannot be written through the
+//! Connector API, and samples without valid data carry no instance to
+//! dispose.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use rticonnector::recorder::Recording;
+//! use rticonnector::replay::{Replayer, SourceTimestamps};
+//! use rticonnector::Connector;
+//!
+//! let connector = Connector::new(
+//!     "MyParticipantLibrary::MyPubParticipant",
+//!     "ShapeExample.xml",
+//!     &[],
+//! );
+//! let output = connector.get_output("MyPublisher::MySquareWriter").unwrap();
+//! let recording = Recording::open("capture.jsonl").unwrap();
+//! let replayer = Replayer::new()
+//!     .with_output("Square", output)
+//!     .with_speed(2.0)
+//!     .with_start(Duration::from_secs(60))
+//!     .with_source_timestamps(SourceTimestamps::Now);
+//! let stats = replayer.replay(&recording).unwrap();
+//! println!("{} written, {} skipped", stats.written, stats.skipped);
+//! ```
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::recorder::{Payload, Record, RecordError, Recording};
+use crate::typed::{self, Output};
+
+/// Error returned by [`Replayer::replay`].
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error(transparent)]
+    Record(#[from] RecordError),
+    #[error("`{writer}`: {source}")]
+    Write {
+        writer: String,
+        #[source]
+        source: typed::Error,
+    },
+    #[error("the speed must be a positive number, not {0}")]
+    InvalidSpeed(f64),
+}
+
+/// The source timestamp of the replayed samples.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SourceTimestamps {
+    /// The source timestamp of the recorded sample, when it has one.
+    #[default]
+    Original,
+    /// The time the sample is replayed, as set by the writer.
+    Now,
+}
+
+/// What a [`Replayer::replay`] did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    /// The samples written, over every loop.
+    pub written: usize,
+    /// The records in the time range of a replayed topic that were not
+    /// written, because they have no JSON data.
+    pub skipped: usize,
+}
+
+/// Where a [`Replayer`] writes the samples of a topic: an [`Output`], or
+/// anything else that takes JSON samples.
+pub trait Sink {
+    /// The name [`ReplayError::Write`] reports.
+    fn name(&self) -> &str;
+
+    /// Writes `data`, with the source timestamp `source_timestamp` when
+    /// given.
+    fn write(&self, data: &Value, source_timestamp: Option<i64>) -> Result<(), typed::Error>;
+}
+
+impl Sink for Output<'_, Value> {
+    fn name(&self) -> &str {
+        Output::name(self)
+    }
+
+    fn write(&self, data: &Value, source_timestamp: Option<i64>) -> Result<(), typed::Error> {
+        match source_timestamp {
+            Some(source_timestamp) => self.write_at(data, source_timestamp),
+            None => Output::write(self, data),
+        }
+    }
+}
+
+/// Writes the records of a recording with their original timing.
+pub struct Replayer<'a> {
+    /// The sink of each replayed topic.
+    sinks: Vec<(String, Box<dyn Sink + 'a>)>,
+    speed: f64,
+    start: Option<Duration>,
+    end: Option<Duration>,
+    looping: bool,
+    source_timestamps: SourceTimestamps,
+}
+
+impl Default for Replayer<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Replayer<'a> {
+    /// A replayer at the original speed, of the whole recording, once, that
+    /// has no topic to replay yet.
+    pub fn new() -> Self {
+        Self {
+            sinks: Vec::new(),
+            speed: 1.0,
+            start: None,
+            end: None,
+            looping: false,
+            source_timestamps: SourceTimestamps::default(),
+        }
+    }
+
+    /// Replays the records of `topic` with `output`; the writer of `output`
+    /// must have a type compatible with the recorded one.
+    pub fn with_output(self, topic: &str, output: Output<'a, Value>) -> Self {
+        self.with_sink(topic, output)
+    }
+
+    /// Replays the records of `topic` with `sink`.
+    pub fn with_sink(mut self, topic: &str, sink: impl Sink + 'a) -> Self {
+        self.sinks.retain(|(t, _)| t != topic);
+        self.sinks.push((topic.to_string(), Box::new(sink)));
+        self
+    }
+
+    /// Divides the time between samples by `speed`: `2.0` replays twice as
+    /// fast as recorded.
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Skips the records received less than `start` after the first record
+    /// of the recording.
+    pub fn with_start(mut self, start: Duration) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Skips the records received `end` or more after the first record of
+    /// the recording.
+    pub fn with_end(mut self, end: Duration) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// Replays the recording again from `start` once it is exhausted, until
+    /// an error occurs.
+    pub fn with_loop(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn with_source_timestamps(mut self, source_timestamps: SourceTimestamps) -> Self {
+        self.source_timestamps = source_timestamps;
+        self
+    }
+
+    /// Writes the records of `recording`, sleeping until each one is due.
+    /// Returns once the recording, or its time range, is exhausted; never
+    /// when looping, unless writing fails.
+    pub fn replay(&self, recording: &Recording) -> Result<ReplayStats, ReplayError> {
+        if !(self.speed > 0.0 && self.speed.is_finite()) {
+            return Err(ReplayError::InvalidSpeed(self.speed));
+        }
+        let mut stats = ReplayStats::default();
+        loop {
+            let written = stats.written;
+            self.replay_once(recording, &mut stats)?;
+            // A loop that writes nothing would spin without ever sleeping.
+            if !self.looping || stats.written == written {
+                return Ok(stats);
+            }
+        }
+    }
+
+    fn replay_once(
+        &self,
+        recording: &Recording,
+        stats: &mut ReplayStats,
+    ) -> Result<(), ReplayError> {
+        let started = Instant::now();
+        let start = self.start.unwrap_or_default();
+        // The time of the first record, from which the range is measured.
+        let mut first = None;
+        // The offset of the last record with a timestamp, used for the
+        // records without one.
+        let mut offset = Duration::ZERO;
+        for record in recording.records() {
+            let record = record?;
+            if let Some(time) = time(&record) {
+                let first = *first.get_or_insert(time);
+                offset = Duration::from_nanos(time.saturating_sub(first).max(0) as u64);
+            }
+            let Some((_, sink)) = self.sinks.iter().find(|(t, _)| *t == record.topic) else {
+                continue;
+            };
+            if offset < start || self.end.is_some_and(|end| offset >= end) {
+                continue;
+            }
+            let Payload::Json(data) = &record.payload else {
+                stats.skipped += 1;
+                continue;
+            };
+            let due = started + (offset - start).div_f64(self.speed);
+            let now = Instant::now();
+            if due > now {
+                thread::sleep(due - now);
+            }
+            let source_timestamp = match self.source_timestamps {
+                SourceTimestamps::Original => record.source_timestamp,
+                SourceTimestamps::Now => None,
+            };
+            sink.write(data, source_timestamp)
+                .map_err(|source| ReplayError::Write {
+                    writer: sink.name().to_string(),
+                    source,
+                })?;
+            stats.written += 1;
+        }
+        Ok(())
+    }
+}
+
+/// When the sample of `record` was received, or sent when that is unknown.
+fn time(record: &Record) -> Option<i64> {
+    record.reception_timestamp.or(record.source_timestamp)
+}
@@ -0,0 +1,549 @@
+//! A serde [`Serializer`] that writes a value straight into a writer instance.
+//!
+//! Writing a struct through [`Connector::set_json_instance`] means rendering
+//! it to JSON and having the C library parse it back. The
+//! [`SampleSerializer`] instead sets each member with
+//! `set_number_into_samples`, `set_string_into_samples` and
+//! `set_boolean_into_samples`, building the field name (`"pos.x"`,
+//! `"points[1].y"`, ...) from the struct and sequence nesting.
+//!
+//! Unit enum variants are written as the value of the enumerator with the same
+//! name, looked up with [`Connector::enumeration`]; enums deriving `DdsType`
+//! serialize themselves as that value already. `None` clears the optional
+//! member, and sequences are cleared before their elements are set so no
+//! element of a previous, longer value is left behind.
+//!
+//! Connector numbers are doubles, so 64-bit integers beyond ±2^53 are
+//! rejected rather than rounded.
+//!
+//! The serializer sets the members through the [`Instance`] trait, which
+//! [`WriterInstance`] implements over a data writer of a [`Connector`].
+
+use std::fmt;
+use std::sync::Arc;
+
+use serde::ser::{self, Impossible, Serialize};
+use serde::Serializer;
+use thiserror::Error;
+
+use crate::config::types::Enum;
+use crate::{Connector, ConnectorError};
+
+/// The largest magnitude up to which a double holds every integer.
+const MAX_EXACT_INTEGER: u64 = 1 << 53;
+
+/// Sets `value` into the instance of the writer `entity_name`.
+///
+/// Members that the Rust value does not have keep their current value.
+pub fn to_instance<T>(connector: &Connector, entity_name: &str, value: &T) -> Result<(), Error>
+where
+    T: Serialize + ?Sized,
+{
+    let mut serializer = SampleSerializer::new(connector, entity_name);
+    value.serialize(&mut serializer)
+}
+
+/// Error returned while setting a value into an instance, with the field it
+/// occurred on.
+#[derive(Debug)]
+pub struct Error {
+    field: Option<String>,
+    kind: ErrorKind,
+}
+
+#[derive(Debug, Error)]
+pub enum ErrorKind {
+    /// The Connector rejected the member, typically because the DDS type has
+    /// no member with that name or its kind does not match the Rust value.
+    #[error("rejected by the DDS type: {message}")]
+    Rejected {
+        #[source]
+        source: ConnectorError,
+        /// The last error reported by the C library.
+        message: String,
+    },
+    #[error("{0} cannot be represented exactly as a DDS number")]
+    InvalidNumber(String),
+    /// The member is not a DDS enum, or the configuration could not be
+    /// parsed to find out.
+    #[error("variant `{variant}` of `{name}` needs a DDS enum member")]
+    NotAnEnum {
+        name: &'static str,
+        variant: &'static str,
+    },
+    #[error("the DDS enum has no enumerator `{0}`")]
+    UnknownEnumerator(&'static str),
+    #[error("{0} cannot be mapped to a DDS member")]
+    Unsupported(&'static str),
+    #[error("{0}")]
+    Custom(String),
+}
+
+impl Error {
+    /// The field name of the offending member, if the error is tied to one.
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "field `{}`: {}", field, self.kind),
+            None => self.kind.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.kind.source()
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self {
+            field: None,
+            kind: ErrorKind::Custom(msg.to_string()),
+        }
+    }
+}
+
+/// The instance of a data writer whose members are set by their field name
+/// (`"pos.x"`, `"points[1].y"`).
+pub trait Instance {
+    fn set_number(&self, field: &str, value: f64) -> Result<(), ConnectorError>;
+    fn set_string(&self, field: &str, value: &str) -> Result<(), ConnectorError>;
+    fn set_boolean(&self, field: &str, value: bool) -> Result<(), ConnectorError>;
+    /// Unsets the optional member `field`, or empties the sequence `field`.
+    fn clear(&self, field: &str) -> Result<(), ConnectorError>;
+    /// Whether the elements of a sequence are addressed starting at `1`.
+    fn one_based_sequence_indexing(&self) -> bool;
+    /// The DDS enum of the member `field`, if it is one.
+    fn enumeration(&self, field: &str) -> Option<Arc<Enum>>;
+}
+
+/// The instance of the writer `entity_name` of a [`Connector`].
+pub struct WriterInstance<'a> {
+    connector: &'a Connector,
+    entity_name: &'a str,
+}
+
+impl<'a> WriterInstance<'a> {
+    pub fn new(connector: &'a Connector, entity_name: &'a str) -> Self {
+        Self {
+            connector,
+            entity_name,
+        }
+    }
+}
+
+impl Instance for WriterInstance<'_> {
+    fn set_number(&self, field: &str, value: f64) -> Result<(), ConnectorError> {
+        self.connector
+            .set_number_into_samples(self.entity_name, field, value)
+    }
+
+    fn set_string(&self, field: &str, value: &str) -> Result<(), ConnectorError> {
+        self.connector
+            .set_string_into_samples(self.entity_name, field, value)
+    }
+
+    fn set_boolean(&self, field: &str, value: bool) -> Result<(), ConnectorError> {
+        self.connector
+            .set_boolean_into_samples(self.entity_name, field, value)
+    }
+
+    fn clear(&self, field: &str) -> Result<(), ConnectorError> {
+        self.connector.clear_member(self.entity_name, field)
+    }
+
+    fn one_based_sequence_indexing(&self) -> bool {
+        self.connector.one_based_sequence_indexing()
+    }
+
+    fn enumeration(&self, field: &str) -> Option<Arc<Enum>> {
+        self.connector.enumeration(self.entity_name, field)
+    }
+}
+
+/// Serializer into an instance, such as a [`WriterInstance`].
+pub struct SampleSerializer<I> {
+    instance: I,
+    /// Field name of the member currently being set; empty at the root.
+    path: String,
+}
+
+impl<'a> SampleSerializer<WriterInstance<'a>> {
+    pub fn new(connector: &'a Connector, entity_name: &'a str) -> Self {
+        Self::from_instance(WriterInstance::new(connector, entity_name))
+    }
+}
+
+impl<I: Instance> SampleSerializer<I> {
+    pub fn from_instance(instance: I) -> Self {
+        Self {
+            instance,
+            path: String::new(),
+        }
+    }
+
+    /// The instance the members were set into.
+    pub fn into_instance(self) -> I {
+        self.instance
+    }
+
+    fn error(&self, kind: ErrorKind) -> Error {
+        Error {
+            field: (!self.path.is_empty()).then(|| self.path.clone()),
+            kind,
+        }
+    }
+
+    fn rejected(&self, source: ConnectorError) -> Error {
+        self.error(ErrorKind::Rejected {
+            source,
+            message: Connector::get_last_error(),
+        })
+    }
+
+    fn number(&self, value: f64) -> Result<(), Error> {
+        self.instance
+            .set_number(&self.path, value)
+            .map_err(|e| self.rejected(e))
+    }
+
+    fn string(&self, value: &str) -> Result<(), Error> {
+        self.instance
+            .set_string(&self.path, value)
+            .map_err(|e| self.rejected(e))
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        self.instance
+            .clear(&self.path)
+            .map_err(|e| self.rejected(e))
+    }
+
+    fn element_path(&mut self, index: usize) {
+        let base = if self.instance.one_based_sequence_indexing() {
+            1
+        } else {
+            0
+        };
+        self.path.push_str(&format!("[{}]", index + base));
+    }
+
+    fn member_path(&mut self, name: &str) {
+        if !self.path.is_empty() {
+            self.path.push('.');
+        }
+        self.path.push_str(name);
+    }
+
+    fn compound(&mut self) -> Compound<'_, I> {
+        Compound {
+            parent_len: self.path.len(),
+            ser: self,
+            next: 0,
+            key: None,
+        }
+    }
+}
+
+impl<'s, I: Instance> Serializer for &'s mut SampleSerializer<I> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'s, I>;
+    type SerializeTuple = Compound<'s, I>;
+    type SerializeTupleStruct = Compound<'s, I>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Compound<'s, I>;
+    type SerializeStruct = Compound<'s, I>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.instance
+            .set_boolean(&self.path, v)
+            .map_err(|e| self.rejected(e))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.number(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.number(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.number(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        if v.unsigned_abs() > MAX_EXACT_INTEGER {
+            return Err(self.error(ErrorKind::InvalidNumber(v.to_string())));
+        }
+        self.number(v as f64)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.number(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.number(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.number(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        if v > MAX_EXACT_INTEGER {
+            return Err(self.error(ErrorKind::InvalidNumber(v.to_string())));
+        }
+        self.number(v as f64)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.number(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.number(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.string(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.string(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        let mut seq = self.serialize_seq(Some(v.len()))?;
+        for byte in v {
+            ser::SerializeSeq::serialize_element(&mut seq, byte)?;
+        }
+        ser::SerializeSeq::end(seq)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.clear()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        let enumeration = self
+            .instance
+            .enumeration(&self.path)
+            .ok_or_else(|| self.error(ErrorKind::NotAnEnum { name, variant }))?;
+        let enumerator = enumeration
+            .enumerators
+            .iter()
+            .find(|e| e.name == variant)
+            .ok_or_else(|| self.error(ErrorKind::UnknownEnumerator(variant)))?;
+        self.number(enumerator.value.into())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Error> {
+        Err(self.error(ErrorKind::Unsupported("an enum variant with data")))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'s, I>, Error> {
+        self.clear()?;
+        Ok(self.compound())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'s, I>, Error> {
+        Ok(self.compound())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'s, I>, Error> {
+        Ok(self.compound())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(self.error(ErrorKind::Unsupported("an enum variant with data")))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'s, I>, Error> {
+        Ok(self.compound())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'s, I>, Error> {
+        Ok(self.compound())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(self.error(ErrorKind::Unsupported("an enum variant with data")))
+    }
+}
+
+/// Sets the members or elements of a struct, map, sequence or array.
+pub struct Compound<'s, I> {
+    ser: &'s mut SampleSerializer<I>,
+    parent_len: usize,
+    next: usize,
+    /// Member name of the map entry whose value comes next.
+    key: Option<String>,
+}
+
+impl<I: Instance> Compound<'_, I> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.ser.element_path(self.next);
+        self.next += 1;
+        let result = value.serialize(&mut *self.ser);
+        self.ser.path.truncate(self.parent_len);
+        result
+    }
+
+    fn member<T: Serialize + ?Sized>(&mut self, name: &str, value: &T) -> Result<(), Error> {
+        self.ser.member_path(name);
+        let result = value.serialize(&mut *self.ser);
+        self.ser.path.truncate(self.parent_len);
+        result
+    }
+}
+
+impl<I: Instance> ser::SerializeSeq for Compound<'_, I> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<I: Instance> ser::SerializeTuple for Compound<'_, I> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<I: Instance> ser::SerializeTupleStruct for Compound<'_, I> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<I: Instance> ser::SerializeMap for Compound<'_, I> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        // Map keys (and `#[serde(flatten)]` members) name DDS members, so only
+        // string keys are meaningful.
+        match key.serialize(serde_json::value::Serializer) {
+            Ok(serde_json::Value::String(key)) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(self.ser.error(ErrorKind::Unsupported("a non-string map key"))),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ser::Error::custom("map value serialized before its key"))?;
+        self.member(&key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<I: Instance> ser::SerializeStruct for Compound<'_, I> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.member(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+//! Readers and writers bound to the Rust type of their topic.
+//!
+//! An [`Input`] deserializes the samples of a data reader into `T` with
+//! [`de::from_sample`], and an [`Output`] writes `T` with
+//! [`ser::to_instance`]. Both are looked up by their `"Subscriber::Reader"` /
+//! `"Publisher::Writer"` name when they are created, so a name that does not
+//! exist in the participant is reported once, up front.
+//!
+//! An [`Input`] keeps the content filter its data reader was created with.
+//! The C library creates every reader of the participant along with the
+//! [`Connector`], so the filter is set before that, with
+//! [`ConnectorBuilder::content_filter`](crate::ConnectorBuilder::content_filter)
+//! or in the configuration, and the middleware only delivers the matching
+//! samples:
+//!
+//! ```no_run
+//! use rticonnector::config::filter::ContentFilter;
+//! use rticonnector::Connector;
+//! # #[derive(serde::Deserialize)]
+//! # struct Shape;
+//!
+//! let filter = ContentFilter::new("BlueSquares", "color = %0", &["'BLUE'"]);
+//! let connector = Connector::builder("MyParticipantLibrary::MySubParticipant")
+//!     .config_file("ShapeExample.xml")
+//!     .content_filter("MySubscriber::MySquareReader", filter)
+//!     .build()?;
+//! let input = connector.get_input::<Shape>("MySubscriber::MySquareReader")?;
+//! assert_eq!(input.content_filter().unwrap().expression, "color = %0");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::config::filter::ContentFilter;
+use crate::{de, ser, Connector, ConnectorError, DynamicDataReader, DynamicDataWriter};
+
+/// Error returned by [`Input`] and [`Output`].
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Connector(#[from] ConnectorError),
+    #[error(transparent)]
+    Deserialize(#[from] de::Error),
+    #[error(transparent)]
+    Serialize(#[from] ser::Error),
+}
+
+/// A data reader whose samples are read as `T`.
+pub struct Input<'a, T> {
+    connector: &'a Connector,
+    name: String,
+    reader: DynamicDataReader<'a>,
+    content_filter: Option<ContentFilter>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: DeserializeOwned> Input<'a, T> {
+    /// Looks up the data reader `name` (`"MySubscriber::MySquareReader"`).
+    pub fn new(connector: &'a Connector, name: &str) -> Result<Self, ConnectorError> {
+        Ok(Self {
+            connector,
+            name: name.to_string(),
+            reader: connector.get_dynamic_datareader(name)?,
+            content_filter: connector.content_filter(name).cloned(),
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn reader(&self) -> &DynamicDataReader<'a> {
+        &self.reader
+    }
+
+    /// The filter of the samples delivered to the reader; `None` when it
+    /// receives every sample, or when the configuration cannot be parsed in
+    /// Rust.
+    pub fn content_filter(&self) -> Option<&ContentFilter> {
+        self.content_filter.as_ref()
+    }
+
+    pub(crate) fn connector(&self) -> &'a Connector {
+        self.connector
+    }
+
+    /// Waits until data is available on this reader or `timeout` elapses,
+    /// which is reported as [`ConnectorError::Timeout`].
+    pub fn wait(&self, timeout: Duration) -> Result<(), ConnectorError> {
+        self.reader.wait_for_data_on_reader(timeout).map(drop)
+    }
+
+    /// Loads the available samples, leaving them in the reader's cache.
+    pub fn read(&self) -> Result<(), ConnectorError> {
+        self.connector.read(&self.name)
+    }
+
+    /// Loads the available samples, removing them from the reader's cache.
+    pub fn take(&self) -> Result<(), ConnectorError> {
+        self.connector.take(&self.name)
+    }
+
+    /// The number of samples loaded by the last [`read`](Self::read) or
+    /// [`take`](Self::take), including those without valid data.
+    pub fn sample_count(&self) -> Result<usize, ConnectorError> {
+        self.connector
+            .get_sample_count(&self.name)
+            .map(|count| count as usize)
+            .map_err(|_| ConnectorError::FfiError)
+    }
+
+    /// Deserializes the loaded samples that have valid data, skipping the
+    /// ones that only notify a change of instance state.
+    pub fn samples(&self) -> Result<Vec<T>, Error> {
+        let mut samples = Vec::new();
+        for index in 1..=self.sample_count()? {
+            let valid_data = self
+                .connector
+                .get_boolean_from_infos(&self.name, index, "valid_data")
+                .map_err(|_| ConnectorError::FieldNotFound)?;
+            if valid_data {
+                samples.push(self.connector.deserialize_sample(&self.name, index)?);
+            }
+        }
+        Ok(samples)
+    }
+}
+
+/// A data writer that writes values of type `T`.
+pub struct Output<'a, T: ?Sized> {
+    connector: &'a Connector,
+    name: String,
+    writer: DynamicDataWriter<'a>,
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<'a, T: Serialize + ?Sized> Output<'a, T> {
+    /// Looks up the data writer `name` (`"MyPublisher::MySquareWriter"`).
+    pub fn new(connector: &'a Connector, name: &str) -> Result<Self, ConnectorError> {
+        Ok(Self {
+            connector,
+            name: name.to_string(),
+            writer: connector.get_dynamic_datawriter(name)?,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn writer(&self) -> &DynamicDataWriter<'a> {
+        &self.writer
+    }
+
+    /// Sets `value` into the writer's instance and writes it.
+    pub fn write(&self, value: &T) -> Result<(), Error> {
+        self.connector.clear(&self.name)?;
+        self.connector.serialize_instance(&self.name, value)?;
+        self.connector.write(&self.name, "{}")?;
+        Ok(())
+    }
+
+    /// Writes `value` stamped with `source_timestamp`, in nanoseconds since
+    /// the Unix epoch, instead of the current time.
+    pub fn write_at(&self, value: &T, source_timestamp: i64) -> Result<(), Error> {
+        self.connector.clear(&self.name)?;
+        self.connector.serialize_instance(&self.name, value)?;
+        let params = format!("{{\"source_timestamp\": {}}}", source_timestamp);
+        self.connector.write(&self.name, &params)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+//! Overrides applied by `ConnectorBuilder` before the configuration reaches
+//! the C library.
+
+use rticonnector::builder::BuildError;
+use rticonnector::config::filter::{ContentFilter, FilterError};
+use rticonnector::config::participants::{Participant, Publisher};
+use rticonnector::config::qos::EntityQos;
+use rticonnector::config::{Config, ConfigError};
+use rticonnector::{Connector, ConnectorBuilder};
+
+const XML: &str = r#"<dds>
+    <types>
+        <struct name="ShapeType">
+            <member name="color" type="string" key="true"/>
+        </struct>
+    </types>
+    <domain_participant_library name="Lib">
+        <domain_participant name="Participant" domain_id="0">
+            <register_type name="ShapeType" type_ref="ShapeType"/>
+            <topic name="Square" register_type_ref="ShapeType"/>
+            <publisher name="Pub">
+                <publisher_qos>
+                    <partition>
+                        <name><element>A</element></name>
+                    </partition>
+                </publisher_qos>
+                <data_writer name="Writer" topic_ref="Square"/>
+            </publisher>
+            <subscriber name="Sub">
+                <data_reader name="Reader" topic_ref="Square"/>
+            </subscriber>
+        </domain_participant>
+    </domain_participant_library>
+</dds>"#;
+
+/// The configuration passed to the C library.
+fn resolved(builder: &ConnectorBuilder) -> Config {
+    let url = builder.url().unwrap();
+    Config::from_url(&url).unwrap()
+}
+
+#[test]
+fn overrides() {
+    let builder = ConnectorBuilder::new("Lib::Participant")
+        .config_str(XML)
+        .domain_id(7)
+        .partitions(&["B", "C"])
+        .participant_name("shapes");
+    let config = resolved(&builder);
+    let participant = config.participant("Lib::Participant").unwrap();
+    assert_eq!(participant.domain_id, Some(7));
+    let qos = &participant.publishers[0].qos.as_ref().unwrap().content;
+    assert_eq!(qos.matches("<partition>").count(), 1, "{}", qos);
+    assert!(qos.contains("<element>B</element>") && !qos.contains("<element>A</element>"));
+    let qos = &participant.qos.as_ref().unwrap().content;
+    assert!(qos.contains("<name>shapes</name>"), "{}", qos);
+}
+
+#[test]
+fn unrepresented_elements() {
+    let xml = XML.replace(
+        "<types>",
+        "<application_library name=\"Apps\"/>\n    <types>",
+    );
+    // Passed as written when there is nothing to override.
+    let builder = ConnectorBuilder::new("Lib::Participant").config_str(&xml);
+    assert!(builder.url().unwrap().contains("application_library"));
+
+    let error = builder.domain_id(7).url().unwrap_err();
+    match error {
+        BuildError::Unrepresented { document, source } => {
+            assert_eq!(document, "inline document");
+            assert_eq!(source.line(), Some(2));
+            assert!(source.to_string().contains("<application_library>"));
+        }
+        error => panic!("unexpected error: {}", error),
+    }
+}
+
+#[test]
+fn malformed_qos() {
+    let participant = Participant::new("Participant", "Domains::Shapes")
+        .with_publisher(Publisher::new("Pub").with_qos(EntityQos::from_content("<partition>")));
+    let config = Config::builder().participant("Lib", participant).build();
+    let error = ConnectorBuilder::new("Lib::Participant")
+        .config(&config)
+        .partitions(&["B"])
+        .url()
+        .unwrap_err();
+    assert!(
+        matches!(error, BuildError::Config(ConfigError::Invalid { .. })),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn content_filters() {
+    let filter = ContentFilter::new("BlueSquares", "color = %0", &["'BLUE'"]);
+    let builder = ConnectorBuilder::new("Lib::Participant")
+        .config_str(XML)
+        .content_filter("Sub::Reader", filter.clone());
+    // The filter an `Input` of the reader reports.
+    let connector = Connector::from_config("Lib::Participant", &resolved(&builder), &[]);
+    let kept = connector.content_filter("Sub::Reader").unwrap();
+    assert_eq!(
+        (&kept.name, &kept.expression, &kept.parameters),
+        (&filter.name, &filter.expression, &filter.parameters)
+    );
+    assert!(connector.content_filter("Sub::Other").is_none());
+
+    let unquoted = ContentFilter::new("BlueSquares", "color = %0", &["BLUE"]);
+    let error = ConnectorBuilder::new("Lib::Participant")
+        .config_str(XML)
+        .content_filter("Sub::Reader", unquoted)
+        .url()
+        .unwrap_err();
+    assert!(
+        matches!(
+            &error,
+            BuildError::ContentFilter {
+                source: FilterError::UnquotedParameter { .. },
+                ..
+            }
+        ),
+        "{}",
+        error
+    );
+    let error = ConnectorBuilder::new("Lib::Participant")
+        .config_str(XML)
+        .content_filter("Sub::Other", filter)
+        .url()
+        .unwrap_err();
+    assert!(matches!(error, BuildError::UnknownReader(name) if name == "Sub::Other"));
+}
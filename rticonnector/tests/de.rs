@@ -0,0 +1,298 @@
+//! `SampleDeserializer` over a sample held as JSON, walking the members the
+//! way it walks the members of a received sample.
+
+use std::sync::Arc;
+
+use rticonnector::config::types::{Enum, Types};
+use rticonnector::de::{ErrorKind, Sample, SampleDeserializer};
+use rticonnector::ConnectorError;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const TYPES: &str = r#"<dds>
+    <types>
+        <enum name="Color">
+            <enumerator name="RED"/>
+            <enumerator name="GREEN" value="5"/>
+            <enumerator name="BLUE"/>
+        </enum>
+        <struct name="Point">
+            <member name="x" type="long"/>
+            <member name="y" type="long"/>
+        </struct>
+        <struct name="Shape">
+            <member name="color" type="nonBasic" nonBasicTypeName="Color"/>
+            <member name="origin" type="nonBasic" nonBasicTypeName="Point"/>
+            <member name="points" type="nonBasic" nonBasicTypeName="Point" sequenceMaxLength="8"/>
+            <member name="tags" type="string" sequenceMaxLength="4"/>
+            <member name="label" type="string" optional="true"/>
+            <member name="size" type="long" optional="true"/>
+            <member name="corner" type="nonBasic" nonBasicTypeName="Point" optional="true"/>
+        </struct>
+        <struct name="Wide">
+            <member name="small" type="int8"/>
+            <member name="signed" type="int64"/>
+            <member name="unsigned" type="uint64"/>
+        </struct>
+    </types>
+</dds>"#;
+
+/// A sample of `type_name` held as JSON, read the way the Connector reads a
+/// received one.
+struct JsonSample {
+    value: Value,
+    types: Types,
+    type_name: &'static str,
+    one_based: bool,
+}
+
+impl JsonSample {
+    fn new(type_name: &'static str, value: Value) -> Self {
+        Self {
+            value,
+            types: Types::from_xml(TYPES).unwrap(),
+            type_name,
+            one_based: false,
+        }
+    }
+
+    /// The member `field`, `null` when it is unset.
+    fn member(&self, field: &str) -> Result<Value, ConnectorError> {
+        let (field, length) = match field.strip_suffix('#') {
+            Some(field) => (field, true),
+            None => (field, false),
+        };
+        let mut value = &self.value;
+        for segment in field.split('.').filter(|s| !s.is_empty()) {
+            let mut parts = segment.split('[');
+            value = value
+                .get(parts.next().unwrap())
+                .ok_or(ConnectorError::FieldNotFound)?;
+            for index in parts {
+                let index: usize = index.trim_end_matches(']').parse().unwrap();
+                value = value
+                    .get(index - usize::from(self.one_based))
+                    .ok_or(ConnectorError::FieldNotFound)?;
+            }
+        }
+        match (length, value) {
+            (true, Value::Array(elements)) => Ok(elements.len().into()),
+            (true, _) => Err(ConnectorError::FieldNotFound),
+            (false, value) => Ok(value.clone()),
+        }
+    }
+
+    fn scalar<T>(
+        &self,
+        field: &str,
+        get: impl Fn(&Value) -> Option<T>,
+    ) -> Result<T, ConnectorError> {
+        match self.member(field)? {
+            Value::Null => Err(ConnectorError::NoData),
+            value => get(&value).ok_or(ConnectorError::FfiError),
+        }
+    }
+}
+
+impl Sample for JsonSample {
+    fn number(&self, field: &str) -> Result<f64, ConnectorError> {
+        self.scalar(field, Value::as_f64)
+    }
+
+    fn string(&self, field: &str) -> Result<String, ConnectorError> {
+        self.scalar(field, |v| v.as_str().map(str::to_string))
+    }
+
+    fn boolean(&self, field: &str) -> Result<bool, ConnectorError> {
+        self.scalar(field, Value::as_bool)
+    }
+
+    fn json(&self, field: &str) -> Result<String, ConnectorError> {
+        self.member(field).map(|value| value.to_string())
+    }
+
+    fn one_based_sequence_indexing(&self) -> bool {
+        self.one_based
+    }
+
+    fn enumeration(&self, field: &str) -> Option<Arc<Enum>> {
+        let topic_type = self.types.get_struct(self.type_name)?;
+        let member_type = self.types.field_type(topic_type, field)?;
+        match member_type.kind {
+            rticonnector::config::types::TypeKind::NonBasic(name) => {
+                self.types.get_enum(&name).cloned().map(Arc::new)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn deserialize<T: DeserializeOwned>(sample: JsonSample) -> Result<T, rticonnector::de::Error> {
+    T::deserialize(&mut SampleDeserializer::from_sample(sample))
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Shape {
+    color: Color,
+    origin: Point,
+    points: Vec<Point>,
+    tags: Vec<String>,
+    label: Option<String>,
+    size: Option<i32>,
+    corner: Option<Point>,
+}
+
+fn shape_json() -> Value {
+    json!({
+        "color": 5,
+        "origin": { "x": 1, "y": 2 },
+        "points": [{ "x": 3, "y": 4 }, { "x": 5, "y": 6 }],
+        "tags": ["a", "b"],
+        "label": "square",
+        "size": null,
+        "corner": { "x": 7, "y": 8 },
+    })
+}
+
+#[test]
+fn members() {
+    let shape: Shape = deserialize(JsonSample::new("Shape", shape_json())).unwrap();
+    assert_eq!(
+        shape,
+        Shape {
+            color: Color::Green,
+            origin: Point { x: 1, y: 2 },
+            points: vec![Point { x: 3, y: 4 }, Point { x: 5, y: 6 }],
+            tags: vec!["a".to_string(), "b".to_string()],
+            label: Some("square".to_string()),
+            size: None,
+            corner: Some(Point { x: 7, y: 8 }),
+        }
+    );
+
+    let mut json = shape_json();
+    json["color"] = json!(6);
+    json["points"] = json!([]);
+    json["label"] = Value::Null;
+    json["size"] = json!(3);
+    json["corner"] = Value::Null;
+    let shape: Shape = deserialize(JsonSample::new("Shape", json)).unwrap();
+    assert_eq!(shape.color, Color::Blue);
+    assert!(shape.points.is_empty());
+    assert_eq!(
+        (shape.label, shape.size, shape.corner),
+        (None, Some(3), None)
+    );
+}
+
+#[test]
+fn one_based_sequences() {
+    let sample = JsonSample {
+        one_based: true,
+        ..JsonSample::new("Shape", shape_json())
+    };
+    let shape: Shape = deserialize(sample).unwrap();
+    assert_eq!(shape.points[1], Point { x: 5, y: 6 });
+}
+
+/// The field and kind of the error deserializing `json` as a `Shape`.
+fn shape_error(json: Value, one_based: bool) -> (Option<String>, String) {
+    let sample = JsonSample {
+        one_based,
+        ..JsonSample::new("Shape", json)
+    };
+    let error = deserialize::<Shape>(sample).unwrap_err();
+    (error.field().map(str::to_string), error.kind().to_string())
+}
+
+#[test]
+fn errors_name_the_member() {
+    let mut json = shape_json();
+    json["points"][1]["y"] = json!(1.5);
+    let expected = "1.5 is not a valid i32".to_string();
+    assert_eq!(
+        shape_error(json.clone(), false),
+        (Some("points[1].y".to_string()), expected.clone())
+    );
+    assert_eq!(
+        shape_error(json, true),
+        (Some("points[2].y".to_string()), expected)
+    );
+
+    let mut json = shape_json();
+    json["color"] = json!(4);
+    let sample = JsonSample::new("Shape", json);
+    let error = deserialize::<Shape>(sample).unwrap_err();
+    assert_eq!(error.field(), Some("color"));
+    assert!(matches!(error.kind(), ErrorKind::UnknownEnumerator(4)));
+
+    let mut json = shape_json();
+    json["tags"][0] = json!(1);
+    let (field, _) = shape_error(json, false);
+    assert_eq!(field.as_deref(), Some("tags[0]"));
+
+    // `origin` is a struct, not an enum.
+    #[derive(Debug, Deserialize)]
+    struct Mistyped {
+        #[allow(dead_code)]
+        origin: Color,
+    }
+    let error = deserialize::<Mistyped>(JsonSample::new("Shape", shape_json())).unwrap_err();
+    assert_eq!(error.field(), Some("origin"));
+    assert!(matches!(error.kind(), ErrorKind::NotAnEnum("Color")));
+    assert_eq!(
+        error.to_string(),
+        "field `origin`: `Color` needs a DDS enum member"
+    );
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Wide {
+    small: i8,
+    signed: i64,
+    unsigned: u64,
+}
+
+fn wide(small: f64, signed: f64, unsigned: f64) -> Result<Wide, Option<String>> {
+    let json = json!({ "small": small, "signed": signed, "unsigned": unsigned });
+    deserialize(JsonSample::new("Wide", json)).map_err(|e| e.field().map(str::to_string))
+}
+
+#[test]
+fn integer_bounds() {
+    // The largest doubles below 2^63 and 2^64.
+    let signed = 2f64.powi(63) - 1024.0;
+    let unsigned = 2f64.powi(64) - 2048.0;
+    assert_eq!(
+        wide(-128.0, signed, unsigned).unwrap(),
+        Wide {
+            small: -128,
+            signed: signed as i64,
+            unsigned: unsigned as u64,
+        }
+    );
+    assert_eq!(wide(127.0, -(2f64.powi(63)), 0.0).unwrap().signed, i64::MIN);
+
+    let field = |field: &str| Err(Some(field.to_string()));
+    assert_eq!(wide(128.0, 0.0, 0.0), field("small"));
+    assert_eq!(wide(-129.0, 0.0, 0.0), field("small"));
+    assert_eq!(wide(0.0, 2f64.powi(63), 0.0), field("signed"));
+    assert_eq!(wide(0.0, -(2f64.powi(63)) - 2048.0, 0.0), field("signed"));
+    assert_eq!(wide(0.0, 0.0, 2f64.powi(64)), field("unsigned"));
+    assert_eq!(wide(0.0, 0.0, -1.0), field("unsigned"));
+}
@@ -0,0 +1,114 @@
+//! DDS enum members: their types in the configuration, and the enumerations
+//! a connector finds for the members of its readers and writers.
+//!
+//! The participant refers to a domain that does not exist, so the native
+//! connector is never created: everything checked here happens on the Rust
+//! side, before anything is handed to the C library.
+
+use rticonnector::config::types::TypeKind;
+use rticonnector::config::Config;
+use rticonnector::{ser, Connector};
+use serde::Serialize;
+
+const XML: &str = r#"<dds>
+    <types>
+        <enum name="Color">
+            <enumerator name="RED" value="3"/>
+            <enumerator name="GREEN" value="7"/>
+        </enum>
+        <typedef name="Colors" type="nonBasic" nonBasicTypeName="Color" sequenceMaxLength="4"/>
+        <struct name="Shape">
+            <member name="color" type="nonBasic" nonBasicTypeName="Color"/>
+        </struct>
+        <struct name="Paint">
+            <member name="shape" type="nonBasic" nonBasicTypeName="Shape"/>
+            <member name="color" type="nonBasic" nonBasicTypeName="Color"/>
+            <member name="palette" type="nonBasic" nonBasicTypeName="Colors"/>
+            <member name="count" type="int64"/>
+            <member name="total" type="uint64"/>
+        </struct>
+    </types>
+    <domain_participant_library name="Lib">
+        <domain_participant name="Participant" domain_ref="Missing::Domain">
+            <register_type name="Paint" type_ref="Paint"/>
+            <topic name="Paint" register_type_ref="Paint"/>
+            <publisher name="Pub">
+                <data_writer name="Writer" topic_ref="Paint"/>
+            </publisher>
+            <subscriber name="Sub">
+                <data_reader name="Reader" topic_ref="Paint"/>
+            </subscriber>
+        </domain_participant>
+    </domain_participant_library>
+</dds>"#;
+
+fn connector() -> Connector {
+    Connector::from_config("Lib::Participant", &Config::from_xml(XML).unwrap(), &[])
+}
+
+#[derive(Serialize)]
+enum Color {
+    Blue,
+}
+
+#[derive(Serialize)]
+struct Colored {
+    color: Color,
+}
+
+#[test]
+fn config_from_url() {
+    let (types, participants) = XML.split_at(XML.find("<domain_participant_library").unwrap());
+    let url = format!(
+        "str://\"{}</dds>\"; str://\"<dds>{}\"",
+        types.replace('"', "'"),
+        participants.replace('"', "'")
+    );
+    let config = Config::from_url(&url).unwrap();
+    assert_eq!(config.to_xml(), Config::from_xml(XML).unwrap().to_xml());
+}
+
+#[test]
+fn field_types() {
+    let config = Config::from_xml(XML).unwrap();
+    let paint = config
+        .entity_type("Lib::Participant", "Sub::Reader")
+        .unwrap();
+    assert_eq!(paint.name, "Paint");
+    let kind = |field| config.types.field_type(paint, field).map(|t| t.kind);
+    let color = Some(TypeKind::NonBasic("Color".to_string()));
+    assert_eq!(kind("shape.color"), color);
+    assert_eq!(kind("palette[2]"), color);
+    assert_eq!(kind("palette[1][1]"), None);
+    assert_eq!(kind("shape.size"), None);
+    assert_eq!(kind("color.value"), None);
+    assert!(config
+        .entity_type("Lib::Participant", "Pub::Missing")
+        .is_none());
+}
+
+#[test]
+fn enumerations() {
+    let connector = connector();
+    let color = connector.enumeration("Pub::Writer", "shape.color").unwrap();
+    assert_eq!(color.name, "Color");
+    let element = connector.enumeration("Sub::Reader", "palette[1]").unwrap();
+    // Cached whatever the index.
+    let other = connector.enumeration("Sub::Reader", "palette[2]").unwrap();
+    assert!(std::sync::Arc::ptr_eq(&element, &other));
+    assert!(connector.enumeration("Sub::Reader", "palette").is_none());
+    assert!(connector.enumeration("Pub::Writer", "count").is_none());
+}
+
+#[test]
+fn writer_enumerations() {
+    // The enumerations of the writer, as the serializer sees them.
+    let connector = connector();
+    let error =
+        ser::to_instance(&connector, "Pub::Writer", &Colored { color: Color::Blue }).unwrap_err();
+    assert_eq!(error.field(), Some("color"));
+    assert!(matches!(
+        error.kind(),
+        ser::ErrorKind::UnknownEnumerator("Blue")
+    ));
+}
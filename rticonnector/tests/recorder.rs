@@ -0,0 +1,309 @@
+//! The recording file format, its rotation and reading it back.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rticonnector::config::types::Types;
+use rticonnector::recorder::{
+    Payload, Record, RecordError, RecordedTopic, Recorder, Recording, Rotation, FORMAT, VERSION,
+};
+use serde_json::{json, Map, Value};
+
+const TYPES: &str = r#"<dds>
+    <types>
+        <struct name="ShapeType">
+            <member name="color" type="string" stringMaxLength="128" key="true"/>
+            <member name="x" type="long"/>
+        </struct>
+    </types>
+</dds>"#;
+
+/// An empty directory for the files of the test `name`.
+fn directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!(
+        "rticonnector-recorder-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn topic() -> RecordedTopic {
+    RecordedTopic {
+        reader: "MySubscriber::MySquareReader".to_string(),
+        topic: "Square".to_string(),
+        type_name: "ShapeType".to_string(),
+        types: Types::from_xml(TYPES).unwrap(),
+    }
+}
+
+fn record(x: i64, payload: Payload) -> Record {
+    let mut info = Map::new();
+    info.insert(
+        "valid_data".to_string(),
+        json!(!matches!(payload, Payload::None)),
+    );
+    Record {
+        reader: "MySubscriber::MySquareReader".to_string(),
+        topic: "Square".to_string(),
+        reception_timestamp: Some(1_000 + x),
+        source_timestamp: Some(x),
+        info,
+        payload,
+    }
+}
+
+fn square(x: i64) -> Record {
+    record(x, Payload::Json(json!({ "color": "BLUE", "x": x })))
+}
+
+fn lines(path: &Path) -> Vec<Value> {
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn read(path: &Path) -> Vec<Record> {
+    Recording::open(path)
+        .unwrap()
+        .records()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+#[test]
+fn file_format() {
+    let path = directory("format").join("capture.jsonl");
+    let mut recorder = Recorder::create(&path, vec![topic()], Rotation::default()).unwrap();
+    recorder.append(&square(1)).unwrap();
+    recorder.append(&record(2, Payload::None)).unwrap();
+    recorder.flush().unwrap();
+
+    let lines = lines(&path);
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["format"], FORMAT);
+    assert_eq!(lines[0]["version"], VERSION);
+    assert_eq!(lines[0]["index"], 0);
+    let header_topic = &lines[0]["topics"][0];
+    assert_eq!(header_topic["reader"], "MySubscriber::MySquareReader");
+    assert_eq!(header_topic["topic"], "Square");
+    assert_eq!(header_topic["type"], "ShapeType");
+    assert!(header_topic["types"]
+        .as_str()
+        .unwrap()
+        .contains("<struct name=\"ShapeType\""));
+    assert_eq!(
+        lines[1],
+        json!({
+            "reader": "MySubscriber::MySquareReader",
+            "topic": "Square",
+            "reception_timestamp": 1001,
+            "source_timestamp": 1,
+            "info": { "valid_data": true },
+            "data": { "color": "BLUE", "x": 1 },
+        })
+    );
+    assert_eq!(lines[2]["data"], Value::Null);
+
+    // The types are read back from the header, with their line numbers there.
+    let recording = Recording::open(&path).unwrap();
+    let topics = recording.topics();
+    assert_eq!(topics.len(), 1);
+    assert_eq!(topics[0].reader, topic().reader);
+    assert_eq!(topics[0].type_name, "ShapeType");
+    assert_eq!(topics[0].types.to_xml(), topic().types.to_xml());
+    assert!(recording.topic("MySubscriber::MySquareReader").is_some());
+    assert!(recording.topic("MySubscriber::MyCircleReader").is_none());
+    assert_eq!(read(&path), [square(1), record(2, Payload::None)]);
+}
+
+#[test]
+fn rotation_by_size() {
+    let path = directory("size").join("capture.jsonl");
+    let mut recorder = Recorder::create(&path, vec![topic()], Rotation::default()).unwrap();
+    recorder.append(&square(0)).unwrap();
+    recorder.flush().unwrap();
+    // Room for the header and one record.
+    let max_bytes = fs::metadata(&path).unwrap().len();
+    fs::remove_file(&path).unwrap();
+
+    let rotation = Rotation::default().with_max_bytes(max_bytes);
+    let mut recorder = Recorder::create(&path, vec![topic()], rotation).unwrap();
+    for x in 1..=3 {
+        recorder.append(&square(x)).unwrap();
+    }
+    assert_eq!(
+        recorder.current_path(),
+        path.with_file_name("capture.2.jsonl")
+    );
+    recorder.flush().unwrap();
+
+    let recording = Recording::open(&path).unwrap();
+    let names: Vec<_> = recording
+        .paths()
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(
+        names,
+        ["capture.jsonl", "capture.1.jsonl", "capture.2.jsonl"]
+    );
+    for (index, file) in recording.paths().iter().enumerate() {
+        let lines = lines(file);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["index"], index);
+        assert_eq!(lines[0]["topics"][0]["reader"], topic().reader);
+    }
+    assert_eq!(read(&path), [square(1), square(2), square(3)]);
+}
+
+#[test]
+fn rotation_by_duration() {
+    let path = directory("duration").join("capture");
+    let rotation = Rotation::default().with_max_duration(Duration::ZERO);
+    let mut recorder = Recorder::create(&path, vec![topic()], rotation).unwrap();
+    for x in 1..=2 {
+        recorder.append(&square(x)).unwrap();
+    }
+    recorder.flush().unwrap();
+    // A file always holds at least one record.
+    assert_eq!(recorder.current_path(), path.with_file_name("capture.1"));
+    assert_eq!(read(&path), [square(1), square(2)]);
+}
+
+#[test]
+fn existing_recordings_are_kept() {
+    let directory = directory("exists");
+    let path = directory.join("capture.jsonl");
+    Recorder::create(&path, vec![topic()], Rotation::default()).unwrap();
+    let error = Recorder::create(&path, vec![topic()], Rotation::default()).err();
+    assert!(matches!(error, Some(RecordError::Exists(p)) if p == path));
+
+    fs::remove_file(&path).unwrap();
+    fs::write(directory.join("capture.1.jsonl"), "").unwrap();
+    let error = Recorder::create(&path, vec![topic()], Rotation::default()).err();
+    assert!(matches!(error, Some(RecordError::Exists(_))));
+}
+
+#[test]
+fn unrecorded_readers() {
+    let path = directory("unrecorded").join("capture.jsonl");
+    let mut recorder = Recorder::create(&path, vec![topic()], Rotation::default()).unwrap();
+    let other = Record {
+        reader: "MySubscriber::MyCircleReader".to_string(),
+        ..square(1)
+    };
+    assert!(matches!(
+        recorder.append(&other),
+        Err(RecordError::NotRecorded(reader)) if reader == "MySubscriber::MyCircleReader"
+    ));
+}
+
+#[test]
+fn binary_payloads() {
+    let path = directory("binary").join("capture.jsonl");
+    let mut recorder = Recorder::create(&path, vec![topic()], Rotation::default()).unwrap();
+    let payloads: Vec<Vec<u8>> = (0..=7u8)
+        .map(|length| (0..length).map(|i| i.wrapping_mul(97) ^ 0xa5).collect())
+        .chain([b"f".to_vec(), b"fo".to_vec(), b"foobar".to_vec()])
+        .chain([(0..=255).collect()])
+        .collect();
+    for (x, bytes) in payloads.iter().enumerate() {
+        recorder
+            .append(&record(x as i64, Payload::Binary(bytes.clone())))
+            .unwrap();
+    }
+    recorder.flush().unwrap();
+
+    let lines = lines(&path);
+    assert_eq!(lines[1]["binary"], "");
+    assert_eq!(lines[9]["binary"], "Zg==");
+    assert_eq!(lines[10]["binary"], "Zm8=");
+    assert_eq!(lines[11]["binary"], "Zm9vYmFy");
+    assert!(lines[1].get("data").is_none());
+    let read: Vec<_> = read(&path).into_iter().map(|r| r.payload).collect();
+    let expected: Vec<_> = payloads.into_iter().map(Payload::Binary).collect();
+    assert_eq!(read, expected);
+}
+
+#[test]
+fn encoder() {
+    let path = directory("encoder").join("capture.jsonl");
+    let encoder = Box::new(
+        |topic: &RecordedTopic, data: &Value| match data["x"].as_i64() {
+            Some(x) if x >= 0 => Ok(format!("{}:{}", topic.type_name, x).into_bytes()),
+            _ => Err("negative x".to_string()),
+        },
+    );
+    let mut recorder = Recorder::create(&path, vec![topic()], Rotation::default())
+        .unwrap()
+        .with_encoder(encoder);
+    recorder.append(&square(7)).unwrap();
+    recorder.append(&record(8, Payload::None)).unwrap();
+    assert!(matches!(
+        recorder.append(&square(-1)),
+        Err(RecordError::Encode { message, .. }) if message == "negative x"
+    ));
+    recorder.flush().unwrap();
+    assert_eq!(
+        read(&path),
+        [
+            record(7, Payload::Binary(b"ShapeType:7".to_vec())),
+            record(8, Payload::None),
+        ]
+    );
+}
+
+#[test]
+fn invalid_files() {
+    let directory = directory("invalid");
+    let format_error = |path: &Path| match Recording::open(path).err() {
+        Some(RecordError::Format { line, message, .. }) => (line, message),
+        other => panic!("expected a format error, got {:?}", other),
+    };
+
+    let path = directory.join("empty.jsonl");
+    fs::write(&path, "").unwrap();
+    assert_eq!(format_error(&path), (1, "empty file".to_string()));
+
+    let path = directory.join("other.jsonl");
+    fs::write(&path, "{\"format\": \"other\"}\n").unwrap();
+    assert_eq!(format_error(&path), (1, format!("not a `{}` file", FORMAT)));
+
+    let path = directory.join("version.jsonl");
+    let header = json!({ "format": FORMAT, "version": VERSION + 1, "topics": [] });
+    fs::write(&path, format!("{}\n", header)).unwrap();
+    assert_eq!(
+        format_error(&path),
+        (1, format!("unsupported version Some({})", VERSION + 1))
+    );
+
+    // A record that cannot be read ends the iteration.
+    let path = directory.join("capture.jsonl");
+    let mut recorder = Recorder::create(&path, vec![topic()], Rotation::default()).unwrap();
+    recorder.append(&square(1)).unwrap();
+    recorder.flush().unwrap();
+    let mut content = fs::read_to_string(&path).unwrap();
+    content.push_str(
+        "{\"reader\": \"MySubscriber::MySquareReader\", \"topic\": \"Square\", \"binary\": \"Zm9\"}\n",
+    );
+    content.push_str(&format!(
+        "{}\n",
+        json!({ "reader": "MySubscriber::MySquareReader", "topic": "Square", "data": {} })
+    ));
+    fs::write(&path, content).unwrap();
+    let mut records = Recording::open(&path).unwrap().records();
+    assert_eq!(records.next().unwrap().unwrap(), square(1));
+    match records.next() {
+        Some(Err(RecordError::Format { line, message, .. })) => {
+            assert_eq!((line, message.as_str()), (3, "`binary` is not base64"))
+        }
+        other => panic!("expected a format error, got {:?}", other),
+    }
+    assert!(records.next().is_none());
+}
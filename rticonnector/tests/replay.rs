@@ -0,0 +1,293 @@
+//! Replaying a recording written with `Recorder` into sinks that keep what
+//! they are given.
+
+use std::cell::RefCell;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use rticonnector::config::types::Types;
+use rticonnector::recorder::{Payload, Record, RecordedTopic, Recorder, Recording, Rotation};
+use rticonnector::replay::{ReplayError, ReplayStats, Replayer, Sink, SourceTimestamps};
+use rticonnector::typed;
+use serde_json::{json, Map, Value};
+
+const TYPES: &str = r#"<dds>
+    <types>
+        <struct name="ShapeType">
+            <member name="color" type="string" stringMaxLength="128" key="true"/>
+            <member name="x" type="long"/>
+        </struct>
+    </types>
+</dds>"#;
+
+const MILLISECOND: i64 = 1_000_000;
+
+fn topic(reader: &str, topic: &str) -> RecordedTopic {
+    RecordedTopic {
+        reader: reader.to_string(),
+        topic: topic.to_string(),
+        type_name: "ShapeType".to_string(),
+        types: Types::from_xml(TYPES).unwrap(),
+    }
+}
+
+/// A record of `topic` received `millis` after the first one.
+fn record(topic: &str, millis: i64, payload: Payload) -> Record {
+    Record {
+        reader: format!("MySubscriber::My{}Reader", topic),
+        topic: topic.to_string(),
+        reception_timestamp: Some(1_000 * MILLISECOND + millis * MILLISECOND),
+        source_timestamp: Some(millis),
+        info: Map::new(),
+        payload,
+    }
+}
+
+fn square(millis: i64, x: i64) -> Record {
+    record(
+        "Square",
+        millis,
+        Payload::Json(json!({ "color": "BLUE", "x": x })),
+    )
+}
+
+/// Squares 0 to 4 every 20 ms, with a binary square, a circle and a square
+/// without data in between.
+fn recording(name: &str) -> Recording {
+    let directory = std::env::temp_dir().join(format!(
+        "rticonnector-replay-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    let path: PathBuf = directory.join("capture.jsonl");
+    let topics = vec![
+        topic("MySubscriber::MySquareReader", "Square"),
+        topic("MySubscriber::MyCircleReader", "Circle"),
+    ];
+    let mut recorder = Recorder::create(&path, topics, Rotation::default()).unwrap();
+    let records = [
+        square(0, 0),
+        square(20, 1),
+        record("Square", 30, Payload::Binary(vec![1, 2, 3])),
+        square(40, 2),
+        record(
+            "Circle",
+            50,
+            Payload::Json(json!({ "color": "RED", "x": 9 })),
+        ),
+        square(60, 3),
+        record("Square", 70, Payload::None),
+        square(80, 4),
+    ];
+    for record in &records {
+        recorder.append(record).unwrap();
+    }
+    recorder.flush().unwrap();
+    Recording::open(&path).unwrap()
+}
+
+/// A sample given to a [`Collect`], with the time since it was created.
+#[derive(Debug)]
+struct Written {
+    x: i64,
+    source_timestamp: Option<i64>,
+    after: Duration,
+}
+
+/// Keeps the samples it is given, and fails once it has `limit`.
+#[derive(Clone)]
+struct Collect {
+    written: Rc<RefCell<Vec<Written>>>,
+    created: Instant,
+    limit: Option<usize>,
+}
+
+impl Default for Collect {
+    fn default() -> Self {
+        Self {
+            written: Rc::default(),
+            created: Instant::now(),
+            limit: None,
+        }
+    }
+}
+
+impl Collect {
+    fn xs(&self) -> Vec<i64> {
+        self.written.borrow().iter().map(|w| w.x).collect()
+    }
+
+    /// The time between the creation of the sink, before the replay
+    /// starts, and the last sample.
+    fn elapsed(&self) -> Duration {
+        self.written
+            .borrow()
+            .last()
+            .map_or(Duration::ZERO, |w| w.after)
+    }
+}
+
+impl Sink for Collect {
+    fn name(&self) -> &str {
+        "Collect"
+    }
+
+    fn write(&self, data: &Value, source_timestamp: Option<i64>) -> Result<(), typed::Error> {
+        let mut written = self.written.borrow_mut();
+        if Some(written.len()) == self.limit {
+            return Err(typed::Error::Serialize(serde::ser::Error::custom("full")));
+        }
+        written.push(Written {
+            x: data["x"].as_i64().unwrap(),
+            source_timestamp,
+            after: self.created.elapsed(),
+        });
+        Ok(())
+    }
+}
+
+#[test]
+fn whole_recording() {
+    let sink = Collect::default();
+    let stats = Replayer::new()
+        .with_sink("Square", sink.clone())
+        .replay(&recording("whole"))
+        .unwrap();
+    // The binary square and the one without data.
+    assert_eq!(
+        stats,
+        ReplayStats {
+            written: 5,
+            skipped: 2
+        }
+    );
+    assert_eq!(sink.xs(), [0, 1, 2, 3, 4]);
+    let timestamps: Vec<_> = sink
+        .written
+        .borrow()
+        .iter()
+        .map(|w| w.source_timestamp)
+        .collect();
+    assert_eq!(
+        timestamps,
+        [Some(0), Some(20), Some(40), Some(60), Some(80)]
+    );
+    assert!(
+        sink.elapsed() >= Duration::from_millis(80),
+        "{:?}",
+        sink.elapsed()
+    );
+}
+
+#[test]
+fn time_range() {
+    let sink = Collect::default();
+    let stats = Replayer::new()
+        .with_sink("Square", sink.clone())
+        .with_start(Duration::from_millis(20))
+        .with_end(Duration::from_millis(70))
+        .with_source_timestamps(SourceTimestamps::Now)
+        .replay(&recording("range"))
+        .unwrap();
+    // The end is excluded, and with it the square without data.
+    assert_eq!(
+        stats,
+        ReplayStats {
+            written: 3,
+            skipped: 1
+        }
+    );
+    assert_eq!(sink.xs(), [1, 2, 3]);
+    assert!(sink
+        .written
+        .borrow()
+        .iter()
+        .all(|w| w.source_timestamp.is_none()));
+    assert!(
+        sink.elapsed() >= Duration::from_millis(40),
+        "{:?}",
+        sink.elapsed()
+    );
+}
+
+#[test]
+fn speed() {
+    let sink = Collect::default();
+    Replayer::new()
+        .with_sink("Square", sink.clone())
+        .with_end(Duration::from_millis(30))
+        .with_speed(0.5)
+        .replay(&recording("speed"))
+        .unwrap();
+    assert_eq!(sink.xs(), [0, 1]);
+    assert!(
+        sink.elapsed() >= Duration::from_millis(40),
+        "{:?}",
+        sink.elapsed()
+    );
+
+    for speed in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        let error = Replayer::new()
+            .with_sink("Square", Collect::default())
+            .with_speed(speed)
+            .replay(&recording("invalid-speed"))
+            .unwrap_err();
+        assert!(matches!(error, ReplayError::InvalidSpeed(_)), "{}", error);
+    }
+}
+
+#[test]
+fn looping() {
+    let sink = Collect {
+        limit: Some(5),
+        ..Collect::default()
+    };
+    let error = Replayer::new()
+        .with_sink("Square", sink.clone())
+        .with_start(Duration::from_millis(40))
+        .with_speed(10.0)
+        .with_loop(true)
+        .replay(&recording("loop"))
+        .unwrap_err();
+    match error {
+        ReplayError::Write { writer, .. } => assert_eq!(writer, "Collect"),
+        error => panic!("unexpected error: {}", error),
+    }
+    assert_eq!(sink.xs(), [2, 3, 4, 2, 3]);
+
+    // A loop that writes nothing returns.
+    let stats = Replayer::new()
+        .with_sink("Square", Collect::default())
+        .with_start(Duration::from_secs(1))
+        .with_loop(true)
+        .replay(&recording("empty-loop"))
+        .unwrap();
+    assert_eq!(stats, ReplayStats::default());
+}
+
+#[test]
+fn topics_without_a_sink() {
+    let squares = Collect::default();
+    let circles = Collect::default();
+    let stats = Replayer::new()
+        .with_sink("Square", Collect::default())
+        .with_sink("Circle", circles.clone())
+        // Replaces the first sink of the topic.
+        .with_sink("Square", squares.clone())
+        .with_speed(10.0)
+        .replay(&recording("topics"))
+        .unwrap();
+    assert_eq!(stats.written, 6);
+    assert_eq!(squares.xs(), [0, 1, 2, 3, 4]);
+    assert_eq!(circles.xs(), [9]);
+
+    let stats = Replayer::new()
+        .with_sink("Triangle", Collect::default())
+        .replay(&recording("no-topic"))
+        .unwrap();
+    assert_eq!(stats, ReplayStats::default());
+}
@@ -0,0 +1,276 @@
+//! `SampleSerializer` into an instance held as JSON, which keeps the members
+//! it is given and the order they were set in.
+
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use rticonnector::config::types::{Enum, TypeKind, Types};
+use rticonnector::ser::{Error, ErrorKind, Instance, SampleSerializer};
+use rticonnector::ConnectorError;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+const TYPES: &str = r#"<dds>
+    <types>
+        <enum name="Color">
+            <enumerator name="RED" value="3"/>
+            <enumerator name="GREEN" value="7"/>
+        </enum>
+        <typedef name="Colors" type="nonBasic" nonBasicTypeName="Color" sequenceMaxLength="4"/>
+        <struct name="Shape">
+            <member name="color" type="nonBasic" nonBasicTypeName="Color"/>
+        </struct>
+        <struct name="Paint">
+            <member name="shape" type="nonBasic" nonBasicTypeName="Shape"/>
+            <member name="color" type="nonBasic" nonBasicTypeName="Color"/>
+            <member name="palette" type="nonBasic" nonBasicTypeName="Colors"/>
+            <member name="sizes" type="long" sequenceMaxLength="8"/>
+            <member name="count" type="int64"/>
+            <member name="total" type="uint64"/>
+        </struct>
+    </types>
+</dds>"#;
+
+/// An instance of `Paint` held as JSON, with the calls made to it.
+struct JsonInstance {
+    value: RefCell<Value>,
+    calls: RefCell<Vec<String>>,
+    types: Types,
+    one_based: bool,
+}
+
+impl JsonInstance {
+    fn new(value: Value) -> Self {
+        Self {
+            value: RefCell::new(value),
+            calls: RefCell::default(),
+            types: Types::from_xml(TYPES).unwrap(),
+            one_based: false,
+        }
+    }
+
+    /// Sets the member `field`, creating the members and elements on its way.
+    fn set(&self, field: &str, member: Value) -> Result<(), ConnectorError> {
+        self.calls
+            .borrow_mut()
+            .push(format!("{} = {}", field, member));
+        let mut root = self.value.borrow_mut();
+        let mut value = &mut *root;
+        for segment in field.split('.') {
+            let mut parts = segment.split('[');
+            let name = parts.next().unwrap();
+            value = value
+                .as_object_mut()
+                .ok_or(ConnectorError::FieldNotFound)?
+                .entry(name)
+                .or_insert(Value::Null);
+            for index in parts {
+                let index: usize = index.trim_end_matches(']').parse().unwrap();
+                let index = index
+                    .checked_sub(usize::from(self.one_based))
+                    .ok_or(ConnectorError::FieldNotFound)?;
+                if value.is_null() {
+                    *value = json!([]);
+                }
+                let elements = value.as_array_mut().ok_or(ConnectorError::FieldNotFound)?;
+                if elements.len() <= index {
+                    elements.resize(index + 1, Value::Null);
+                }
+                value = &mut elements[index];
+            }
+            if value.is_null() {
+                *value = json!({});
+            }
+        }
+        *value = member;
+        Ok(())
+    }
+}
+
+impl Instance for JsonInstance {
+    fn set_number(&self, field: &str, value: f64) -> Result<(), ConnectorError> {
+        self.set(field, json!(value))
+    }
+
+    fn set_string(&self, field: &str, value: &str) -> Result<(), ConnectorError> {
+        self.set(field, json!(value))
+    }
+
+    fn set_boolean(&self, field: &str, value: bool) -> Result<(), ConnectorError> {
+        self.set(field, json!(value))
+    }
+
+    fn clear(&self, field: &str) -> Result<(), ConnectorError> {
+        self.calls.borrow_mut().push(format!("clear {}", field));
+        if let Some(member) = self.value.borrow_mut().get_mut(field) {
+            *member = match member {
+                Value::Array(_) => json!([]),
+                _ => Value::Null,
+            };
+        }
+        Ok(())
+    }
+
+    fn one_based_sequence_indexing(&self) -> bool {
+        self.one_based
+    }
+
+    fn enumeration(&self, field: &str) -> Option<Arc<Enum>> {
+        let paint = self.types.get_struct("Paint")?;
+        match self.types.field_type(paint, field)?.kind {
+            TypeKind::NonBasic(name) => self.types.get_enum(&name).cloned().map(Arc::new),
+            _ => None,
+        }
+    }
+}
+
+/// Serializes `value` into `instance`, returning the instance afterwards.
+fn serialize<T: Serialize>(instance: JsonInstance, value: &T) -> (JsonInstance, Result<(), Error>) {
+    let mut serializer = SampleSerializer::from_instance(instance);
+    let result = value.serialize(&mut serializer);
+    (serializer.into_instance(), result)
+}
+
+fn serialize_into<T: Serialize>(value: &T) -> Result<Value, Error> {
+    let (instance, result) = serialize(JsonInstance::new(json!({})), value);
+    result.map(|()| instance.value.into_inner())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+#[derive(Serialize)]
+struct Shape {
+    color: Color,
+}
+
+#[derive(Serialize)]
+struct Paint {
+    shape: Shape,
+    color: Color,
+    palette: Vec<Color>,
+}
+
+#[derive(Serialize)]
+struct Count {
+    count: i64,
+}
+
+#[derive(Serialize)]
+struct Total {
+    total: u64,
+}
+
+#[derive(Serialize)]
+struct Sizes {
+    sizes: Vec<i32>,
+}
+
+#[test]
+fn unit_variants() {
+    let paint = Paint {
+        shape: Shape { color: Color::Red },
+        color: Color::Green,
+        palette: vec![Color::Green, Color::Red],
+    };
+    assert_eq!(
+        serialize_into(&paint).unwrap(),
+        json!({
+            "shape": { "color": 3.0 },
+            "color": 7.0,
+            "palette": [7.0, 3.0],
+        })
+    );
+
+    let error = serialize_into(&Paint {
+        palette: vec![Color::Red, Color::Blue],
+        ..paint
+    })
+    .unwrap_err();
+    assert_eq!(error.field(), Some("palette[1]"));
+    assert!(matches!(error.kind(), ErrorKind::UnknownEnumerator("BLUE")));
+
+    #[derive(Serialize)]
+    struct Uncolored {
+        count: Color,
+    }
+    let error = serialize_into(&Uncolored { count: Color::Red }).unwrap_err();
+    assert_eq!(error.field(), Some("count"));
+    assert!(matches!(
+        error.kind(),
+        ErrorKind::NotAnEnum {
+            name: "Color",
+            variant: "RED"
+        }
+    ));
+}
+
+#[test]
+fn sequences_are_cleared_first() {
+    let instance = JsonInstance::new(json!({ "sizes": [1.0, 2.0, 3.0] }));
+    let (instance, result) = serialize(instance, &Sizes { sizes: vec![5] });
+    result.unwrap();
+    assert_eq!(instance.value.into_inner(), json!({ "sizes": [5.0] }));
+    assert_eq!(
+        instance.calls.into_inner(),
+        ["clear sizes", "sizes[0] = 5.0"]
+    );
+
+    let instance = JsonInstance {
+        one_based: true,
+        ..JsonInstance::new(json!({ "sizes": [1.0] }))
+    };
+    let (instance, result) = serialize(instance, &Sizes { sizes: Vec::new() });
+    result.unwrap();
+    assert_eq!(instance.value.into_inner(), json!({ "sizes": [] }));
+
+    let instance = JsonInstance {
+        one_based: true,
+        ..JsonInstance::new(json!({}))
+    };
+    let (instance, result) = serialize(instance, &Sizes { sizes: vec![5, 6] });
+    result.unwrap();
+    assert_eq!(
+        instance.calls.into_inner(),
+        ["clear sizes", "sizes[1] = 5.0", "sizes[2] = 6.0"]
+    );
+}
+
+#[test]
+fn integers_beyond_2_53() {
+    for count in [i64::MAX, i64::MIN, (1 << 53) + 1, -(1 << 53) - 1] {
+        let (instance, result) = serialize(JsonInstance::new(json!({})), &Count { count });
+        let error = result.unwrap_err();
+        assert_eq!(error.field(), Some("count"));
+        assert!(
+            matches!(error.kind(), ErrorKind::InvalidNumber(_)),
+            "{}",
+            error
+        );
+        // Nothing is set rather than a rounded number.
+        assert!(instance.calls.into_inner().is_empty());
+    }
+    for count in [1 << 53, -(1 << 53), 0] {
+        assert_eq!(
+            serialize_into(&Count { count }).unwrap(),
+            json!({ "count": count as f64 })
+        );
+    }
+    for total in [u64::MAX, (1 << 53) + 1] {
+        let error = serialize_into(&Total { total }).unwrap_err();
+        assert!(
+            matches!(error.kind(), ErrorKind::InvalidNumber(_)),
+            "{}",
+            error
+        );
+    }
+    assert_eq!(
+        serialize_into(&Total { total: 1 << 53 }).unwrap(),
+        json!({ "total": 9007199254740992.0 })
+    );
+}